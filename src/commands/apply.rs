@@ -0,0 +1,124 @@
+use crate::commands::checkpoint::{
+    self, AuthorType, CheckpointOptions, resolve_confidence, resolve_model,
+};
+use crate::error::GitAiError;
+use git2::Repository;
+use std::process::{Command, ExitStatus};
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ApplyOptions {
+    /// Who the applied hunks belong to; patches carry no agent, so human unless told otherwise
+    pub author_type: Option<AuthorType>,
+    pub model: Option<String>,
+    /// Author recorded on the checkpoint (defaults to the model, or the git user for human hunks)
+    pub author: Option<String>,
+    /// Everything else, passed to `git apply` unchanged
+    pub git_args: Vec<String>,
+}
+
+impl ApplyOptions {
+    /// `--check`, `--stat` and friends only report on the patch, unless `--apply` is also given
+    fn changes_work_tree(&self) -> bool {
+        let has = |flag: &str| self.git_args.iter().any(|arg| arg == flag);
+        let report_only = ["--check", "--stat", "--numstat", "--summary"]
+            .iter()
+            .any(|flag| has(flag));
+        (!report_only || has("--apply")) && !has("--cached")
+    }
+}
+
+/// Split `apply [--author-type human|ai|generated] [--model <name>] [--author <name>] <git apply args>`
+pub fn parse_apply_args(args: &[String]) -> Result<ApplyOptions, GitAiError> {
+    let mut options = ApplyOptions::default();
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--author-type" => {
+                let value = args
+                    .get(i + 1)
+                    .and_then(|value| AuthorType::parse(value))
+                    .ok_or_else(|| {
+                        GitAiError::Generic(
+                            "--author-type requires 'human', 'ai' or 'generated'".to_string(),
+                        )
+                    })?;
+                options.author_type = Some(value);
+                i += 2;
+            }
+            "--model" | "--author" => {
+                let Some(value) = args.get(i + 1) else {
+                    return Err(GitAiError::Generic(format!(
+                        "Missing argument for {}",
+                        args[i]
+                    )));
+                };
+                if args[i] == "--model" {
+                    options.model = Some(value.clone());
+                } else {
+                    options.author = Some(value.clone());
+                }
+                i += 2;
+            }
+            _ => {
+                options.git_args.push(args[i].clone());
+                i += 1;
+            }
+        }
+    }
+    Ok(options)
+}
+
+/// Run `git apply` and attribute the hunks it applied. Pending edits are checkpointed as
+/// `human_author` first, so the checkpoint after the apply holds exactly the patch's changes.
+/// Index-only (`--cached`) and report-only applies leave the work tree alone and record
+/// nothing. Returns git's exit status; a failed apply adds no checkpoint of its own.
+/// Attribution is best effort: when a checkpoint fails the patch still applies, with a
+/// warning, and only failing to run git is an error.
+pub fn run(
+    repo: &Repository,
+    options: &ApplyOptions,
+    human_author: &str,
+) -> Result<ExitStatus, GitAiError> {
+    // Without the pending edits recorded first, the patch's checkpoint would claim them too
+    let mut attribute = options.changes_work_tree();
+    if attribute
+        && let Err(e) = checkpoint::run(
+            repo,
+            human_author,
+            &CheckpointOptions {
+                quiet: true,
+                ..Default::default()
+            },
+        )
+    {
+        eprintln!("Warning: not attributing the patch: {}", e);
+        attribute = false;
+    }
+
+    let status = Command::new("git")
+        .arg("apply")
+        .args(&options.git_args)
+        .status()?;
+    if !status.success() || !attribute {
+        return Ok(status);
+    }
+
+    let model = resolve_model(options.model.as_deref(), options.author_type);
+    let confidence = resolve_confidence(options.model.as_deref(), options.author_type, None);
+    let author = options
+        .author
+        .as_deref()
+        .or(model.as_deref())
+        .unwrap_or(human_author);
+    let checkpoint_options = CheckpointOptions {
+        human_author: model.as_ref().map(|_| human_author.to_string()),
+        model: model.clone(),
+        confidence,
+        generated: options.author_type == Some(AuthorType::Generated),
+        ..Default::default()
+    };
+    if let Err(e) = checkpoint::run(repo, author, &checkpoint_options) {
+        eprintln!("Warning: failed to attribute the patch: {}", e);
+    }
+    Ok(status)
+}
@@ -1,9 +1,17 @@
+use crate::commands::checkpoint::resolve_base_commit;
 use crate::error::GitAiError;
-use crate::git::refs::get_reference_as_authorship_log;
+use crate::git::refs::{
+    authorship_ref_name, get_reference_as_authorship_log, get_reference_as_working_log,
+};
+use crate::git::repository::{load_mailmap, mailmap_name};
+use crate::git::revspec::resolve_commit;
 use crate::log_fmt::authorship_log::AuthorshipLog;
+use crate::log_fmt::working_log::{AgentMetadata, Checkpoint};
+use crate::utils::{ColorRole, ColorTheme, ThemePreset, terminal_width};
 use chrono::{DateTime, FixedOffset, TimeZone, Utc};
-use git2::{BlameOptions, Repository};
-use std::collections::HashMap;
+use git2::{BlameOptions, Commit, Mailmap, Repository};
+use similar::{DiffOp, TextDiff};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
 use std::io::{self, IsTerminal, Write};
 use std::path::Path;
@@ -37,7 +45,7 @@ pub struct BlameHunk {
     pub is_boundary: bool,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct GitAiBlameOptions {
     // Line range options
     pub line_ranges: Vec<(u32, u32)>,
@@ -73,6 +81,8 @@ pub struct GitAiBlameOptions {
     // Color options
     pub color_lines: bool,
     pub color_by_age: bool,
+    /// Color set for the gutter and lines, instead of the one `git-ai.colors.theme` names
+    pub theme: Option<ThemePreset>,
 
     // Progress options
     pub progress: bool,
@@ -82,50 +92,74 @@ pub struct GitAiBlameOptions {
 
     // Content options
     pub contents_file: Option<String>,
+    /// Content read from `--contents` (a path, or `-` for stdin), blamed in place of the
+    /// file on disk
+    pub contents: Option<String>,
 
     // Revision options
-    #[allow(dead_code)]
+    /// Blame the file as it was at this revision (`--at`), even if it was deleted since
     pub revision: Option<String>,
     pub reverse: Option<String>,
     pub first_parent: bool,
+    /// Lines a merge brought in are credited to the non-merge commit they came from
+    /// (`--no-merges`), so conflict resolutions don't hide who wrote the code
+    pub no_merges: bool,
 
     // Encoding
     pub encoding: Option<String>,
+
+    // git-ai output options
+    pub web: bool,
+    pub no_open: bool,
+    pub json: bool,
+    /// Like `json`, but one line object per output line (NDJSON), written as it is built
+    pub json_lines: bool,
+    /// Write the result to this path instead of stdout
+    pub output: Option<String>,
+    /// Lines last changed at or before this revision are shown as baseline
+    pub since: Option<String>,
+    /// Show author names as recorded instead of mapping them through `.mailmap`
+    pub no_mailmap: bool,
+    /// Collapse consecutive lines with the same attribution into one header per run
+    pub group_hunks: bool,
+    /// With `group_hunks`, also print the first and last line of each run
+    pub group_edges: bool,
+    /// Mark every line not attributed to this author, e.g. what an agent changed on top of
+    /// a person's work
+    pub relative_to: Option<String>,
+    /// Only show lines attributed to a model containing this name (case-insensitive)
+    pub model: Option<String>,
+    /// With `model`, require the model name to match exactly
+    pub model_exact: bool,
+    /// Only show lines in this category; line numbers stay those of the full file
+    pub only: Option<LineCategory>,
+    /// Columns per tab when expanding line content (default: core.whitespace's tabwidth, or 8)
+    pub tabwidth: Option<usize>,
+    /// Gutter columns for the default output, in order (`--fields`)
+    pub fields: Option<Vec<BlameField>>,
+    /// Soft-wrap long content to the terminal width, continuing under a blank gutter
+    pub wrap: bool,
+    /// Show the prompt of the checkpoint that wrote each AI line: truncated in the gutter,
+    /// and in full below the line when a single line is blamed
+    pub show_prompt: bool,
+    /// Stream attribution records chunk by chunk as they are computed (`--porcelain-v2`)
+    pub porcelain_v2: bool,
+    pub compare_with_git: bool,
+    /// Discrepancies tolerated by --compare-with-git before exiting non-zero
+    pub compare_threshold: Option<u32>,
+    /// Print only the percentage of the blamed lines that are AI-authored (`--count`)
+    pub count: bool,
+    /// With `count`, fail when that percentage is above this one
+    pub threshold: Option<f64>,
 }
 
-impl Default for GitAiBlameOptions {
-    fn default() -> Self {
-        Self {
-            line_ranges: Vec::new(),
-            porcelain: false,
-            line_porcelain: false,
-            incremental: false,
-            show_name: false,
-            show_number: false,
-            show_email: false,
-            suppress_author: false,
-            show_stats: false,
-            long_rev: false,
-            raw_timestamp: false,
-            abbrev: None,
-            blank_boundary: false,
-            show_root: false,
-            detect_moves: false,
-            detect_copies: 0,
-            move_threshold: None,
-            ignore_revs: Vec::new(),
-            ignore_revs_file: None,
-            color_lines: false,
-            color_by_age: false,
-            progress: false,
-            date_format: None,
-            contents_file: None,
-            revision: None,
-            reverse: None,
-            first_parent: false,
-            encoding: None,
-        }
-    }
+/// A line where git-ai's commit attribution disagrees with `git blame`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlameDiscrepancy {
+    pub line: u32,
+    /// None when git-ai produced no hunk covering the line
+    pub git_ai_commit: Option<String>,
+    pub git_commit: String,
 }
 
 pub fn run(
@@ -139,8 +173,32 @@ pub fn run(
         .ok_or_else(|| GitAiError::Generic("Repository has no working directory".to_string()))?;
     let abs_file_path = repo_root.join(file_path);
 
+    // An editor buffer passed with --contents stands in for the file on disk
+    let mut buffer_options;
+    let options = match &options.contents_file {
+        Some(source) if options.contents.is_none() => {
+            let contents = if source == "-" {
+                let mut contents = String::new();
+                io::Read::read_to_string(&mut io::stdin(), &mut contents)?;
+                contents
+            } else {
+                fs::read_to_string(source)?
+            };
+            buffer_options = options.clone();
+            buffer_options.contents = Some(contents);
+            &buffer_options
+        }
+        _ => options,
+    };
+
+    // --at reads the file from that revision's tree instead of the working tree
+    let revision_content = match &options.revision {
+        Some(revision) => Some(file_at_revision(repo, revision, file_path)?),
+        None => None,
+    };
+
     // Validate that the file exists
-    if !abs_file_path.exists() {
+    if options.contents.is_none() && revision_content.is_none() && !abs_file_path.exists() {
         return Err(GitAiError::Generic(format!(
             "File not found: {}",
             abs_file_path.display()
@@ -148,7 +206,12 @@ pub fn run(
     }
 
     // Read the current file content
-    let file_content = fs::read_to_string(&abs_file_path)?;
+    let disk_content = fs::read_to_string(&abs_file_path).unwrap_or_default();
+    let file_content = options
+        .contents
+        .as_deref()
+        .or(revision_content.as_deref())
+        .unwrap_or(&disk_content);
     let lines: Vec<&str> = file_content.lines().collect();
     let total_lines = lines.len() as u32;
 
@@ -169,6 +232,29 @@ pub fn run(
         }
     }
 
+    // --porcelain-v2 sends each chunk as soon as it is blamed, before later lines are computed
+    if options.porcelain_v2 {
+        let mut output_file = match &options.output {
+            Some(path) => Some(create_output_file(path)?),
+            None => None,
+        };
+        let stdout = io::stdout();
+        let mut stdout_lock = stdout.lock();
+        let out: &mut dyn Write = match output_file.as_mut() {
+            Some(file) => file,
+            None => &mut stdout_lock,
+        };
+        return stream_porcelain_v2(
+            repo,
+            file_path,
+            &disk_content,
+            file_content,
+            &line_ranges,
+            options,
+            out,
+        );
+    }
+
     // Step 1: Get Git's native blame for all ranges
     let mut all_blame_hunks = Vec::new();
     for (start_line, end_line) in &line_ranges {
@@ -177,10 +263,128 @@ pub fn run(
     }
 
     // Step 2: Overlay AI authorship information
-    let line_authors = overlay_ai_authorship(repo, &all_blame_hunks, file_path)?;
+    let mailmap = load_mailmap(repo, !options.no_mailmap);
+    let mut line_attributions =
+        overlay_line_attributions(repo, &all_blame_hunks, file_path, mailmap.as_ref())?;
+    if options.contents.is_some() {
+        overlay_uncommitted_attributions(
+            repo,
+            file_path,
+            &disk_content,
+            file_content,
+            &all_blame_hunks,
+            &mut line_attributions,
+        )?;
+    }
+    if let Some(since) = &options.since {
+        mark_baseline_lines(repo, since, &all_blame_hunks, &mut line_attributions)?;
+    }
+    // A code generator's lines are on neither side of the --count share
+    let blamed_lines = line_ranges
+        .iter()
+        .flat_map(|(start, end)| *start..=*end)
+        .filter(|line_num| !line_attributions.get(line_num).is_some_and(|a| a.generated))
+        .count() as u32;
+    // --model narrows the ranges to that model's lines, so every output format follows
+    let line_ranges = match &options.model {
+        Some(model) => {
+            filter_ranges_by_model(&line_attributions, &line_ranges, model, options.model_exact)
+        }
+        None => line_ranges,
+    };
+    let line_ranges = match options.only {
+        Some(category) => filter_ranges_by_category(
+            &line_categories(&all_blame_hunks, &line_attributions),
+            &line_ranges,
+            category,
+        ),
+        None => line_ranges,
+    };
+    let line_authors: HashMap<u32, String> = line_attributions
+        .iter()
+        .filter(|(line_num, _)| {
+            line_ranges
+                .iter()
+                .any(|(start, end)| (*start..=*end).contains(*line_num))
+        })
+        .map(|(line_num, attribution)| (*line_num, attribution.author.clone()))
+        .collect();
+
+    // A gate for hooks: the AI share of the blamed lines (of one model's, with --model) and
+    // nothing else
+    if options.count {
+        let ai_lines = line_ranges
+            .iter()
+            .flat_map(|(start, end)| *start..=*end)
+            .filter(|line_num| line_attributions.get(line_num).is_some_and(|a| a.is_ai()))
+            .count();
+        let percent = if blamed_lines == 0 {
+            0.0
+        } else {
+            ai_lines as f64 * 100.0 / blamed_lines as f64
+        };
+        println!("{:.1}", percent);
+        if let Some(threshold) = options.threshold
+            && percent > threshold
+        {
+            return Err(GitAiError::Generic(format!(
+                "{:.1}% of {} is AI-authored (threshold {}%)",
+                percent, file_path, threshold
+            )));
+        }
+        return Ok(line_authors);
+    }
+
+    if options.compare_with_git {
+        let discrepancies = compare_with_git(repo, file_path, &all_blame_hunks, &line_ranges)?;
+        print_discrepancies(file_path, &discrepancies, &line_ranges);
+        let threshold = options.compare_threshold.unwrap_or(0);
+        if discrepancies.len() as u32 > threshold {
+            return Err(GitAiError::Generic(format!(
+                "{} line(s) differ from git blame (threshold {})",
+                discrepancies.len(),
+                threshold
+            )));
+        }
+        return Ok(line_authors);
+    }
 
     // Output based on format
-    if options.porcelain || options.line_porcelain {
+    if options.web {
+        // The HTML view manages its own file (--output picks where it goes)
+        output_web_format(
+            &all_blame_hunks,
+            &line_attributions,
+            file_path,
+            &lines,
+            &line_ranges,
+            options,
+        )?;
+        return Ok(line_authors);
+    }
+
+    let mut output_file = match &options.output {
+        Some(path) => Some(create_output_file(path)?),
+        None => None,
+    };
+    let stdout = io::stdout();
+    let mut stdout_lock = stdout.lock();
+    let out: &mut dyn Write = match output_file.as_mut() {
+        Some(file) => file,
+        None => &mut stdout_lock,
+    };
+
+    if options.json || options.json_lines {
+        output_json_format(
+            &all_blame_hunks,
+            &line_attributions,
+            file_path,
+            &lines,
+            &line_ranges,
+            options.json_lines,
+            out,
+        )?;
+    } else if options.porcelain || options.line_porcelain {
         output_porcelain_format(
             repo,
             &line_authors,
@@ -188,6 +392,16 @@ pub fn run(
             &lines,
             &line_ranges,
             options,
+            out,
+        )?;
+    } else if options.group_hunks {
+        output_grouped_format(
+            &line_attributions,
+            &lines,
+            &line_ranges,
+            options,
+            tab_width(repo, options),
+            out,
         )?;
     } else if options.incremental {
         output_incremental_format(
@@ -197,18 +411,30 @@ pub fn run(
             &lines,
             &line_ranges,
             options,
+            out,
         )?;
     } else {
+        // Only hand over a sink for --output; stdout goes through the pager
+        drop(stdout_lock);
         output_default_format(
             repo,
             &line_authors,
+            &line_attributions,
             file_path,
             &lines,
             &line_ranges,
             options,
+            output_file.as_mut().map(|file| file as &mut dyn Write),
         )?;
     }
 
+    if let Some(file) = output_file.as_mut() {
+        file.flush()?;
+    }
+    if let Some(path) = &options.output {
+        eprintln!("Wrote blame for {} to {}", file_path, path);
+    }
+
     Ok(line_authors)
 }
 
@@ -233,7 +459,17 @@ pub fn get_git_blame_hunks(
         // We'll handle root commit detection in the output formatting
     }
 
+    if let Some(revision) = &options.revision {
+        blame_opts.newest_commit(resolve_commit(repo, revision)?.id());
+    }
+
     let blame = repo.blame_file(Path::new(file_path), Some(&mut blame_opts))?;
+    // Lines of a --contents buffer that differ from HEAD come back with a zero commit id
+    let blame = match &options.contents {
+        Some(contents) => blame.blame_buffer(contents.as_bytes())?,
+        None => blame,
+    };
+    let mailmap = load_mailmap(repo, !options.no_mailmap);
     let mut hunks = Vec::new();
 
     let num_hunks = blame.len();
@@ -246,6 +482,13 @@ pub fn get_git_blame_hunks(
         let end = start + hunk.lines_in_hunk() - 1;
 
         let commit_id = hunk.final_commit_id();
+        if commit_id.is_zero() {
+            hunks.push(BlameHunk::not_committed_yet(
+                (start.try_into().unwrap(), end.try_into().unwrap()),
+                options,
+            ));
+            continue;
+        }
         let commit = match repo.find_commit(commit_id) {
             Ok(commit) => commit,
             Err(_) => {
@@ -253,70 +496,293 @@ pub fn get_git_blame_hunks(
             }
         };
 
-        let author = commit.author();
-        let committer = commit.committer();
-        let commit_sha = commit_id.to_string();
+        let range = (start.try_into().unwrap(), end.try_into().unwrap());
+        if options.no_merges && commit.parent_count() > 1 {
+            let orig_path = hunk
+                .path()
+                .map(Path::to_path_buf)
+                .unwrap_or_else(|| Path::new(file_path).to_path_buf());
+            let orig_start = hunk.orig_start_line() as u32;
+            for offset in 0..range.1 - range.0 + 1 {
+                let origin = merge_line_origin(repo, &commit, &orig_path, orig_start + offset)?;
+                let origin = origin.as_ref().unwrap_or(&commit);
+                let line = range.0 + offset;
+                // Neighbouring lines from the same commit stay one hunk
+                if let Some(last) = hunks.last_mut()
+                    && last.commit_sha == origin.id().to_string()
+                    && last.range.1 + 1 == line
+                {
+                    last.range.1 = line;
+                    continue;
+                }
+                hunks.push(commit_hunk(
+                    origin,
+                    (line, line),
+                    mailmap.as_ref(),
+                    options,
+                )?);
+            }
+            continue;
+        }
+        hunks.push(commit_hunk(&commit, range, mailmap.as_ref(), options)?);
+    }
+
+    Ok(hunks)
+}
 
-        // Determine hash length based on options
-        let hash_len = if options.long_rev {
-            40 // Full hash for long revision
-        } else if let Some(abbrev) = options.abbrev {
-            abbrev as usize
-        } else {
-            7 // Default 7 chars
+fn commit_hunk(
+    commit: &Commit,
+    range: (u32, u32),
+    mailmap: Option<&Mailmap>,
+    options: &GitAiBlameOptions,
+) -> Result<BlameHunk, GitAiError> {
+    // Identities go through the mailmap, as in git's own blame
+    let (author, committer) = match mailmap {
+        Some(mailmap) => (
+            mailmap.resolve_signature(&commit.author())?,
+            mailmap.resolve_signature(&commit.committer())?,
+        ),
+        None => (commit.author().to_owned(), commit.committer().to_owned()),
+    };
+    let commit_sha = commit.id().to_string();
+
+    // Determine hash length based on options
+    let hash_len = if options.long_rev {
+        40 // Full hash for long revision
+    } else if let Some(abbrev) = options.abbrev {
+        abbrev as usize
+    } else {
+        7 // Default 7 chars
+    };
+
+    let abbrev_sha = if hash_len < commit_sha.len() {
+        commit_sha[..hash_len].to_string()
+    } else {
+        commit_sha.clone()
+    };
+
+    let original_author = String::from_utf8_lossy(author.name_bytes()).into_owned();
+    let author_email = String::from_utf8_lossy(author.email_bytes()).into_owned();
+    let author_time = author.when().seconds();
+    let author_tz = format!(
+        "{:+03}{:02}",
+        author.when().offset_minutes() / 60,
+        (author.when().offset_minutes().abs() % 60)
+    );
+    let committer_name = String::from_utf8_lossy(committer.name_bytes()).into_owned();
+    let committer_email = String::from_utf8_lossy(committer.email_bytes()).into_owned();
+    let committer_time = committer.when().seconds();
+    let committer_tz = format!(
+        "{:+03}{:02}",
+        committer.when().offset_minutes() / 60,
+        (committer.when().offset_minutes().abs() % 60)
+    );
+
+    // Check if this is a boundary commit (has no parent)
+    let is_boundary = commit.parent_count() == 0;
+
+    Ok(BlameHunk {
+        range,
+        commit_sha,
+        abbrev_sha,
+        original_author,
+        author_email,
+        author_time,
+        author_tz,
+        committer: committer_name,
+        committer_email,
+        committer_time,
+        committer_tz,
+        is_boundary,
+    })
+}
+
+/// How far `--no-merges` follows a line through merges of merges
+const MAX_MERGE_DEPTH: usize = 16;
+
+/// The non-merge commit a line of `merge` came from. Blame only stops at a merge for lines
+/// that match none of its parents, i.e. ones written while resolving it, so the line is
+/// matched to the parent line it most resembles (one the merge left as it was is preferred)
+/// and blamed from there. None when no parent has such a line, which means the merge itself
+/// added it.
+fn merge_line_origin<'repo>(
+    repo: &'repo Repository,
+    merge: &Commit<'repo>,
+    path: &Path,
+    line: u32,
+) -> Result<Option<Commit<'repo>>, GitAiError> {
+    let (mut commit, mut path, mut line) = (merge.clone(), path.to_path_buf(), line);
+    for _ in 0..MAX_MERGE_DEPTH {
+        let Some((parent, parent_line)) = parent_line(repo, &commit, &path, line)? else {
+            return Ok(None);
+        };
+        let mut blame_opts = BlameOptions::new();
+        blame_opts
+            .newest_commit(parent.id())
+            .min_line(parent_line as usize)
+            .max_line(parent_line as usize);
+        let blame = repo.blame_file(&path, Some(&mut blame_opts))?;
+        let Some(hunk) = blame.get_line(parent_line as usize) else {
+            return Ok(None);
         };
+        let origin = repo.find_commit(hunk.final_commit_id())?;
+        if origin.parent_count() <= 1 {
+            return Ok(Some(origin));
+        }
+        line = hunk.orig_start_line() as u32 + (parent_line - hunk.final_start_line() as u32);
+        if let Some(orig_path) = hunk.path() {
+            path = orig_path.to_path_buf();
+        }
+        commit = origin;
+    }
+    Ok(None)
+}
 
-        let abbrev_sha = if hash_len < commit_sha.len() {
-            commit_sha[..hash_len].to_string()
-        } else {
-            commit_sha.clone()
+/// The parent of `merge` and 1-based line in it that `line` of `path` is closest to
+fn parent_line<'repo>(
+    repo: &'repo Repository,
+    merge: &Commit<'repo>,
+    path: &Path,
+    line: u32,
+) -> Result<Option<(Commit<'repo>, u32)>, GitAiError> {
+    let Some(merged) = blob_text(repo, merge, path) else {
+        return Ok(None);
+    };
+    let index = line.saturating_sub(1) as usize;
+    // (unchanged, similarity, parent, parent line)
+    let mut best: Option<(bool, f32, Commit, u32)> = None;
+    for parent in merge.parents() {
+        let Some(text) = blob_text(repo, &parent, path) else {
+            continue;
+        };
+        let diff = TextDiff::from_lines(&text, &merged);
+        let Some(op) = diff.ops().iter().find(|op| op.new_range().contains(&index)) else {
+            continue;
+        };
+        let offset = index - op.new_range().start;
+        let candidate = match *op {
+            DiffOp::Equal { old_index, .. } => (true, 1.0, old_index + offset),
+            DiffOp::Replace {
+                old_index, old_len, ..
+            } => {
+                let old_line = old_index + offset.min(old_len - 1);
+                let ratio = TextDiff::from_chars(
+                    text.lines().nth(old_line).unwrap_or(""),
+                    merged.lines().nth(index).unwrap_or(""),
+                )
+                .ratio();
+                (false, ratio, old_line)
+            }
+            _ => continue,
         };
+        let better = best.as_ref().is_none_or(|(unchanged, ratio, _, _)| {
+            (candidate.0, candidate.1) > (*unchanged, *ratio)
+        });
+        if better {
+            best = Some((candidate.0, candidate.1, parent, candidate.2 as u32 + 1));
+        }
+    }
+    Ok(best.map(|(_, _, parent, line)| (parent, line)))
+}
 
-        let original_author = author.name().unwrap_or("unknown").to_string();
-        let author_email = author.email().unwrap_or("").to_string();
-        let author_time = author.when().seconds();
-        let author_tz = format!(
-            "{:+03}{:02}",
-            author.when().offset_minutes() / 60,
-            (author.when().offset_minutes().abs() % 60)
-        );
-        let committer_name = committer.name().unwrap_or("").to_string();
-        let committer_email = committer.email().unwrap_or("").to_string();
-        let committer_time = committer.when().seconds();
-        let committer_tz = format!(
-            "{:+03}{:02}",
-            committer.when().offset_minutes() / 60,
-            (committer.when().offset_minutes().abs() % 60)
-        );
+fn blob_text(repo: &Repository, commit: &Commit, path: &Path) -> Option<String> {
+    let blob = commit
+        .tree()
+        .ok()?
+        .get_path(path)
+        .ok()?
+        .to_object(repo)
+        .ok()?
+        .peel_to_blob()
+        .ok()?;
+    Some(String::from_utf8_lossy(blob.content()).into_owned())
+}
 
-        // Check if this is a boundary commit (has no parent)
-        let is_boundary = commit.parent_count() == 0;
+/// Content of `file_path` in the tree of `revision`
+fn file_at_revision(
+    repo: &Repository,
+    revision: &str,
+    file_path: &str,
+) -> Result<String, GitAiError> {
+    let commit = resolve_commit(repo, revision)?;
+    let entry = commit.tree()?.get_path(Path::new(file_path)).map_err(|_| {
+        GitAiError::Generic(format!("{} does not exist at {}", file_path, revision))
+    })?;
+    let blob = entry.to_object(repo)?.peel_to_blob()?;
+    Ok(String::from_utf8_lossy(blob.content()).into_owned())
+}
+
+/// Author git itself shows for lines that are not in any commit
+pub const NOT_COMMITTED_YET: &str = "Not Committed Yet";
 
-        hunks.push(BlameHunk {
-            range: (start.try_into().unwrap(), end.try_into().unwrap()),
+impl BlameHunk {
+    /// A hunk of uncommitted lines, shaped like git's own output for them
+    fn not_committed_yet(range: (u32, u32), options: &GitAiBlameOptions) -> Self {
+        let now = Utc::now().timestamp();
+        let commit_sha = "0".repeat(40);
+        let abbrev_len = if options.long_rev {
+            40
+        } else {
+            options.abbrev.map(|abbrev| abbrev as usize).unwrap_or(7)
+        };
+        Self {
+            range,
+            abbrev_sha: commit_sha[..abbrev_len.min(40)].to_string(),
             commit_sha,
-            abbrev_sha,
-            original_author,
-            author_email,
-            author_time,
-            author_tz,
-            committer: committer_name,
-            committer_email,
-            committer_time,
-            committer_tz,
-            is_boundary,
-        });
+            original_author: NOT_COMMITTED_YET.to_string(),
+            author_email: "not.committed.yet".to_string(),
+            author_time: now,
+            author_tz: "+0000".to_string(),
+            committer: NOT_COMMITTED_YET.to_string(),
+            committer_email: "not.committed.yet".to_string(),
+            committer_time: now,
+            committer_tz: "+0000".to_string(),
+            is_boundary: false,
+        }
     }
+}
 
-    Ok(hunks)
+/// Author shown for lines unchanged since `--since`
+pub const BASELINE_AUTHOR: &str = "baseline";
+
+/// AI authorship information attached to a single blamed line
+#[derive(Debug, Clone)]
+pub struct LineAttribution {
+    pub author: String,
+    pub agent_metadata: Option<AgentMetadata>,
+    /// Written by a code generator, so neither human nor AI
+    pub generated: bool,
+}
+
+impl LineAttribution {
+    fn from_git_author(author: &str) -> Self {
+        Self {
+            author: author.to_string(),
+            agent_metadata: None,
+            generated: false,
+        }
+    }
+
+    /// Lines are AI-authored when their authorship entry carries agent metadata
+    pub fn is_ai(&self) -> bool {
+        self.agent_metadata.is_some()
+    }
+
+    /// AI lines whose checkpoint only inferred that the agent wrote them
+    pub fn is_low_confidence(&self) -> bool {
+        self.agent_metadata
+            .as_ref()
+            .is_some_and(|metadata| !metadata.confidence.is_high())
+    }
 }
 
-pub fn overlay_ai_authorship(
+pub fn overlay_line_attributions(
     repo: &Repository,
     blame_hunks: &[BlameHunk],
     file_path: &str,
-) -> Result<HashMap<u32, String>, GitAiError> {
-    let mut line_authors: HashMap<u32, String> = HashMap::new();
+    mailmap: Option<&Mailmap>,
+) -> Result<HashMap<u32, LineAttribution>, GitAiError> {
+    let mut line_attributions: HashMap<u32, LineAttribution> = HashMap::new();
 
     // Group hunks by commit SHA to avoid repeated lookups
     let mut commit_authorship_cache: HashMap<String, Option<AuthorshipLog>> = HashMap::new();
@@ -327,42 +793,43 @@ pub fn overlay_ai_authorship(
             cached.clone()
         } else {
             // Try to get authorship log for this commit
-            let ref_name = format!("ai/authorship/{}", hunk.commit_sha);
-            let authorship = match get_reference_as_authorship_log(repo, &ref_name) {
-                Ok(log) => Some(log),
-                Err(_) => None, // No AI authorship data for this commit
-            };
+            let ref_name = authorship_ref_name(repo, &hunk.commit_sha);
+            // No AI authorship data for this commit is not an error
+            let authorship = get_reference_as_authorship_log(repo, &ref_name).ok();
             commit_authorship_cache.insert(hunk.commit_sha.clone(), authorship.clone());
             authorship
         };
 
-        // If we have AI authorship data, look up the author for lines in this hunk
-        if let Some(authorship_log) = authorship_log {
-            if let Some(file_authorship) = authorship_log.files.get(file_path) {
-                // Check each line in this hunk for AI authorship
-                for line_num in hunk.range.0..=hunk.range.1 {
-                    if let Some(author) = file_authorship.get_author(line_num) {
-                        line_authors.insert(line_num, author.to_string());
-                    } else {
-                        // Fall back to original author if no AI authorship
-                        line_authors.insert(line_num, hunk.original_author.clone());
-                    }
-                }
-            } else {
-                // No file authorship data, use original author for all lines in hunk
-                for line_num in hunk.range.0..=hunk.range.1 {
-                    line_authors.insert(line_num, hunk.original_author.clone());
+        let file_authorship = authorship_log
+            .as_ref()
+            .and_then(|log| log.files.get(file_path));
+
+        for line_num in hunk.range.0..=hunk.range.1 {
+            // Fall back to the original git author if there is no AI authorship for the line
+            let attribution = match file_authorship.and_then(|f| f.get_author_entry(line_num)) {
+                // Human checkpoint authors are canonicalized against the commit's raw email
+                Some(entry)
+                    if entry.agent_metadata.is_none() && !entry.generated && mailmap.is_some() =>
+                {
+                    let commit = repo.find_commit(git2::Oid::from_str(&hunk.commit_sha)?)?;
+                    LineAttribution::from_git_author(&mailmap_name(
+                        mailmap,
+                        &entry.author,
+                        commit.author().email().unwrap_or(""),
+                    ))
                 }
-            }
-        } else {
-            // No authorship log, use original author for all lines in hunk
-            for line_num in hunk.range.0..=hunk.range.1 {
-                line_authors.insert(line_num, hunk.original_author.clone());
-            }
+                Some(entry) => LineAttribution {
+                    author: entry.author.clone(),
+                    agent_metadata: entry.agent_metadata.clone(),
+                    generated: entry.generated,
+                },
+                None => LineAttribution::from_git_author(&hunk.original_author),
+            };
+            line_attributions.insert(line_num, attribution);
         }
     }
 
-    Ok(line_authors)
+    Ok(line_attributions)
 }
 
 #[allow(unused_variables)]
@@ -412,6 +879,7 @@ fn output_porcelain_format(
     lines: &[&str],
     line_ranges: &[(u32, u32)],
     options: &GitAiBlameOptions,
+    out: &mut dyn Write,
 ) -> Result<(), GitAiError> {
     // Build a map from line number to BlameHunk for fast lookup
     let mut line_to_hunk: HashMap<u32, BlameHunk> = HashMap::new();
@@ -455,61 +923,63 @@ fn output_porcelain_format(
                 if options.line_porcelain {
                     if last_hunk_id.as_ref() != Some(&hunk_id) {
                         // First line of hunk: 4-field header
-                        println!(
+                        writeln!(
+                            out,
                             "{} {} {} {}",
                             commit_sha,
                             line_num,
                             line_num,
                             hunk.range.1 - hunk.range.0 + 1
-                        );
+                        )?;
                         last_hunk_id = Some(hunk_id);
                     } else {
                         // Subsequent lines: 3-field header
-                        println!("{} {} {}", commit_sha, line_num, line_num);
+                        writeln!(out, "{} {} {}", commit_sha, line_num, line_num)?;
                     }
-                    println!("author {}", author_name);
-                    println!("author-mail <{}>", author_email);
-                    println!("author-time {}", author_time);
-                    println!("author-tz {}", author_tz);
-                    println!("committer {}", committer_name);
-                    println!("committer-mail <{}>", committer_email);
-                    println!("committer-time {}", committer_time);
-                    println!("committer-tz {}", committer_tz);
-                    println!("summary {}", summary);
+                    writeln!(out, "author {}", author_name)?;
+                    writeln!(out, "author-mail <{}>", author_email)?;
+                    writeln!(out, "author-time {}", author_time)?;
+                    writeln!(out, "author-tz {}", author_tz)?;
+                    writeln!(out, "committer {}", committer_name)?;
+                    writeln!(out, "committer-mail <{}>", committer_email)?;
+                    writeln!(out, "committer-time {}", committer_time)?;
+                    writeln!(out, "committer-tz {}", committer_tz)?;
+                    writeln!(out, "summary {}", summary)?;
                     if boundary {
-                        println!("boundary");
+                        writeln!(out, "boundary")?;
                     }
-                    println!("filename {}", filename);
-                    println!("\t{}", line_content);
+                    writeln!(out, "filename {}", filename)?;
+                    writeln!(out, "\t{}", line_content)?;
                 } else if options.porcelain {
                     if last_hunk_id.as_ref() != Some(&hunk_id) {
                         // Print full block for first line of hunk
-                        println!(
+                        writeln!(
+                            out,
                             "{} {} {} {}",
                             commit_sha,
                             line_num,
                             line_num,
                             hunk.range.1 - hunk.range.0 + 1
-                        );
-                        println!("author {}", author_name);
-                        println!("author-mail <{}>", author_email);
-                        println!("author-time {}", author_time);
-                        println!("author-tz {}", author_tz);
-                        println!("committer {}", committer_name);
-                        println!("committer-mail <{}>", committer_email);
-                        println!("committer-time {}", committer_time);
-                        println!("committer-tz {}", committer_tz);
-                        println!("summary {}", summary);
+                        )?;
+                        writeln!(out, "author {}", author_name)?;
+                        writeln!(out, "author-mail <{}>", author_email)?;
+                        writeln!(out, "author-time {}", author_time)?;
+                        writeln!(out, "author-tz {}", author_tz)?;
+                        writeln!(out, "committer {}", committer_name)?;
+                        writeln!(out, "committer-mail <{}>", committer_email)?;
+                        writeln!(out, "committer-time {}", committer_time)?;
+                        writeln!(out, "committer-tz {}", committer_tz)?;
+                        writeln!(out, "summary {}", summary)?;
                         if boundary {
-                            println!("boundary");
+                            writeln!(out, "boundary")?;
                         }
-                        println!("filename {}", filename);
-                        println!("\t{}", line_content);
+                        writeln!(out, "filename {}", filename)?;
+                        writeln!(out, "\t{}", line_content)?;
                         last_hunk_id = Some(hunk_id);
                     } else {
                         // For subsequent lines, print only the header and content (no metadata block)
-                        println!("{} {} {}", commit_sha, line_num, line_num);
-                        println!("\t{}", line_content);
+                        writeln!(out, "{} {} {}", commit_sha, line_num, line_num)?;
+                        writeln!(out, "\t{}", line_content)?;
                     }
                 }
             }
@@ -525,6 +995,7 @@ fn output_incremental_format(
     _lines: &[&str],
     line_ranges: &[(u32, u32)],
     options: &GitAiBlameOptions,
+    out: &mut dyn Write,
 ) -> Result<(), GitAiError> {
     // Build a map from line number to BlameHunk for fast lookup
     let mut line_to_hunk: HashMap<u32, BlameHunk> = HashMap::new();
@@ -556,60 +1027,67 @@ fn output_incremental_format(
                 let hunk_id = (hunk.commit_sha.clone(), hunk.range.0);
                 if last_hunk_id.as_ref() != Some(&hunk_id) {
                     // Print full block - match git's format exactly
-                    println!(
+                    writeln!(
+                        out,
                         "{} {} {} {}",
                         commit_sha,
                         line_num,
                         line_num,
                         hunk.range.1 - hunk.range.0 + 1
-                    );
-                    println!("author {}", author_name);
-                    println!("author-mail <{}>", author_email);
-                    println!("author-time {}", author_time);
-                    println!("author-tz {}", author_tz);
-                    println!("committer {}", committer_name);
-                    println!("committer-mail <{}>", committer_email);
-                    println!("committer-time {}", committer_time);
-                    println!("committer-tz {}", committer_tz);
-                    println!("summary Initial commit");
+                    )?;
+                    writeln!(out, "author {}", author_name)?;
+                    writeln!(out, "author-mail <{}>", author_email)?;
+                    writeln!(out, "author-time {}", author_time)?;
+                    writeln!(out, "author-tz {}", author_tz)?;
+                    writeln!(out, "committer {}", committer_name)?;
+                    writeln!(out, "committer-mail <{}>", committer_email)?;
+                    writeln!(out, "committer-time {}", committer_time)?;
+                    writeln!(out, "committer-tz {}", committer_tz)?;
+                    writeln!(out, "summary Initial commit")?;
                     if hunk.is_boundary {
-                        println!("boundary");
+                        writeln!(out, "boundary")?;
                     }
-                    println!("filename {}", file_path);
+                    writeln!(out, "filename {}", file_path)?;
                     last_hunk_id = Some(hunk_id);
                 }
                 // For incremental, no content lines (no \tLine)
             } else {
                 // Fallback for lines without blame info
-                println!(
+                writeln!(
+                    out,
                     "0000000000000000000000000000000000000000 {} {} 1",
                     line_num, line_num
-                );
-                println!("author unknown");
-                println!("author-mail <unknown@example.com>");
-                println!("author-time 0");
-                println!("author-tz +0000");
-                println!("committer unknown");
-                println!("committer-mail <unknown@example.com>");
-                println!("committer-time 0");
-                println!("committer-tz +0000");
-                println!("summary unknown");
-                println!("filename {}", file_path);
+                )?;
+                writeln!(out, "author unknown")?;
+                writeln!(out, "author-mail <unknown@example.com>")?;
+                writeln!(out, "author-time 0")?;
+                writeln!(out, "author-tz +0000")?;
+                writeln!(out, "committer unknown")?;
+                writeln!(out, "committer-mail <unknown@example.com>")?;
+                writeln!(out, "committer-time 0")?;
+                writeln!(out, "committer-tz +0000")?;
+                writeln!(out, "summary unknown")?;
+                writeln!(out, "filename {}", file_path)?;
             }
         }
     }
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 fn output_default_format(
     repo: &Repository,
     line_authors: &HashMap<u32, String>,
+    line_attributions: &HashMap<u32, LineAttribution>,
     file_path: &str,
     lines: &[&str],
     line_ranges: &[(u32, u32)],
     options: &GitAiBlameOptions,
+    out: Option<&mut dyn Write>,
 ) -> Result<(), GitAiError> {
     let mut output = String::new();
+    let tab_width = tab_width(repo, options);
+    let wrap_width = options.wrap.then(terminal_width);
 
     // Build a map from line number to BlameHunk for fast lookup
     let mut line_to_hunk: HashMap<u32, BlameHunk> = HashMap::new();
@@ -622,44 +1100,68 @@ fn output_default_format(
         }
     }
 
+    // Baseline lines (--since) are dimmed and --relative-to deltas highlighted on a terminal;
+    // otherwise lines take the theme's AI, human or mixed color
+    let preset_theme;
+    let theme = match options.theme {
+        Some(preset) => {
+            preset_theme = ColorTheme::load_with_preset(Some(preset));
+            &preset_theme
+        }
+        None => ColorTheme::load(),
+    };
+    let is_terminal = out.is_none() && io::stdout().is_terminal() && theme.enabled;
+    let dim_baseline = options.since.is_some() && is_terminal;
+    let is_ai = |line_num: u32| line_attributions.get(&line_num).is_some_and(|a| a.is_ai());
+    let mixed_hunks: HashSet<(u32, u32)> = line_to_hunk
+        .values()
+        .filter(|hunk| {
+            let lines = hunk.range.0..=hunk.range.1;
+            lines.clone().any(is_ai) && !lines.into_iter().all(is_ai)
+        })
+        .map(|hunk| hunk.range)
+        .collect();
+
+    let prompts = if options.show_prompt {
+        line_prompts(repo, file_path, &line_to_hunk, line_attributions)?
+    } else {
+        HashMap::new()
+    };
+
+    let gutters = match &options.fields {
+        Some(fields) => field_gutters(
+            fields,
+            line_authors,
+            line_attributions,
+            &line_to_hunk,
+            line_ranges,
+            options,
+        ),
+        None => HashMap::new(),
+    };
+
     for (start_line, end_line) in line_ranges {
         for line_num in *start_line..=*end_line {
             let line_index = (line_num - 1) as usize;
             let line_content = if line_index < lines.len() {
-                lines[line_index]
+                expand_tabs(lines[line_index], tab_width)
             } else {
-                ""
+                String::new()
             };
-
-            if let Some(hunk) = line_to_hunk.get(&line_num) {
-                // Determine hash length - match git blame default (7 chars)
-                let hash_len = if options.long_rev {
-                    40 // Full hash for long revision
-                } else if let Some(abbrev) = options.abbrev {
-                    abbrev as usize
-                } else {
-                    7 // Default 7 chars
-                };
-                let sha = if hash_len < hunk.commit_sha.len() {
-                    &hunk.commit_sha[..hash_len]
-                } else {
-                    &hunk.commit_sha
-                };
-
-                // Add boundary marker if this is a boundary commit
-                let boundary_marker = if hunk.is_boundary && options.blank_boundary {
-                    "^"
-                } else {
-                    ""
-                };
-                let full_sha = if hunk.is_boundary && options.blank_boundary {
-                    format!("{}{}", boundary_marker, "        ") // Empty hash for boundary
-                } else {
-                    format!("{}{}", boundary_marker, sha)
-                };
-
-                // Get the author for this line (AI authorship or original)
-                let author = line_authors.get(&line_num).unwrap_or(&hunk.original_author);
+            let line_start = output.len();
+
+            if let Some(gutter) = gutters.get(&line_num) {
+                output.push_str(&format!("({}) {}\n", gutter, line_content));
+            } else if let Some(hunk) = line_to_hunk.get(&line_num) {
+                let mut full_sha = display_sha(hunk, options);
+                if options.show_prompt {
+                    let prompt = prompts.get(&line_num).map(String::as_str).unwrap_or("");
+                    full_sha.push_str(&format!(
+                        " {:<width$}",
+                        truncate_prompt(prompt, PROMPT_GUTTER_WIDTH),
+                        width = PROMPT_GUTTER_WIDTH
+                    ));
+                }
 
                 // Format date according to options
                 let date_str = format_blame_date(hunk.author_time, &hunk.author_tz, options);
@@ -667,10 +1169,8 @@ fn output_default_format(
                 // Handle different output formats based on flags
                 let author_display = if options.suppress_author {
                     "".to_string()
-                } else if options.show_email {
-                    format!("{} <{}>", author, &hunk.author_email)
                 } else {
-                    author.to_string()
+                    display_author(line_num, hunk, line_authors, line_attributions, options)
                 };
 
                 let _filename_display = if options.show_name {
@@ -718,6 +1218,70 @@ fn output_default_format(
                     "????????", line_num, line_content
                 ));
             }
+
+            // Every format above ends in the content, so what precedes it is the gutter
+            let gutter_end = output.len() - 1 - line_content.len();
+            if let Some(width) = wrap_width {
+                let indent = output[line_start..gutter_end].chars().count()
+                    + if options.relative_to.is_some() { 2 } else { 0 };
+                let wrapped = wrap_content(&line_content, indent, width);
+                output.replace_range(gutter_end..output.len() - 1, &wrapped);
+            }
+
+            if let Some(baseline_author) = &options.relative_to {
+                let differs = line_to_hunk.get(&line_num).is_none_or(|hunk| {
+                    line_authors.get(&line_num).unwrap_or(&hunk.original_author) != baseline_author
+                });
+                output.insert_str(line_start, if differs { "* " } else { "  " });
+                if differs && is_terminal {
+                    output.insert_str(line_start, "\x1b[33m");
+                    output.insert_str(output.len() - 1, "\x1b[0m");
+                }
+            }
+
+            let is_baseline =
+                line_authors.get(&line_num).map(String::as_str) == Some(BASELINE_AUTHOR);
+            if dim_baseline && is_baseline {
+                output.insert_str(line_start, "\x1b[2m");
+                output.insert_str(output.len() - 1, "\x1b[0m");
+            } else if is_terminal && options.relative_to.is_none() {
+                let role = if is_ai(line_num) {
+                    ColorRole::AiLine
+                } else if line_to_hunk
+                    .get(&line_num)
+                    .is_some_and(|hunk| mixed_hunks.contains(&hunk.range))
+                {
+                    ColorRole::MixedLine
+                } else {
+                    ColorRole::HumanLine
+                };
+                match (theme.sgr(ColorRole::Gutter), theme.sgr(role)) {
+                    (Some(gutter), line) => {
+                        if let Some(sgr) = line {
+                            output.insert_str(output.len() - 1, "\x1b[0m");
+                            output.insert_str(gutter_end, &format!("\x1b[{}m", sgr));
+                        }
+                        output.insert_str(gutter_end, "\x1b[0m");
+                        output.insert_str(line_start, &format!("\x1b[{}m", gutter));
+                    }
+                    (None, Some(sgr)) => {
+                        output.insert_str(line_start, &format!("\x1b[{}m", sgr));
+                        output.insert_str(output.len() - 1, "\x1b[0m");
+                    }
+                    (None, None) => {}
+                }
+            }
+        }
+    }
+
+    // A single blamed line gets its whole prompt, which the gutter only had room to start
+    let selected_lines: u32 = line_ranges.iter().map(|(start, end)| end - start + 1).sum();
+    if selected_lines == 1
+        && let Some(prompt) = prompts.get(&line_ranges[0].0)
+    {
+        output.push_str("\nPrompt:\n");
+        for line in prompt.lines() {
+            output.push_str(&format!("    {}\n", line));
         }
     }
 
@@ -728,6 +1292,12 @@ fn output_default_format(
         output.push_str(stats);
     }
 
+    // Writing to --output bypasses the pager
+    if let Some(out) = out {
+        out.write_all(output.as_bytes())?;
+        return Ok(());
+    }
+
     // Output handling - respect pager environment variables
     let pager = std::env::var("GIT_PAGER")
         .or_else(|_| std::env::var("PAGER"))
@@ -767,68 +1337,1152 @@ fn output_default_format(
     Ok(())
 }
 
-fn format_blame_date(author_time: i64, author_tz: &str, options: &GitAiBlameOptions) -> String {
-    let dt = DateTime::from_timestamp(author_time, 0)
-        .unwrap_or_else(|| DateTime::from_timestamp(0, 0).unwrap());
-
-    // Parse timezone string like +0200 or -0500
-    let offset = if author_tz.len() == 5 {
-        let sign = if &author_tz[0..1] == "+" { 1 } else { -1 };
-        let hours: i32 = author_tz[1..3].parse().unwrap_or(0);
-        let mins: i32 = author_tz[3..5].parse().unwrap_or(0);
-        FixedOffset::east_opt(sign * (hours * 3600 + mins * 60))
-            .unwrap_or(FixedOffset::east_opt(0).unwrap())
+/// Abbreviated as git blame does (7 characters unless `--abbrev` or `-l`), with boundary
+/// commits blanked under `-b`
+fn display_sha(hunk: &BlameHunk, options: &GitAiBlameOptions) -> String {
+    let hash_len = if options.long_rev {
+        40
+    } else if let Some(abbrev) = options.abbrev {
+        abbrev as usize
     } else {
-        FixedOffset::east_opt(0).unwrap()
+        7
     };
+    if hunk.is_boundary && options.blank_boundary {
+        return format!("^{}", " ".repeat(8));
+    }
+    hunk.commit_sha[..hash_len.min(hunk.commit_sha.len())].to_string()
+}
 
-    let dt = offset.from_utc_datetime(&dt.naive_utc());
+/// The line's AI author, or git's when it has none. Low-confidence AI lines read "Claude?":
+/// probably the agent, but a guess. A code generator's lines read "protoc [generated]".
+fn display_author(
+    line_num: u32,
+    hunk: &BlameHunk,
+    line_authors: &HashMap<u32, String>,
+    line_attributions: &HashMap<u32, LineAttribution>,
+    options: &GitAiBlameOptions,
+) -> String {
+    let author = line_authors.get(&line_num).unwrap_or(&hunk.original_author);
+    let attribution = line_attributions.get(&line_num);
+    let author = if attribution.is_some_and(|a| a.is_low_confidence()) {
+        format!("{}?", author)
+    } else if attribution.is_some_and(|a| a.generated) {
+        format!("{} [generated]", author)
+    } else {
+        author.to_string()
+    };
+    if options.show_email {
+        format!("{} <{}>", author, &hunk.author_email)
+    } else {
+        author
+    }
+}
 
-    // Format date according to options (default: iso)
-    if let Some(fmt) = &options.date_format {
-        // TODO: support all git date formats
-        match fmt.as_str() {
-            "iso" | "iso8601" => dt.format("%Y-%m-%d %H:%M:%S %z").to_string(),
-            "short" => dt.format("%Y-%m-%d").to_string(),
-            "relative" => format!("{} seconds ago", (Utc::now().timestamp() - author_time)),
-            _ => dt.format("%Y-%m-%d %H:%M:%S %z").to_string(),
+/// The `--fields` gutter of every blamed line, each column padded to its widest value
+/// among the lines shown
+fn field_gutters(
+    fields: &[BlameField],
+    line_authors: &HashMap<u32, String>,
+    line_attributions: &HashMap<u32, LineAttribution>,
+    line_to_hunk: &HashMap<u32, BlameHunk>,
+    line_ranges: &[(u32, u32)],
+    options: &GitAiBlameOptions,
+) -> HashMap<u32, String> {
+    let mut cells: Vec<(u32, Vec<String>)> = Vec::new();
+    for (start_line, end_line) in line_ranges {
+        for line_num in *start_line..=*end_line {
+            let hunk = line_to_hunk.get(&line_num);
+            let row = fields
+                .iter()
+                .map(|field| match (field, hunk) {
+                    (BlameField::Line, _) => line_num.to_string(),
+                    (BlameField::Model, _) => line_attributions
+                        .get(&line_num)
+                        .and_then(|a| a.agent_metadata.as_ref())
+                        .map(|metadata| metadata.model.clone())
+                        .unwrap_or_else(|| "-".to_string()),
+                    (BlameField::Commit, Some(hunk)) => display_sha(hunk, options),
+                    (BlameField::Author, Some(hunk)) => {
+                        display_author(line_num, hunk, line_authors, line_attributions, options)
+                    }
+                    (BlameField::Date, Some(hunk)) => {
+                        format_blame_date(hunk.author_time, &hunk.author_tz, options)
+                    }
+                    (_, None) => "?".to_string(),
+                })
+                .collect();
+            cells.push((line_num, row));
         }
-    } else {
-        dt.format("%Y-%m-%d %H:%M:%S %z").to_string()
     }
+
+    let widths: Vec<usize> = (0..fields.len())
+        .map(|column| {
+            cells
+                .iter()
+                .map(|(_, row)| row[column].chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    cells
+        .into_iter()
+        .map(|(line_num, row)| {
+            let gutter = row
+                .iter()
+                .zip(fields.iter().zip(&widths))
+                .map(|(cell, (field, width))| match field {
+                    BlameField::Line => format!("{:>width$}", cell, width = width),
+                    _ => format!("{:<width$}", cell, width = width),
+                })
+                .collect::<Vec<_>>()
+                .join(" ");
+            (line_num, gutter)
+        })
+        .collect()
 }
 
-pub fn parse_blame_args(args: &[String]) -> Result<(String, GitAiBlameOptions), GitAiError> {
-    let mut options = GitAiBlameOptions::default();
-    let mut file_path = None;
-    let mut i = 0;
+/// Attribute uncommitted lines of a --contents buffer from the current working log. The
+/// working log numbers lines as they are on disk, so buffer lines are mapped back through a
+/// diff against the disk content; lines that only exist in the buffer stay uncommitted.
+fn overlay_uncommitted_attributions(
+    repo: &Repository,
+    file_path: &str,
+    disk_content: &str,
+    buffer: &str,
+    hunks: &[BlameHunk],
+    line_attributions: &mut HashMap<u32, LineAttribution>,
+) -> Result<(), GitAiError> {
+    let base_commit = resolve_base_commit(repo)?;
+    let working_log =
+        get_reference_as_working_log(repo, &format!("ai-working-log/{}", base_commit))
+            .unwrap_or_default();
+    let authorship = AuthorshipLog::from_working_log(&working_log);
+    let Some(file_authorship) = authorship.files.get(file_path) else {
+        return Ok(());
+    };
 
-    while i < args.len() {
-        match args[i].as_str() {
-            // Line range options
-            "-L" => {
-                if i + 1 >= args.len() {
-                    return Err(GitAiError::Generic("Missing argument for -L".to_string()));
-                }
-                let range_str = &args[i + 1];
-                if let Some((start, end)) = parse_line_range(range_str) {
-                    options.line_ranges.push((start, end));
-                } else {
-                    return Err(GitAiError::Generic(format!(
-                        "Invalid line range: {}",
-                        range_str
-                    )));
-                }
-                i += 2;
-            }
+    let disk_lines: Vec<&str> = disk_content.lines().collect();
+    let buffer_lines: Vec<&str> = buffer.lines().collect();
+    let diff = TextDiff::from_slices(&disk_lines, &buffer_lines);
+    let mut buffer_to_disk: HashMap<u32, u32> = HashMap::new();
+    for change in diff.iter_all_changes() {
+        if let (Some(old_index), Some(new_index)) = (change.old_index(), change.new_index()) {
+            buffer_to_disk.insert(new_index as u32 + 1, old_index as u32 + 1);
+        }
+    }
 
-            // Output format options
-            "--porcelain" => {
-                options.porcelain = true;
-                i += 1;
+    for hunk in hunks
+        .iter()
+        .filter(|hunk| hunk.original_author == NOT_COMMITTED_YET)
+    {
+        for line_num in hunk.range.0..=hunk.range.1 {
+            if let Some(entry) = buffer_to_disk
+                .get(&line_num)
+                .and_then(|disk_line| file_authorship.get_author_entry(*disk_line))
+            {
+                line_attributions.insert(
+                    line_num,
+                    LineAttribution {
+                        author: entry.author.clone(),
+                        agent_metadata: entry.agent_metadata.clone(),
+                        generated: entry.generated,
+                    },
+                );
             }
-            "--line-porcelain" => {
-                options.line_porcelain = true;
+        }
+    }
+    Ok(())
+}
+
+/// Replace the attribution of every line last changed at or before `since` with the
+/// baseline marker, so only lines from the current cycle carry an author
+fn mark_baseline_lines(
+    repo: &Repository,
+    since: &str,
+    hunks: &[BlameHunk],
+    line_attributions: &mut HashMap<u32, LineAttribution>,
+) -> Result<(), GitAiError> {
+    let since_oid = resolve_commit(repo, since)?.id();
+
+    for hunk in hunks {
+        let hunk_oid = git2::Oid::from_str(&hunk.commit_sha)?;
+        let is_baseline = hunk_oid == since_oid || repo.graph_descendant_of(since_oid, hunk_oid)?;
+        if !is_baseline {
+            continue;
+        }
+        for line_num in hunk.range.0..=hunk.range.1 {
+            line_attributions.insert(line_num, LineAttribution::from_git_author(BASELINE_AUTHOR));
+        }
+    }
+    Ok(())
+}
+
+/// Lines blamed per `--porcelain-v2` chunk. Each chunk is a separate libgit2 blame limited
+/// to its lines, so the first records arrive without waiting on the rest of a large file.
+const STREAM_CHUNK_LINES: u32 = 256;
+
+/// `--porcelain-v2`: attribution streamed in line order, flushed after every chunk. The
+/// format is stable; readers should skip keys they don't know, as new ones may be added.
+///
+/// ```text
+/// hunk <start> <end> <commit-sha>   inclusive line range, all zeros when uncommitted
+/// author <name>                     git's author of the commit
+/// author-time <unix seconds>
+/// attribution <name>                the agent for AI lines, otherwise the human author
+/// model <name>                      AI lines only
+/// confidence low                    low-confidence AI lines only
+///                                   (an empty line ends the record)
+/// ```
+///
+/// A record covers a run of lines sharing a commit and an attribution. After the last
+/// record comes `done <lines>`, the number of lines sent, so a reader can tell a complete
+/// stream from one cut short.
+fn stream_porcelain_v2(
+    repo: &Repository,
+    file_path: &str,
+    disk_content: &str,
+    file_content: &str,
+    line_ranges: &[(u32, u32)],
+    options: &GitAiBlameOptions,
+    out: &mut dyn Write,
+) -> Result<HashMap<u32, String>, GitAiError> {
+    let mailmap = load_mailmap(repo, !options.no_mailmap);
+    let mut line_authors = HashMap::new();
+    let mut sent = 0;
+    for (start_line, end_line) in line_ranges {
+        let mut chunk_start = *start_line;
+        while chunk_start <= *end_line {
+            let chunk_end = (chunk_start + STREAM_CHUNK_LINES - 1).min(*end_line);
+            let hunks = get_git_blame_hunks(repo, file_path, chunk_start, chunk_end, options)?;
+            let mut attributions =
+                overlay_line_attributions(repo, &hunks, file_path, mailmap.as_ref())?;
+            if options.contents.is_some() {
+                overlay_uncommitted_attributions(
+                    repo,
+                    file_path,
+                    disk_content,
+                    file_content,
+                    &hunks,
+                    &mut attributions,
+                )?;
+            }
+            if let Some(since) = &options.since {
+                mark_baseline_lines(repo, since, &hunks, &mut attributions)?;
+            }
+
+            let key = |line_num: u32| {
+                attributions.get(&line_num).map(|a| {
+                    (
+                        a.author.clone(),
+                        a.agent_metadata.as_ref().map(|m| m.model.clone()),
+                        a.is_low_confidence(),
+                        a.generated,
+                    )
+                })
+            };
+            for hunk in &hunks {
+                let (first, last) = (hunk.range.0.max(chunk_start), hunk.range.1.min(chunk_end));
+                let mut run_start = first;
+                for line_num in first..=last {
+                    if line_num != last && key(line_num) == key(line_num + 1) {
+                        continue;
+                    }
+                    writeln!(out, "hunk {} {} {}", run_start, line_num, hunk.commit_sha)?;
+                    writeln!(out, "author {}", hunk.original_author)?;
+                    writeln!(out, "author-time {}", hunk.author_time)?;
+                    let attribution = attributions.get(&run_start);
+                    let author = attribution.map_or(&hunk.original_author, |a| &a.author);
+                    writeln!(out, "attribution {}", author)?;
+                    if let Some(metadata) = attribution.and_then(|a| a.agent_metadata.as_ref()) {
+                        writeln!(out, "model {}", metadata.model)?;
+                        if !metadata.confidence.is_high() {
+                            writeln!(out, "confidence low")?;
+                        }
+                    }
+                    if attribution.is_some_and(|a| a.generated) {
+                        writeln!(out, "generated")?;
+                    }
+                    writeln!(out)?;
+                    for line in run_start..=line_num {
+                        line_authors.insert(line, author.clone());
+                    }
+                    sent += line_num - run_start + 1;
+                    run_start = line_num + 1;
+                }
+            }
+            out.flush()?;
+            chunk_start = chunk_end + 1;
+        }
+    }
+    writeln!(out, "done {}", sent)?;
+    out.flush()?;
+    Ok(line_authors)
+}
+
+/// Open `--output` for writing, creating any missing parent directories
+fn create_output_file(path: &str) -> Result<io::BufWriter<fs::File>, GitAiError> {
+    let path = Path::new(path);
+    if let Some(parent) = path.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        fs::create_dir_all(parent)?;
+    }
+    Ok(io::BufWriter::new(fs::File::create(path)?))
+}
+
+/// One JSON object per blamed line, with the git commit and git-ai attribution side by side.
+/// With `json_lines` each object goes out on its own line as soon as it is built, with no
+/// enclosing document, so a consumer never has to hold the whole file.
+fn output_json_format(
+    hunks: &[BlameHunk],
+    line_attributions: &HashMap<u32, LineAttribution>,
+    file_path: &str,
+    lines: &[&str],
+    line_ranges: &[(u32, u32)],
+    json_lines: bool,
+    out: &mut dyn Write,
+) -> Result<(), GitAiError> {
+    let mut line_to_hunk: HashMap<u32, &BlameHunk> = HashMap::new();
+    for hunk in hunks {
+        for line_num in hunk.range.0..=hunk.range.1 {
+            line_to_hunk.insert(line_num, hunk);
+        }
+    }
+
+    let mut entries = Vec::new();
+    for (start_line, end_line) in line_ranges {
+        for line_num in *start_line..=*end_line {
+            let hunk = line_to_hunk.get(&line_num);
+            let attribution = line_attributions.get(&line_num);
+            let entry = serde_json::json!({
+                "line": line_num,
+                "commit": hunk.map(|h| h.commit_sha.as_str()),
+                "author": attribution
+                    .map(|a| a.author.as_str())
+                    .or_else(|| hunk.map(|h| h.original_author.as_str())),
+                "ai": attribution.is_some_and(|a| a.is_ai()),
+                "generated": attribution.is_some_and(|a| a.generated),
+                "model": attribution
+                    .and_then(|a| a.agent_metadata.as_ref())
+                    .map(|m| m.model.as_str()),
+                "confidence": attribution
+                    .and_then(|a| a.agent_metadata.as_ref())
+                    .map(|m| m.confidence),
+                "content": lines.get((line_num - 1) as usize).copied().unwrap_or(""),
+            });
+            if json_lines {
+                writeln!(out, "{}", serde_json::to_string(&entry)?)?;
+            } else {
+                entries.push(entry);
+            }
+        }
+    }
+    if json_lines {
+        return Ok(());
+    }
+
+    let document = serde_json::json!({ "file": file_path, "lines": entries });
+    writeln!(out, "{}", serde_json::to_string_pretty(&document)?)?;
+    Ok(())
+}
+
+/// The parts of `line_ranges` whose lines are attributed to `model`: a case-insensitive
+/// substring match, or an exact one with `exact`. Ranges split around other lines.
+pub fn filter_ranges_by_model(
+    line_attributions: &HashMap<u32, LineAttribution>,
+    line_ranges: &[(u32, u32)],
+    model: &str,
+    exact: bool,
+) -> Vec<(u32, u32)> {
+    let model = model.to_lowercase();
+    let matches = |line_num: u32| {
+        line_attributions
+            .get(&line_num)
+            .and_then(|attribution| attribution.agent_metadata.as_ref())
+            .is_some_and(|metadata| {
+                let name = metadata.model.to_lowercase();
+                if exact {
+                    name == model
+                } else {
+                    name.contains(&model)
+                }
+            })
+    };
+
+    narrow_ranges(line_ranges, matches)
+}
+
+/// Which kind of authorship a line falls under for `--only`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineCategory {
+    Human,
+    Ai,
+    /// A human line in a hunk that also holds AI lines, e.g. a person's fix-up of generated code
+    Mixed,
+    /// A code generator's line (protoc, a build script), neither human nor AI
+    Generated,
+}
+
+impl LineCategory {
+    pub fn parse(value: &str) -> Result<Self, GitAiError> {
+        match value {
+            "human" => Ok(LineCategory::Human),
+            "ai" => Ok(LineCategory::Ai),
+            "mixed" => Ok(LineCategory::Mixed),
+            "generated" => Ok(LineCategory::Generated),
+            other => Err(GitAiError::Generic(format!(
+                "Invalid --only value: {}. Expected human, ai, mixed or generated",
+                other
+            ))),
+        }
+    }
+}
+
+/// A gutter column of the default output, chosen with `--fields`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlameField {
+    Commit,
+    Author,
+    /// The model behind an AI line, `-` on human lines
+    Model,
+    Date,
+    Line,
+}
+
+impl BlameField {
+    /// The gutter git blame itself shows, and what `--fields` starts from
+    pub const DEFAULT: [BlameField; 4] = [
+        BlameField::Commit,
+        BlameField::Author,
+        BlameField::Date,
+        BlameField::Line,
+    ];
+
+    /// Parse a comma-separated `--fields` list; `default` stands for [`BlameField::DEFAULT`]
+    pub fn parse_list(value: &str) -> Result<Vec<Self>, GitAiError> {
+        let mut fields = Vec::new();
+        for name in value.split(',').map(str::trim) {
+            let parsed: &[BlameField] = match name {
+                "commit" | "sha" => &[BlameField::Commit],
+                "author" => &[BlameField::Author],
+                "model" => &[BlameField::Model],
+                "date" | "timestamp" => &[BlameField::Date],
+                "line" => &[BlameField::Line],
+                "default" => &BlameField::DEFAULT,
+                other => {
+                    return Err(GitAiError::Generic(format!(
+                        "Invalid --fields value: {}. Expected commit, author, model, date or line",
+                        other
+                    )));
+                }
+            };
+            for field in parsed {
+                if fields.contains(field) {
+                    return Err(GitAiError::Generic(format!(
+                        "--fields lists {:?} more than once",
+                        field
+                    )));
+                }
+                fields.push(*field);
+            }
+        }
+        Ok(fields)
+    }
+}
+
+/// Categorize every blamed line. A line is AI when its attribution is; a human line is
+/// mixed when its hunk also has AI lines, the same split the terminal colors use.
+pub fn line_categories(
+    hunks: &[BlameHunk],
+    line_attributions: &HashMap<u32, LineAttribution>,
+) -> HashMap<u32, LineCategory> {
+    let is_ai = |line_num: u32| line_attributions.get(&line_num).is_some_and(|a| a.is_ai());
+    let mut categories = HashMap::new();
+    for hunk in hunks {
+        let lines = hunk.range.0..=hunk.range.1;
+        let mixed = lines.clone().any(is_ai);
+        for line_num in lines {
+            let generated = line_attributions
+                .get(&line_num)
+                .is_some_and(|a| a.generated);
+            let category = if is_ai(line_num) {
+                LineCategory::Ai
+            } else if generated {
+                LineCategory::Generated
+            } else if mixed {
+                LineCategory::Mixed
+            } else {
+                LineCategory::Human
+            };
+            categories.insert(line_num, category);
+        }
+    }
+    categories
+}
+
+/// The parts of `line_ranges` whose lines are in `category`. Lines outside every hunk
+/// (nothing blamed them) count as human.
+pub fn filter_ranges_by_category(
+    categories: &HashMap<u32, LineCategory>,
+    line_ranges: &[(u32, u32)],
+    category: LineCategory,
+) -> Vec<(u32, u32)> {
+    narrow_ranges(line_ranges, |line_num| {
+        categories
+            .get(&line_num)
+            .copied()
+            .unwrap_or(LineCategory::Human)
+            == category
+    })
+}
+
+/// Split `line_ranges` into the runs of lines that satisfy `matches`
+fn narrow_ranges(line_ranges: &[(u32, u32)], matches: impl Fn(u32) -> bool) -> Vec<(u32, u32)> {
+    let mut filtered: Vec<(u32, u32)> = Vec::new();
+    for (start_line, end_line) in line_ranges {
+        let mut run: Option<(u32, u32)> = None;
+        for line_num in *start_line..=*end_line {
+            if !matches(line_num) {
+                filtered.extend(run.take());
+                continue;
+            }
+            run = Some(run.map_or((line_num, line_num), |(start, _)| (start, line_num)));
+        }
+        filtered.extend(run);
+    }
+    filtered
+}
+
+/// Consecutive lines sharing an author, model and confidence
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttributionRun {
+    pub start: u32,
+    pub end: u32,
+    pub author: String,
+    pub model: Option<String>,
+    pub low_confidence: bool,
+    pub generated: bool,
+}
+
+/// Collapse the blamed ranges into runs of identical attribution. Runs never span two
+/// `-L` ranges, even adjacent ones.
+pub fn group_attribution_runs(
+    line_attributions: &HashMap<u32, LineAttribution>,
+    line_ranges: &[(u32, u32)],
+) -> Vec<AttributionRun> {
+    let mut runs: Vec<AttributionRun> = Vec::new();
+    for (start_line, end_line) in line_ranges {
+        let range_start = runs.len();
+        for line_num in *start_line..=*end_line {
+            let (author, model, low_confidence, generated) = match line_attributions.get(&line_num)
+            {
+                Some(attribution) => (
+                    attribution.author.clone(),
+                    attribution.agent_metadata.as_ref().map(|m| m.model.clone()),
+                    attribution.is_low_confidence(),
+                    attribution.generated,
+                ),
+                None => ("unknown".to_string(), None, false, false),
+            };
+            if runs.len() > range_start
+                && let Some(last) = runs.last_mut()
+                && last.author == author
+                && last.model == model
+                && last.low_confidence == low_confidence
+                && last.generated == generated
+            {
+                last.end = line_num;
+                continue;
+            }
+            runs.push(AttributionRun {
+                start: line_num,
+                end: line_num,
+                author,
+                model,
+                low_confidence,
+                generated,
+            });
+        }
+    }
+    runs
+}
+
+fn output_grouped_format(
+    line_attributions: &HashMap<u32, LineAttribution>,
+    lines: &[&str],
+    line_ranges: &[(u32, u32)],
+    options: &GitAiBlameOptions,
+    tab_width: usize,
+    out: &mut dyn Write,
+) -> Result<(), GitAiError> {
+    let line_content = |line_num: u32| {
+        expand_tabs(
+            lines.get((line_num - 1) as usize).copied().unwrap_or(""),
+            tab_width,
+        )
+    };
+    for run in group_attribution_runs(line_attributions, line_ranges) {
+        let attribution = match &run.model {
+            Some(model) if run.low_confidence => {
+                format!("{} [AI: {}, low confidence]", run.author, model)
+            }
+            Some(model) => format!("{} [AI: {}]", run.author, model),
+            None if run.generated => format!("{} [generated]", run.author),
+            None => run.author.clone(),
+        };
+        let range = if run.start == run.end {
+            run.start.to_string()
+        } else {
+            format!("{}-{}", run.start, run.end)
+        };
+        let count = run.end - run.start + 1;
+        writeln!(
+            out,
+            "{} (lines {}, {} line{})",
+            attribution,
+            range,
+            count,
+            if count == 1 { "" } else { "s" }
+        )?;
+        if options.group_edges {
+            writeln!(out, "{:>6}  {}", run.start, line_content(run.start))?;
+            if count > 2 {
+                writeln!(out, "{:>6}", "...")?;
+            }
+            if run.end != run.start {
+                writeln!(out, "{:>6}  {}", run.end, line_content(run.end))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Tab stops for the human-readable formats: `--tabwidth`, else the `tabwidth=<n>` in
+/// `core.whitespace`, else 8
+fn tab_width(repo: &Repository, options: &GitAiBlameOptions) -> usize {
+    options
+        .tabwidth
+        .or_else(|| {
+            let whitespace = repo.config().ok()?.get_string("core.whitespace").ok()?;
+            whitespace
+                .split(',')
+                .find_map(|rule| rule.trim().strip_prefix("tabwidth=")?.parse().ok())
+                .filter(|width| *width > 0)
+        })
+        .unwrap_or(8)
+}
+
+/// Replace tabs with spaces up to the next tab stop. The blame gutter shifts content right,
+/// so raw tabs would land on the terminal's stops and misalign; counting columns from the
+/// start of the content keeps mixed tab/space indentation lined up.
+pub fn expand_tabs(line: &str, tab_width: usize) -> String {
+    if !line.contains('\t') {
+        return line.to_string();
+    }
+    let mut expanded = String::with_capacity(line.len() + tab_width);
+    let mut column = 0;
+    for c in line.chars() {
+        if c == '\t' {
+            let spaces = tab_width - column % tab_width;
+            expanded.extend(std::iter::repeat_n(' ', spaces));
+            column += spaces;
+        } else {
+            expanded.push(c);
+            column += 1;
+        }
+    }
+    expanded
+}
+
+/// Content columns never shrink below this, however wide the gutter
+const MIN_WRAP_COLUMNS: usize = 20;
+
+/// Columns of the `--show-prompt` gutter cell
+const PROMPT_GUTTER_WIDTH: usize = 24;
+
+/// The prompt behind each AI line: the latest one recorded at or before the checkpoint that
+/// last wrote the line, as `search` groups an agent's edits under the prompt before them.
+/// Checkpoints come from the working log the line's commit was built from (the
+/// current one for uncommitted lines), replayed the way the authorship log was.
+pub fn line_prompts(
+    repo: &Repository,
+    file_path: &str,
+    line_to_hunk: &HashMap<u32, BlameHunk>,
+    line_attributions: &HashMap<u32, LineAttribution>,
+) -> Result<HashMap<u32, String>, GitAiError> {
+    let mut lines_by_commit: HashMap<&str, Vec<u32>> = HashMap::new();
+    for (line_num, hunk) in line_to_hunk {
+        if line_attributions
+            .get(line_num)
+            .is_some_and(LineAttribution::is_ai)
+        {
+            lines_by_commit
+                .entry(hunk.commit_sha.as_str())
+                .or_default()
+                .push(*line_num);
+        }
+    }
+
+    let mut prompts = HashMap::new();
+    for (commit_sha, line_nums) in lines_by_commit {
+        let base_commit = if commit_sha.bytes().all(|b| b == b'0') {
+            resolve_base_commit(repo)?
+        } else {
+            repo.find_commit(git2::Oid::from_str(commit_sha)?)?
+                .parent_id(0)
+                .map(|oid| oid.to_string())
+                .unwrap_or_else(|_| "initial".to_string())
+        };
+        let working_log =
+            get_reference_as_working_log(repo, &format!("ai-working-log/{}", base_commit))
+                .unwrap_or_default();
+        let writers = line_writers(&working_log, file_path);
+        for line_num in line_nums {
+            let Some(&index) = writers.get(&line_num) else {
+                continue;
+            };
+            if let Some(prompt) = working_log[..=index]
+                .iter()
+                .rev()
+                .find_map(|checkpoint| checkpoint.prompt.clone())
+            {
+                prompts.insert(line_num, prompt);
+            }
+        }
+    }
+    Ok(prompts)
+}
+
+/// Index of the checkpoint that last wrote each line of `file_path`
+fn line_writers(working_log: &[Checkpoint], file_path: &str) -> HashMap<u32, usize> {
+    let mut writers = HashMap::new();
+    for (index, checkpoint) in working_log.iter().enumerate() {
+        for entry in checkpoint
+            .entries
+            .iter()
+            .filter(|entry| entry.file == file_path)
+        {
+            for line in &entry.deleted_lines {
+                for line_num in line.start()..=line.end() {
+                    writers.remove(&line_num);
+                }
+            }
+            for line in &entry.added_lines {
+                for line_num in line.start()..=line.end() {
+                    writers.insert(line_num, index);
+                }
+            }
+        }
+    }
+    writers
+}
+
+/// A prompt on one line, cut to `width` columns with "..." when it doesn't fit
+pub fn truncate_prompt(prompt: &str, width: usize) -> String {
+    let prompt = prompt.split_whitespace().collect::<Vec<_>>().join(" ");
+    if prompt.chars().count() <= width {
+        return prompt;
+    }
+    let mut truncated: String = prompt.chars().take(width.saturating_sub(3)).collect();
+    truncated.push_str("...");
+    truncated
+}
+
+/// Break `content` that starts `indent` columns in so no row passes `width`; continuation
+/// rows are indented by `indent` blanks so they stay clear of the gutter
+pub fn wrap_content(content: &str, indent: usize, width: usize) -> String {
+    let columns = width.saturating_sub(indent).max(MIN_WRAP_COLUMNS);
+    let chars: Vec<char> = content.chars().collect();
+    if chars.len() <= columns {
+        return content.to_string();
+    }
+    chars
+        .chunks(columns)
+        .map(|row| row.iter().collect::<String>())
+        .collect::<Vec<_>>()
+        .join(&format!("\n{}", " ".repeat(indent)))
+}
+
+/// Run `git blame --porcelain` over the same ranges and report every line whose commit
+/// differs from the hunks git-ai computed
+pub fn compare_with_git(
+    repo: &Repository,
+    file_path: &str,
+    hunks: &[BlameHunk],
+    line_ranges: &[(u32, u32)],
+) -> Result<Vec<BlameDiscrepancy>, GitAiError> {
+    let repo_root = repo
+        .workdir()
+        .ok_or_else(|| GitAiError::Generic("Repository has no working directory".to_string()))?;
+
+    let mut git_ai_commits: HashMap<u32, &str> = HashMap::new();
+    for hunk in hunks {
+        for line in hunk.range.0..=hunk.range.1 {
+            git_ai_commits.insert(line, &hunk.commit_sha);
+        }
+    }
+
+    let mut discrepancies = Vec::new();
+    for (start, end) in line_ranges {
+        let output = std::process::Command::new("git")
+            .arg("-C")
+            .arg(repo_root)
+            .args(["blame", "--porcelain", "-L", &format!("{},{}", start, end)])
+            .arg("--")
+            .arg(file_path)
+            .output()?;
+        if !output.status.success() {
+            return Err(GitAiError::Generic(format!(
+                "git blame failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        for (line, git_commit) in parse_porcelain_commits(&String::from_utf8_lossy(&output.stdout))
+        {
+            let git_ai_commit = git_ai_commits.get(&line).copied();
+            if git_ai_commit != Some(git_commit.as_str()) {
+                discrepancies.push(BlameDiscrepancy {
+                    line,
+                    git_ai_commit: git_ai_commit.map(str::to_string),
+                    git_commit,
+                });
+            }
+        }
+    }
+    discrepancies.sort_by_key(|d| d.line);
+    Ok(discrepancies)
+}
+
+/// Map final line number -> commit from `git blame --porcelain` header lines
+/// (`<sha> <orig-line> <final-line> [<group-size>]`)
+fn parse_porcelain_commits(porcelain: &str) -> Vec<(u32, String)> {
+    porcelain
+        .lines()
+        .filter(|line| !line.starts_with('\t'))
+        .filter_map(|line| {
+            let parts: Vec<&str> = line.split(' ').collect();
+            let is_header = (parts.len() == 3 || parts.len() == 4)
+                // SHA-1 or SHA-256 object names
+                && matches!(parts[0].len(), 40 | 64)
+                && parts[0].chars().all(|c| c.is_ascii_hexdigit());
+            if !is_header {
+                return None;
+            }
+            let final_line = parts[2].parse::<u32>().ok()?;
+            Some((final_line, parts[0].to_string()))
+        })
+        .collect()
+}
+
+fn print_discrepancies(
+    file_path: &str,
+    discrepancies: &[BlameDiscrepancy],
+    line_ranges: &[(u32, u32)],
+) {
+    let total: u32 = line_ranges.iter().map(|(start, end)| end - start + 1).sum();
+    if discrepancies.is_empty() {
+        println!("{}: all {} line(s) match git blame", file_path, total);
+        return;
+    }
+
+    println!(
+        "{}: {} of {} line(s) differ from git blame",
+        file_path,
+        discrepancies.len(),
+        total
+    );
+    for discrepancy in discrepancies {
+        let git_ai_commit = discrepancy
+            .git_ai_commit
+            .as_deref()
+            .map(|sha| &sha[..sha.len().min(8)])
+            .unwrap_or("(none)");
+        println!(
+            "  line {}: git-ai {}  git {}",
+            discrepancy.line,
+            git_ai_commit,
+            &discrepancy.git_commit[..8]
+        );
+    }
+}
+
+fn output_web_format(
+    blame_hunks: &[BlameHunk],
+    line_attributions: &HashMap<u32, LineAttribution>,
+    file_path: &str,
+    lines: &[&str],
+    line_ranges: &[(u32, u32)],
+    options: &GitAiBlameOptions,
+) -> Result<(), GitAiError> {
+    let mut line_to_hunk: HashMap<u32, &BlameHunk> = HashMap::new();
+    for hunk in blame_hunks {
+        for line_num in hunk.range.0..=hunk.range.1 {
+            line_to_hunk.insert(line_num, hunk);
+        }
+    }
+
+    let mut rows = String::new();
+    let mut ai_lines = 0;
+    let mut human_lines = 0;
+    let mut author_counts: BTreeMap<&str, u32> = BTreeMap::new();
+
+    for (start_line, end_line) in line_ranges {
+        for line_num in *start_line..=*end_line {
+            let line_content = lines.get((line_num - 1) as usize).copied().unwrap_or("");
+            let attribution = line_attributions.get(&line_num);
+            let is_ai = attribution.is_some_and(|a| a.is_ai());
+            let author = attribution.map(|a| a.author.as_str()).unwrap_or("unknown");
+            let sha = line_to_hunk
+                .get(&line_num)
+                .map(|h| &h.commit_sha[..7.min(h.commit_sha.len())])
+                .unwrap_or("");
+
+            if is_ai {
+                ai_lines += 1;
+            } else {
+                human_lines += 1;
+            }
+            *author_counts.entry(author).or_insert(0) += 1;
+
+            let title = match attribution.and_then(|a| a.agent_metadata.as_ref()) {
+                Some(metadata) if !metadata.confidence.is_high() => {
+                    format!("{} ({}, low confidence)", author, metadata.model)
+                }
+                Some(metadata) => format!("{} ({})", author, metadata.model),
+                None => author.to_string(),
+            };
+            rows.push_str(&format!(
+                "<tr class=\"{}\"><td class=\"num\">{}</td><td class=\"sha\">{}</td><td class=\"author\" title=\"{}\">{}</td><td class=\"code\">{}</td></tr>\n",
+                if is_ai { "ai" } else { "human" },
+                line_num,
+                sha,
+                escape_html(&title),
+                escape_html(author),
+                highlight_code(line_content)
+            ));
+        }
+    }
+
+    let total = ai_lines + human_lines;
+    let percent = |n: u32| {
+        if total == 0 {
+            0.0
+        } else {
+            n as f64 * 100.0 / total as f64
+        }
+    };
+    let authors_summary = author_counts
+        .iter()
+        .map(|(author, count)| format!("<li>{} &mdash; {} lines</li>", escape_html(author), count))
+        .collect::<Vec<_>>()
+        .join("");
+
+    let html = format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>git-ai blame: {file}</title>
+<style>
+body {{ font-family: -apple-system, sans-serif; margin: 2em; color: #222; }}
+table {{ border-collapse: collapse; width: 100%; font-family: ui-monospace, Menlo, monospace; font-size: 13px; }}
+td {{ padding: 0 8px; white-space: pre; vertical-align: top; }}
+td.num, td.sha {{ color: #888; text-align: right; }}
+td.author {{ color: #555; max-width: 16em; overflow: hidden; text-overflow: ellipsis; }}
+tr.ai {{ background: #f3e8ff; }}
+tr.ai td.author {{ color: #7c3aed; }}
+tr.human {{ background: #ffffff; }}
+.legend span {{ display: inline-block; padding: 2px 10px; margin-right: 8px; border: 1px solid #ddd; }}
+.legend .ai {{ background: #f3e8ff; }}
+.legend .human {{ background: #ffffff; }}
+.str {{ color: #0a7d2c; }}
+.cmt {{ color: #8a8a8a; font-style: italic; }}
+</style>
+</head>
+<body>
+<h1>{file}</h1>
+<p class="legend"><span class="ai">AI</span><span class="human">Human</span></p>
+<p><strong>{total}</strong> lines &middot; AI {ai} ({ai_pct:.0}%) &middot; Human {human} ({human_pct:.0}%)</p>
+<ul>{authors}</ul>
+<table>
+{rows}</table>
+</body>
+</html>
+"#,
+        file = escape_html(file_path),
+        total = total,
+        ai = ai_lines,
+        ai_pct = percent(ai_lines),
+        human = human_lines,
+        human_pct = percent(human_lines),
+        authors = authors_summary,
+        rows = rows
+    );
+
+    let safe_name: String = file_path
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let html_path = match &options.output {
+        Some(path) => {
+            let mut file = create_output_file(path)?;
+            file.write_all(html.as_bytes())?;
+            file.flush()?;
+            std::path::PathBuf::from(path)
+        }
+        None => {
+            // A random name that is created exclusively, so nobody can plant the file first
+            let (mut file, path) = tempfile::Builder::new()
+                .prefix(&format!("git-ai-blame-{}-", safe_name))
+                .suffix(".html")
+                .tempfile()?
+                .keep()
+                .map_err(|e| e.error)?;
+            file.write_all(html.as_bytes())?;
+            file.flush()?;
+            path
+        }
+    };
+
+    if options.no_open {
+        println!("{}", html_path.display());
+    } else {
+        open_in_browser(&html_path)?;
+        eprintln!("Opened {}", html_path.display());
+    }
+    Ok(())
+}
+
+fn open_in_browser(path: &Path) -> Result<(), GitAiError> {
+    let opener = if cfg!(target_os = "macos") {
+        "open"
+    } else if cfg!(target_os = "windows") {
+        "explorer"
+    } else {
+        "xdg-open"
+    };
+    std::process::Command::new(opener)
+        .arg(path)
+        .spawn()
+        .map_err(|e| {
+            GitAiError::Generic(format!(
+                "Failed to open browser with {}: {}. Use --no-open to print the path instead",
+                opener, e
+            ))
+        })?;
+    Ok(())
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Lightweight highlighting: string literals and trailing line comments
+fn highlight_code(line: &str) -> String {
+    let mut out = String::new();
+    let mut chars = line.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let rest = &line[i..];
+        if rest.starts_with("//") || (rest.starts_with('#') && line[..i].trim().is_empty()) {
+            out.push_str(&format!("<span class=\"cmt\">{}</span>", escape_html(rest)));
+            return out;
+        }
+        if c == '"' || c == '\'' {
+            let mut literal = String::from(c);
+            let mut escaped = false;
+            for (_, next) in chars.by_ref() {
+                literal.push(next);
+                if escaped {
+                    escaped = false;
+                } else if next == '\\' {
+                    escaped = true;
+                } else if next == c {
+                    break;
+                }
+            }
+            out.push_str(&format!(
+                "<span class=\"str\">{}</span>",
+                escape_html(&literal)
+            ));
+            continue;
+        }
+        out.push_str(&escape_html(&c.to_string()));
+    }
+    out
+}
+
+fn format_blame_date(author_time: i64, author_tz: &str, options: &GitAiBlameOptions) -> String {
+    let dt = DateTime::from_timestamp(author_time, 0)
+        .unwrap_or_else(|| DateTime::from_timestamp(0, 0).unwrap());
+
+    // Parse timezone string like +0200 or -0500
+    let offset = if author_tz.len() == 5 {
+        let sign = if &author_tz[0..1] == "+" { 1 } else { -1 };
+        let hours: i32 = author_tz[1..3].parse().unwrap_or(0);
+        let mins: i32 = author_tz[3..5].parse().unwrap_or(0);
+        FixedOffset::east_opt(sign * (hours * 3600 + mins * 60))
+            .unwrap_or(FixedOffset::east_opt(0).unwrap())
+    } else {
+        FixedOffset::east_opt(0).unwrap()
+    };
+
+    let dt = offset.from_utc_datetime(&dt.naive_utc());
+
+    // Format date according to options (default: iso)
+    if let Some(fmt) = &options.date_format {
+        // TODO: support all git date formats
+        match fmt.as_str() {
+            "iso" | "iso8601" => dt.format("%Y-%m-%d %H:%M:%S %z").to_string(),
+            "short" => dt.format("%Y-%m-%d").to_string(),
+            "relative" => format!("{} seconds ago", (Utc::now().timestamp() - author_time)),
+            _ => dt.format("%Y-%m-%d %H:%M:%S %z").to_string(),
+        }
+    } else {
+        dt.format("%Y-%m-%d %H:%M:%S %z").to_string()
+    }
+}
+
+pub fn parse_blame_args(args: &[String]) -> Result<(String, GitAiBlameOptions), GitAiError> {
+    let mut options = GitAiBlameOptions::default();
+    let mut file_path = None;
+    let mut i = 0;
+
+    while i < args.len() {
+        match args[i].as_str() {
+            // Line range options
+            "-L" => {
+                if i + 1 >= args.len() {
+                    return Err(GitAiError::Generic("Missing argument for -L".to_string()));
+                }
+                let range_str = &args[i + 1];
+                if let Some((start, end)) = parse_line_range(range_str) {
+                    options.line_ranges.push((start, end));
+                } else {
+                    return Err(GitAiError::Generic(format!(
+                        "Invalid line range: {}",
+                        range_str
+                    )));
+                }
+                i += 2;
+            }
+
+            // Output format options
+            "--porcelain" => {
+                options.porcelain = true;
+                i += 1;
+            }
+            "--line-porcelain" => {
+                options.line_porcelain = true;
                 options.porcelain = true; // Implies --porcelain
                 i += 1;
             }
@@ -836,6 +2490,10 @@ pub fn parse_blame_args(args: &[String]) -> Result<(String, GitAiBlameOptions),
                 options.incremental = true;
                 i += 1;
             }
+            "--porcelain-v2" => {
+                options.porcelain_v2 = true;
+                i += 1;
+            }
             "-f" | "--show-name" => {
                 options.show_name = true;
                 i += 1;
@@ -949,6 +2607,17 @@ pub fn parse_blame_args(args: &[String]) -> Result<(String, GitAiBlameOptions),
                 options.color_by_age = true;
                 i += 1;
             }
+            "--theme" => {
+                match args.get(i + 1).and_then(|value| ThemePreset::parse(value)) {
+                    Some(theme) => options.theme = Some(theme),
+                    None => {
+                        return Err(GitAiError::Generic(
+                            "--theme requires dark, light, high-contrast or mono".to_string(),
+                        ));
+                    }
+                }
+                i += 2;
+            }
 
             // Progress options
             "--progress" => {
@@ -992,6 +2661,17 @@ pub fn parse_blame_args(args: &[String]) -> Result<(String, GitAiBlameOptions),
                 options.first_parent = true;
                 i += 1;
             }
+            "--no-merges" => {
+                options.no_merges = true;
+                i += 1;
+            }
+            "--at" => {
+                if i + 1 >= args.len() {
+                    return Err(GitAiError::Generic("Missing argument for --at".to_string()));
+                }
+                options.revision = Some(args[i + 1].clone());
+                i += 2;
+            }
 
             // Encoding
             "--encoding" => {
@@ -1004,6 +2684,162 @@ pub fn parse_blame_args(args: &[String]) -> Result<(String, GitAiBlameOptions),
                 i += 2;
             }
 
+            // git-ai output options
+            "--web" => {
+                options.web = true;
+                i += 1;
+            }
+            "--no-open" => {
+                options.no_open = true;
+                i += 1;
+            }
+            "--json" => {
+                options.json = true;
+                i += 1;
+            }
+            "--json-lines" => {
+                options.json_lines = true;
+                i += 1;
+            }
+            "--output" => {
+                if i + 1 >= args.len() {
+                    return Err(GitAiError::Generic(
+                        "Missing argument for --output".to_string(),
+                    ));
+                }
+                options.output = Some(args[i + 1].clone());
+                i += 2;
+            }
+            "--since" => {
+                if i + 1 >= args.len() {
+                    return Err(GitAiError::Generic(
+                        "Missing argument for --since".to_string(),
+                    ));
+                }
+                options.since = Some(args[i + 1].clone());
+                i += 2;
+            }
+            "--no-mailmap" => {
+                options.no_mailmap = true;
+                i += 1;
+            }
+            "--relative-to" => {
+                if i + 1 >= args.len() {
+                    return Err(GitAiError::Generic(
+                        "Missing argument for --relative-to".to_string(),
+                    ));
+                }
+                options.relative_to = Some(args[i + 1].clone());
+                i += 2;
+            }
+            "--model" | "--model-exact" => {
+                if i + 1 >= args.len() {
+                    return Err(GitAiError::Generic(format!(
+                        "Missing argument for {}",
+                        args[i]
+                    )));
+                }
+                options.model = Some(args[i + 1].clone());
+                options.model_exact = args[i] == "--model-exact";
+                i += 2;
+            }
+            "--only" => {
+                let Some(value) = args.get(i + 1) else {
+                    return Err(GitAiError::Generic(
+                        "Missing argument for --only".to_string(),
+                    ));
+                };
+                options.only = Some(LineCategory::parse(value)?);
+                i += 2;
+            }
+            "--tabwidth" => {
+                match args
+                    .get(i + 1)
+                    .and_then(|value| value.parse::<usize>().ok())
+                {
+                    Some(width) if width > 0 => options.tabwidth = Some(width),
+                    _ => {
+                        return Err(GitAiError::Generic(
+                            "--tabwidth requires a positive number of columns".to_string(),
+                        ));
+                    }
+                }
+                i += 2;
+            }
+            "--fields" => {
+                let Some(value) = args.get(i + 1) else {
+                    return Err(GitAiError::Generic(
+                        "Missing argument for --fields".to_string(),
+                    ));
+                };
+                options.fields = Some(BlameField::parse_list(value)?);
+                i += 2;
+            }
+            "--group-hunks" => {
+                options.group_hunks = true;
+                i += 1;
+            }
+            "--wrap" => {
+                options.wrap = true;
+                i += 1;
+            }
+            "--show-prompt" => {
+                options.show_prompt = true;
+                i += 1;
+            }
+            "--group-edges" => {
+                options.group_edges = true;
+                i += 1;
+            }
+            "--compare-with-git" => {
+                options.compare_with_git = true;
+                i += 1;
+            }
+            "--compare-with" => {
+                if args.get(i + 1).map(String::as_str) != Some("git") {
+                    return Err(GitAiError::Generic(
+                        "--compare-with only supports 'git'".to_string(),
+                    ));
+                }
+                options.compare_with_git = true;
+                i += 2;
+            }
+            "--compare-threshold" => {
+                if i + 1 >= args.len() {
+                    return Err(GitAiError::Generic(
+                        "Missing argument for --compare-threshold".to_string(),
+                    ));
+                }
+                if let Ok(n) = args[i + 1].parse::<u32>() {
+                    options.compare_threshold = Some(n);
+                } else {
+                    return Err(GitAiError::Generic(
+                        "Invalid number for --compare-threshold".to_string(),
+                    ));
+                }
+                i += 2;
+            }
+
+            "--count" => {
+                options.count = true;
+                i += 1;
+            }
+            "--threshold" => {
+                let threshold = args
+                    .get(i + 1)
+                    .and_then(|value| value.trim_end_matches('%').parse::<f64>().ok())
+                    .filter(|threshold| (0.0..=100.0).contains(threshold));
+                match threshold {
+                    Some(threshold) => options.threshold = Some(threshold),
+                    None => {
+                        return Err(GitAiError::Generic(
+                            "--threshold requires a percentage from 0 to 100".to_string(),
+                        ));
+                    }
+                }
+                i += 2;
+            }
+
             // File path (non-option argument)
             arg if !arg.starts_with('-') => {
                 if file_path.is_none() {
@@ -1026,6 +2862,121 @@ pub fn parse_blame_args(args: &[String]) -> Result<(String, GitAiBlameOptions),
     let file_path =
         file_path.ok_or_else(|| GitAiError::Generic("No file path specified".to_string()))?;
 
+    if options.revision.is_some() && options.contents_file.is_some() {
+        return Err(GitAiError::Generic(
+            "--at cannot be combined with --contents".to_string(),
+        ));
+    }
+    if options.json && options.json_lines {
+        return Err(GitAiError::Generic(
+            "--json and --json-lines are alternative formats; pick one".to_string(),
+        ));
+    }
+    if options.relative_to.is_some()
+        && (options.porcelain_v2
+            || options.json
+            || options.json_lines
+            || options.porcelain
+            || options.line_porcelain
+            || options.incremental
+            || options.group_hunks
+            || options.web)
+    {
+        return Err(GitAiError::Generic(
+            "--relative-to applies to the default blame output".to_string(),
+        ));
+    }
+    if options.porcelain_v2
+        && (options.json
+            || options.json_lines
+            || options.porcelain
+            || options.line_porcelain
+            || options.incremental
+            || options.group_hunks
+            || options.web
+            || options.model.is_some()
+            || options.only.is_some()
+            || options.compare_with_git)
+    {
+        return Err(GitAiError::Generic(
+            "--porcelain-v2 streams every line and cannot be combined with other output formats, --model or --only"
+                .to_string(),
+        ));
+    }
+    if options.fields.is_some()
+        && (options.porcelain_v2
+            || options.json
+            || options.json_lines
+            || options.porcelain
+            || options.line_porcelain
+            || options.incremental
+            || options.group_hunks
+            || options.web)
+    {
+        return Err(GitAiError::Generic(
+            "--fields applies to the default blame output".to_string(),
+        ));
+    }
+    if options.wrap
+        && (options.porcelain_v2
+            || options.json
+            || options.json_lines
+            || options.porcelain
+            || options.line_porcelain
+            || options.incremental
+            || options.group_hunks
+            || options.web)
+    {
+        return Err(GitAiError::Generic(
+            "--wrap applies to the default blame output".to_string(),
+        ));
+    }
+    if options.show_prompt
+        && (options.porcelain_v2
+            || options.json
+            || options.json_lines
+            || options.porcelain
+            || options.line_porcelain
+            || options.incremental
+            || options.group_hunks
+            || options.web
+            || options.fields.is_some())
+    {
+        return Err(GitAiError::Generic(
+            "--show-prompt applies to the default blame output".to_string(),
+        ));
+    }
+    if options.count
+        && (options.porcelain_v2
+            || options.json
+            || options.json_lines
+            || options.porcelain
+            || options.line_porcelain
+            || options.incremental
+            || options.group_hunks
+            || options.web
+            || options.fields.is_some()
+            || options.wrap
+            || options.show_prompt
+            || options.only.is_some()
+            || options.compare_with_git)
+    {
+        return Err(GitAiError::Generic(
+            "--count prints only the AI percentage and cannot be combined with other output formats, --only or --compare-with-git"
+                .to_string(),
+        ));
+    }
+    if options.threshold.is_some() && !options.count {
+        return Err(GitAiError::Generic(
+            "--threshold requires --count".to_string(),
+        ));
+    }
+    if options.group_edges && !options.group_hunks {
+        return Err(GitAiError::Generic(
+            "--group-edges requires --group-hunks".to_string(),
+        ));
+    }
+
     Ok((file_path, options))
 }
 
@@ -0,0 +1,374 @@
+use crate::error::GitAiError;
+use crate::git::notes::remove_note;
+use crate::git::refs::{
+    authorship_namespace, authorship_ref_name, get_reference_as_authorship_log, put_reference,
+};
+use crate::log_fmt::authorship_log::{AuthorEntry, AuthorshipLog, LineRange};
+use crate::log_fmt::working_log::Confidence;
+use git2::{Oid, Repository};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use std::process::Command;
+
+/// Where `import` fetches a bundle's authorship refs before reconciling them with the local
+/// ones; emptied again once it has
+const IMPORT_STAGING: &str = "refs/ai-import";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    GitBundle,
+}
+
+impl ExportFormat {
+    pub fn parse(value: &str) -> Result<Self, GitAiError> {
+        match value {
+            "git-bundle" => Ok(ExportFormat::GitBundle),
+            other => Err(GitAiError::Generic(format!(
+                "Unknown export format: {}. Expected git-bundle",
+                other
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportOptions {
+    pub format: ExportFormat,
+    pub output: String,
+}
+
+/// Parse `export [--format git-bundle] --output <file>`
+pub fn parse_export_args(args: &[String]) -> Result<ExportOptions, GitAiError> {
+    let mut format = ExportFormat::GitBundle;
+    let mut output = None;
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--format" | "--output" | "-o" => {
+                let Some(value) = args.get(i + 1) else {
+                    return Err(GitAiError::Generic(format!(
+                        "Missing argument for {}",
+                        args[i]
+                    )));
+                };
+                if args[i] == "--format" {
+                    format = ExportFormat::parse(value)?;
+                } else {
+                    output = Some(value.clone());
+                }
+                i += 2;
+            }
+            _ => {
+                return Err(GitAiError::Generic(format!("Unknown option: {}", args[i])));
+            }
+        }
+    }
+
+    let Some(output) = output else {
+        return Err(GitAiError::Generic(
+            "export requires --output <file>".to_string(),
+        ));
+    };
+    Ok(ExportOptions { format, output })
+}
+
+/// Write every authorship log, plus the local branches holding the commits they describe,
+/// to a git bundle. The result is an ordinary bundle: `git bundle verify` and `git clone`
+/// read it, and `git-ai import` pulls the authorship refs back out. Returns the number of
+/// authorship logs exported.
+pub fn export(repo: &Repository, options: &ExportOptions) -> Result<usize, GitAiError> {
+    let namespace = authorship_namespace(repo);
+    let logs = count_refs(repo, &namespace)?;
+    if logs == 0 {
+        return Err(GitAiError::Generic(format!(
+            "No authorship logs under refs/{} to export",
+            namespace
+        )));
+    }
+
+    // git runs from the work tree root, so pin the path to where the user ran git-ai
+    let output = std::path::absolute(&options.output)?;
+    match options.format {
+        ExportFormat::GitBundle => run_git(
+            repo,
+            &[
+                "bundle",
+                "create",
+                &output.to_string_lossy(),
+                &format!("--glob=refs/{}/*", namespace),
+                "--branches",
+            ],
+        )?,
+    }
+    Ok(logs)
+}
+
+/// How `import` reconciles a commit's incoming authorship log with one already here, line
+/// by line. Lines only one side attributes keep that attribution whatever the strategy.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MergeStrategy {
+    /// Where both sides attribute a line, the local attribution stays
+    PreferLocal,
+    /// Where both sides attribute a line, the incoming attribution replaces the local one
+    PreferIncoming,
+    /// The side more sure of who wrote the line wins: a human's line or an AI line recorded
+    /// with high confidence beats an AI line recorded with low confidence. Ties stay local.
+    #[default]
+    HighestConfidence,
+    /// A line either side credits to an agent stays AI, so no AI attribution is lost. When
+    /// both credit an agent, or neither does, the local attribution stays.
+    Union,
+}
+
+impl MergeStrategy {
+    pub fn parse(value: &str) -> Result<Self, GitAiError> {
+        match value {
+            "prefer-local" => Ok(MergeStrategy::PreferLocal),
+            "prefer-incoming" => Ok(MergeStrategy::PreferIncoming),
+            "highest-confidence" => Ok(MergeStrategy::HighestConfidence),
+            "union" => Ok(MergeStrategy::Union),
+            other => Err(GitAiError::Generic(format!(
+                "Unknown merge strategy: {}. Expected prefer-local, prefer-incoming, highest-confidence or union",
+                other
+            ))),
+        }
+    }
+
+    /// Whether the incoming attribution of a line both sides attribute wins
+    fn takes_incoming(self, local: &AuthorEntry, incoming: &AuthorEntry) -> bool {
+        // A human's line is as certain as it gets
+        let confidence = |entry: &AuthorEntry| {
+            entry
+                .agent_metadata
+                .as_ref()
+                .map_or(Confidence::High, |metadata| metadata.confidence)
+        };
+        match self {
+            MergeStrategy::PreferLocal => false,
+            MergeStrategy::PreferIncoming => true,
+            MergeStrategy::HighestConfidence => confidence(incoming) > confidence(local),
+            MergeStrategy::Union => {
+                incoming.agent_metadata.is_some() && local.agent_metadata.is_none()
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportOptions {
+    pub bundle: String,
+    pub merge_strategy: MergeStrategy,
+}
+
+/// Parse `import [--merge-strategy <strategy>] <bundle>`
+pub fn parse_import_args(args: &[String]) -> Result<ImportOptions, GitAiError> {
+    let mut bundle = None;
+    let mut merge_strategy = MergeStrategy::default();
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--merge-strategy" => {
+                let Some(value) = args.get(i + 1) else {
+                    return Err(GitAiError::Generic(
+                        "Missing argument for --merge-strategy".to_string(),
+                    ));
+                };
+                merge_strategy = MergeStrategy::parse(value)?;
+                i += 2;
+            }
+            arg if arg.starts_with('-') => {
+                return Err(GitAiError::Generic(format!("Unknown option: {}", arg)));
+            }
+            arg => {
+                if bundle.replace(arg.to_string()).is_some() {
+                    return Err(GitAiError::Generic(
+                        "import requires exactly one bundle file".to_string(),
+                    ));
+                }
+                i += 1;
+            }
+        }
+    }
+
+    let Some(bundle) = bundle else {
+        return Err(GitAiError::Generic(
+            "import requires exactly one bundle file".to_string(),
+        ));
+    };
+    Ok(ImportOptions {
+        bundle,
+        merge_strategy,
+    })
+}
+
+/// What an import changed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ImportSummary {
+    /// Logs for commits that had none here
+    pub added: usize,
+    /// Local logs the incoming ones changed under the merge strategy
+    pub merged: usize,
+    /// Authorship logs present afterwards
+    pub total: usize,
+}
+
+/// Fetch the authorship refs out of a bundle written by `export`. Commits are left to git:
+/// `git fetch <bundle> <branch>` brings in any the repository doesn't have yet. A commit
+/// that already has a log here gets the two reconciled under `--merge-strategy`.
+pub fn import(repo: &Repository, options: &ImportOptions) -> Result<ImportSummary, GitAiError> {
+    if !Path::new(&options.bundle).is_file() {
+        return Err(GitAiError::Generic(format!(
+            "No such bundle: {}",
+            options.bundle
+        )));
+    }
+    let bundle = std::path::absolute(&options.bundle)?;
+    let bundle = bundle.to_string_lossy();
+    run_git(repo, &["bundle", "verify", "--quiet", &bundle])?;
+
+    let namespace = authorship_namespace(repo);
+    run_git(
+        repo,
+        &[
+            "fetch",
+            "--quiet",
+            &bundle,
+            &format!("+refs/{}/*:{}/*", namespace, IMPORT_STAGING),
+        ],
+    )?;
+
+    let mut summary = ImportSummary::default();
+    let staged: Vec<(String, String)> = repo
+        .references_glob(&format!("{}/*", IMPORT_STAGING))?
+        .flatten()
+        .filter_map(|reference| {
+            let name = reference.name()?.to_string();
+            let commit = name
+                .strip_prefix(&format!("{}/", IMPORT_STAGING))?
+                .to_string();
+            Some((name, commit))
+        })
+        .collect();
+    for (staged_ref, commit) in staged {
+        let local_ref = authorship_ref_name(repo, &commit);
+        let incoming_ref = staged_ref.trim_start_matches("refs/");
+        match get_reference_as_authorship_log(repo, &local_ref) {
+            Ok(local) => {
+                let incoming = get_reference_as_authorship_log(repo, incoming_ref)?;
+                let merged = merge_authorship_logs(&local, &incoming, options.merge_strategy);
+                if serde_json::to_value(&merged)? != serde_json::to_value(&local)? {
+                    let merged_json = if cfg!(debug_assertions) {
+                        serde_json::to_string_pretty(&merged)?
+                    } else {
+                        serde_json::to_string(&merged)?
+                    };
+                    put_reference(
+                        repo,
+                        &local_ref,
+                        &merged_json,
+                        &format!("AI authorship merged from {}", options.bundle),
+                    )?;
+                    remove_note(repo, Oid::from_str(&commit)?)?;
+                    summary.merged += 1;
+                }
+            }
+            Err(_) => {
+                let blob = repo.find_reference(&staged_ref)?.peel_to_blob()?.id();
+                repo.reference(
+                    &format!("refs/{}", local_ref),
+                    blob,
+                    true,
+                    &format!("AI authorship imported from {}", options.bundle),
+                )?;
+                remove_note(repo, Oid::from_str(&commit)?)?;
+                summary.added += 1;
+            }
+        }
+        repo.find_reference(&staged_ref)?.delete()?;
+    }
+    summary.total = count_refs(repo, &namespace)?;
+    Ok(summary)
+}
+
+/// Reconcile two authorship logs for the same commit, line by line, under `strategy`
+pub fn merge_authorship_logs(
+    local: &AuthorshipLog,
+    incoming: &AuthorshipLog,
+    strategy: MergeStrategy,
+) -> AuthorshipLog {
+    let mut merged = AuthorshipLog::new();
+    let paths: BTreeSet<&String> = local.files.keys().chain(incoming.files.keys()).collect();
+    for path in paths {
+        let local_lines = line_entries(local, path);
+        let incoming_lines = line_entries(incoming, path);
+
+        // Winning lines, grouped by the entry they come from so its metadata goes along
+        let mut winners: BTreeMap<(bool, usize), Vec<u32>> = BTreeMap::new();
+        let lines: BTreeSet<u32> = local_lines
+            .keys()
+            .chain(incoming_lines.keys())
+            .copied()
+            .collect();
+        for line in lines {
+            let from_incoming = match (local_lines.get(&line), incoming_lines.get(&line)) {
+                (Some(local), Some(incoming)) => strategy.takes_incoming(local.1, incoming.1),
+                (None, Some(_)) => true,
+                _ => false,
+            };
+            let (index, _) = if from_incoming {
+                incoming_lines[&line]
+            } else {
+                local_lines[&line]
+            };
+            winners
+                .entry((from_incoming, index))
+                .or_default()
+                .push(line);
+        }
+
+        let file = merged.get_or_create_file(path);
+        for ((from_incoming, index), lines) in winners {
+            let source = if from_incoming { incoming } else { local };
+            let entry = &source.files[path].authors[index];
+            file.add_lines_like(entry, &lines);
+        }
+    }
+    merged.files.retain(|_, file| !file.is_empty());
+    merged
+}
+
+/// Each attributed line of `path` and the entry attributing it. As in blame, the last
+/// entry covering a line is the one that counts.
+fn line_entries<'a>(log: &'a AuthorshipLog, path: &str) -> BTreeMap<u32, (usize, &'a AuthorEntry)> {
+    let mut lines = BTreeMap::new();
+    if let Some(file) = log.files.get(path) {
+        for (index, entry) in file.authors.iter().enumerate() {
+            for line in entry.lines.iter().flat_map(LineRange::expand) {
+                lines.insert(line, (index, entry));
+            }
+        }
+    }
+    lines
+}
+
+fn count_refs(repo: &Repository, namespace: &str) -> Result<usize, GitAiError> {
+    Ok(repo
+        .references_glob(&format!("refs/{}/*", namespace))?
+        .flatten()
+        .count())
+}
+
+fn run_git(repo: &Repository, args: &[&str]) -> Result<(), GitAiError> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(repo.workdir().unwrap_or_else(|| repo.path()))
+        .output()?;
+    if !output.status.success() {
+        return Err(GitAiError::Generic(format!(
+            "git {} failed: {}",
+            args[0],
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(())
+}
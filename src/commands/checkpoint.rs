@@ -1,36 +1,74 @@
 use crate::error::GitAiError;
-use crate::git::refs::{get_reference, put_reference};
-use crate::log_fmt::working_log::{AgentMetadata, Checkpoint, Line, WorkingLogEntry};
-use crate::utils::debug_log;
-use git2::{Repository, StatusOptions};
+use crate::git::attributes::{Attributes, ForcedAttribution};
+use crate::git::notes::remove_note;
+use crate::git::refs::{
+    authorship_ref_name, get_reference, get_reference_as_working_log, list_working_logs,
+    put_reference,
+};
+use crate::git::revspec::resolve_commit;
+use crate::log_fmt::authorship_log::AuthorshipLog;
+use crate::log_fmt::working_log::{AgentMetadata, Checkpoint, Confidence, Line, WorkingLogEntry};
+use crate::utils::{debug_log, glob_to_regex};
+use git2::{ObjectType, Oid, Repository, StatusOptions};
 use sha2::{Digest, Sha256};
 use similar::{ChangeTag, TextDiff};
 use std::collections::HashMap;
 use std::path::Path;
+use std::process::{Command, ExitStatus, Stdio};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CheckpointOptions {
+    /// Print the working log (with debug logging on) instead of recording a checkpoint
+    pub show_working_log: bool,
+    /// Start a fresh working log, diffing against the base commit
+    pub reset: bool,
+    /// Skip the summary on stderr
+    pub quiet: bool,
+    /// The agent's model; a checkpoint with one is an AI checkpoint
+    pub model: Option<String>,
+    /// The human working with the agent, recorded in the agent metadata
+    pub human_author: Option<String>,
+    pub confidence: Confidence,
+    /// Prompt that led to the edits, from `--message` or an editor hook
+    pub prompt: Option<String>,
+    pub scope: ChangeScope,
+    /// Also store the changed files' full content
+    pub snapshot: bool,
+    /// Files larger than this many bytes are recorded at file level (see `resolve_max_file_size`)
+    pub max_file_size: Option<u64>,
+    /// The edits are a code generator's
+    pub generated: bool,
+    /// `author` as the raw `user.name` bytes, when it was decoded from them
+    pub author_raw: Option<Vec<u8>>,
+}
 
 pub fn run(
     repo: &Repository,
     author: &str,
-    show_working_log: bool,
-    reset: bool,
-    quiet: bool,
-    model: Option<&str>,
-    human_author: Option<&str>,
+    options: &CheckpointOptions,
 ) -> Result<(usize, usize, usize), GitAiError> {
-    // Robustly handle zero-commit repos
-    let base_commit = match repo.head() {
-        Ok(head) => {
-            if let Some(oid) = head.target() {
-                oid.to_string()
-            } else {
-                "initial".to_string()
-            }
-        }
-        Err(_) => "initial".to_string(),
-    };
+    let CheckpointOptions {
+        show_working_log,
+        reset,
+        quiet,
+        confidence,
+        scope,
+        snapshot,
+        max_file_size,
+        generated,
+        ..
+    } = *options;
+    let model = options.model.as_deref();
+    let human_author = options.human_author.as_deref();
+    let prompt = options.prompt.as_deref();
+    let base_commit = resolve_base_commit(repo)?;
+    let max_file_size = resolve_max_file_size(repo, max_file_size);
 
     // aidan
-    let files = get_all_files(repo)?;
+    let attributes = Attributes::load(repo)?;
+    let mut files = get_all_files(repo, scope)?;
+    files.retain(|file| !attributes.policy(file).ignore);
     let mut working_log = if reset {
         // If reset flag is set, start with an empty working log
         Vec::new()
@@ -48,7 +86,7 @@ pub fn run(
             debug_log("No working log entries found.");
         } else {
             debug_log("Working Log Entries:");
-            debug_log(&format!("{}", "=".repeat(80)));
+            debug_log(&"=".repeat(80));
             for (i, checkpoint) in working_log.iter().enumerate() {
                 debug_log(&format!("Checkpoint {}: {}", i + 1, checkpoint.snapshot));
                 debug_log(&format!("  Diff: {}", checkpoint.diff));
@@ -59,6 +97,7 @@ pub fn run(
                     if let Some(human_author) = &metadata.human_author {
                         debug_log(&format!("    Human Author: {}", human_author));
                     }
+                    debug_log(&format!("    Confidence: {:?}", metadata.confidence));
                 }
                 debug_log("  Entries:");
                 for entry in &checkpoint.entries {
@@ -72,11 +111,14 @@ pub fn run(
         return Ok((0, files.len(), working_log.len()));
     }
 
+    // Touches carry no file states, so diffs start from the last checkpoint that saved some
+    let recorded = working_log.iter().filter(|c| !c.touch_only);
     let previous_commit = if reset {
         None
     } else {
-        working_log.last().map(|c| c.snapshot.clone())
+        recorded.clone().next_back().map(|c| c.snapshot.clone())
     };
+    let first_checkpoint = recorded.count() == 0;
 
     let file_hashes: std::collections::HashMap<String, String> = files
         .iter()
@@ -100,9 +142,9 @@ pub fn run(
     let combined_hash = format!("{:x}", combined_hasher.finalize());
 
     // If this is not the first checkpoint, diff against the last saved state
-    let entries = if working_log.is_empty() || reset {
+    let entries = if first_checkpoint || reset {
         // First checkpoint or reset - diff against base commit
-        get_initial_checkpoint_entries(repo, &files, &base_commit)?
+        get_initial_checkpoint_entries(repo, &files, &base_commit, max_file_size)?
     } else {
         // Subsequent checkpoint - diff against last saved state
         get_subsequent_checkpoint_entries(
@@ -111,13 +153,61 @@ pub fn run(
             &file_hashes,
             previous_commit.as_deref(),
             &base_commit,
+            max_file_size,
         )?
     };
 
-    let checkpoint = if let Some(model) = model {
+    // .git-ai/attributes can credit a file's edits to the other side from this checkpoint,
+    // or to a code generator; those go into checkpoints of their own, recorded just before
+    // this one. A checkpoint that is itself a generator's keeps everything.
+    let mut entries = entries;
+    let (generated_entries, forced) = if generated {
+        (Vec::new(), Vec::new())
+    } else {
+        let generated_entries = take_generated_entries(&attributes, &mut entries);
+        let forced = take_forced_entries(&attributes, &mut entries, model.map(|_| confidence));
+        (generated_entries, forced)
+    };
+    let generated_checkpoint = (!generated_entries.is_empty()).then(|| {
+        let mut generated_checkpoint = Checkpoint::new(
+            base_commit.clone(),
+            combined_hash.clone(),
+            author.to_string(),
+            generated_entries.clone(),
+        );
+        generated_checkpoint.generated = true;
+        generated_checkpoint
+    });
+    let forced_checkpoint = if forced.is_empty() {
+        None
+    } else if model.is_some() {
+        Some(Checkpoint::new(
+            base_commit.clone(),
+            combined_hash.clone(),
+            human_author.unwrap_or(author).to_string(),
+            forced.clone(),
+        ))
+    } else {
+        // As with `--author-type ai`, the agent is unknown and the attribution a guess
+        let agent_metadata = AgentMetadata {
+            model: "unknown".to_string(),
+            human_author: Some(author.to_string()),
+            confidence: Confidence::Low,
+        };
+        Some(Checkpoint::new_with_metadata(
+            base_commit.clone(),
+            combined_hash.clone(),
+            author.to_string(),
+            forced.clone(),
+            agent_metadata,
+        ))
+    };
+
+    let mut checkpoint = if let Some(model) = model {
         let agent_metadata = AgentMetadata {
             model: model.to_string(),
             human_author: human_author.map(|s| s.to_string()),
+            confidence,
         };
         Checkpoint::new_with_metadata(
             base_commit.clone(),
@@ -135,7 +225,56 @@ pub fn run(
         )
     };
 
+    checkpoint.prompt = prompt.map(|p| p.to_string());
+    checkpoint.generated = generated && model.is_none();
+    if snapshot && !entries.is_empty() {
+        checkpoint.content_tree = Some(save_content_snapshot(repo, &base_commit, &entries)?);
+    }
+    let mut forced_checkpoint = match forced_checkpoint {
+        Some(mut forced_checkpoint) if snapshot => {
+            forced_checkpoint.content_tree =
+                Some(save_content_snapshot(repo, &base_commit, &forced)?);
+            Some(forced_checkpoint)
+        }
+        forced_checkpoint => forced_checkpoint,
+    };
+    let mut generated_checkpoint = match generated_checkpoint {
+        Some(mut generated_checkpoint) if snapshot => {
+            generated_checkpoint.content_tree = Some(save_content_snapshot(
+                repo,
+                &base_commit,
+                &generated_entries,
+            )?);
+            Some(generated_checkpoint)
+        }
+        generated_checkpoint => generated_checkpoint,
+    };
+
+    // An AI checkpoint finalizes what its author announced with --touch-only
+    if model.is_some() {
+        let unchanged = finalize_touches(&mut working_log, author, &entries);
+        if !quiet && !unchanged.is_empty() {
+            eprintln!(
+                "{} announced but did not change: {}",
+                author,
+                unchanged.join(", ")
+            );
+        }
+    }
+    // Checkpoints credited to `author` keep the name as configured, whatever its encoding
+    if let Some(raw) = &options.author_raw {
+        let recorded = generated_checkpoint
+            .iter_mut()
+            .chain(forced_checkpoint.iter_mut())
+            .chain(std::iter::once(&mut checkpoint));
+        for recorded in recorded.filter(|recorded| recorded.author == author) {
+            recorded.set_author_bytes(raw);
+        }
+    }
+    working_log.extend(generated_checkpoint);
+    working_log.extend(forced_checkpoint);
     working_log.push(checkpoint);
+    let entries_len = entries.len() + forced.len() + generated_entries.len();
 
     // Use pretty formatting in debug builds, single-line in release builds
     let working_log_json = if cfg!(debug_assertions) {
@@ -151,14 +290,14 @@ pub fn run(
         &format!("Checkpoint by {}", author),
     )?;
 
-    save_current_file_states(repo, &base_commit, &files)?;
+    save_current_file_states(repo, &base_commit, &files, max_file_size)?;
 
     // Print summary with new format
     if reset {
         debug_log("Working log reset. Starting fresh checkpoint.");
     }
 
-    let label = if entries.len() > 1 {
+    let label = if entries_len > 1 {
         "checkpoint"
     } else {
         "commit"
@@ -168,21 +307,547 @@ pub fn run(
         eprintln!(
             "{} changed {} of the {} file(s) that have changed since the last {}",
             author,
-            entries.len(),
+            entries_len,
             files.len(),
             label
         );
     }
 
     // Return the requested values: (entries_len, files_len, working_log_len)
-    Ok((entries.len(), files.len(), working_log.len()))
+    Ok((entries_len, files.len(), working_log.len()))
+}
+
+/// Remove and return the entries for paths `.git-ai/attributes` marks `generated`
+fn take_generated_entries(
+    attributes: &Attributes,
+    entries: &mut Vec<WorkingLogEntry>,
+) -> Vec<WorkingLogEntry> {
+    let (generated, kept) = entries.drain(..).partition(|entry| {
+        attributes.policy(&entry.file).force == Some(ForcedAttribution::Generated)
+    });
+    *entries = kept;
+    generated
+}
+
+/// Remove and return the entries `.git-ai/attributes` credits to the other side from the
+/// checkpoint: in an AI checkpoint (`ai_confidence` given), edits to `force-human` paths or
+/// recorded below a path's `min-confidence`; in a human one, edits to `force-ai` paths
+fn take_forced_entries(
+    attributes: &Attributes,
+    entries: &mut Vec<WorkingLogEntry>,
+    ai_confidence: Option<Confidence>,
+) -> Vec<WorkingLogEntry> {
+    let (forced, kept) = entries.drain(..).partition(|entry| {
+        let policy = attributes.policy(&entry.file);
+        match ai_confidence {
+            Some(confidence) => {
+                policy.force == Some(ForcedAttribution::Human)
+                    || policy
+                        .min_confidence
+                        .is_some_and(|floor| confidence < floor)
+            }
+            None => policy.force == Some(ForcedAttribution::Ai),
+        }
+    });
+    *entries = kept;
+    forced
+}
+
+/// First half of a two-phase agent protocol: record that `author` is about to edit `files`,
+/// before any content has changed. The touch holds no lines, so it never affects blame or
+/// stats; the author's next AI checkpoint captures the edits and finalizes it. Paths are
+/// relative to the repository root and need not exist yet. Returns the number of files.
+pub fn touch(
+    repo: &Repository,
+    files: &[String],
+    author: &str,
+    agent_metadata: AgentMetadata,
+) -> Result<usize, GitAiError> {
+    let mut touched: Vec<String> = Vec::new();
+    for file in files {
+        let file = file.trim().trim_start_matches("./");
+        if file.is_empty() || Path::new(file).is_absolute() {
+            return Err(GitAiError::Generic(format!(
+                "--files takes paths relative to the repository root, got '{}'",
+                file
+            )));
+        }
+        if !touched.iter().any(|existing| existing == file) {
+            touched.push(file.to_string());
+        }
+    }
+    if touched.is_empty() {
+        return Err(GitAiError::Generic(
+            "--touch-only requires --files <path,...>".to_string(),
+        ));
+    }
+
+    let base_commit = resolve_base_commit(repo)?;
+    let mut working_log = get_or_create_working_log(repo, &base_commit)?;
+    let entries = touched
+        .iter()
+        .map(|file| WorkingLogEntry::new(file.clone(), Vec::new(), Vec::new()))
+        .collect();
+    let mut checkpoint = Checkpoint::new_with_metadata(
+        base_commit.clone(),
+        "touch".to_string(),
+        author.to_string(),
+        entries,
+        agent_metadata,
+    );
+    checkpoint.touch_only = true;
+    working_log.push(checkpoint);
+
+    let working_log_json = if cfg!(debug_assertions) {
+        serde_json::to_string_pretty(&working_log)?
+    } else {
+        serde_json::to_string(&working_log)?
+    };
+    put_reference(
+        repo,
+        &format!("ai-working-log/{}", base_commit),
+        &working_log_json,
+        &format!("Touch by {}", author),
+    )?;
+    Ok(touched.len())
+}
+
+/// Drop `author`'s pending touches now that a full checkpoint has recorded `entries`, and
+/// return the touched files it found no changes in
+fn finalize_touches(
+    working_log: &mut Vec<Checkpoint>,
+    author: &str,
+    entries: &[WorkingLogEntry],
+) -> Vec<String> {
+    let mut unchanged: Vec<String> = Vec::new();
+    working_log.retain(|checkpoint| {
+        if !checkpoint.touch_only || checkpoint.author != author {
+            return true;
+        }
+        for touched in &checkpoint.entries {
+            if !entries.iter().any(|entry| entry.file == touched.file)
+                && !unchanged.contains(&touched.file)
+            {
+                unchanged.push(touched.file.clone());
+            }
+        }
+        false
+    });
+    unchanged
+}
+
+/// What the latest checkpoint recorded, as printed by `checkpoint --json`. The id is the
+/// base commit and the checkpoint's 1-based position in that base's working log.
+pub fn last_checkpoint_json(repo: &Repository) -> Result<serde_json::Value, GitAiError> {
+    let base_commit = resolve_base_commit(repo)?;
+    let ref_name = format!("ai-working-log/{}", base_commit);
+    let working_log = get_reference_as_working_log(repo, &ref_name)?;
+    let checkpoint = working_log
+        .last()
+        .ok_or_else(|| GitAiError::Generic("No checkpoint recorded".to_string()))?;
+
+    let count = |lines: &[Line]| lines.iter().map(Line::line_count).sum::<u32>();
+    Ok(serde_json::json!({
+        "status": if checkpoint.touch_only { "touched" } else { "recorded" },
+        "id": format!("{}:{}", base_commit, working_log.len()),
+        "ref": format!("refs/{}", ref_name),
+        "author": checkpoint.author,
+        "author_raw": checkpoint.author_raw,
+        "model": checkpoint.agent_metadata.as_ref().map(|metadata| &metadata.model),
+        "files": checkpoint.entries.len(),
+        "added_lines": checkpoint.entries.iter().map(|entry| count(&entry.added_lines)).sum::<u32>(),
+        "deleted_lines": checkpoint.entries.iter().map(|entry| count(&entry.deleted_lines)).sum::<u32>(),
+        "content_tree": checkpoint.content_tree,
+        "file_level": checkpoint
+            .entries
+            .iter()
+            .filter(|entry| entry.file_level)
+            .map(|entry| &entry.file)
+            .collect::<Vec<_>>(),
+    }))
+}
+
+/// Which working changes a checkpoint scans. Scoping is per file: a file with both staged
+/// and unstaged edits is recorded as it is on disk under either scope.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ChangeScope {
+    /// Staged and unstaged changes alike
+    #[default]
+    All,
+    /// Only files with changes not yet in the index (`--no-index`)
+    Unstaged,
+    /// Only files with changes in the index (`--staged-only`)
+    Staged,
+}
+
+impl ChangeScope {
+    fn includes(&self, status: git2::Status) -> bool {
+        match self {
+            ChangeScope::All => true,
+            ChangeScope::Unstaged => status.intersects(
+                git2::Status::WT_NEW
+                    | git2::Status::WT_MODIFIED
+                    | git2::Status::WT_DELETED
+                    | git2::Status::WT_RENAMED
+                    | git2::Status::WT_TYPECHANGE,
+            ),
+            ChangeScope::Staged => status.intersects(
+                git2::Status::INDEX_NEW
+                    | git2::Status::INDEX_MODIFIED
+                    | git2::Status::INDEX_DELETED
+                    | git2::Status::INDEX_RENAMED
+                    | git2::Status::INDEX_TYPECHANGE,
+            ),
+        }
+    }
+}
+
+/// Explicit classification for a checkpoint, overriding the `--model` heuristic
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthorType {
+    Human,
+    Ai,
+    /// A code generator (protoc, a build script): neither human nor AI
+    Generated,
+}
+
+impl AuthorType {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "human" => Some(AuthorType::Human),
+            "ai" => Some(AuthorType::Ai),
+            "generated" => Some(AuthorType::Generated),
+            _ => None,
+        }
+    }
+}
+
+/// Edits are AI-authored when a model is given. The editor presets rely on this: their
+/// post-edit hooks pass `--model`, their pre-edit hooks don't. `--author-type` wins over
+/// both, so a hook that fires on the wrong side of a human edit can be forced either way.
+pub fn resolve_model(model: Option<&str>, author_type: Option<AuthorType>) -> Option<String> {
+    match author_type {
+        Some(AuthorType::Human | AuthorType::Generated) => None,
+        Some(AuthorType::Ai) => Some(model.unwrap_or("unknown").to_string()),
+        None => model.map(str::to_string),
+    }
+}
+
+/// Environment variables that name the model outright, read first and in this order
+pub const MODEL_ENV_VARS: [&str; 3] = ["ANTHROPIC_MODEL", "AIDER_MODEL", "OPENAI_MODEL"];
+
+/// Environment variables agents set for the commands they run, and the agent each names
+pub const AGENT_ENV_VARS: [(&str, &str); 3] = [
+    ("CLAUDECODE", "claude"),
+    ("CURSOR_TRACE_ID", "cursor"),
+    ("CODEX_SANDBOX_NETWORK_DISABLED", "codex"),
+];
+
+/// Process name prefixes (lowercased) of agents, matched against each ancestor of git-ai
+pub const AGENT_PROCESS_NAMES: [(&str, &str); 6] = [
+    ("claude", "claude"),
+    ("cursor", "cursor"),
+    ("copilot", "copilot"),
+    ("codex", "codex"),
+    ("aider", "aider"),
+    ("gemini", "gemini"),
+];
+
+/// `--detect-model`: a best-effort guess at the model for wrappers that weren't told it.
+/// See [`detect_model_from`] for the signals read.
+pub fn detect_model() -> Option<String> {
+    detect_model_from(
+        |name| std::env::var(name).ok().filter(|value| !value.is_empty()),
+        &ancestor_process_names(),
+    )
+}
+
+/// The model named by the first of [`MODEL_ENV_VARS`] that is set, else the agent named by
+/// the first of [`AGENT_ENV_VARS`], else the agent whose name starts the nearest process in
+/// `process_names` (parent first). None when nothing identifies the agent.
+pub fn detect_model_from(
+    env: impl Fn(&str) -> Option<String>,
+    process_names: &[String],
+) -> Option<String> {
+    if let Some(model) = MODEL_ENV_VARS.iter().find_map(|name| env(name)) {
+        return Some(model);
+    }
+    if let Some((_, agent)) = AGENT_ENV_VARS.iter().find(|(name, _)| env(name).is_some()) {
+        return Some(agent.to_string());
+    }
+    process_names.iter().find_map(|name| {
+        let name = name.rsplit('/').next().unwrap_or(name).to_lowercase();
+        AGENT_PROCESS_NAMES
+            .iter()
+            .find(|(prefix, _)| name.starts_with(prefix))
+            .map(|(_, agent)| agent.to_string())
+    })
 }
 
-fn get_all_files(repo: &Repository) -> Result<Vec<String>, GitAiError> {
+/// Names of the processes above this one, parent first, as `ps` reports them
+#[cfg(unix)]
+fn ancestor_process_names() -> Vec<String> {
+    let mut names = Vec::new();
+    let mut pid = std::os::unix::process::parent_id();
+    // Agents launch git-ai through a few layers of shells and hooks at most
+    for _ in 0..16 {
+        if pid <= 1 {
+            break;
+        }
+        let Ok(output) = Command::new("ps")
+            .args(["-o", "ppid=", "-o", "comm=", "-p", &pid.to_string()])
+            .stderr(Stdio::null())
+            .output()
+        else {
+            break;
+        };
+        let line = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        let Some((ppid, name)) = line.split_once(char::is_whitespace) else {
+            break;
+        };
+        names.push(name.trim().to_string());
+        match ppid.parse() {
+            Ok(ppid) => pid = ppid,
+            Err(_) => break,
+        }
+    }
+    names
+}
+
+#[cfg(not(unix))]
+fn ancestor_process_names() -> Vec<String> {
+    Vec::new()
+}
+
+/// How sure a checkpoint is that its AI edits came from the agent. `--confidence` wins;
+/// otherwise a named model means the agent's own hook reported the edit, while
+/// `--author-type ai` without one is a guess about which edits were the agent's.
+pub fn resolve_confidence(
+    model: Option<&str>,
+    author_type: Option<AuthorType>,
+    confidence: Option<Confidence>,
+) -> Confidence {
+    match (confidence, model, author_type) {
+        (Some(confidence), _, _) => confidence,
+        (None, None, Some(AuthorType::Ai)) => Confidence::Low,
+        _ => Confidence::High,
+    }
+}
+
+/// Backfill attribution for a commit made without git-ai: every line it added is credited to
+/// `author` (as AI when `model` is given), and the authorship ref is written as if the commit
+/// had gone through the proxy. Refuses to replace existing attribution unless `overwrite`.
+/// Returns the ref written and the log stored in it.
+pub fn link_commit(
+    repo: &Repository,
+    commit_spec: &str,
+    author: &str,
+    model: Option<&str>,
+    human_author: Option<&str>,
+    confidence: Confidence,
+    overwrite: bool,
+) -> Result<(String, AuthorshipLog), GitAiError> {
+    let commit = resolve_commit(repo, commit_spec)?;
+    let commit_sha = commit.id().to_string();
+    let ref_name = authorship_ref_name(repo, &commit_sha);
+    if !overwrite && repo.find_reference(&format!("refs/{}", ref_name)).is_ok() {
+        return Err(GitAiError::Generic(format!(
+            "Commit {} already has attribution. Use --overwrite to replace it",
+            commit_spec
+        )));
+    }
+
+    let parent_tree = match commit.parent(0) {
+        Ok(parent) => Some(parent.tree()?),
+        Err(_) => None,
+    };
+    let diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&commit.tree()?), None)?;
+    let blob_text = |oid: git2::Oid| -> Result<Option<String>, GitAiError> {
+        if oid.is_zero() {
+            return Ok(Some(String::new()));
+        }
+        let blob = repo.find_blob(oid)?;
+        Ok((!blob.is_binary()).then(|| String::from_utf8_lossy(blob.content()).into_owned()))
+    };
+
+    let mut entries = Vec::new();
+    for delta in diff.deltas() {
+        let Some(path) = delta.new_file().path() else {
+            continue;
+        };
+        if delta.status() == git2::Delta::Deleted {
+            continue;
+        }
+        let (Some(old_content), Some(new_content)) = (
+            blob_text(delta.old_file().id())?,
+            blob_text(delta.new_file().id())?,
+        ) else {
+            continue;
+        };
+        let (added_lines, deleted_lines) = get_changed_lines(&old_content, &new_content)?;
+        if !added_lines.is_empty() {
+            entries.push(WorkingLogEntry::new(
+                path.to_string_lossy().to_string(),
+                added_lines,
+                deleted_lines,
+            ));
+        }
+    }
+
+    let parent_sha = commit
+        .parent_id(0)
+        .map(|oid| oid.to_string())
+        .unwrap_or_else(|_| "initial".to_string());
+    let checkpoint = match model {
+        Some(model) => Checkpoint::new_with_metadata(
+            parent_sha,
+            commit_sha.clone(),
+            author.to_string(),
+            entries,
+            AgentMetadata {
+                model: model.to_string(),
+                human_author: human_author.map(str::to_string),
+                confidence,
+            },
+        ),
+        None => Checkpoint::new(parent_sha, commit_sha.clone(), author.to_string(), entries),
+    };
+    let authorship_log = AuthorshipLog::from_working_log(&[checkpoint]);
+
+    // Same encoding as post-commit: pretty in debug builds, single-line in release builds
+    let authorship_json = if cfg!(debug_assertions) {
+        serde_json::to_string_pretty(&authorship_log)?
+    } else {
+        serde_json::to_string(&authorship_log)?
+    };
+    put_reference(
+        repo,
+        &ref_name,
+        &authorship_json,
+        &format!("Linked attribution for commit {}", commit_sha),
+    )?;
+    // With --overwrite, a stats note would still summarize the replaced attribution
+    remove_note(repo, commit.id())?;
+    Ok((ref_name, authorship_log))
+}
+
+/// Cursor's beforeSubmitPrompt and Claude's UserPromptSubmit hooks send JSON with a
+/// "prompt" field; anything that isn't JSON is taken as the prompt text itself
+pub fn prompt_from_hook_payload(payload: &str) -> Option<String> {
+    let payload = payload.trim();
+    if payload.is_empty() {
+        return None;
+    }
+    match serde_json::from_str::<serde_json::Value>(payload) {
+        Ok(value) => value
+            .get("prompt")
+            .and_then(|prompt| prompt.as_str())
+            .map(|prompt| prompt.to_string()),
+        Err(_) => Some(payload.to_string()),
+    }
+}
+
+/// Budget editor hooks get by default: long enough for a normal checkpoint, short enough
+/// not to be felt while typing
+pub const DEFAULT_HOOK_MAX_MS: u64 = 300;
+
+/// Run `git-ai checkpoint <args>` in a detached child and wait at most `budget` for it.
+/// Returns the child's exit status if it finished in time, or None when it was left to
+/// finish the write in the background. The child gets its own process group and none of
+/// the hook's pipes, so an editor waiting on them is released as soon as this process exits
+/// and cannot take a half-written checkpoint down with it.
+pub fn run_with_budget(
+    args: &[String],
+    budget: Duration,
+) -> Result<Option<ExitStatus>, GitAiError> {
+    let mut command = Command::new(std::env::current_exe()?);
+    command
+        .arg("checkpoint")
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut command, 0);
+    let mut child = command.spawn()?;
+
+    let started = Instant::now();
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        let elapsed = started.elapsed();
+        if elapsed >= budget {
+            debug_log(&format!(
+                "checkpoint exceeded {}ms, finishing in the background",
+                budget.as_millis()
+            ));
+            return Ok(None);
+        }
+        std::thread::sleep((budget - elapsed).min(Duration::from_millis(5)));
+    }
+}
+
+/// Changed paths (untracked and deleted files included, binary or not) that match none of
+/// the `allow` globs, sorted. Globs match the whole repository-relative path: `*` and `?`
+/// stay within one directory, `**` crosses directories (`src/**` is everything under src).
+pub fn verify_clean(
+    repo: &Repository,
+    allow: &[String],
+    scope: ChangeScope,
+) -> Result<Vec<String>, GitAiError> {
+    let allowed = allow
+        .iter()
+        .map(|glob| {
+            regex::Regex::new(&glob_to_regex(glob))
+                .map_err(|e| GitAiError::Generic(format!("Invalid --allow glob {}: {}", glob, e)))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut status_opts = StatusOptions::new();
+    status_opts
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .include_ignored(false);
+    let mut offenders: Vec<String> = repo
+        .statuses(Some(&mut status_opts))?
+        .iter()
+        .filter(|entry| scope.includes(entry.status()))
+        .filter_map(|entry| entry.path().map(str::to_string))
+        .filter(|path| !allowed.iter().any(|glob| glob.is_match(path)))
+        .collect();
+    offenders.sort();
+    offenders.dedup();
+    Ok(offenders)
+}
+
+/// Resolve the commit the working log is keyed on.
+///
+/// In a freshly initialized repo HEAD is unborn, so checkpoints are recorded against the
+/// working tree under the "initial" placeholder. Post-commit folds that log into the first
+/// commit once it exists.
+pub fn resolve_base_commit(repo: &Repository) -> Result<String, GitAiError> {
+    match repo.head() {
+        Ok(head) => Ok(head
+            .target()
+            .map(|oid| oid.to_string())
+            .unwrap_or_else(|| "initial".to_string())),
+        Err(e)
+            if e.code() == git2::ErrorCode::UnbornBranch
+                || e.code() == git2::ErrorCode::NotFound =>
+        {
+            Ok("initial".to_string())
+        }
+        Err(e) => Err(GitAiError::GitError(e)),
+    }
+}
+
+fn get_all_files(repo: &Repository, scope: ChangeScope) -> Result<Vec<String>, GitAiError> {
     let mut files = Vec::new();
 
     let mut status_opts = StatusOptions::new();
     status_opts.include_untracked(true);
+    status_opts.recurse_untracked_dirs(true);
     status_opts.include_ignored(false);
     status_opts.include_unmodified(false);
 
@@ -190,57 +855,59 @@ fn get_all_files(repo: &Repository) -> Result<Vec<String>, GitAiError> {
     for entry in statuses.iter() {
         if let Some(path) = entry.path() {
             // Only include text files
-            if is_text_file(repo, path) {
+            if scope.includes(entry.status()) && is_text_file(repo, path) {
                 files.push(path.to_string());
             }
         }
     }
 
-    // Also check for deleted files by looking at the working directory vs HEAD
-    if let Ok(head) = repo.head() {
-        if let Some(target) = head.target() {
-            if let Ok(commit) = repo.find_commit(target) {
-                if let Ok(tree) = commit.tree() {
-                    // Recursively traverse the tree to find files that exist in HEAD but not in working directory
-                    fn walk_tree(
-                        tree: &git2::Tree,
-                        repo: &Repository,
-                        files: &mut Vec<String>,
-                        prefix: &str,
-                    ) -> Result<(), GitAiError> {
-                        for entry in tree.iter() {
-                            let name = entry.name().unwrap_or("");
-                            let path = if prefix.is_empty() {
-                                name.to_string()
-                            } else {
-                                format!("{}/{}", prefix, name)
-                            };
-
-                            match entry.kind() {
-                                Some(git2::ObjectType::Blob) => {
-                                    // Check if file exists in working directory and is a text file
-                                    if !Path::new(&path).exists()
-                                        && !files.contains(&path)
-                                        && is_text_file(repo, &path)
-                                    {
-                                        files.push(path);
-                                    }
-                                }
-                                Some(git2::ObjectType::Tree) => {
-                                    if let Ok(subtree) = repo.find_tree(entry.id()) {
-                                        walk_tree(&subtree, repo, files, &path)?;
-                                    }
-                                }
-                                _ => {}
-                            }
+    // Also check for deleted files by looking at the working directory vs HEAD. A scoped
+    // scan relies on the status flags alone, which already report deletions.
+    if scope == ChangeScope::All
+        && let Ok(head) = repo.head()
+        && let Some(target) = head.target()
+        && let Ok(commit) = repo.find_commit(target)
+        && let Ok(tree) = commit.tree()
+    {
+        // Recursively traverse the tree to find files that exist in HEAD but not in working directory
+        fn walk_tree(
+            tree: &git2::Tree,
+            repo: &Repository,
+            workdir: &Path,
+            files: &mut Vec<String>,
+            prefix: &str,
+        ) -> Result<(), GitAiError> {
+            for entry in tree.iter() {
+                let name = entry.name().unwrap_or("");
+                let path = if prefix.is_empty() {
+                    name.to_string()
+                } else {
+                    format!("{}/{}", prefix, name)
+                };
+
+                match entry.kind() {
+                    // Check if file exists in working directory and is a text file
+                    Some(git2::ObjectType::Blob)
+                        if !workdir.join(&path).exists()
+                            && !files.contains(&path)
+                            && is_text_file(repo, &path) =>
+                    {
+                        files.push(path);
+                    }
+                    Some(git2::ObjectType::Tree) => {
+                        if let Ok(subtree) = repo.find_tree(entry.id()) {
+                            walk_tree(&subtree, repo, workdir, files, &path)?;
                         }
-                        Ok(())
                     }
-
-                    walk_tree(&tree, repo, &mut files, "")?;
+                    _ => {}
                 }
             }
+            Ok(())
         }
+
+        // Paths are relative to the work tree root, not to wherever git-ai was run from
+        let workdir = repo.workdir().unwrap_or_else(|| Path::new("."));
+        walk_tree(&tree, repo, workdir, &mut files, "")?;
     }
 
     Ok(files)
@@ -250,13 +917,20 @@ fn save_current_file_states(
     repo: &Repository,
     base_commit: &str,
     files: &[String],
+    max_file_size: u64,
 ) -> Result<HashMap<String, String>, GitAiError> {
     let mut file_hashes = HashMap::new();
 
     for file_path in files {
         let repo_workdir = repo.workdir().unwrap_or_else(|| Path::new("."));
         let abs_path = repo_workdir.join(file_path);
-        let content = if abs_path.exists() {
+        let content = if is_oversized(&abs_path, max_file_size) {
+            format!(
+                "{}{}",
+                OVERSIZED_STATE,
+                Oid::hash_file(ObjectType::Blob, &abs_path)?
+            )
+        } else if abs_path.exists() {
             // Read file as bytes first, then convert to string with UTF-8 lossy conversion
             match std::fs::read(&abs_path) {
                 Ok(bytes) => String::from_utf8_lossy(&bytes).to_string(),
@@ -285,6 +959,183 @@ fn save_current_file_states(
     Ok(file_hashes)
 }
 
+/// Write the current content of each entry's file to the object store as one tree, and
+/// return its id. The tree is an ordinary one, so `git diff <tree> <tree>` and `git show
+/// <tree>:<path>` read snapshots directly; a ref under `ai-working-log/snapshots/` keeps it
+/// from being garbage-collected. Files deleted on disk are left out.
+fn save_content_snapshot(
+    repo: &Repository,
+    base_commit: &str,
+    entries: &[WorkingLogEntry],
+) -> Result<String, GitAiError> {
+    let workdir = repo.workdir().unwrap_or_else(|| Path::new("."));
+    let mut files = Vec::new();
+    for entry in entries.iter().filter(|entry| !entry.file_level) {
+        if let Ok(content) = std::fs::read(workdir.join(&entry.file)) {
+            files.push((entry.file.clone(), content));
+        }
+    }
+    let tree = write_content_tree(repo, &files)?;
+    repo.reference(
+        &format!("refs/ai-working-log/snapshots/{}-{}", base_commit, tree),
+        tree,
+        true,
+        "Checkpoint content snapshot",
+    )?;
+    Ok(tree.to_string())
+}
+
+fn write_content_tree(
+    repo: &Repository,
+    files: &[(String, Vec<u8>)],
+) -> Result<git2::Oid, GitAiError> {
+    let mut builder = repo.treebuilder(None)?;
+    let mut directories: std::collections::BTreeMap<&str, Vec<(String, Vec<u8>)>> =
+        std::collections::BTreeMap::new();
+    for (path, content) in files {
+        match path.split_once('/') {
+            Some((directory, rest)) => directories
+                .entry(directory)
+                .or_default()
+                .push((rest.to_string(), content.clone())),
+            None => {
+                builder.insert(path, repo.blob(content)?, 0o100644)?;
+            }
+        }
+    }
+    for (directory, files) in directories {
+        builder.insert(directory, write_content_tree(repo, &files)?, 0o040000)?;
+    }
+    Ok(builder.write()?)
+}
+
+/// The branch HEAD is on, unborn ones included; None when HEAD is detached
+pub fn current_branch(repo: &Repository) -> Option<String> {
+    let head = repo.find_reference("HEAD").ok()?;
+    let target = head.symbolic_target()?;
+    target.strip_prefix("refs/heads/").map(str::to_string)
+}
+
+/// Every glob a multi-valued config key holds, each value a comma-separated list
+fn config_globs(repo: &Repository, key: &str) -> Vec<String> {
+    let mut globs = Vec::new();
+    let Ok(config) = repo.config() else {
+        return globs;
+    };
+    if let Ok(entries) = config.multivar(key, None) {
+        let _ = entries.for_each(|entry| {
+            globs.extend(
+                entry
+                    .value()
+                    .unwrap_or_default()
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|glob| !glob.is_empty())
+                    .map(str::to_string),
+            );
+        });
+    }
+    globs
+}
+
+/// Whether checkpoints on the current branch are skipped: it matches a `--skip-branch` glob,
+/// or `--on-branch` globs were given and it matches none. Without the flags the lists come
+/// from `git-ai.skipBranch` and `git-ai.onBranch`; a flag replaces its list rather than
+/// adding to it. Globs match the branch name as path globs do (`release/*`, `hotfix/**`). A
+/// detached HEAD is on no branch, so only an allow list skips it.
+pub fn branch_excluded(
+    repo: &Repository,
+    on_branch: &[String],
+    skip_branch: &[String],
+) -> Result<bool, GitAiError> {
+    let compile = |globs: &[String], key: &str| {
+        let globs = if globs.is_empty() {
+            config_globs(repo, key)
+        } else {
+            globs.to_vec()
+        };
+        globs
+            .iter()
+            .map(|glob| {
+                regex::Regex::new(&glob_to_regex(glob)).map_err(|e| {
+                    GitAiError::Generic(format!("Invalid branch pattern {}: {}", glob, e))
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()
+    };
+    let on_branch = compile(on_branch, "git-ai.onBranch")?;
+    let skip_branch = compile(skip_branch, "git-ai.skipBranch")?;
+
+    let Some(branch) = current_branch(repo) else {
+        return Ok(!on_branch.is_empty());
+    };
+    let matches = |globs: &[regex::Regex]| globs.iter().any(|regex| regex.is_match(&branch));
+    Ok(matches(&skip_branch) || (!on_branch.is_empty() && !matches(&on_branch)))
+}
+
+/// Files larger than this many bytes are recorded at file level, unless `--max-file-size`
+/// or `git-ai.maxFileSize` sets another limit
+pub const DEFAULT_MAX_FILE_SIZE: u64 = 1024 * 1024;
+
+/// Saved state of a file over the size limit: this prefix and its blob id. The content
+/// isn't kept, only enough to tell whether the file changed.
+const OVERSIZED_STATE: &str = "git-ai-oversized ";
+
+/// `--max-file-size` when given, then `git-ai.maxFileSize`, then the default. 0 means no
+/// limit.
+pub fn resolve_max_file_size(repo: &Repository, max_file_size: Option<u64>) -> u64 {
+    max_file_size
+        .or_else(|| {
+            let config = repo.config().ok()?;
+            let value = config.get_i64("git-ai.maxFileSize").ok()?;
+            u64::try_from(value).ok()
+        })
+        .unwrap_or(DEFAULT_MAX_FILE_SIZE)
+}
+
+fn is_oversized(path: &Path, max_file_size: u64) -> bool {
+    max_file_size > 0
+        && std::fs::metadata(path).is_ok_and(|metadata| metadata.len() > max_file_size)
+}
+
+/// Compare a file by blob id alone, against its saved state when there is one, otherwise
+/// its version in the base commit. Reading and diffing its lines is what the size limit
+/// avoids, so a change is recorded for the whole file.
+fn file_level_entry(
+    repo: &Repository,
+    file_path: &str,
+    abs_path: &Path,
+    saved_state: Option<String>,
+    base_commit: &str,
+) -> Result<Option<WorkingLogEntry>, GitAiError> {
+    let current = if abs_path.exists() {
+        Some(Oid::hash_file(ObjectType::Blob, abs_path)?)
+    } else {
+        None
+    };
+    let previous = match saved_state {
+        Some(state) => match state.strip_prefix(OVERSIZED_STATE) {
+            Some(blob) => Oid::from_str(blob.trim()).ok(),
+            None => Some(Oid::hash_object(ObjectType::Blob, state.as_bytes())?),
+        },
+        None if base_commit == "initial" => None,
+        None => repo
+            .find_commit(Oid::from_str(base_commit)?)?
+            .tree()?
+            .get_path(Path::new(file_path))
+            .ok()
+            .map(|entry| entry.id()),
+    };
+    if current == previous {
+        return Ok(None);
+    }
+    debug_log(&format!(
+        "{} is over the checkpoint size limit; recording it at file level",
+        file_path
+    ));
+    Ok(Some(WorkingLogEntry::file_level(file_path.to_string())))
+}
+
 fn get_or_create_working_log(
     repo: &Repository,
     base_commit: &str,
@@ -302,12 +1153,23 @@ fn get_initial_checkpoint_entries(
     repo: &Repository,
     files: &[String],
     base_commit: &str,
+    max_file_size: u64,
 ) -> Result<Vec<WorkingLogEntry>, GitAiError> {
     let mut entries = Vec::new();
 
     for file_path in files {
         let repo_workdir = repo.workdir().unwrap_or_else(|| Path::new("."));
         let abs_path = repo_workdir.join(file_path);
+        if is_oversized(&abs_path, max_file_size) {
+            entries.extend(file_level_entry(
+                repo,
+                file_path,
+                &abs_path,
+                None,
+                base_commit,
+            )?);
+            continue;
+        }
 
         let current_content = if abs_path.exists() {
             match std::fs::read(&abs_path) {
@@ -358,12 +1220,33 @@ fn get_subsequent_checkpoint_entries(
     file_hashes: &HashMap<String, String>,
     previous_commit: Option<&str>,
     base_commit: &str,
+    max_file_size: u64,
 ) -> Result<Vec<WorkingLogEntry>, GitAiError> {
     let mut entries = Vec::new();
 
     for file_path in files {
         let repo_workdir = repo.workdir().unwrap_or_else(|| Path::new("."));
         let abs_path = repo_workdir.join(file_path);
+        let saved_state = || {
+            let file_hash = file_hashes.get(file_path)?;
+            let ref_name = format!("ai-working-log/diffs/{}-{}", previous_commit?, file_hash);
+            get_reference(repo, &ref_name).ok()
+        };
+        // Over the limit now, or last time (when no lines were kept to diff against)
+        let saved = saved_state();
+        let was_oversized = saved
+            .as_deref()
+            .is_some_and(|state| state.starts_with(OVERSIZED_STATE));
+        if was_oversized || is_oversized(&abs_path, max_file_size) {
+            entries.extend(file_level_entry(
+                repo,
+                file_path,
+                &abs_path,
+                saved,
+                base_commit,
+            )?);
+            continue;
+        }
         let current_content = if abs_path.exists() {
             // Read file as bytes first, then convert to string with UTF-8 lossy conversion
             match std::fs::read(&abs_path) {
@@ -613,9 +1496,13 @@ fn is_text_file(repo: &Repository, file_path: &str) -> bool {
     }
 
     // First check if the file exists in the working directory
-    if Path::new(file_path).exists() {
+    let abs_path = repo
+        .workdir()
+        .unwrap_or_else(|| Path::new("."))
+        .join(file_path);
+    if abs_path.exists() {
         // Read a sample of the file to check for null bytes and other binary indicators
-        if let Ok(bytes) = std::fs::read(file_path) {
+        if let Ok(bytes) = std::fs::read(&abs_path) {
             // Check for null bytes in the first 8KB (git's default sample size)
             let sample_size = std::cmp::min(bytes.len(), 8192);
             if sample_size > 0 {
@@ -641,35 +1528,32 @@ fn is_text_file(repo: &Repository, file_path: &str) -> bool {
         }
     } else {
         // File doesn't exist in working directory, check if it exists in HEAD
-        if let Ok(head) = repo.head() {
-            if let Some(target) = head.target() {
-                if let Ok(commit) = repo.find_commit(target) {
-                    if let Ok(tree) = commit.tree() {
-                        if let Ok(entry) = tree.get_path(Path::new(file_path)) {
-                            // Check if the blob in git is binary
-                            if let Ok(blob) = repo.find_blob(entry.id()) {
-                                let content = blob.content();
-                                let sample_size = std::cmp::min(content.len(), 8192);
-                                if sample_size > 0 {
-                                    let sample = &content[..sample_size];
-
-                                    // Check for null bytes
-                                    if sample.contains(&0) {
-                                        return false; // Contains null bytes, likely binary
-                                    }
+        if let Ok(head) = repo.head()
+            && let Some(target) = head.target()
+            && let Ok(commit) = repo.find_commit(target)
+            && let Ok(tree) = commit.tree()
+            && let Ok(entry) = tree.get_path(Path::new(file_path))
+        {
+            // Check if the blob in git is binary
+            if let Ok(blob) = repo.find_blob(entry.id()) {
+                let content = blob.content();
+                let sample_size = std::cmp::min(content.len(), 8192);
+                if sample_size > 0 {
+                    let sample = &content[..sample_size];
+
+                    // Check for null bytes
+                    if sample.contains(&0) {
+                        return false; // Contains null bytes, likely binary
+                    }
 
-                                    // Check for high percentage of control characters
-                                    let control_chars = sample
-                                        .iter()
-                                        .filter(|&&b| b < 32 && b != 9 && b != 10 && b != 13)
-                                        .count();
+                    // Check for high percentage of control characters
+                    let control_chars = sample
+                        .iter()
+                        .filter(|&&b| b < 32 && b != 9 && b != 10 && b != 13)
+                        .count();
 
-                                    if control_chars > sample_size / 4 {
-                                        return false; // Too many control characters, likely binary
-                                    }
-                                }
-                            }
-                        }
+                    if control_chars > sample_size / 4 {
+                        return false; // Too many control characters, likely binary
                     }
                 }
             }
@@ -679,8 +1563,59 @@ fn is_text_file(repo: &Repository, file_path: &str) -> bool {
     true
 }
 
-/// Clear all ai-working-log/diffs references for a specific base commit
-/// This is called when the --reset flag is used to clean up old diff references
+/// Drop checkpoints older than `max_age` seconds from every working log except the one for
+/// `current_base`. Those logs belong to commits that already exist, so their attribution
+/// lives on in the authorship refs. Logs left empty are deleted along with their file
+/// snapshots. Returns the number of checkpoints expired.
+pub fn expire_committed_working_logs(
+    repo: &Repository,
+    current_base: &str,
+    max_age: u64,
+) -> Result<usize, GitAiError> {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let cutoff = now.saturating_sub(max_age);
+
+    let mut expired = 0;
+    for (base_commit, mut working_log) in list_working_logs(repo)? {
+        if base_commit == current_base {
+            continue;
+        }
+        let before = working_log.len();
+        working_log.retain(|checkpoint| checkpoint.timestamp >= cutoff);
+        if working_log.len() == before {
+            continue;
+        }
+        expired += before - working_log.len();
+
+        let ref_name = format!("ai-working-log/{}", base_commit);
+        if working_log.is_empty() {
+            repo.find_reference(&format!("refs/{}", ref_name))?
+                .delete()?;
+            clear_working_log_diffs(repo, &base_commit)?;
+        } else {
+            let working_log_json = if cfg!(debug_assertions) {
+                serde_json::to_string_pretty(&working_log)?
+            } else {
+                serde_json::to_string(&working_log)?
+            };
+            put_reference(repo, &ref_name, &working_log_json, "Expire old checkpoints")?;
+        }
+    }
+
+    if expired > 0 {
+        debug_log(&format!(
+            "Expired {} committed working log entries",
+            expired
+        ));
+    }
+    Ok(expired)
+}
+
+/// Clear all ai-working-log/diffs and ai-working-log/snapshots references for a specific
+/// base commit. This is called when the --reset flag is used to clean up old diff references
 fn clear_working_log_diffs(repo: &Repository, base_commit: &str) -> Result<(), GitAiError> {
     // Use git CLI to list and remove references that match the pattern
     let output = std::process::Command::new("git")
@@ -688,17 +1623,21 @@ fn clear_working_log_diffs(repo: &Repository, base_commit: &str) -> Result<(), G
             "for-each-ref",
             "--format=%(refname)",
             "refs/ai-working-log/diffs/",
+            "refs/ai-working-log/snapshots/",
         ])
         .current_dir(repo.workdir().unwrap_or_else(|| Path::new(".")))
         .output()?;
 
     if output.status.success() {
         let refs_output = String::from_utf8_lossy(&output.stdout);
-        let prefix = format!("refs/ai-working-log/diffs/{}-", base_commit);
+        let prefixes = [
+            format!("refs/ai-working-log/diffs/{}-", base_commit),
+            format!("refs/ai-working-log/snapshots/{}-", base_commit),
+        ];
 
         for line in refs_output.lines() {
             let ref_name = line.trim();
-            if ref_name.starts_with(&prefix) {
+            if prefixes.iter().any(|prefix| ref_name.starts_with(prefix)) {
                 // Remove the reference using git CLI
                 let _ = std::process::Command::new("git")
                     .args(["update-ref", "-d", ref_name])
@@ -0,0 +1,369 @@
+use crate::error::GitAiError;
+
+/// A git-ai command and the flags it understands. Everything else is proxied to git, so
+/// clap only ever sees a trailing var-arg and can't generate these itself.
+struct CompletionCommand {
+    name: &'static str,
+    about: &'static str,
+    flags: &'static [&'static str],
+}
+
+const COMMANDS: &[CompletionCommand] = &[
+    CompletionCommand {
+        name: "checkpoint",
+        about: "checkpoint working changes and specify author",
+        flags: &[
+            "--author",
+            "--model",
+            "--detect-model",
+            "--author-type",
+            "--confidence",
+            "--verify-clean",
+            "--allow",
+            "--link-commit",
+            "--overwrite",
+            "--touch-only",
+            "--snapshot",
+            "--max-file-size",
+            "--on-branch",
+            "--skip-branch",
+            "--files",
+            "--message",
+            "--hook-input",
+            "--hook-input-file",
+            "--no-index",
+            "--staged-only",
+            "--expire-working-log",
+            "--max-ms",
+            "--json",
+            "--repo",
+            "--show-working-log",
+            "--reset",
+        ],
+    },
+    CompletionCommand {
+        name: "blame",
+        about: "git blame with AI authorship tracking",
+        flags: &[
+            "-L",
+            "--porcelain",
+            "--line-porcelain",
+            "--incremental",
+            "--porcelain-v2",
+            "-f",
+            "--show-name",
+            "-n",
+            "--show-number",
+            "-e",
+            "--show-email",
+            "-s",
+            "--show-stats",
+            "-l",
+            "-t",
+            "--abbrev",
+            "-b",
+            "--root",
+            "-M",
+            "-C",
+            "--ignore-rev",
+            "--ignore-revs-file",
+            "--color-lines",
+            "--color-by-age",
+            "--theme",
+            "--progress",
+            "--date",
+            "--contents",
+            "--reverse",
+            "--first-parent",
+            "--no-merges",
+            "--at",
+            "--encoding",
+            "--web",
+            "--no-open",
+            "--json",
+            "--json-lines",
+            "--output",
+            "--since",
+            "--no-mailmap",
+            "--relative-to",
+            "--model",
+            "--model-exact",
+            "--only",
+            "--tabwidth",
+            "--fields",
+            "--wrap",
+            "--show-prompt",
+            "--group-hunks",
+            "--group-edges",
+            "--compare-with-git",
+            "--compare-with",
+            "--compare-threshold",
+            "--count",
+            "--threshold",
+        ],
+    },
+    CompletionCommand {
+        name: "stats",
+        about: "human vs AI line stats for a commit or range",
+        flags: &[
+            "--me",
+            "--author-me",
+            "--since",
+            "--until",
+            "--fetch",
+            "--no-fetch",
+            "--zero-when-empty",
+            "--cache",
+            "--no-cache",
+            "--annotate-commit",
+            "--include-merges",
+            "--no-mailmap",
+            "--author-domain",
+            "--min-confidence",
+            "--baseline-branch",
+            "--per-commit",
+            "--format",
+            "--no-color",
+            "--output",
+            "--emit-prometheus",
+            "--resolve-only",
+            "--output-file",
+            "--author-regex",
+            "--by-author",
+            "--round",
+            "--decimals",
+            "--sum-to-100",
+            "--markdown-table",
+            "--only-ai-files",
+            "--top",
+            "--with-counts",
+            "--weighted-by",
+            "--human-effort",
+            "--accept-rate",
+            "--delta-from-parent",
+            "--normalize-by",
+            "--json-schema",
+            "--min-lines",
+            "--exclude-small",
+            "--watch",
+            "--interval",
+            "--count-reformat-as",
+        ],
+    },
+    CompletionCommand {
+        name: "log",
+        about: "git log, or a file's checkpoint history with --file",
+        flags: &[
+            "--ai",
+            "--file",
+            "-L",
+            "--author",
+            "--model",
+            "--limit",
+            "--reverse",
+            "--oneline",
+            "--author-stats",
+            "--model-breakdown",
+            "--between",
+            "--json",
+            "--json-lines",
+        ],
+    },
+    CompletionCommand {
+        name: "status",
+        about: "git status, or AI/human line counts per path with --ai",
+        flags: &["--ai", "--porcelain"],
+    },
+    CompletionCommand {
+        name: "search",
+        about: "search checkpoint prompts",
+        flags: &["--regex"],
+    },
+    CompletionCommand {
+        name: "mark-ai",
+        about: "tag line ranges pasted from an AI chat",
+        flags: &["--lines", "--model", "--author"],
+    },
+    CompletionCommand {
+        name: "doctor",
+        about: "check git-ai setup",
+        flags: &["--json"],
+    },
+    CompletionCommand {
+        name: "gate",
+        about: "fail when AI-authored lines exceed a threshold",
+        flags: &[
+            "--max",
+            "--per-file-max",
+            "--min-lines",
+            "--fetch",
+            "--no-fetch",
+        ],
+    },
+    CompletionCommand {
+        name: "export",
+        about: "bundle authorship refs for offline transfer",
+        flags: &["--format", "--output"],
+    },
+    CompletionCommand {
+        name: "import",
+        about: "fetch authorship refs from an exported bundle",
+        flags: &["--merge-strategy"],
+    },
+    CompletionCommand {
+        name: "diff-checkpoints",
+        about: "diff two checkpoints, or one against the work tree",
+        flags: &[],
+    },
+    CompletionCommand {
+        name: "reset-attribution",
+        about: "delete attribution refs without touching history",
+        flags: &["--working", "--commits", "--all", "--confirm"],
+    },
+    CompletionCommand {
+        name: "hook-exec",
+        about: "run an editor hook (payload on stdin)",
+        flags: &[],
+    },
+    CompletionCommand {
+        name: "hooks",
+        about: "show which git hooks run git-ai (hooks status)",
+        flags: &["--json"],
+    },
+    CompletionCommand {
+        name: "init",
+        about: "git init, or write editor hook configs with --with-presets",
+        flags: &["--with-presets", "--install-hooks"],
+    },
+    CompletionCommand {
+        name: "apply",
+        about: "git apply, attributing the applied hunks",
+        flags: &["--author-type", "--model", "--author"],
+    },
+    CompletionCommand {
+        name: "commit",
+        about: "git commit with git-ai hooks",
+        flags: &[],
+    },
+    CompletionCommand {
+        name: "cherry-pick",
+        about: "git cherry-pick, carrying AI attribution to the new commits",
+        flags: &["--continue", "--skip", "--abort"],
+    },
+    CompletionCommand {
+        name: "fetch",
+        about: "fetch with AI authorship refs",
+        flags: &[],
+    },
+    CompletionCommand {
+        name: "push",
+        about: "push with AI authorship refs",
+        flags: &[],
+    },
+    CompletionCommand {
+        name: "completions",
+        about: "print a shell completion script",
+        flags: &["bash", "zsh", "fish", "powershell"],
+    },
+];
+
+pub fn run(shell: &str) -> Result<String, GitAiError> {
+    match shell {
+        "bash" => Ok(bash()),
+        "zsh" => Ok(zsh()),
+        "fish" => Ok(fish()),
+        "powershell" | "pwsh" => Ok(powershell()),
+        _ => Err(GitAiError::Generic(format!(
+            "Unsupported shell: {} (expected bash, zsh, fish or powershell)",
+            shell
+        ))),
+    }
+}
+
+fn command_names() -> String {
+    COMMANDS
+        .iter()
+        .map(|command| command.name)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn bash() -> String {
+    let mut script = String::from("_git_ai() {\n    local cur=\"${COMP_WORDS[COMP_CWORD]}\"\n");
+    script.push_str(&format!(
+        "    if [ \"$COMP_CWORD\" -eq 1 ]; then\n        COMPREPLY=($(compgen -W \"{}\" -- \"$cur\"))\n        return\n    fi\n",
+        command_names()
+    ));
+    script.push_str("    case \"${COMP_WORDS[1]}\" in\n");
+    for command in COMMANDS.iter().filter(|command| !command.flags.is_empty()) {
+        script.push_str(&format!(
+            "        {})\n            COMPREPLY=($(compgen -W \"{}\" -- \"$cur\"))\n            ;;\n",
+            command.name,
+            command.flags.join(" ")
+        ));
+    }
+    script.push_str("    esac\n}\ncomplete -o default -F _git_ai git-ai\n");
+    script
+}
+
+fn zsh() -> String {
+    let mut script =
+        String::from("#compdef git-ai\n\n_git_ai() {\n    local -a commands\n    commands=(\n");
+    for command in COMMANDS {
+        script.push_str(&format!("        '{}:{}'\n", command.name, command.about));
+    }
+    script.push_str("    )\n    if (( CURRENT == 2 )); then\n        _describe 'command' commands\n        return\n    fi\n");
+    script.push_str("    case $words[2] in\n");
+    for command in COMMANDS.iter().filter(|command| !command.flags.is_empty()) {
+        script.push_str(&format!(
+            "        {})\n            compadd -- {}\n            ;;\n",
+            command.name,
+            command.flags.join(" ")
+        ));
+    }
+    script.push_str("    esac\n    _files\n}\n\n_git_ai \"$@\"\n");
+    script
+}
+
+fn fish() -> String {
+    let mut script = String::new();
+    for command in COMMANDS {
+        script.push_str(&format!(
+            "complete -c git-ai -n '__fish_use_subcommand' -a {} -d '{}'\n",
+            command.name, command.about
+        ));
+    }
+    for command in COMMANDS {
+        let condition = format!("__fish_seen_subcommand_from {}", command.name);
+        for flag in command.flags {
+            let spec = if let Some(long) = flag.strip_prefix("--") {
+                format!("-l {}", long)
+            } else if let Some(short) = flag.strip_prefix('-') {
+                format!("-s {}", short)
+            } else {
+                format!("-f -a {}", flag)
+            };
+            script.push_str(&format!("complete -c git-ai -n '{}' {}\n", condition, spec));
+        }
+    }
+    script
+}
+
+fn powershell() -> String {
+    let mut script = String::from(
+        "Register-ArgumentCompleter -Native -CommandName git-ai -ScriptBlock {\n    param($wordToComplete, $commandAst, $cursorPosition)\n    $commands = @{\n",
+    );
+    for command in COMMANDS {
+        let flags = command
+            .flags
+            .iter()
+            .map(|flag| format!("'{}'", flag))
+            .collect::<Vec<_>>()
+            .join(", ");
+        script.push_str(&format!("        '{}' = @({})\n", command.name, flags));
+    }
+    script.push_str(
+        "    }\n    $elements = @($commandAst.CommandElements | ForEach-Object { $_.ToString() })\n    if ($elements.Count -le 1 -or ($elements.Count -eq 2 -and $wordToComplete)) {\n        $candidates = $commands.Keys\n    } else {\n        $candidates = $commands[$elements[1]]\n    }\n    $candidates | Where-Object { $_ -like \"$wordToComplete*\" } | Sort-Object | ForEach-Object {\n        [System.Management.Automation.CompletionResult]::new($_, $_, 'ParameterValue', $_)\n    }\n}\n",
+    );
+    script
+}
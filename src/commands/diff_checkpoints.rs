@@ -0,0 +1,270 @@
+use crate::commands::checkpoint::resolve_base_commit;
+use crate::error::GitAiError;
+use crate::git::refs::get_reference_as_working_log;
+use crate::log_fmt::working_log::{Checkpoint, Line};
+use git2::{Oid, Repository};
+use similar::TextDiff;
+use std::collections::BTreeSet;
+use std::path::Path;
+
+/// One side of the comparison
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CheckpointSide {
+    /// A checkpoint by its 1-based position in a working log, as in `checkpoint --json` ids
+    /// (`<base>:<n>`). Without a base the current base commit's working log is meant.
+    Checkpoint { base: Option<String>, index: usize },
+    /// The files as they are on disk now
+    WorkTree,
+}
+
+impl CheckpointSide {
+    /// `<n>`, `<base>:<n>`, or `worktree`
+    pub fn parse(value: &str) -> Result<Self, GitAiError> {
+        if value == "worktree" {
+            return Ok(CheckpointSide::WorkTree);
+        }
+        let (base, index) = match value.rsplit_once(':') {
+            Some((base, index)) => (Some(base.to_string()), index),
+            None => (None, value),
+        };
+        match index.parse::<usize>() {
+            Ok(index) => Ok(CheckpointSide::Checkpoint { base, index }),
+            Err(_) => Err(GitAiError::Generic(format!(
+                "Invalid checkpoint: {}. Expected <n>, <base>:<n> or worktree",
+                value
+            ))),
+        }
+    }
+}
+
+/// Parse `diff-checkpoints <a> [<b>]`; `<b>` defaults to the work tree
+pub fn parse_diff_checkpoints_args(
+    args: &[String],
+) -> Result<(CheckpointSide, CheckpointSide), GitAiError> {
+    if let Some(arg) = args.iter().find(|arg| arg.starts_with('-')) {
+        return Err(GitAiError::Generic(format!("Unknown option: {}", arg)));
+    }
+    match args {
+        [a] => Ok((CheckpointSide::parse(a)?, CheckpointSide::WorkTree)),
+        [a, b] => Ok((CheckpointSide::parse(a)?, CheckpointSide::parse(b)?)),
+        _ => Err(GitAiError::Generic(
+            "diff-checkpoints requires one or two checkpoints: <a> [<b>]".to_string(),
+        )),
+    }
+}
+
+/// Compare what two checkpoints (or a checkpoint and the work tree) saw, file by file.
+/// A file's content at a checkpoint comes from the `--snapshot` of the last checkpoint that
+/// changed it, or from the base commit when none had. Files whose content can't be rebuilt
+/// that way fall back to the line changes recorded between the two points. Each file is
+/// headed by the checkpoints that changed it in between, so the diff reads as who did what.
+pub fn run(
+    repo: &Repository,
+    a: &CheckpointSide,
+    b: &CheckpointSide,
+) -> Result<String, GitAiError> {
+    let (base_a, position_a) = resolve_side(repo, a)?;
+    let (base_b, position_b) = resolve_side(repo, b)?;
+    if base_a != base_b {
+        return Err(GitAiError::Generic(format!(
+            "Checkpoints are from different working logs ({} and {})",
+            short(&base_a),
+            short(&base_b)
+        )));
+    }
+    let working_log = get_reference_as_working_log(repo, &format!("ai-working-log/{}", base_a))
+        .unwrap_or_default();
+    for position in [position_a, position_b].into_iter().flatten() {
+        if position == 0 || position > working_log.len() {
+            return Err(GitAiError::Generic(format!(
+                "No checkpoint #{} in the working log for {} (it has {})",
+                position,
+                short(&base_a),
+                working_log.len()
+            )));
+        }
+    }
+    // The work tree comes after every checkpoint
+    let end_a = position_a.unwrap_or(working_log.len() + 1);
+    let end_b = position_b.unwrap_or(working_log.len() + 1);
+    if end_a == end_b {
+        return Err(GitAiError::Generic(
+            "diff-checkpoints needs two different points to compare".to_string(),
+        ));
+    }
+    let (from, to) = (end_a.min(end_b), end_a.max(end_b));
+    let label = |position: Option<usize>| match position {
+        Some(position) => format!("#{}", position),
+        None => "worktree".to_string(),
+    };
+    let (label_a, label_b) = (label(position_a), label(position_b));
+
+    // Checkpoints strictly after `from`, up to and including `to`
+    let between: Vec<(usize, &Checkpoint)> = working_log
+        .iter()
+        .enumerate()
+        .map(|(i, checkpoint)| (i + 1, checkpoint))
+        .filter(|(position, checkpoint)| {
+            *position > from && *position <= to && !checkpoint.touch_only
+        })
+        .collect();
+    let mut files: BTreeSet<&str> = between
+        .iter()
+        .flat_map(|(_, checkpoint)| checkpoint.entries.iter().map(|entry| entry.file.as_str()))
+        .collect();
+    // Against the work tree, edits made since the last checkpoint count too
+    if to > working_log.len() {
+        files.extend(
+            working_log
+                .iter()
+                .flat_map(|checkpoint| checkpoint.entries.iter().map(|entry| entry.file.as_str())),
+        );
+    }
+
+    let mut output = String::new();
+    for file in files {
+        let changed_by: Vec<&(usize, &Checkpoint)> = between
+            .iter()
+            .filter(|(_, checkpoint)| checkpoint.entries.iter().any(|entry| entry.file == file))
+            .collect();
+        let old = content_at(repo, &base_a, &working_log, end_a, file)?;
+        let new = content_at(repo, &base_a, &working_log, end_b, file)?;
+        if let (Some(old), Some(new)) = (&old, &new)
+            && old == new
+        {
+            continue;
+        }
+
+        output.push_str(&format!("=== {}\n", file));
+        if changed_by.is_empty() {
+            output.push_str("changed since the last checkpoint\n");
+        } else {
+            output.push_str(&format!(
+                "changed in {}\n",
+                changed_by
+                    .iter()
+                    .map(|(position, checkpoint)| {
+                        format!("#{} {}", position, checkpoint.attribution())
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+        match (old, new) {
+            (Some(old), Some(new)) => {
+                let diff = TextDiff::from_lines(&old, &new);
+                output.push_str(
+                    &diff
+                        .unified_diff()
+                        .header(
+                            &format!("{} {}", label_a, file),
+                            &format!("{} {}", label_b, file),
+                        )
+                        .to_string(),
+                );
+            }
+            _ => {
+                output.push_str("no content snapshot; recorded line changes:\n");
+                for (position, checkpoint) in changed_by {
+                    for entry in checkpoint.entries.iter().filter(|entry| entry.file == file) {
+                        output.push_str(&format!(
+                            "  #{} {}  {}\n",
+                            position,
+                            checkpoint.attribution(),
+                            line_changes(&entry.added_lines, &entry.deleted_lines)
+                        ));
+                    }
+                }
+            }
+        }
+    }
+    if output.is_empty() {
+        output = format!("No changes between {} and {}\n", label_a, label_b);
+    }
+    Ok(output)
+}
+
+/// The side's working log base commit, and its checkpoint position (none for the work tree)
+fn resolve_side(
+    repo: &Repository,
+    side: &CheckpointSide,
+) -> Result<(String, Option<usize>), GitAiError> {
+    match side {
+        CheckpointSide::WorkTree => Ok((resolve_base_commit(repo)?, None)),
+        CheckpointSide::Checkpoint { base: None, index } => {
+            Ok((resolve_base_commit(repo)?, Some(*index)))
+        }
+        CheckpointSide::Checkpoint {
+            base: Some(base),
+            index,
+        } => {
+            let base = if base == "initial" {
+                base.clone()
+            } else {
+                repo.revparse_single(base)
+                    .and_then(|object| object.peel_to_commit())
+                    .map(|commit| commit.id().to_string())
+                    .map_err(|_| GitAiError::Generic(format!("Unknown base commit: {}", base)))?
+            };
+            Ok((base, Some(*index)))
+        }
+    }
+}
+
+/// `file` as of `position` (past the end means the work tree), or None when the checkpoint
+/// that last changed it took no snapshot
+fn content_at(
+    repo: &Repository,
+    base_commit: &str,
+    working_log: &[Checkpoint],
+    position: usize,
+    file: &str,
+) -> Result<Option<String>, GitAiError> {
+    if position > working_log.len() {
+        let workdir = repo.workdir().unwrap_or_else(|| Path::new("."));
+        return Ok(Some(
+            std::fs::read(workdir.join(file))
+                .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+                .unwrap_or_default(),
+        ));
+    }
+
+    let last_change = working_log[..position].iter().rev().find(|checkpoint| {
+        !checkpoint.touch_only && checkpoint.entries.iter().any(|entry| entry.file == file)
+    });
+    let tree = match last_change {
+        Some(checkpoint) => match &checkpoint.content_tree {
+            Some(tree) => repo.find_tree(Oid::from_str(tree)?)?,
+            None => return Ok(None),
+        },
+        None if base_commit == "initial" => return Ok(Some(String::new())),
+        None => repo.find_commit(Oid::from_str(base_commit)?)?.tree()?,
+    };
+    // A file missing from the snapshot was deleted; one missing from the base commit is new
+    Ok(Some(match tree.get_path(Path::new(file)) {
+        Ok(entry) => {
+            let blob = entry.to_object(repo)?.peel_to_blob()?;
+            String::from_utf8_lossy(blob.content()).into_owned()
+        }
+        Err(_) => String::new(),
+    }))
+}
+
+fn line_changes(added: &[Line], deleted: &[Line]) -> String {
+    let spans = |sign: char, lines: &[Line]| {
+        lines
+            .iter()
+            .map(|line| match line {
+                Line::Single(line) => format!("{}{}", sign, line),
+                Line::Range(start, end) => format!("{}{}-{}", sign, start, end),
+            })
+            .collect::<Vec<_>>()
+    };
+    let mut changes = spans('+', added);
+    changes.extend(spans('-', deleted));
+    changes.join(" ")
+}
+
+fn short(sha: &str) -> &str {
+    &sha[..sha.len().min(8)]
+}
@@ -0,0 +1,208 @@
+use crate::commands::hooks::hooks_dir;
+use crate::commands::init::{GIT_HOOK_MARKER, Preset};
+use crate::git::refs::{get_reference, get_reference_as_working_log};
+use crate::git::repository::user_name;
+use git2::Repository;
+use serde::Serialize;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+/// Outcome of one setup check
+#[derive(Debug, Clone, Serialize)]
+pub struct Check {
+    pub id: &'static str,
+    pub status: CheckStatus,
+    pub message: String,
+    /// What to run or change when the check doesn't pass
+    pub remediation: Option<String>,
+}
+
+impl Check {
+    fn pass(id: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            id,
+            status: CheckStatus::Pass,
+            message: message.into(),
+            remediation: None,
+        }
+    }
+
+    fn problem(
+        id: &'static str,
+        status: CheckStatus,
+        message: impl Into<String>,
+        remediation: impl Into<String>,
+    ) -> Self {
+        Self {
+            id,
+            status,
+            message: message.into(),
+            remediation: Some(remediation.into()),
+        }
+    }
+}
+
+/// Check that git-ai can record and report attribution here. `repo` is None outside a
+/// repository; `home` is where the editor hook configs live.
+pub fn run_checks(repo: Option<&Repository>, home: Option<&Path>) -> Vec<Check> {
+    let mut checks = vec![check_git()];
+    match repo {
+        Some(repo) => {
+            checks.push(Check::pass("repository", "Inside a git repository"));
+            checks.push(check_identity(repo));
+            checks.push(check_working_log(repo));
+            checks.push(check_post_rewrite_hook(repo));
+        }
+        None => checks.push(Check::problem(
+            "repository",
+            CheckStatus::Fail,
+            "Not inside a git repository",
+            "Run git-ai from inside a repository, or `git init` one",
+        )),
+    }
+    checks.push(check_editor_hooks(home));
+    checks
+}
+
+/// 0 when everything passes, 1 for warnings, 2 when any check fails
+pub fn exit_code(checks: &[Check]) -> i32 {
+    match checks.iter().map(|check| check.status).max() {
+        Some(CheckStatus::Fail) => 2,
+        Some(CheckStatus::Warn) => 1,
+        _ => 0,
+    }
+}
+
+pub fn print_checks(checks: &[Check]) {
+    for check in checks {
+        let status = match check.status {
+            CheckStatus::Pass => "pass",
+            CheckStatus::Warn => "warn",
+            CheckStatus::Fail => "fail",
+        };
+        println!("[{}] {}: {}", status, check.id, check.message);
+        if let Some(remediation) = &check.remediation {
+            println!("       {}", remediation);
+        }
+    }
+}
+
+fn check_git() -> Check {
+    match std::process::Command::new("git").arg("--version").output() {
+        Ok(output) if output.status.success() => Check::pass(
+            "git",
+            String::from_utf8_lossy(&output.stdout).trim().to_string(),
+        ),
+        _ => Check::problem(
+            "git",
+            CheckStatus::Fail,
+            "git is not on PATH",
+            "Install git; git-ai proxies every command it doesn't handle to it",
+        ),
+    }
+}
+
+fn check_identity(repo: &Repository) -> Check {
+    match user_name(repo) {
+        Some(name) => Check::pass(
+            "user-name",
+            format!("Human checkpoints recorded as {}", name),
+        ),
+        None => Check::problem(
+            "user-name",
+            CheckStatus::Warn,
+            "user.name is not set, so human checkpoints are recorded as 'unknown'",
+            "git config --global user.name \"Your Name\"",
+        ),
+    }
+}
+
+fn check_working_log(repo: &Repository) -> Check {
+    let base_commit = match crate::commands::checkpoint::resolve_base_commit(repo) {
+        Ok(base_commit) => base_commit,
+        Err(e) => {
+            return Check::problem(
+                "working-log",
+                CheckStatus::Fail,
+                format!("Cannot resolve HEAD: {}", e),
+                "Check the repository with `git status`",
+            );
+        }
+    };
+    let ref_name = format!("ai-working-log/{}", base_commit);
+    if get_reference(repo, &ref_name).is_err() {
+        return Check::pass("working-log", "No checkpoints since the last commit");
+    }
+    match get_reference_as_working_log(repo, &ref_name) {
+        Ok(working_log) => Check::pass(
+            "working-log",
+            format!("{} checkpoint(s) since the last commit", working_log.len()),
+        ),
+        Err(e) => Check::problem(
+            "working-log",
+            CheckStatus::Fail,
+            format!("refs/{} is unreadable: {}", ref_name, e),
+            "git-ai checkpoint --reset",
+        ),
+    }
+}
+
+fn check_post_rewrite_hook(repo: &Repository) -> Check {
+    let path = hooks_dir(repo).join("post-rewrite");
+    match std::fs::read_to_string(&path) {
+        Ok(hook) if hook.contains(GIT_HOOK_MARKER) || hook.contains("git-ai post-rewrite") => {
+            Check::pass(
+                "post-rewrite-hook",
+                "Rebased and amended commits keep attribution",
+            )
+        }
+        Ok(_) => Check::problem(
+            "post-rewrite-hook",
+            CheckStatus::Warn,
+            format!("{} does not call git-ai", path.display()),
+            "Add `git-ai post-rewrite \"$@\"` to the existing hook",
+        ),
+        Err(_) => Check::problem(
+            "post-rewrite-hook",
+            CheckStatus::Warn,
+            "No post-rewrite hook, so rebased commits lose their attribution",
+            "git-ai init --install-hooks",
+        ),
+    }
+}
+
+fn check_editor_hooks(home: Option<&Path>) -> Check {
+    let configured: Vec<String> = home
+        .map(|home| {
+            [Preset::Cursor, Preset::Claude]
+                .into_iter()
+                .filter(|preset| {
+                    std::fs::read_to_string(preset.config_path(home)).is_ok_and(|config| {
+                        config.contains("git-ai checkpoint") || config.contains("hook-exec")
+                    })
+                })
+                .map(|preset| format!("{:?}", preset))
+                .collect()
+        })
+        .unwrap_or_default();
+    if configured.is_empty() {
+        Check::problem(
+            "editor-hooks",
+            CheckStatus::Warn,
+            "No editor runs git-ai checkpoint hooks, so AI edits are only recorded by hand",
+            "git-ai init --with-presets cursor,claude",
+        )
+    } else {
+        Check::pass(
+            "editor-hooks",
+            format!("Checkpoint hooks configured for {}", configured.join(", ")),
+        )
+    }
+}
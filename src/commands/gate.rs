@@ -0,0 +1,206 @@
+use crate::commands::stats::{self, StatsOptions, StatsReport, percentage};
+use crate::error::GitAiError;
+use git2::Repository;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GateOptions {
+    /// Fail when the AI share of all added lines is above this percentage
+    pub max: Option<f64>,
+    /// Fail when any single file's AI share is above this percentage
+    pub per_file_max: Option<f64>,
+    /// Files with fewer added lines than this are exempt from `per_file_max`
+    pub min_lines: Option<u32>,
+    pub fetch: bool,
+    pub no_fetch: bool,
+}
+
+/// A file whose AI share is over `--per-file-max`
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileViolation {
+    pub path: String,
+    pub ai_additions: u32,
+    pub total_additions: u32,
+}
+
+impl FileViolation {
+    pub fn ai_percentage(&self) -> f64 {
+        percentage(self.ai_additions, self.total_additions)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GateOutcome {
+    pub ai_percentage: f64,
+    pub files: usize,
+    /// The aggregate share is over `--max`
+    pub aggregate_exceeded: bool,
+    /// Files over `--per-file-max`, most AI lines first
+    pub violations: Vec<FileViolation>,
+}
+
+impl GateOutcome {
+    pub fn passed(&self) -> bool {
+        !self.aggregate_exceeded && self.violations.is_empty()
+    }
+}
+
+/// Check a commit or range against the AI thresholds. The two checks are independent: a
+/// range can pass `--max` overall while one file in it is entirely AI-authored.
+pub fn run(
+    repo: &Repository,
+    revspec: Option<&str>,
+    options: &GateOptions,
+) -> Result<GateOutcome, GitAiError> {
+    let stats_options = StatsOptions {
+        fetch: options.fetch,
+        no_fetch: options.no_fetch,
+        ..Default::default()
+    };
+    let report = stats::report(repo, revspec, &stats_options)?;
+    Ok(evaluate(&report, options))
+}
+
+pub fn evaluate(report: &StatsReport, options: &GateOptions) -> GateOutcome {
+    let ai_percentage = percentage(report.ai_additions, report.total_additions());
+    let aggregate_exceeded = options.max.is_some_and(|max| ai_percentage > max);
+
+    let mut violations: Vec<FileViolation> = match options.per_file_max {
+        Some(max) => report
+            .file_stats
+            .iter()
+            .filter(|(_, file)| file.total_additions >= options.min_lines.unwrap_or(0))
+            .filter(|(_, file)| percentage(file.ai_additions, file.total_additions) > max)
+            .map(|(path, file)| FileViolation {
+                path: path.clone(),
+                ai_additions: file.ai_additions,
+                total_additions: file.total_additions,
+            })
+            .collect(),
+        None => Vec::new(),
+    };
+    violations.sort_by(|a, b| {
+        b.ai_additions
+            .cmp(&a.ai_additions)
+            .then_with(|| a.path.cmp(&b.path))
+    });
+
+    GateOutcome {
+        ai_percentage,
+        files: report.file_stats.len(),
+        aggregate_exceeded,
+        violations,
+    }
+}
+
+/// What the gate prints: a verdict line, then each violating file
+pub fn render(outcome: &GateOutcome, options: &GateOptions) -> String {
+    let mut output = String::new();
+    if outcome.passed() {
+        output.push_str(&format!(
+            "git-ai gate passed: {:.0}% AI across {} file{}\n",
+            outcome.ai_percentage,
+            outcome.files,
+            if outcome.files == 1 { "" } else { "s" }
+        ));
+        return output;
+    }
+    if let Some(max) = options.max
+        && outcome.aggregate_exceeded
+    {
+        output.push_str(&format!(
+            "git-ai gate failed: {:.0}% AI is over --max {}%\n",
+            outcome.ai_percentage, max
+        ));
+    }
+    if let Some(max) = options.per_file_max
+        && !outcome.violations.is_empty()
+    {
+        output.push_str(&format!(
+            "git-ai gate failed: {} file{} over --per-file-max {}%:\n",
+            outcome.violations.len(),
+            if outcome.violations.len() == 1 {
+                " is"
+            } else {
+                "s are"
+            },
+            max
+        ));
+        for violation in &outcome.violations {
+            output.push_str(&format!(
+                "  {}  {:.0}% AI ({} of {} lines)\n",
+                violation.path,
+                violation.ai_percentage(),
+                violation.ai_additions,
+                violation.total_additions
+            ));
+        }
+    }
+    output
+}
+
+/// Parse `gate [<commit|range>] [--max <percent>] [--per-file-max <percent>]
+/// [--min-lines <n>] [--fetch | --no-fetch]`
+pub fn parse_gate_args(args: &[String]) -> Result<(Option<String>, GateOptions), GitAiError> {
+    let mut revspec = None;
+    let mut options = GateOptions::default();
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--max" | "--per-file-max" => {
+                let percent = args
+                    .get(i + 1)
+                    .and_then(|value| value.trim_end_matches('%').parse::<f64>().ok())
+                    .filter(|percent| (0.0..=100.0).contains(percent))
+                    .ok_or_else(|| {
+                        GitAiError::Generic(format!(
+                            "{} requires a percentage between 0 and 100",
+                            args[i]
+                        ))
+                    })?;
+                if args[i] == "--max" {
+                    options.max = Some(percent);
+                } else {
+                    options.per_file_max = Some(percent);
+                }
+                i += 2;
+            }
+            "--min-lines" => {
+                let lines = args
+                    .get(i + 1)
+                    .and_then(|value| value.parse::<u32>().ok())
+                    .ok_or_else(|| {
+                        GitAiError::Generic("--min-lines requires a number of lines".to_string())
+                    })?;
+                options.min_lines = Some(lines);
+                i += 2;
+            }
+            "--fetch" => {
+                options.fetch = true;
+                i += 1;
+            }
+            "--no-fetch" => {
+                options.no_fetch = true;
+                i += 1;
+            }
+            arg if !arg.starts_with('-') && revspec.is_none() => {
+                revspec = Some(arg.to_string());
+                i += 1;
+            }
+            _ => {
+                return Err(GitAiError::Generic(format!("Unknown option: {}", args[i])));
+            }
+        }
+    }
+
+    if options.max.is_none() && options.per_file_max.is_none() {
+        return Err(GitAiError::Generic(
+            "gate requires --max <percent> and/or --per-file-max <percent>".to_string(),
+        ));
+    }
+    if options.min_lines.is_some() && options.per_file_max.is_none() {
+        return Err(GitAiError::Generic(
+            "--min-lines applies to --per-file-max".to_string(),
+        ));
+    }
+    Ok((revspec, options))
+}
@@ -0,0 +1,120 @@
+use crate::commands::checkpoint::{self, DEFAULT_HOOK_MAX_MS, prompt_from_hook_payload};
+use crate::commands::init::Preset;
+use crate::error::GitAiError;
+use crate::git::repository::find_repository;
+use serde_json::Value;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// What an editor hook event asks of git-ai
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HookAction {
+    /// Checkpoint pending human edits, recording the submitted prompt if there is one
+    Human { prompt: Option<String> },
+    /// Checkpoint the agent's edits
+    Ai {
+        author: &'static str,
+        model: &'static str,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HookInvocation {
+    pub action: HookAction,
+    /// Directory the editor ran the agent in, when the payload says
+    pub cwd: Option<PathBuf>,
+}
+
+/// Work out what to do from the editor's hook payload. `event` (from the command line) wins
+/// over the payload's `hook_event_name`, which both Cursor and Claude Code send.
+pub fn parse_hook_payload(
+    preset: Preset,
+    payload: &str,
+    event: Option<&str>,
+) -> Result<HookInvocation, GitAiError> {
+    let json: Value = serde_json::from_str(payload.trim()).unwrap_or(Value::Null);
+    let event = event
+        .or_else(|| json.get("hook_event_name").and_then(Value::as_str))
+        .ok_or_else(|| {
+            GitAiError::Generic("Hook payload has no hook_event_name; pass the event".to_string())
+        })?;
+
+    let action = match (preset, event) {
+        (Preset::Claude, "UserPromptSubmit") | (Preset::Cursor, "beforeSubmitPrompt") => {
+            HookAction::Human {
+                prompt: prompt_from_hook_payload(payload),
+            }
+        }
+        (Preset::Claude, "PreToolUse") => HookAction::Human { prompt: None },
+        (Preset::Claude, "PostToolUse") => HookAction::Ai {
+            author: "Claude",
+            model: "claude",
+        },
+        (Preset::Cursor, "afterFileEdit") => HookAction::Ai {
+            author: "Cursor",
+            model: "cursor",
+        },
+        _ => {
+            return Err(GitAiError::Generic(format!(
+                "Unsupported {} hook event: {}",
+                preset.name(),
+                event
+            )));
+        }
+    };
+
+    // Claude Code sends the session's cwd, Cursor the open workspace folders
+    let cwd = json
+        .get("cwd")
+        .and_then(Value::as_str)
+        .or_else(|| {
+            json.get("workspace_roots")
+                .and_then(|roots| roots.get(0))
+                .and_then(Value::as_str)
+        })
+        .map(PathBuf::from);
+
+    Ok(HookInvocation { action, cwd })
+}
+
+/// `git-ai checkpoint` arguments for an action
+pub fn checkpoint_args(action: &HookAction) -> Vec<String> {
+    match action {
+        HookAction::Human { prompt: None } => Vec::new(),
+        HookAction::Human {
+            prompt: Some(prompt),
+        } => vec!["--message".to_string(), prompt.clone()],
+        HookAction::Ai { author, model } => vec![
+            "--author".to_string(),
+            author.to_string(),
+            "--model".to_string(),
+            model.to_string(),
+        ],
+    }
+}
+
+/// Run one editor hook: move to the agent's directory and checkpoint under the hook time
+/// budget, so editors need no shell or profile sourcing to find the repository
+pub fn run(preset: Preset, payload: &str, event: Option<&str>) -> Result<(), GitAiError> {
+    let invocation = parse_hook_payload(preset, payload, event)?;
+    if let Some(cwd) = &invocation.cwd {
+        std::env::set_current_dir(cwd)
+            .map_err(|e| GitAiError::Generic(format!("Cannot enter {}: {}", cwd.display(), e)))?;
+    }
+    // Agents also work outside repositories; there is nothing to checkpoint there
+    if find_repository().is_err() {
+        return Ok(());
+    }
+
+    let status = checkpoint::run_with_budget(
+        &checkpoint_args(&invocation.action),
+        Duration::from_millis(DEFAULT_HOOK_MAX_MS),
+    )?;
+    match status {
+        Some(status) if !status.success() => Err(GitAiError::Generic(format!(
+            "Checkpoint exited with {}",
+            status
+        ))),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,129 @@
+use crate::commands::init::GIT_HOOK_MARKER;
+use crate::error::GitAiError;
+use git2::Repository;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The git hooks `hooks status` reports on. git-ai installs post-rewrite; repositories set
+/// up by older versions still have pre-commit and post-commit hooks calling it.
+pub const GIT_HOOKS: [&str; 4] = ["pre-commit", "post-commit", "post-merge", "post-rewrite"];
+
+/// One hook as git will run it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HookStatus {
+    pub name: &'static str,
+    pub path: String,
+    /// A script exists at the hook's path
+    pub installed: bool,
+    /// git skips hooks that aren't executable (always true off Unix)
+    pub executable: bool,
+    /// The script calls git-ai
+    pub runs_git_ai: bool,
+    /// git-ai wrote the script (`init --install-hooks`); it never overwrites one of yours
+    pub installed_by_git_ai: bool,
+    /// The script is your own and calls git-ai too, so both keep running
+    pub chained: bool,
+}
+
+/// Parse `hooks status [--json]`; returns whether `--json` was given
+pub fn parse_hooks_args(args: &[String]) -> Result<bool, GitAiError> {
+    match args.first().map(String::as_str) {
+        Some("status") => {}
+        Some(other) => {
+            return Err(GitAiError::Generic(format!(
+                "Unknown hooks subcommand: {}. Expected status",
+                other
+            )));
+        }
+        None => return Err(GitAiError::Generic("hooks requires status".to_string())),
+    }
+    let mut json = false;
+    for arg in &args[1..] {
+        match arg.as_str() {
+            "--json" => json = true,
+            _ => return Err(GitAiError::Generic(format!("Unknown option: {}", arg))),
+        }
+    }
+    Ok(json)
+}
+
+/// Where git looks for hooks: `core.hooksPath` (relative to the work tree) when set, as
+/// git does, otherwise the `hooks` directory of the common git dir, which linked worktrees
+/// share with the main one
+pub fn hooks_dir(repo: &Repository) -> PathBuf {
+    let hooks_path = repo
+        .config()
+        .and_then(|config| config.get_path("core.hooksPath"))
+        .ok();
+    match hooks_path {
+        Some(path) if path.is_absolute() => path,
+        Some(path) => repo.workdir().unwrap_or_else(|| repo.path()).join(path),
+        None => repo.commondir().join("hooks"),
+    }
+}
+
+pub fn run(repo: &Repository) -> Vec<HookStatus> {
+    let dir = hooks_dir(repo);
+    GIT_HOOKS
+        .iter()
+        .map(|name| hook_status(name, &dir.join(name)))
+        .collect()
+}
+
+fn hook_status(name: &'static str, path: &Path) -> HookStatus {
+    let script = fs::read_to_string(path).ok();
+    let installed_by_git_ai = script
+        .as_ref()
+        .is_some_and(|script| script.contains(GIT_HOOK_MARKER));
+    let runs_git_ai = script
+        .as_ref()
+        .is_some_and(|script| script.contains("git-ai"));
+    HookStatus {
+        name,
+        path: path.display().to_string(),
+        installed: script.is_some(),
+        executable: script.is_some() && is_executable(path),
+        runs_git_ai,
+        installed_by_git_ai,
+        chained: runs_git_ai && !installed_by_git_ai,
+    }
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path).is_ok_and(|metadata| metadata.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(_path: &Path) -> bool {
+    true
+}
+
+/// One aligned row per hook, e.g. "post-rewrite  installed by git-ai"
+pub fn render(hooks: &[HookStatus]) -> String {
+    let width = hooks.iter().map(|hook| hook.name.len()).max().unwrap_or(0);
+    let mut output = String::new();
+    for hook in hooks {
+        let mut state = if !hook.installed {
+            "not installed".to_string()
+        } else if hook.installed_by_git_ai {
+            "installed by git-ai".to_string()
+        } else if hook.chained {
+            "your hook, chained to git-ai".to_string()
+        } else {
+            "your hook, does not run git-ai".to_string()
+        };
+        if hook.installed && !hook.executable {
+            state.push_str(" (not executable, so git skips it)");
+        }
+        output.push_str(&format!(
+            "{:<width$}  {}\n",
+            hook.name,
+            state,
+            width = width
+        ));
+    }
+    output
+}
@@ -0,0 +1,251 @@
+use crate::commands::hooks::hooks_dir;
+use crate::error::GitAiError;
+use git2::Repository;
+use serde_json::{Value, json};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Editors git-ai knows how to wire up with checkpoint hooks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Preset {
+    Cursor,
+    Claude,
+}
+
+impl Preset {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim() {
+            "cursor" => Some(Preset::Cursor),
+            "claude" | "claude-code" => Some(Preset::Claude),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Preset::Cursor => "cursor",
+            Preset::Claude => "claude",
+        }
+    }
+
+    pub(crate) fn config_path(&self, home: &Path) -> PathBuf {
+        match self {
+            Preset::Cursor => home.join(".cursor").join("hooks.json"),
+            Preset::Claude => home.join(".claude").join("settings.json"),
+        }
+    }
+
+    /// Checkpoint the human's changes before the agent edits, then the agent's changes after.
+    /// Checkpointing on prompt submit also records the prompt for `git-ai search`.
+    fn hooks(&self) -> Vec<(&'static str, Value)> {
+        let hook =
+            |event: &str| format!("{} hook-exec {} {}", git_ai_command(), self.name(), event);
+        match self {
+            Preset::Cursor => {
+                let prompt = hook("beforeSubmitPrompt");
+                let ai = hook("afterFileEdit");
+                vec![
+                    ("beforeSubmitPrompt", json!({ "command": prompt })),
+                    ("afterFileEdit", json!({ "command": ai })),
+                ]
+            }
+            Preset::Claude => {
+                let prompt = hook("UserPromptSubmit");
+                let human = hook("PreToolUse");
+                let ai = hook("PostToolUse");
+                vec![
+                    (
+                        "UserPromptSubmit",
+                        json!({ "hooks": [{ "type": "command", "command": prompt }] }),
+                    ),
+                    (
+                        "PreToolUse",
+                        json!({
+                            "matcher": "Write|Edit|MultiEdit",
+                            "hooks": [{ "type": "command", "command": human }]
+                        }),
+                    ),
+                    (
+                        "PostToolUse",
+                        json!({
+                            "matcher": "Write|Edit|MultiEdit",
+                            "hooks": [{ "type": "command", "command": ai }]
+                        }),
+                    ),
+                ]
+            }
+        }
+    }
+}
+
+/// A hook config file written by `init --with-presets`
+#[derive(Debug, Clone)]
+pub struct WrittenConfig {
+    pub preset: Preset,
+    pub path: PathBuf,
+    /// Where the previous contents were copied, if the file already existed
+    pub backup: Option<PathBuf>,
+    /// The backup was there from an earlier run and was left alone, so it still holds the
+    /// config from before git-ai first touched it
+    pub kept_backup: bool,
+    pub contents: String,
+}
+
+pub fn parse_presets(value: &str) -> Result<Vec<Preset>, GitAiError> {
+    let mut presets = Vec::new();
+    for name in value.split(',').filter(|name| !name.trim().is_empty()) {
+        match Preset::parse(name) {
+            Some(preset) if !presets.contains(&preset) => presets.push(preset),
+            Some(_) => {}
+            None => {
+                return Err(GitAiError::Generic(format!(
+                    "Unknown preset: {} (expected cursor or claude)",
+                    name
+                )));
+            }
+        }
+    }
+    if presets.is_empty() {
+        return Err(GitAiError::Generic(
+            "--with-presets requires at least one preset".to_string(),
+        ));
+    }
+    Ok(presets)
+}
+
+/// Write hook configuration for each preset under `home`, merging into existing configs
+/// after backing them up to `<file>.bak`. An existing backup is never overwritten.
+pub fn run(home: &Path, presets: &[Preset]) -> Result<Vec<WrittenConfig>, GitAiError> {
+    let mut written = Vec::new();
+    for preset in presets {
+        let path = preset.config_path(home);
+        let mut config = json!({});
+        let mut backup = None;
+        let mut kept_backup = false;
+
+        if path.exists() {
+            let existing = fs::read_to_string(&path)?;
+            let backup_path = path.with_extension("json.bak");
+            if backup_path.exists() {
+                kept_backup = true;
+            } else {
+                fs::copy(&path, &backup_path)?;
+            }
+            backup = Some(backup_path);
+            if !existing.trim().is_empty() {
+                config = serde_json::from_str(&existing)?;
+            }
+        }
+
+        merge_hooks(&mut config, *preset)?;
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let contents = serde_json::to_string_pretty(&config)?;
+        fs::write(&path, format!("{}\n", contents))?;
+
+        written.push(WrittenConfig {
+            preset: *preset,
+            path,
+            backup,
+            kept_backup,
+            contents,
+        });
+    }
+    Ok(written)
+}
+
+fn merge_hooks(config: &mut Value, preset: Preset) -> Result<(), GitAiError> {
+    let root = config.as_object_mut().ok_or_else(|| {
+        GitAiError::Generic("Existing hook config is not a JSON object".to_string())
+    })?;
+    if preset == Preset::Cursor {
+        root.entry("version").or_insert(json!(1));
+    }
+    let hooks = root
+        .entry("hooks")
+        .or_insert_with(|| json!({}))
+        .as_object_mut()
+        .ok_or_else(|| {
+            GitAiError::Generic("Existing \"hooks\" is not a JSON object".to_string())
+        })?;
+
+    for (event, hook) in preset.hooks() {
+        let entries = hooks
+            .entry(event)
+            .or_insert_with(|| json!([]))
+            .as_array_mut()
+            .ok_or_else(|| GitAiError::Generic(format!("Existing \"{}\" is not a list", event)))?;
+        // Hooks from older versions sourced a shell profile to run `git-ai checkpoint`
+        entries.retain(|entry| !entry.to_string().contains("; git-ai checkpoint"));
+        // Re-running init, even from another install path, should not stack duplicate hooks
+        let ours = format!("hook-exec {} {}", preset.name(), event);
+        entries.retain(|entry| {
+            !hook_commands(entry).any(|command| command.trim_end().ends_with(&ours))
+        });
+        entries.push(hook);
+    }
+    Ok(())
+}
+
+/// The commands a hook entry runs: Cursor puts one on the entry, Claude lists them under
+/// "hooks"
+fn hook_commands(entry: &Value) -> impl Iterator<Item = &str> {
+    let nested = entry["hooks"].as_array().into_iter().flatten();
+    std::iter::once(entry)
+        .chain(nested)
+        .filter_map(|hook| hook["command"].as_str())
+}
+
+/// Marks hook scripts git-ai wrote, so re-running init can recognize and refresh them
+pub(crate) const GIT_HOOK_MARKER: &str = "# Installed by git-ai";
+
+/// Install the repository's post-rewrite hook, which keeps authorship logs attached to
+/// commits rewritten by `git rebase` and `git commit --amend`, in the directory git runs
+/// hooks from. A post-rewrite hook git-ai did not write is left alone rather than
+/// overwritten. Returns the hook's path.
+pub fn install_git_hooks(repo: &Repository) -> Result<PathBuf, GitAiError> {
+    let hooks_dir = hooks_dir(repo);
+    let path = hooks_dir.join("post-rewrite");
+    if let Ok(existing) = fs::read_to_string(&path)
+        && !existing.contains(GIT_HOOK_MARKER)
+    {
+        return Err(GitAiError::Generic(format!(
+            "{} already exists; add `git-ai post-rewrite \"$@\"` to it by hand",
+            path.display()
+        )));
+    }
+
+    fs::create_dir_all(&hooks_dir)?;
+    fs::write(
+        &path,
+        format!(
+            "#!/bin/sh\n{}: keeps AI authorship attached to rewritten commits\n{} post-rewrite \"$@\"\n",
+            GIT_HOOK_MARKER,
+            git_ai_command()
+        ),
+    )?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755))?;
+    }
+    Ok(path)
+}
+
+/// Editors run hooks without the user's login shell, so PATH may not include git-ai. Hooks
+/// call the binary running init by its absolute path, falling back to `git-ai` when init
+/// runs from somewhere else (such as a test harness).
+fn git_ai_command() -> String {
+    let exe = std::env::current_exe()
+        .ok()
+        .filter(|exe| exe.file_stem().is_some_and(|stem| stem == "git-ai"));
+    match exe {
+        Some(exe) if exe.to_string_lossy().contains(char::is_whitespace) => {
+            format!("\"{}\"", exe.display())
+        }
+        Some(exe) => exe.display().to_string(),
+        None => "git-ai".to_string(),
+    }
+}
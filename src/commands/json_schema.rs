@@ -0,0 +1,196 @@
+use serde_json::json;
+
+/// Bumped whenever a field is removed, renamed or changes type. Adding optional fields
+/// keeps the version.
+pub const JSON_SCHEMA_VERSION: u32 = 1;
+
+/// JSON Schema (draft 2020-12) for every `--json`-style output, one definition per output
+pub fn json_schema() -> serde_json::Value {
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "$id": format!("git-ai/v{}", JSON_SCHEMA_VERSION),
+        "title": "git-ai JSON outputs",
+        "version": JSON_SCHEMA_VERSION,
+        "$defs": {
+            "stats_per_commit": {
+                "description": "git-ai stats <range> --per-commit --format json",
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "required": [
+                        "sha",
+                        "summary",
+                        "author",
+                        "human_additions",
+                        "ai_additions",
+                        "ai_percentage"
+                    ],
+                    "properties": {
+                        "sha": { "type": "string" },
+                        "summary": { "type": "string" },
+                        "author": { "type": "string" },
+                        "human_additions": { "type": "integer", "minimum": 0 },
+                        "ai_additions": { "type": "integer", "minimum": 0 },
+                        "ai_percentage": { "type": "number", "minimum": 0, "maximum": 100 }
+                    }
+                }
+            },
+            "checkpoint": {
+                "description": "git-ai checkpoint --json",
+                "type": "object",
+                "required": ["status"],
+                "properties": {
+                    "status": { "enum": ["recorded", "pending", "linked", "touched", "skipped", "disabled"] },
+                    "branch": { "type": ["string", "null"] },
+                    "id": { "type": "string" },
+                    "ref": { "type": "string" },
+                    "author": { "type": "string" },
+                    "model": { "type": ["string", "null"] },
+                    "files": { "type": "integer", "minimum": 0 },
+                    "added_lines": { "type": "integer", "minimum": 0 },
+                    "deleted_lines": { "type": "integer", "minimum": 0 },
+                    "content_tree": { "type": ["string", "null"] },
+                    "file_level": { "type": "array", "items": { "type": "string" } }
+                }
+            },
+            "doctor": {
+                "description": "git-ai doctor --json",
+                "type": "object",
+                "required": ["status", "checks"],
+                "properties": {
+                    "status": { "enum": ["pass", "warn", "fail"] },
+                    "checks": {
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "required": ["id", "status", "message", "remediation"],
+                            "properties": {
+                                "id": { "type": "string" },
+                                "status": { "enum": ["pass", "warn", "fail"] },
+                                "message": { "type": "string" },
+                                "remediation": { "type": ["string", "null"] }
+                            }
+                        }
+                    }
+                }
+            },
+            "hooks_status": {
+                "description": "git-ai hooks status --json",
+                "type": "object",
+                "required": ["hooks_dir", "hooks"],
+                "properties": {
+                    "hooks_dir": { "type": "string" },
+                    "hooks": {
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "required": [
+                                "name",
+                                "path",
+                                "installed",
+                                "executable",
+                                "runs_git_ai",
+                                "installed_by_git_ai",
+                                "chained"
+                            ],
+                            "properties": {
+                                "name": {
+                                    "enum": ["pre-commit", "post-commit", "post-merge", "post-rewrite"]
+                                },
+                                "path": { "type": "string" },
+                                "installed": { "type": "boolean" },
+                                "executable": { "type": "boolean" },
+                                "runs_git_ai": { "type": "boolean" },
+                                "installed_by_git_ai": { "type": "boolean" },
+                                "chained": { "type": "boolean" }
+                            }
+                        }
+                    }
+                }
+            },
+            "blame": {
+                "description": "git-ai blame --json <file>",
+                "type": "object",
+                "required": ["file", "lines"],
+                "properties": {
+                    "file": { "type": "string" },
+                    "lines": {
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "required": ["line", "commit", "author", "ai", "model", "content"],
+                            "properties": {
+                                "line": { "type": "integer", "minimum": 1 },
+                                "commit": { "type": ["string", "null"] },
+                                "author": { "type": ["string", "null"] },
+                                "ai": { "type": "boolean" },
+                                "generated": { "type": "boolean" },
+                                "model": { "type": ["string", "null"] },
+                                "confidence": { "enum": ["high", "low", null] },
+                                "content": { "type": "string" }
+                            }
+                        }
+                    }
+                }
+            },
+            "blame_json_lines": {
+                "description": "git-ai blame --json-lines <file>, one object per output line",
+                "$ref": "#/$defs/blame/properties/lines/items"
+            },
+            "log": {
+                "description": "git-ai log --file <path> --json",
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "required": [
+                        "base_commit",
+                        "index",
+                        "timestamp",
+                        "author",
+                        "model",
+                        "prompt",
+                        "files"
+                    ],
+                    "properties": {
+                        "base_commit": { "type": "string" },
+                        "index": { "type": "integer", "minimum": 1 },
+                        "timestamp": { "type": "integer", "minimum": 0 },
+                        "author": { "type": "string" },
+                        "model": { "type": ["string", "null"] },
+                        "human_author": { "type": ["string", "null"] },
+                        "generated": { "type": "boolean" },
+                        "prompt": { "type": ["string", "null"] },
+                        "files": {
+                            "type": "array",
+                            "items": {
+                                "type": "object",
+                                "required": ["file", "added_lines", "deleted_lines"],
+                                "properties": {
+                                    "file": { "type": "string" },
+                                    "added_lines": { "type": "integer", "minimum": 0 },
+                                    "deleted_lines": { "type": "integer", "minimum": 0 }
+                                }
+                            }
+                        },
+                        "models": {
+                            "description": "With --model-breakdown, tracked lines per model (\"human\" for people)",
+                            "type": "array",
+                            "items": {
+                                "type": "object",
+                                "required": ["model", "lines"],
+                                "properties": {
+                                    "model": { "type": "string" },
+                                    "lines": { "type": "integer", "minimum": 0 }
+                                }
+                            }
+                        }
+                    }
+                }
+            },
+            "log_json_lines": {
+                "description": "git-ai log --file <path> --json-lines, one object per output line",
+                "$ref": "#/$defs/log/items"
+            }
+        }
+    })
+}
@@ -0,0 +1,706 @@
+use crate::commands::stats::PercentFormat;
+use crate::error::GitAiError;
+use crate::git::refs::list_working_logs;
+use crate::git::revspec::resolve_commit;
+use crate::log_fmt::authorship_log::AuthorshipLog;
+use crate::log_fmt::working_log::{Checkpoint, Line, WorkingLogEntry};
+use crate::utils::glob_to_regex;
+use git2::Repository;
+use regex::Regex;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Default)]
+pub struct LogOptions {
+    /// Paths, directories or globs (`src/**/*.rs`); a checkpoint matches if it touched any
+    pub files: Vec<String>,
+    /// Only checkpoints whose added or deleted lines touch this range (inclusive)
+    pub line_range: Option<(u32, u32)>,
+    /// Oldest checkpoint first
+    pub reverse: bool,
+    /// One terse line per checkpoint
+    pub oneline: bool,
+    /// Only checkpoints whose author contains this (case-insensitive)
+    pub author: Option<String>,
+    /// Only AI checkpoints whose model contains this (case-insensitive)
+    pub model: Option<String>,
+    /// At most this many checkpoints, the newest ones
+    pub limit: Option<usize>,
+    /// Follow the log with checkpoint and line totals per author and model
+    pub author_stats: bool,
+    /// Expand the model column into each model's share of the matching files' tracked lines
+    pub model_breakdown: bool,
+    /// Only checkpoints recorded for the commits in `<from>..<to>`, e.g. two tags around a
+    /// session; with no `files`, every file they touched
+    pub between: Option<(String, String)>,
+    /// The checkpoints as one JSON array
+    pub json: bool,
+    /// One JSON object per checkpoint per line, with the same fields as a `--json` element
+    pub json_lines: bool,
+}
+
+/// Flags only git-ai's log takes. `log` with any of them, `--flag=value` forms included, is a
+/// checkpoint history; everything else is `git log`. `--ai` asks for the history explicitly,
+/// for options such as `--oneline` that git log shares.
+const GIT_AI_LOG_FLAGS: &[&str] = &[
+    "--ai",
+    "--file",
+    "--between",
+    "--model",
+    "--limit",
+    "--author-stats",
+    "--model-breakdown",
+    "--json",
+    "--json-lines",
+];
+
+/// Options that take a value, which may also be given as `--flag=value`
+const VALUE_FLAGS: &[&str] = &["--file", "--author", "--model", "--limit"];
+
+/// Whether `log <args>` is git-ai's checkpoint history rather than `git log`
+pub fn is_git_ai_log(args: &[String]) -> bool {
+    args.iter().take_while(|arg| *arg != "--").any(|arg| {
+        let name = arg.split_once('=').map_or(arg.as_str(), |(name, _)| name);
+        GIT_AI_LOG_FLAGS.contains(&name)
+    })
+}
+
+/// Longest prompt excerpt `--oneline` shows before truncating
+const ONELINE_MESSAGE_CHARS: usize = 50;
+
+/// One checkpoint that modified a requested path
+#[derive(Debug, Clone)]
+pub struct FileLogEntry {
+    pub base_commit: String,
+    /// 1-based position of the checkpoint in its working log
+    pub index: usize,
+    pub checkpoint: Checkpoint,
+    /// The checkpoint's changes to the matching files
+    pub entries: Vec<WorkingLogEntry>,
+    /// The prompt this checkpoint answered: its own, or the latest one before it
+    pub prompt: Option<String>,
+    /// With `--model-breakdown`: tracked lines per model ("human" for people) in the
+    /// matching files as of this checkpoint, most lines first
+    pub model_lines: Vec<(String, u32)>,
+}
+
+/// A checkpoint that matched, located in its working log; the entry is only built for it
+/// once it is known to be shown
+struct Match {
+    log: usize,
+    index: usize,
+    /// Checkpoint whose prompt this one answered
+    prompt: Option<usize>,
+    entries: Vec<usize>,
+}
+
+/// Checkpoints that touched any of `options.files` (any file when none are given), newest
+/// first (oldest first with `--reverse`), handed to `emit` one at a time. Line numbers in a
+/// checkpoint refer to the file as it was at that checkpoint, so a line range follows those
+/// numbers.
+pub fn run(
+    repo: &Repository,
+    options: &LogOptions,
+    mut emit: impl FnMut(FileLogEntry) -> Result<(), GitAiError>,
+) -> Result<(), GitAiError> {
+    let patterns = options
+        .files
+        .iter()
+        .map(|pattern| path_regex(pattern))
+        .collect::<Result<Vec<_>, _>>()?;
+    let author = options.author.as_deref().map(str::to_lowercase);
+    let model = options.model.as_deref().map(str::to_lowercase);
+    let bases = match &options.between {
+        Some((from, to)) => Some(between_bases(repo, from, to)?),
+        None => None,
+    };
+
+    let mut logs = list_working_logs(repo)?;
+    if let Some(bases) = &bases {
+        logs.retain(|(base_commit, _)| bases.contains_key(base_commit));
+    }
+    let mut matches = Vec::new();
+    for (log, (_, working_log)) in logs.iter().enumerate() {
+        let mut prompt = None;
+        for (index, checkpoint) in working_log.iter().enumerate() {
+            if checkpoint.prompt.is_some() {
+                prompt = Some(index);
+            }
+            if let Some(author) = &author
+                && !checkpoint.author.to_lowercase().contains(author)
+            {
+                continue;
+            }
+            if let Some(model) = &model
+                && !checkpoint
+                    .agent_metadata
+                    .as_ref()
+                    .is_some_and(|metadata| metadata.model.to_lowercase().contains(model))
+            {
+                continue;
+            }
+            let entries: Vec<usize> = checkpoint
+                .entries
+                .iter()
+                .enumerate()
+                .filter(|(_, entry)| {
+                    patterns.is_empty()
+                        || patterns.iter().any(|pattern| pattern.is_match(&entry.file))
+                })
+                .filter(|(_, entry)| {
+                    options
+                        .line_range
+                        .is_none_or(|(start, end)| touches_range(entry, start, end))
+                })
+                .map(|(i, _)| i)
+                .collect();
+            if !entries.is_empty() {
+                matches.push(Match {
+                    log,
+                    index,
+                    prompt,
+                    entries,
+                });
+            }
+        }
+    }
+
+    let checkpoint = |m: &Match| &logs[m.log].1[m.index];
+    let position = |m: &Match| {
+        bases
+            .as_ref()
+            .and_then(|bases| bases.get(&logs[m.log].0).copied())
+    };
+    matches.sort_by(|a, b| {
+        checkpoint(b)
+            .timestamp
+            .cmp(&checkpoint(a).timestamp)
+            .then(position(a).cmp(&position(b)))
+            .then(b.index.cmp(&a.index))
+    });
+    // Like `git log -n`, the limit keeps the newest even when printing oldest first
+    if let Some(limit) = options.limit {
+        matches.truncate(limit);
+    }
+    if options.reverse {
+        matches.reverse();
+    }
+
+    for m in matches {
+        let (base_commit, working_log) = &logs[m.log];
+        let checkpoint = &working_log[m.index];
+        let entries: Vec<WorkingLogEntry> = m
+            .entries
+            .iter()
+            .map(|&i| checkpoint.entries[i].clone())
+            .collect();
+        let model_lines = if options.model_breakdown {
+            model_lines(&working_log[..=m.index], &entries)
+        } else {
+            Vec::new()
+        };
+        emit(FileLogEntry {
+            base_commit: base_commit.clone(),
+            index: m.index + 1,
+            checkpoint: checkpoint.clone(),
+            entries,
+            prompt: m.prompt.and_then(|i| working_log[i].prompt.clone()),
+            model_lines,
+        })?;
+    }
+    Ok(())
+}
+
+/// The working logs behind the commits in `from..to`, each commit having been built from the
+/// log of its first parent ("initial" for a root commit). Each maps to its commit's place in
+/// the range, newest first, which orders checkpoints recorded within the same second.
+fn between_bases(
+    repo: &Repository,
+    from: &str,
+    to: &str,
+) -> Result<HashMap<String, usize>, GitAiError> {
+    let mut revwalk = repo.revwalk()?;
+    revwalk.set_sorting(git2::Sort::TOPOLOGICAL)?;
+    revwalk.push(resolve_commit(repo, to)?.id())?;
+    revwalk.hide(resolve_commit(repo, from)?.id())?;
+    let mut bases = HashMap::new();
+    for (position, oid) in revwalk.enumerate() {
+        let commit = repo.find_commit(oid?)?;
+        let base = commit
+            .parent_id(0)
+            .map(|parent| parent.to_string())
+            .unwrap_or_else(|_| "initial".to_string());
+        bases.entry(base).or_insert(position);
+    }
+    Ok(bases)
+}
+
+/// A checkpoint has a single model, so the mix comes from the lines it left behind: replay
+/// the working log up to it and count who each surviving line in `matching`'s files is by
+fn model_lines(checkpoints: &[Checkpoint], matching: &[WorkingLogEntry]) -> Vec<(String, u32)> {
+    let authorship_log = AuthorshipLog::from_working_log(checkpoints);
+    let mut lines: Vec<(String, u32)> = Vec::new();
+    for entry in matching {
+        let Some(file) = authorship_log.files.get(&entry.file) else {
+            continue;
+        };
+        for author in &file.authors {
+            let model = author
+                .agent_metadata
+                .as_ref()
+                .map(|metadata| metadata.model.clone())
+                .unwrap_or_else(|| "human".to_string());
+            let count = author
+                .lines
+                .iter()
+                .map(|range| range.expand().len() as u32)
+                .sum::<u32>();
+            match lines.iter_mut().find(|(name, _)| *name == model) {
+                Some((_, total)) => *total += count,
+                None => lines.push((model, count)),
+            }
+        }
+    }
+    lines.retain(|(_, count)| *count > 0);
+    lines.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    lines
+}
+
+/// The `--model-breakdown` model column, e.g. "claude-4-sonnet:67%,human:33%"; None while
+/// only one model has lines, where the plain column already says it all
+pub fn model_breakdown(entry: &FileLogEntry) -> Option<String> {
+    if entry.model_lines.len() < 2 {
+        return None;
+    }
+    let total: u32 = entry.model_lines.iter().map(|(_, count)| count).sum();
+    let shares: Vec<f64> = entry
+        .model_lines
+        .iter()
+        .map(|(_, count)| *count as f64 * 100.0 / total as f64)
+        .collect();
+    let percent = PercentFormat {
+        sum_to_100: true,
+        ..PercentFormat::default()
+    };
+    Some(
+        entry
+            .model_lines
+            .iter()
+            .zip(percent.format_split(&shares))
+            .map(|((model, _), share)| format!("{}:{}", model, share))
+            .collect::<Vec<_>>()
+            .join(","),
+    )
+}
+
+/// A pattern with glob characters is a glob; anything else names a file or a directory
+fn path_regex(pattern: &str) -> Result<Regex, GitAiError> {
+    let regex = if pattern.contains(['*', '?']) {
+        glob_to_regex(pattern)
+    } else {
+        format!(
+            "^{}(?:/.*)?$",
+            regex::escape(pattern.trim_start_matches("./").trim_end_matches('/'))
+        )
+    };
+    Regex::new(&regex)
+        .map_err(|e| GitAiError::Generic(format!("Invalid path pattern {}: {}", pattern, e)))
+}
+
+fn touches_range(entry: &WorkingLogEntry, start: u32, end: u32) -> bool {
+    entry
+        .added_lines
+        .iter()
+        .chain(entry.deleted_lines.iter())
+        .any(|line| line.start() <= end && line.end() >= start)
+}
+
+/// `--oneline` form, e.g. "1a2b3c4:2 C claude-4-sonnet Split the parser": short checkpoint
+/// id, author initial, model (or "human") and the first line of the prompt, truncated
+pub fn oneline(entry: &FileLogEntry) -> String {
+    let initial = entry
+        .checkpoint
+        .author
+        .chars()
+        .next()
+        .map(|c| c.to_uppercase().to_string())
+        .unwrap_or_else(|| "?".to_string());
+    let model = model_breakdown(entry).unwrap_or_else(|| {
+        entry
+            .checkpoint
+            .agent_metadata
+            .as_ref()
+            .map(|metadata| metadata.model.clone())
+            .unwrap_or_else(|| "human".to_string())
+    });
+    let message = entry
+        .prompt
+        .as_deref()
+        .and_then(|prompt| prompt.lines().next())
+        .unwrap_or("");
+    let message = if message.chars().count() > ONELINE_MESSAGE_CHARS {
+        let truncated: String = message.chars().take(ONELINE_MESSAGE_CHARS - 3).collect();
+        format!("{}...", truncated)
+    } else {
+        message.to_string()
+    };
+    format!(
+        "{}:{} {} {} {}",
+        &entry.base_commit[..entry.base_commit.len().min(7)],
+        entry.index,
+        initial,
+        model,
+        message
+    )
+    .trim_end()
+    .to_string()
+}
+
+/// Totals for one author and model across the shown checkpoints
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthorStats {
+    pub author: String,
+    /// None for human checkpoints
+    pub model: Option<String>,
+    pub checkpoints: usize,
+    /// Lines in the matching files only, like the per-checkpoint counts
+    pub added_lines: u32,
+    pub deleted_lines: u32,
+}
+
+impl AuthorStats {
+    /// Same label as a checkpoint's, e.g. "Claude [AI: claude-4-sonnet]"
+    pub fn attribution(&self) -> String {
+        match &self.model {
+            Some(model) => format!("{} [AI: {}]", self.author, model),
+            None => self.author.clone(),
+        }
+    }
+}
+
+/// Count one more checkpoint toward its author and model's row
+pub fn tally(stats: &mut Vec<AuthorStats>, entry: &FileLogEntry) {
+    let model = entry
+        .checkpoint
+        .agent_metadata
+        .as_ref()
+        .map(|metadata| metadata.model.clone());
+    let index = match stats
+        .iter()
+        .position(|s| s.author == entry.checkpoint.author && s.model == model)
+    {
+        Some(index) => index,
+        None => {
+            stats.push(AuthorStats {
+                author: entry.checkpoint.author.clone(),
+                model,
+                checkpoints: 0,
+                added_lines: 0,
+                deleted_lines: 0,
+            });
+            stats.len() - 1
+        }
+    };
+    let rollup = &mut stats[index];
+    rollup.checkpoints += 1;
+    for file_entry in &entry.entries {
+        let (added, deleted) = line_counts(file_entry);
+        rollup.added_lines += added;
+        rollup.deleted_lines += deleted;
+    }
+}
+
+/// Order the `--author-stats` rows: most checkpoints first, then most lines changed
+pub fn sort_author_stats(stats: &mut [AuthorStats]) {
+    stats.sort_by(|a, b| {
+        b.checkpoints
+            .cmp(&a.checkpoints)
+            .then_with(|| (b.added_lines + b.deleted_lines).cmp(&(a.added_lines + a.deleted_lines)))
+            .then_with(|| a.attribution().cmp(&b.attribution()))
+    });
+}
+
+fn line_counts(entry: &WorkingLogEntry) -> (u32, u32) {
+    let count = |lines: &[Line]| lines.iter().map(Line::line_count).sum::<u32>();
+    (count(&entry.added_lines), count(&entry.deleted_lines))
+}
+
+/// The `--author-stats` footer: one aligned row per author and model
+pub fn render_author_stats(stats: &[AuthorStats]) -> String {
+    let width = stats
+        .iter()
+        .map(|s| s.attribution().chars().count())
+        .max()
+        .unwrap_or(0);
+    let mut output = String::from("Checkpoints by author:\n");
+    for s in stats {
+        output.push_str(&format!(
+            "    {:<width$}  {} checkpoint{}  +{} -{}\n",
+            s.attribution(),
+            s.checkpoints,
+            if s.checkpoints == 1 { "" } else { "s" },
+            s.added_lines,
+            s.deleted_lines,
+            width = width
+        ));
+    }
+    output
+}
+
+/// The `--json` / `--json-lines` record for one checkpoint: its matching files with their
+/// line counts rather than the line ranges, like the text form
+pub fn json_entry(entry: &FileLogEntry) -> serde_json::Value {
+    let metadata = entry.checkpoint.agent_metadata.as_ref();
+    let files: Vec<serde_json::Value> = entry
+        .entries
+        .iter()
+        .map(|file_entry| {
+            let (added, deleted) = line_counts(file_entry);
+            serde_json::json!({
+                "file": file_entry.file,
+                "added_lines": added,
+                "deleted_lines": deleted,
+            })
+        })
+        .collect();
+    let models: Vec<serde_json::Value> = entry
+        .model_lines
+        .iter()
+        .map(|(model, lines)| serde_json::json!({ "model": model, "lines": lines }))
+        .collect();
+    serde_json::json!({
+        "base_commit": entry.base_commit,
+        "index": entry.index,
+        "timestamp": entry.checkpoint.timestamp,
+        "author": entry.checkpoint.author,
+        "model": metadata.map(|m| m.model.as_str()),
+        "human_author": metadata.and_then(|m| m.human_author.as_deref()),
+        "generated": entry.checkpoint.generated,
+        "prompt": entry.prompt,
+        "files": files,
+        "models": models,
+    })
+}
+
+/// Run the log and print each entry as `run` produces it. `--json-lines` needs nothing
+/// held back, so a consumer can stream a long history; `--json` writes the same array
+/// `serde_json` would, one element at a time.
+pub fn print(repo: &Repository, options: &LogOptions) -> Result<(), GitAiError> {
+    let mut shown = 0;
+    let mut stats = Vec::new();
+    let result = run(repo, options, |entry| {
+        if options.json_lines {
+            println!("{}", json_entry(&entry));
+        } else if options.json {
+            let record = serde_json::to_string_pretty(&json_entry(&entry))?;
+            let element: Vec<String> = record.lines().map(|line| format!("  {}", line)).collect();
+            println!("{}", if shown == 0 { "[" } else { "," });
+            print!("{}", element.join("\n"));
+        } else {
+            print_entry(options, &entry);
+            if options.author_stats {
+                tally(&mut stats, &entry);
+            }
+        }
+        shown += 1;
+        Ok(())
+    });
+    // Close an array already under way even when the walk fails partway, so the records
+    // printed so far still parse
+    if options.json && (result.is_ok() || shown > 0) {
+        println!("{}", if shown == 0 { "[]" } else { "\n]" });
+    }
+    result?;
+
+    if options.json_lines || options.json {
+        return Ok(());
+    }
+    if shown == 0 {
+        match &options.between {
+            Some((from, to)) if options.files.is_empty() => {
+                println!("No checkpoints between {} and {}", from, to)
+            }
+            None if options.files.is_empty() => println!("No checkpoints recorded"),
+            _ => println!("No checkpoints touched {}", options.files.join(", ")),
+        }
+        return Ok(());
+    }
+    if options.author_stats {
+        sort_author_stats(&mut stats);
+        print!("\n{}", render_author_stats(&stats));
+    }
+    Ok(())
+}
+
+fn print_entry(options: &LogOptions, entry: &FileLogEntry) {
+    if options.oneline {
+        println!("{}", oneline(entry));
+        return;
+    }
+    let time = chrono::DateTime::from_timestamp(entry.checkpoint.timestamp as i64, 0)
+        .map(|time| time.format("%Y-%m-%d %H:%M:%S").to_string())
+        .unwrap_or_default();
+    let (added, deleted) = entry
+        .entries
+        .iter()
+        .map(line_counts)
+        .fold((0, 0), |(a, d), (added, deleted)| (a + added, d + deleted));
+    println!(
+        "{}  {} #{}  {}  +{} -{}",
+        time,
+        &entry.base_commit[..entry.base_commit.len().min(8)],
+        entry.index,
+        entry.checkpoint.attribution(),
+        added,
+        deleted
+    );
+    // Name the files unless the only one is exactly what was asked for
+    if entry.entries.len() > 1 || options.files != [entry.entries[0].file.clone()] {
+        for file_entry in &entry.entries {
+            let (added, deleted) = line_counts(file_entry);
+            println!("    {}  +{} -{}", file_entry.file, added, deleted);
+        }
+    }
+    if let Some(breakdown) = model_breakdown(entry) {
+        println!("    models: {}", breakdown);
+    }
+    if let Some(prompt) = &entry.prompt {
+        println!("    {}", prompt);
+    }
+}
+
+/// Parse `log [--ai] --file <path|dir|glob>... [-L <start>,<end>] [--author <name>] [--model <name>]
+/// [--limit <n>] [--reverse] [--oneline] [--author-stats] [--model-breakdown]
+/// [--between <from> <to>] [--json | --json-lines]`, where `--file` repeats and may be left
+/// out with `--between` or `--ai` (every file)
+pub fn parse_log_args(args: &[String]) -> Result<LogOptions, GitAiError> {
+    let args: Vec<String> = args
+        .iter()
+        .flat_map(|arg| match arg.split_once('=') {
+            Some((name, value)) if VALUE_FLAGS.contains(&name) => {
+                vec![name.to_string(), value.to_string()]
+            }
+            _ => vec![arg.clone()],
+        })
+        .collect();
+    let mut options = LogOptions::default();
+    let mut explicit = false;
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--ai" => {
+                explicit = true;
+                i += 1;
+            }
+            "--file" => {
+                if i + 1 >= args.len() {
+                    return Err(GitAiError::Generic(
+                        "Missing argument for --file".to_string(),
+                    ));
+                }
+                options.files.push(args[i + 1].clone());
+                i += 2;
+            }
+            "--author" | "--model" => {
+                let Some(value) = args.get(i + 1) else {
+                    return Err(GitAiError::Generic(format!(
+                        "Missing argument for {}",
+                        args[i]
+                    )));
+                };
+                if args[i] == "--author" {
+                    options.author = Some(value.clone());
+                } else {
+                    options.model = Some(value.clone());
+                }
+                i += 2;
+            }
+            "--limit" | "-n" => {
+                match args
+                    .get(i + 1)
+                    .and_then(|value| value.parse::<usize>().ok())
+                {
+                    Some(limit) if limit > 0 => options.limit = Some(limit),
+                    _ => {
+                        return Err(GitAiError::Generic(
+                            "--limit requires a positive number".to_string(),
+                        ));
+                    }
+                }
+                i += 2;
+            }
+            "-L" => {
+                if i + 1 >= args.len() {
+                    return Err(GitAiError::Generic("Missing argument for -L".to_string()));
+                }
+                let range = args[i + 1]
+                    .split_once(',')
+                    .and_then(|(start, end)| Some((start.parse().ok()?, end.parse().ok()?)))
+                    .filter(|(start, end): &(u32, u32)| *start > 0 && start <= end)
+                    .ok_or_else(|| {
+                        GitAiError::Generic(format!("Invalid line range: {}", args[i + 1]))
+                    })?;
+                options.line_range = Some(range);
+                i += 2;
+            }
+            "--reverse" => {
+                options.reverse = true;
+                i += 1;
+            }
+            "--oneline" => {
+                options.oneline = true;
+                i += 1;
+            }
+            "--author-stats" => {
+                options.author_stats = true;
+                i += 1;
+            }
+            "--model-breakdown" => {
+                options.model_breakdown = true;
+                i += 1;
+            }
+            "--json" => {
+                options.json = true;
+                i += 1;
+            }
+            "--json-lines" => {
+                options.json_lines = true;
+                i += 1;
+            }
+            "--between" => {
+                let revisions = args
+                    .get(i + 1..i + 3)
+                    .filter(|revisions| revisions.iter().all(|rev| !rev.starts_with('-')));
+                let Some([from, to]) = revisions else {
+                    return Err(GitAiError::Generic(
+                        "--between requires two revisions: <from> <to>".to_string(),
+                    ));
+                };
+                options.between = Some((from.clone(), to.clone()));
+                i += 3;
+            }
+            _ => {
+                return Err(GitAiError::Generic(format!("Unknown option: {}", args[i])));
+            }
+        }
+    }
+
+    if options.files.is_empty() && options.between.is_none() && !explicit {
+        return Err(GitAiError::Generic(
+            "log requires --file <path>, --between <from> <to> or --ai".to_string(),
+        ));
+    }
+    if options.line_range.is_some() && options.files.is_empty() {
+        return Err(GitAiError::Generic("-L requires --file".to_string()));
+    }
+    if options.json && options.json_lines {
+        return Err(GitAiError::Generic(
+            "--json and --json-lines are alternative formats; pick one".to_string(),
+        ));
+    }
+    if (options.json || options.json_lines) && (options.oneline || options.author_stats) {
+        return Err(GitAiError::Generic(
+            "--oneline and --author-stats are text formats; they cannot be combined with --json or --json-lines".to_string(),
+        ));
+    }
+    Ok(options)
+}
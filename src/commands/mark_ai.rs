@@ -0,0 +1,162 @@
+use crate::commands::checkpoint::{self, CheckpointOptions, resolve_base_commit};
+use crate::error::GitAiError;
+use crate::git::refs::{get_reference_as_working_log, put_reference};
+use crate::log_fmt::working_log::{AgentMetadata, Checkpoint, Confidence, Line, WorkingLogEntry};
+use git2::Repository;
+use std::fs;
+
+#[derive(Debug, Clone, Default)]
+pub struct MarkAiOptions {
+    pub file: String,
+    /// Inclusive, 1-based line ranges in the file as it is now
+    pub lines: Vec<(u32, u32)>,
+    pub model: String,
+    /// Author recorded on the checkpoint (defaults to the model)
+    pub author: Option<String>,
+}
+
+/// Tag line ranges of a file as AI-authored, for code pasted from a chat that no edit hook
+/// saw. Pending edits are checkpointed as `human_author` first, so the marked lines are the
+/// only ones the AI checkpoint claims and later checkpoints diff against the current file.
+/// Returns the number of lines marked.
+pub fn run(
+    repo: &Repository,
+    options: &MarkAiOptions,
+    human_author: &str,
+) -> Result<u32, GitAiError> {
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| GitAiError::Generic("Repository has no working directory".to_string()))?;
+    let content = fs::read_to_string(workdir.join(&options.file))
+        .map_err(|e| GitAiError::Generic(format!("Failed to read {}: {}", options.file, e)))?;
+    let total_lines = content.lines().count() as u32;
+    for (start, end) in &options.lines {
+        if *end > total_lines {
+            return Err(GitAiError::Generic(format!(
+                "Invalid line range: {}-{}. {} has {} lines",
+                start, end, options.file, total_lines
+            )));
+        }
+    }
+
+    checkpoint::run(
+        repo,
+        human_author,
+        &CheckpointOptions {
+            quiet: true,
+            ..Default::default()
+        },
+    )?;
+
+    let base_commit = resolve_base_commit(repo)?;
+    let ref_name = format!("ai-working-log/{}", base_commit);
+    let mut working_log = get_reference_as_working_log(repo, &ref_name).unwrap_or_default();
+    let diff = working_log
+        .last()
+        .map(|checkpoint| checkpoint.diff.clone())
+        .unwrap_or_default();
+
+    let added_lines = options
+        .lines
+        .iter()
+        .map(|(start, end)| {
+            if start == end {
+                Line::Single(*start)
+            } else {
+                Line::Range(*start, *end)
+            }
+        })
+        .collect::<Vec<_>>();
+    let marked = added_lines.iter().map(Line::line_count).sum();
+    let author = options.author.as_deref().unwrap_or(&options.model);
+    working_log.push(Checkpoint::new_with_metadata(
+        base_commit,
+        diff,
+        author.to_string(),
+        vec![WorkingLogEntry::new(
+            options.file.clone(),
+            added_lines,
+            Vec::new(),
+        )],
+        AgentMetadata {
+            model: options.model.clone(),
+            human_author: Some(human_author.to_string()),
+            // The user named the lines and the model themselves
+            confidence: Confidence::High,
+        },
+    ));
+
+    // Same encoding as checkpoint: pretty in debug builds, single-line in release builds
+    let working_log_json = if cfg!(debug_assertions) {
+        serde_json::to_string_pretty(&working_log)?
+    } else {
+        serde_json::to_string(&working_log)?
+    };
+    put_reference(
+        repo,
+        &ref_name,
+        &working_log_json,
+        &format!("Marked AI lines in {}", options.file),
+    )?;
+    Ok(marked)
+}
+
+/// Parse `mark-ai <file> --lines <ranges> --model <model> [--author <name>]`, where ranges
+/// look like `3-5,10,12-14`
+pub fn parse_mark_ai_args(args: &[String]) -> Result<MarkAiOptions, GitAiError> {
+    let mut options = MarkAiOptions::default();
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--lines" | "--model" | "--author" => {
+                let Some(value) = args.get(i + 1) else {
+                    return Err(GitAiError::Generic(format!(
+                        "Missing argument for {}",
+                        args[i]
+                    )));
+                };
+                match args[i].as_str() {
+                    "--lines" => options.lines.extend(parse_ranges(value)?),
+                    "--model" => options.model = value.clone(),
+                    _ => options.author = Some(value.clone()),
+                }
+                i += 2;
+            }
+            arg if !arg.starts_with('-') && options.file.is_empty() => {
+                options.file = arg.to_string();
+                i += 1;
+            }
+            _ => {
+                return Err(GitAiError::Generic(format!("Unknown option: {}", args[i])));
+            }
+        }
+    }
+
+    if options.file.is_empty() {
+        return Err(GitAiError::Generic("mark-ai requires a file".to_string()));
+    }
+    if options.lines.is_empty() {
+        return Err(GitAiError::Generic(
+            "mark-ai requires --lines <ranges>".to_string(),
+        ));
+    }
+    if options.model.is_empty() {
+        return Err(GitAiError::Generic(
+            "mark-ai requires --model <model>".to_string(),
+        ));
+    }
+    Ok(options)
+}
+
+fn parse_ranges(value: &str) -> Result<Vec<(u32, u32)>, GitAiError> {
+    value
+        .split(',')
+        .map(|part| {
+            let (start, end) = part.split_once('-').unwrap_or((part, part));
+            match (start.trim().parse::<u32>(), end.trim().parse::<u32>()) {
+                (Ok(start), Ok(end)) if start > 0 && start <= end => Ok((start, end)),
+                _ => Err(GitAiError::Generic(format!("Invalid line range: {}", part))),
+            }
+        })
+        .collect()
+}
@@ -1,4 +1,19 @@
+pub mod apply;
 pub mod blame;
+pub mod bundle;
 pub mod checkpoint;
+pub mod completions;
+pub mod diff_checkpoints;
+pub mod doctor;
+pub mod gate;
+pub mod hook_exec;
+pub mod hooks;
+pub mod init;
+pub mod json_schema;
+pub mod log;
+pub mod mark_ai;
+pub mod reset_attribution;
+pub mod search;
 pub mod stats;
+pub mod status;
 pub use checkpoint::run as checkpoint;
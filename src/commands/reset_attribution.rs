@@ -0,0 +1,133 @@
+use crate::commands::checkpoint::resolve_base_commit;
+use crate::commands::stats::walk_commits;
+use crate::error::GitAiError;
+use crate::git::notes::{remove_all_notes, remove_note};
+use crate::git::refs::{authorship_namespace, authorship_ref_name};
+use git2::Repository;
+
+/// What `reset-attribution` deletes. Commits and branches are never touched, only the refs
+/// git-ai keeps alongside them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResetScope {
+    /// The working log for the current base commit, with its file states and snapshots
+    Working,
+    /// The authorship logs of every commit in a commit or `<base>..<head>` range
+    Commits(String),
+    /// Every authorship log and every working log
+    All,
+}
+
+/// Parse `reset-attribution (--working | --commits <range> | --all) --confirm`
+pub fn parse_reset_attribution_args(args: &[String]) -> Result<ResetScope, GitAiError> {
+    let mut scopes = Vec::new();
+    let mut confirm = false;
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--working" => {
+                scopes.push(ResetScope::Working);
+                i += 1;
+            }
+            "--commits" => {
+                let Some(revspec) = args.get(i + 1).filter(|value| !value.starts_with('-')) else {
+                    return Err(GitAiError::Generic(
+                        "--commits requires a commit or <base>..<head> range".to_string(),
+                    ));
+                };
+                scopes.push(ResetScope::Commits(revspec.clone()));
+                i += 2;
+            }
+            "--all" => {
+                scopes.push(ResetScope::All);
+                i += 1;
+            }
+            "--confirm" => {
+                confirm = true;
+                i += 1;
+            }
+            _ => {
+                return Err(GitAiError::Generic(format!("Unknown option: {}", args[i])));
+            }
+        }
+    }
+
+    if scopes.len() != 1 {
+        return Err(GitAiError::Generic(
+            "reset-attribution requires exactly one of --working, --commits <range> or --all"
+                .to_string(),
+        ));
+    }
+    if !confirm {
+        return Err(GitAiError::Generic(
+            "reset-attribution deletes attribution that cannot be recovered; pass --confirm"
+                .to_string(),
+        ));
+    }
+    Ok(scopes.remove(0))
+}
+
+/// Delete the attribution refs in `scope` and return their names, sorted. A commit in the
+/// range that never had an authorship log is skipped rather than reported.
+pub fn run(repo: &Repository, scope: &ResetScope) -> Result<Vec<String>, GitAiError> {
+    let mut ref_names = match scope {
+        ResetScope::Working => {
+            let base_commit = resolve_base_commit(repo)?;
+            let mut ref_names = vec![format!("refs/ai-working-log/{}", base_commit)];
+            for kind in ["diffs", "snapshots"] {
+                ref_names.extend(glob_refs(
+                    repo,
+                    &format!("refs/ai-working-log/{}/{}-*", kind, base_commit),
+                )?);
+            }
+            ref_names
+        }
+        ResetScope::Commits(revspec) => walk_commits(repo, revspec)?
+            .map(|oid| {
+                oid.map(|oid| format!("refs/{}", authorship_ref_name(repo, &oid.to_string())))
+            })
+            .collect::<Result<_, _>>()?,
+        ResetScope::All => {
+            let mut ref_names = glob_refs(repo, &format!("refs/{}/*", authorship_namespace(repo)))?;
+            ref_names.extend(glob_refs(repo, "refs/ai-working-log/*")?);
+            ref_names
+        }
+    };
+    ref_names.sort();
+    ref_names.dedup();
+
+    // Stats notes summarize the attribution going away
+    match scope {
+        ResetScope::Working => {}
+        ResetScope::Commits(revspec) => {
+            for oid in walk_commits(repo, revspec)? {
+                remove_note(repo, oid?)?;
+            }
+        }
+        ResetScope::All => {
+            remove_all_notes(repo)?;
+        }
+    }
+
+    let mut deleted = Vec::new();
+    for ref_name in ref_names {
+        match repo.find_reference(&ref_name) {
+            Ok(mut reference) => {
+                reference.delete()?;
+                deleted.push(ref_name);
+            }
+            Err(e) if e.code() == git2::ErrorCode::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Ok(deleted)
+}
+
+fn glob_refs(repo: &Repository, glob: &str) -> Result<Vec<String>, GitAiError> {
+    let mut ref_names = Vec::new();
+    for reference in repo.references_glob(glob)? {
+        if let Some(name) = reference?.name() {
+            ref_names.push(name.to_string());
+        }
+    }
+    Ok(ref_names)
+}
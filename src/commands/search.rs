@@ -0,0 +1,126 @@
+use crate::error::GitAiError;
+use crate::git::refs::list_working_logs;
+use crate::log_fmt::working_log::{Checkpoint, Line};
+use git2::Repository;
+use regex::RegexBuilder;
+
+#[derive(Debug, Clone, Default)]
+pub struct SearchOptions {
+    /// Treat the query as a regular expression instead of a substring
+    pub regex: bool,
+}
+
+/// A checkpoint whose prompt matched, plus the checkpoints recorded in response to it
+#[derive(Debug, Clone)]
+pub struct SearchMatch {
+    /// Commit the working log is keyed on ("initial" before the first commit)
+    pub base_commit: String,
+    /// 1-based position of the matching checkpoint in its working log
+    pub index: usize,
+    pub checkpoint: Checkpoint,
+    /// Checkpoints up to the next prompt, i.e. the edits made for this prompt
+    pub followups: Vec<Checkpoint>,
+}
+
+/// Search the prompts of every working log (committed or not). Matching is case-insensitive;
+/// with `--regex` the query is a pattern, otherwise a plain substring.
+pub fn run(
+    repo: &Repository,
+    query: &str,
+    options: &SearchOptions,
+) -> Result<Vec<SearchMatch>, GitAiError> {
+    let pattern = if options.regex {
+        query.to_string()
+    } else {
+        regex::escape(query)
+    };
+    let matcher = RegexBuilder::new(&pattern)
+        .case_insensitive(true)
+        .build()
+        .map_err(|e| GitAiError::Generic(format!("Invalid regex: {}", e)))?;
+
+    let mut matches = Vec::new();
+    for (base_commit, working_log) in list_working_logs(repo)? {
+        for (i, checkpoint) in working_log.iter().enumerate() {
+            let Some(prompt) = &checkpoint.prompt else {
+                continue;
+            };
+            if !matcher.is_match(prompt) {
+                continue;
+            }
+            let followups = working_log[i + 1..]
+                .iter()
+                .take_while(|next| next.prompt.is_none())
+                .cloned()
+                .collect();
+            matches.push(SearchMatch {
+                base_commit: base_commit.clone(),
+                index: i + 1,
+                checkpoint: checkpoint.clone(),
+                followups,
+            });
+        }
+    }
+
+    matches.sort_by_key(|m| m.checkpoint.timestamp);
+    Ok(matches)
+}
+
+pub fn print_matches(matches: &[SearchMatch]) {
+    if matches.is_empty() {
+        println!("No matching checkpoints.");
+        return;
+    }
+
+    for m in matches {
+        let base = &m.base_commit[..m.base_commit.len().min(8)];
+        println!(
+            "{} checkpoint {} ({})",
+            base,
+            m.index,
+            chrono::DateTime::from_timestamp(m.checkpoint.timestamp as i64, 0)
+                .map(|time| time.format("%Y-%m-%d %H:%M:%S").to_string())
+                .unwrap_or_default()
+        );
+        println!("  prompt: {}", m.checkpoint.prompt.as_deref().unwrap_or(""));
+        for checkpoint in std::iter::once(&m.checkpoint).chain(m.followups.iter()) {
+            for entry in &checkpoint.entries {
+                println!(
+                    "  {} (+{} -{}) {}",
+                    entry.file,
+                    entry.added_lines.iter().map(Line::line_count).sum::<u32>(),
+                    entry
+                        .deleted_lines
+                        .iter()
+                        .map(Line::line_count)
+                        .sum::<u32>(),
+                    checkpoint.attribution()
+                );
+            }
+        }
+        println!();
+    }
+}
+
+pub fn parse_search_args(args: &[String]) -> Result<(String, SearchOptions), GitAiError> {
+    let mut options = SearchOptions::default();
+    let mut query = None;
+
+    for arg in args {
+        match arg.as_str() {
+            "--regex" => options.regex = true,
+            arg if !arg.starts_with('-') => {
+                if query.is_some() {
+                    return Err(GitAiError::Generic(format!("Unexpected argument: {}", arg)));
+                }
+                query = Some(arg.to_string());
+            }
+            _ => {
+                return Err(GitAiError::Generic(format!("Unknown option: {}", arg)));
+            }
+        }
+    }
+
+    let query = query.ok_or_else(|| GitAiError::Generic("search requires a query".to_string()))?;
+    Ok((query, options))
+}
@@ -1,179 +1,2972 @@
-use crate::commands::blame::{GitAiBlameOptions, get_git_blame_hunks, overlay_ai_authorship};
+use crate::commands::checkpoint::resolve_base_commit;
+use crate::commands::json_schema::json_schema;
 use crate::error::GitAiError;
-use git2::{DiffOptions, Repository};
-use std::collections::HashMap;
+use crate::git::attributes::{Attributes, ForcedAttribution};
+use crate::git::notes::{GIT_AI_NOTES_REF, read_note, write_note};
+use crate::git::refs::{
+    authorship_namespace, authorship_ref_name, authorship_refspec, get_reference_as_authorship_log,
+    get_reference_as_working_log,
+};
+use crate::git::repository::{config_string_lossy, get_default_remote, load_mailmap, mailmap_name};
+use crate::git::revspec::{resolve_commit, resolve_range};
+use crate::log_fmt::authorship_log::{AuthorshipLog, LineRange};
+use crate::log_fmt::working_log::{Checkpoint, Confidence, Line};
+use crate::utils::{ColorRole, ColorTheme, debug_log, terminal_width};
+use git2::{Commit, DiffOptions, Mailmap, Oid, Repository};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::{IsTerminal, Write};
+use std::time::Duration;
 
-#[derive(Debug, Clone)]
-#[allow(dead_code)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct FileStats {
     pub additions: HashMap<String, u32>,
     pub deletions: u32,
     pub total_additions: u32,
+    /// Added lines attributed to an AI agent (authorship entries with agent metadata)
+    pub ai_additions: u32,
+    /// Added lines a code generator wrote, left out of `total_additions`
+    #[serde(default)]
+    pub generated_additions: u32,
+    /// Commits in the range whose diff touched this file
+    pub commits: u32,
+    /// Added lines split by the commit author's email domain, with --author-domain
+    #[serde(default)]
+    pub domains: BTreeMap<String, DomainStats>,
+}
+
+/// Human and AI lines added by commits from one email domain
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DomainStats {
+    pub human_additions: u32,
+    pub ai_additions: u32,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct StatsOptions {
+    /// Only count commits authored by the current git user (user.name / user.email)
+    pub author_me: bool,
+    /// Only count commits committed at or after this time (unix seconds). With a single
+    /// revision, its history inside the window is walked rather than the one commit.
+    pub since: Option<i64>,
+    /// Only count commits committed at or before this time (unix seconds)
+    pub until: Option<i64>,
+    /// Continuously re-render working-tree attribution as checkpoints arrive
+    pub watch: bool,
+    /// Seconds between redraws in watch mode (defaults to 2)
+    pub watch_interval: Option<u64>,
+    /// How whitespace-only / formatting-only additions are counted
+    pub count_reformat_as: ReformatMode,
+    /// Fetch the authorship namespace from the default remote first when commits in the range
+    /// have no local authorship ref (also `git-ai.fetchAuthorship`)
+    pub fetch: bool,
+    /// Never contact the remote, whatever `git-ai.fetchAuthorship` says
+    pub no_fetch: bool,
+    /// Count merge commits (diffed against their first parent). Off by default: a merge's
+    /// lines were already attributed on the side that introduced them
+    pub include_merges: bool,
+    /// Leave files with fewer added lines than this out of the per-file breakdown
+    pub min_lines: Option<u32>,
+    /// With `min_lines`, also drop those files from the totals
+    pub exclude_small: bool,
+    /// Print one line per commit instead of the aggregate breakdown
+    pub per_commit: bool,
+    /// Output format for --per-commit (and `table` for the full breakdown)
+    pub format: StatsFormat,
+    /// Never color `--format table`, whatever `git-ai.colors.*` says
+    pub no_color: bool,
+    /// Report author names as recorded instead of mapping them through `.mailmap`
+    pub no_mailmap: bool,
+    /// What to print: the full breakdown or a single summary line
+    pub output: StatsOutput,
+    /// Print the JSON Schema of the JSON outputs instead of computing stats
+    pub json_schema: bool,
+    /// With --only-ai-files, follow each path with a tab and its AI line count
+    pub with_counts: bool,
+    /// Also report the AI ratio under this weighting
+    pub weighted_by: Option<Weighting>,
+    /// Also report AI lines against this denominator
+    pub normalize_by: Option<Normalization>,
+    /// Reuse and store the report under `.git/git-ai/stats-cache` (also `git-ai.statsCache`)
+    pub cache: bool,
+    /// Never read or write the cache, whatever `git-ai.statsCache` says
+    pub no_cache: bool,
+    /// Group attribution by the domain of commit author emails instead of by file and author
+    pub author_domain: bool,
+    /// Count AI lines recorded with less confidence than this as the commit author's
+    pub min_confidence: Option<Confidence>,
+    /// Report on `merge-base(<ref>, head)..head`, i.e. only the branch's own commits
+    pub baseline_branch: Option<String>,
+    /// Print how the revision resolves (SHAs, refnames, merge base) and compute nothing
+    pub resolve_only: bool,
+    /// Write the output to this file, atomically, instead of stdout
+    pub output_file: Option<String>,
+    /// Only count lines whose author matches, and deletions in commits whose author matches
+    pub author_regex: Option<Regex>,
+    /// How percentages are rounded and displayed in the text outputs
+    pub percent: PercentFormat,
+    /// Follow the full breakdown with the review-burden score, weighted by `git-ai.effort.*`
+    pub human_effort: bool,
+    /// Follow the full breakdown with how many AI-suggested lines survived to the commit
+    pub accept_rate: bool,
+    /// Report one commit's own diff against its first parent, merges included
+    pub delta_from_parent: bool,
+    /// Store the report as the commit's note under `refs/notes/git-ai`, which later cached
+    /// runs (`--cache`, `git-ai.statsCache`) with the same options and attribution reuse
+    pub annotate_commit: bool,
+    /// Report zero counts, in whatever output was asked for, when there is nothing to count
+    /// yet because HEAD has no commits, instead of failing on the unresolvable revision
+    pub zero_when_empty: bool,
+}
+
+/// How a displayed percentage is rounded to `--decimals` places (`--round`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Rounding {
+    Floor,
+    Ceil,
+    /// Half-way values round away from zero
+    Nearest,
+    /// Half-way values round to the even neighbour, as the output always has
+    #[default]
+    Banker,
+}
+
+impl Rounding {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "floor" => Some(Rounding::Floor),
+            "ceil" => Some(Rounding::Ceil),
+            "nearest" => Some(Rounding::Nearest),
+            "banker" => Some(Rounding::Banker),
+            _ => None,
+        }
+    }
+
+    fn apply(self, value: f64) -> f64 {
+        match self {
+            Rounding::Floor => value.floor(),
+            Rounding::Ceil => value.ceil(),
+            Rounding::Nearest => value.round(),
+            Rounding::Banker => value.round_ties_even(),
+        }
+    }
+}
+
+/// Display of percentages in the text, Markdown and summary outputs. JSON, CSV and
+/// Prometheus keep the exact values.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PercentFormat {
+    pub round: Rounding,
+    pub decimals: u8,
+    /// Make the shares of a split (human/AI, per author) add up to exactly 100
+    pub sum_to_100: bool,
+}
+
+impl PercentFormat {
+    /// One percentage, e.g. `62%`, or `61.5%` with one decimal
+    pub fn format(&self, value: f64) -> String {
+        let scale = 10f64.powi(self.decimals as i32);
+        self.render(self.round.apply(value * scale) / scale)
+    }
+
+    /// Percentages that partition a whole. Rounded one by one they can add up to 99 or 101;
+    /// with `sum_to_100` they are floored instead and the units still missing from 100 go to
+    /// the shares with the largest remainders.
+    pub fn format_split(&self, values: &[f64]) -> Vec<String> {
+        if !self.sum_to_100 || values.iter().all(|value| *value == 0.0) {
+            return values.iter().map(|value| self.format(*value)).collect();
+        }
+        let scale = 10f64.powi(self.decimals as i32);
+        let scaled: Vec<f64> = values.iter().map(|value| value * scale).collect();
+        let mut units: Vec<i64> = scaled.iter().map(|value| value.floor() as i64).collect();
+        let missing = (100.0 * scale).round() as i64 - units.iter().sum::<i64>();
+        let mut by_remainder: Vec<usize> = (0..values.len()).collect();
+        by_remainder.sort_by(|&a, &b| {
+            (scaled[b] - scaled[b].floor())
+                .total_cmp(&(scaled[a] - scaled[a].floor()))
+                .then(a.cmp(&b))
+        });
+        for &index in by_remainder.iter().take(missing.max(0) as usize) {
+            units[index] += 1;
+        }
+        units
+            .iter()
+            .map(|unit| self.render(*unit as f64 / scale))
+            .collect()
+    }
+
+    fn render(&self, value: f64) -> String {
+        format!("{:.*}%", self.decimals as usize, value)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Normalization {
+    /// AI-added lines over every changed line in the range, deletions included
+    DiffSize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Weighting {
+    /// Each file's lines divided by the number of commits that touched it
+    Churn,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StatsOutput {
+    #[default]
+    Full,
+    /// `git-ai: 62% human / 38% AI across 4 files`
+    SummaryLine,
+    /// By-file and by-author Markdown tables with totals, for PR comments
+    MarkdownTable,
+    /// Paths of the files with any AI-authored lines, one per line
+    AiFiles,
+    /// The files with the most AI lines and the files with the most human lines
+    Top(usize),
+    /// Prometheus text exposition format, labelled with the repository and branch
+    Prometheus,
+    /// Added lines per author with each one's share, most lines first
+    ByAuthor,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StatsFormat {
+    #[default]
+    Text,
+    Json,
+    Csv,
+    /// Bordered, aligned table sized to the terminal, for the full breakdown or --per-commit
+    Table,
+}
+
+/// Human/AI split of a single commit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommitStats {
+    pub sha: String,
+    pub summary: String,
+    pub author: String,
+    pub ai_additions: u32,
+    pub human_additions: u32,
+}
+
+impl CommitStats {
+    pub fn ai_percentage(&self) -> f64 {
+        percentage(self.ai_additions, self.ai_additions + self.human_additions)
+    }
+}
+
+/// How additions from reformat hunks (same content once whitespace is ignored) are counted
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReformatMode {
+    /// Counted like any other addition, by whoever the checkpoints attribute them to
+    #[default]
+    Attributed,
+    /// Counted as human additions under the commit author
+    Human,
+    /// Counted as AI additions under the attributed author
+    Ai,
+    /// Left out of the human/AI split entirely
+    Exclude,
+}
+
+impl ReformatMode {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "attributed" => Some(ReformatMode::Attributed),
+            "human" => Some(ReformatMode::Human),
+            "ai" => Some(ReformatMode::Ai),
+            "exclude" | "excluded" => Some(ReformatMode::Exclude),
+            _ => None,
+        }
+    }
+}
+
+/// Aggregated statistics for a single commit or a range of commits
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StatsReport {
+    pub file_stats: BTreeMap<String, FileStats>,
+    pub total_additions_by_author: HashMap<String, u32>,
+    pub total_deletions: u32,
+    pub ai_additions: u32,
+    pub human_additions: u32,
+    /// Added lines that only reformat existing code, regardless of how they were counted
+    pub reformat_additions: u32,
+    /// Added lines a code generator wrote (`generated` in the authorship log or
+    /// `.git-ai/attributes`), counted on neither side of the human/AI split
+    #[serde(default)]
+    pub generated_additions: u32,
+    pub commits: usize,
+    /// Merge commits left out because --include-merges was not given
+    pub merges_skipped: usize,
+    /// Per-commit splits, in the order commits were walked (newest first for ranges)
+    pub per_commit: Vec<CommitStats>,
+}
+
+impl StatsReport {
+    pub fn total_additions(&self) -> u32 {
+        self.ai_additions + self.human_additions
+    }
+}
+
+pub fn run(
+    repo: &Repository,
+    revspec: Option<&str>,
+    options: &StatsOptions,
+) -> Result<StatsReport, GitAiError> {
+    if options.json_schema {
+        let schema = format!("{}\n", serde_json::to_string_pretty(&json_schema())?);
+        emit(schema.as_bytes(), options)?;
+        return Ok(StatsReport::default());
+    }
+
+    if options.resolve_only {
+        let resolution = render_resolution(&resolve_only(repo, revspec, options)?);
+        emit(resolution.as_bytes(), options)?;
+        return Ok(StatsReport::default());
+    }
+
+    if options.watch {
+        if revspec.is_some() {
+            return Err(GitAiError::Generic(
+                "--watch reports on uncommitted working-tree attribution and takes no revision"
+                    .to_string(),
+            ));
+        }
+        watch(repo, options)?;
+    }
+
+    if options.delta_from_parent {
+        let delta = delta_from_parent(repo, revspec, options)?;
+        emit(render_delta(&delta, &options.percent).as_bytes(), options)?;
+        return Ok(delta.report);
+    }
+
+    let nothing_committed = zero_when_empty(repo, revspec, options);
+    let report = match report(repo, revspec, options) {
+        Err(_) if nothing_committed => StatsReport::default(),
+        result => result?,
+    };
+    // There is no commit to hang a note on yet
+    if options.annotate_commit && !nothing_committed {
+        annotate_commit(repo, revspec.unwrap_or("HEAD"), options, &report)?;
+    }
+    let mut rendered: Vec<u8> = Vec::new();
+    let out = &mut rendered;
+    match options.output {
+        StatsOutput::SummaryLine => writeln!(out, "{}", summary_line(&report, &options.percent))?,
+        StatsOutput::MarkdownTable => write!(
+            out,
+            "{}",
+            markdown_table(&report, options.min_lines.unwrap_or(0), &options.percent)
+        )?,
+        StatsOutput::AiFiles => write!(
+            out,
+            "{}",
+            ai_files(&report, options.min_lines.unwrap_or(0), options.with_counts)
+        )?,
+        StatsOutput::Top(n) => write!(
+            out,
+            "{}",
+            top_files(&report, n, options.min_lines.unwrap_or(0), &options.percent)
+        )?,
+        StatsOutput::ByAuthor => write!(out, "{}", by_author(&report, &options.percent))?,
+        StatsOutput::Prometheus => {
+            let (repo_name, branch) = metric_labels(repo);
+            write!(out, "{}", prometheus_metrics(&report, &repo_name, &branch))?
+        }
+        StatsOutput::Full if options.per_commit => write_per_commit(
+            out,
+            &report.per_commit,
+            options.format,
+            &options.percent,
+            &table_style(options),
+        )?,
+        StatsOutput::Full if options.author_domain => {
+            write_domain_stats(out, &report, &options.percent)?
+        }
+        StatsOutput::Full => {
+            if options.format == StatsFormat::Table {
+                let min_lines = options.min_lines.unwrap_or(0);
+                let table =
+                    stats_table(&report, min_lines, &options.percent, &table_style(options));
+                write!(out, "{}", table)?;
+            } else {
+                write_stats(out, &report, options)?;
+            }
+            if options.human_effort {
+                let weights = effort_weights(repo)?;
+                let effort = human_effort(&report, &weights);
+                writeln!(out, "{}", render_human_effort(&effort, &weights))?;
+            }
+            if options.accept_rate {
+                let rate = match accept_rate(repo, revspec, options) {
+                    Err(_) if nothing_committed => AcceptRate::default(),
+                    result => result?,
+                };
+                write!(out, "{}", render_accept_rate(&rate, &options.percent))?;
+            }
+        }
+    }
+    emit(&rendered, options)?;
+
+    Ok(report)
+}
+
+/// `--zero-when-empty` applies: the report is of HEAD, implicitly or by name, and HEAD
+/// names a branch with no commits yet, as in a freshly initialized repository. Any other
+/// revision that fails to resolve is still an error.
+fn zero_when_empty(repo: &Repository, revspec: Option<&str>, options: &StatsOptions) -> bool {
+    options.zero_when_empty
+        && revspec.is_none_or(|revspec| revspec == "HEAD")
+        && matches!(repo.head(), Err(e) if e.code() == git2::ErrorCode::UnbornBranch)
+}
+
+/// Print the rendered output, or with `--output-file` replace that file with it atomically:
+/// the bytes go to a temporary file beside it, which is then renamed over the target, so an
+/// interrupted run leaves either the old artifact or the new one and never a partial write
+fn emit(rendered: &[u8], options: &StatsOptions) -> Result<(), GitAiError> {
+    let Some(path) = &options.output_file else {
+        std::io::stdout().write_all(rendered)?;
+        return Ok(());
+    };
+    let path = std::path::Path::new(path);
+    let parent = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or_else(|| std::path::Path::new("."));
+    std::fs::create_dir_all(parent)?;
+
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| GitAiError::Generic(format!("Invalid --output-file: {}", path.display())))?;
+    let tmp_path = parent.join(format!(
+        ".{}.{}.tmp",
+        file_name.to_string_lossy(),
+        std::process::id()
+    ));
+    let written = std::fs::File::create(&tmp_path).and_then(|mut file| {
+        file.write_all(rendered)?;
+        file.sync_all()
+    });
+    if let Err(e) = written.and_then(|_| std::fs::rename(&tmp_path, path)) {
+        let _ = std::fs::remove_file(&tmp_path);
+        return Err(e.into());
+    }
+    Ok(())
+}
+
+/// Compute the report for `revspec` (HEAD when absent) without printing it: the cache, the
+/// one-time authorship fetch and `--baseline-branch` all apply as in `run`
+pub fn report(
+    repo: &Repository,
+    revspec: Option<&str>,
+    options: &StatsOptions,
+) -> Result<StatsReport, GitAiError> {
+    let revspec = &effective_revspec(revspec, options)?;
+
+    let cache_path = if cache_enabled(repo, options) {
+        Some(stats_cache_path(repo, revspec, options)?)
+    } else {
+        None
+    };
+    // A commit annotated under the same options is already computed; --annotate-commit
+    // itself recomputes, so a stale note gets updated. Notes are a cache too, so only
+    // read when caching is on.
+    let noted = if revspec.contains("..")
+        || has_window(options)
+        || cache_path.is_none()
+        || options.annotate_commit
+    {
+        None
+    } else {
+        let commit = resolve_commit(repo, revspec)?.id();
+        read_stats_note(repo, commit, &stats_cache_key(repo, revspec, options)?)
+    };
+    let cached = noted.or_else(|| cache_path.as_deref().and_then(read_cached_report));
+
+    Ok(match cached {
+        Some(report) => report,
+        None => {
+            // The only time stats touches the network, and only when asked: some commits
+            // have no local authorship ref (e.g. after a plain `git fetch` or a fresh
+            // clone), so pull the authorship namespace once. Commits made before git-ai
+            // never have one, which is why this isn't the default.
+            if fetch_enabled(repo, options) {
+                fetch_missing_authorship(repo, walk_report_commits(repo, revspec, options)?)?;
+            }
+
+            let report = build_report(repo, walk_report_commits(repo, revspec, options)?, options)?;
+            // Keyed again: the fetch may have brought in authorship refs the lookup lacked
+            if cache_path.is_some()
+                && let Err(e) = stats_cache_path(repo, revspec, options)
+                    .and_then(|cache_path| write_cached_report(&cache_path, &report))
+            {
+                debug_log(&format!("Failed to write stats cache: {}", e));
+            }
+            report
+        }
+    })
+}
+
+/// The revision stats walks: HEAD when none is given, and with `--baseline-branch`,
+/// `base...head`, which walks merge-base(base, head)..head, exactly a branch's divergence
+fn effective_revspec(revspec: Option<&str>, options: &StatsOptions) -> Result<String, GitAiError> {
+    let revspec = revspec.unwrap_or("HEAD");
+    match &options.baseline_branch {
+        Some(_) if revspec.contains("..") => Err(GitAiError::Generic(
+            "--baseline-branch takes a single revision, not a range".to_string(),
+        )),
+        Some(baseline) => Ok(format!("{}...{}", baseline, revspec)),
+        None => Ok(revspec.to_string()),
+    }
+}
+
+/// One side of a resolved revision: what was typed, the ref it named, if any, and the commit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedRevision {
+    pub spec: String,
+    pub refname: Option<String>,
+    pub sha: String,
+}
+
+/// How stats reads its revision argument, as printed by `--resolve-only`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Resolution {
+    pub revspec: String,
+    /// The excluded side of a range
+    pub base: Option<ResolvedRevision>,
+    pub head: ResolvedRevision,
+    /// Present for ranges. `base...head` walks from here; `base..head` only reports it.
+    pub merge_base: Option<String>,
+    pub symmetric: bool,
+}
+
+/// Resolve the revision stats would walk without computing any attribution
+pub fn resolve_only(
+    repo: &Repository,
+    revspec: Option<&str>,
+    options: &StatsOptions,
+) -> Result<Resolution, GitAiError> {
+    let revspec = effective_revspec(revspec, options)?;
+    let refname = |spec: &str| {
+        let spec = if spec.is_empty() { "HEAD" } else { spec };
+        let reference = repo.resolve_reference_from_short_name(spec).ok()?;
+        // HEAD reports the branch it points at
+        match reference.symbolic_target() {
+            Some(target) => Some(target.to_string()),
+            None => reference.name().map(str::to_string),
+        }
+    };
+    let side = |spec: &str, sha: Oid| ResolvedRevision {
+        spec: if spec.is_empty() { "HEAD" } else { spec }.to_string(),
+        refname: refname(spec),
+        sha: sha.to_string(),
+    };
+
+    if !revspec.contains("..") {
+        let sha = resolve_commit(repo, &revspec)?.id();
+        return Ok(Resolution {
+            head: side(&revspec, sha),
+            revspec,
+            base: None,
+            merge_base: None,
+            symmetric: false,
+        });
+    }
+
+    let spec = resolve_range(repo, &revspec)?;
+    let (Some(from), Some(to)) = (spec.from(), spec.to()) else {
+        return Err(GitAiError::Generic(format!(
+            "Invalid range: {}. Expected <base>..<head>",
+            revspec
+        )));
+    };
+    let (from, to) = (from.peel_to_commit()?.id(), to.peel_to_commit()?.id());
+    let symmetric = spec.mode().contains(git2::RevparseMode::MERGE_BASE);
+    let (base_spec, head_spec) = revspec
+        .split_once("...")
+        .or_else(|| revspec.split_once(".."))
+        .unwrap_or((&revspec, ""));
+    Ok(Resolution {
+        base: Some(side(base_spec, from)),
+        head: side(head_spec, to),
+        merge_base: repo.merge_base(from, to).ok().map(|oid| oid.to_string()),
+        symmetric,
+        revspec,
+    })
+}
+
+pub fn render_resolution(resolution: &Resolution) -> String {
+    let describe = |side: &ResolvedRevision| match &side.refname {
+        Some(refname) => format!("{} -> {} -> {}", side.spec, refname, side.sha),
+        None => format!("{} -> {}", side.spec, side.sha),
+    };
+    let mut output = format!("revspec:    {}\n", resolution.revspec);
+    let Some(base) = &resolution.base else {
+        output.push_str(&format!("commit:     {}\n", describe(&resolution.head)));
+        return output;
+    };
+    output.push_str(&format!("base:       {}\n", describe(base)));
+    output.push_str(&format!("head:       {}\n", describe(&resolution.head)));
+    output.push_str(&format!(
+        "merge-base: {}\n",
+        resolution.merge_base.as_deref().unwrap_or("(none)")
+    ));
+    let walked_from = if resolution.symmetric {
+        resolution.merge_base.as_deref().unwrap_or(&base.sha)
+    } else {
+        &base.sha
+    };
+    output.push_str(&format!(
+        "walks:      {}..{}\n",
+        &walked_from[..walked_from.len().min(7)],
+        &resolution.head.sha[..7]
+    ));
+    output
+}
+
+/// `--delta-from-parent`: one commit measured against its first parent and nothing else.
+/// The default view reads its revision more loosely: `--baseline-branch` widens a SHA into a
+/// range, merges are skipped unless `--include-merges` is given, and the per-file breakdown
+/// is a rollup over whatever was walked. Here the revision must be a single commit, a merge
+/// counts its first-parent diff, and the result is that diff's own human/AI split.
+#[derive(Debug, Clone)]
+pub struct DeltaFromParent {
+    pub sha: String,
+    /// None for a root commit, which is measured against the empty tree
+    pub parent: Option<String>,
+    pub report: StatsReport,
+}
+
+pub fn delta_from_parent(
+    repo: &Repository,
+    revspec: Option<&str>,
+    options: &StatsOptions,
+) -> Result<DeltaFromParent, GitAiError> {
+    let revspec = revspec.unwrap_or("HEAD");
+    if revspec.contains("..") {
+        return Err(GitAiError::Generic(
+            "--delta-from-parent takes a single commit, not a range".to_string(),
+        ));
+    }
+    let commit = resolve_commit(repo, revspec)?;
+    let options = StatsOptions {
+        include_merges: true,
+        ..options.clone()
+    };
+    let report = build_report(repo, std::iter::once(Ok(commit.id())), &options)?;
+    Ok(DeltaFromParent {
+        sha: commit.id().to_string(),
+        parent: commit.parent_id(0).ok().map(|oid| oid.to_string()),
+        report,
+    })
+}
+
+/// e.g. "Delta of 5d6e7f8 from its parent 1a2b3c4:", a row per file, then the split
+pub fn render_delta(delta: &DeltaFromParent, percent: &PercentFormat) -> String {
+    let short = |sha: &str| sha[..sha.len().min(7)].to_string();
+    let mut output = match &delta.parent {
+        Some(parent) => format!(
+            "Delta of {} from its parent {}:\n",
+            short(&delta.sha),
+            short(parent)
+        ),
+        None => format!("Delta of {} from the empty tree:\n", short(&delta.sha)),
+    };
+    let report = &delta.report;
+    let width = report
+        .file_stats
+        .keys()
+        .map(|path| path.chars().count())
+        .max()
+        .unwrap_or(0);
+    for (path, stats) in &report.file_stats {
+        output.push_str(&format!(
+            "    {:<width$}  AI +{}  human +{}  -{}\n",
+            path,
+            stats.ai_additions,
+            stats.total_additions - stats.ai_additions,
+            stats.deletions,
+            width = width
+        ));
+    }
+    let total = report.total_additions();
+    let split = percent.format_split(&[
+        percentage(report.ai_additions, total),
+        percentage(report.human_additions, total),
+    ]);
+    output.push_str(&format!(
+        "AI +{} ({})  Human +{} ({})  -{} deleted\n",
+        report.ai_additions, split[0], report.human_additions, split[1], report.total_deletions
+    ));
+    output
+}
+
+/// Aggregate commits one at a time as the walk yields them, so memory stays flat however
+/// long the range is. Per-commit rows are only kept when --per-commit asks for them.
+fn build_report(
+    repo: &Repository,
+    commits: impl Iterator<Item = Result<Oid, GitAiError>>,
+    options: &StatsOptions,
+) -> Result<StatsReport, GitAiError> {
+    let me = if options.author_me {
+        Some(current_identity(repo)?)
+    } else {
+        None
+    };
+
+    let mailmap = load_mailmap(repo, !options.no_mailmap);
+    let attributes = Attributes::load(repo)?;
+    let mut report = StatsReport::default();
+    for oid in commits {
+        let commit = repo.find_commit(oid?)?;
+        if let Some((name, email)) = &me
+            && !is_authored_by(&commit, name.as_deref(), email.as_deref())
+        {
+            continue;
+        }
+        // Applies to a single merge SHA as well as to merges inside a range
+        if commit.parent_count() > 1 && !options.include_merges {
+            report.merges_skipped += 1;
+            continue;
+        }
+        let (ai_before, human_before) = (report.ai_additions, report.human_additions);
+        accumulate_commit_stats(
+            repo,
+            &commit,
+            options,
+            mailmap.as_ref(),
+            &attributes,
+            &mut report,
+        )?;
+        // Under --author-regex a commit counts if its author matches or it added matching lines
+        if let Some(regex) = &options.author_regex
+            && !regex.is_match(&commit_author_name(&commit, mailmap.as_ref()))
+            && (report.ai_additions, report.human_additions) == (ai_before, human_before)
+        {
+            continue;
+        }
+        report.commits += 1;
+        if !options.per_commit {
+            continue;
+        }
+        report.per_commit.push(CommitStats {
+            sha: commit.id().to_string(),
+            summary: commit.summary().unwrap_or("").to_string(),
+            author: commit_author_name(&commit, mailmap.as_ref()),
+            ai_additions: report.ai_additions - ai_before,
+            human_additions: report.human_additions - human_before,
+        });
+    }
+
+    // Files touched only by authors the regex filtered out have nothing left to show
+    if options.author_regex.is_some() {
+        report
+            .file_stats
+            .retain(|_, file| file.total_additions > 0 || file.deletions > 0);
+    }
+    if options.exclude_small
+        && let Some(min_lines) = options.min_lines
+    {
+        exclude_small_files(&mut report, min_lines);
+    }
+    Ok(report)
+}
+
+/// Churn-weighted AI percentage: every file counts its added lines divided by the number of
+/// commits in the range that touched it,
+///
+/// ```text
+/// sum(ai_lines(f) / commits(f)) / sum(added_lines(f) / commits(f)) * 100
+/// ```
+///
+/// so a file reworked in ten commits weighs as much as one written once, rather than ten
+/// times as much.
+pub fn churn_weighted_ai_percentage(report: &StatsReport) -> f64 {
+    let (mut ai, mut total) = (0.0, 0.0);
+    for stats in report.file_stats.values() {
+        let commits = f64::from(stats.commits.max(1));
+        ai += f64::from(stats.ai_additions) / commits;
+        total += f64::from(stats.total_additions) / commits;
+    }
+    if total == 0.0 {
+        0.0
+    } else {
+        ai / total * 100.0
+    }
+}
+
+/// Weights of the `--human-effort` score, each overridable with `git-ai.effort.<name>`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EffortWeights {
+    /// Per AI-added line (`git-ai.effort.lineWeight`)
+    pub line: f64,
+    /// Per file with AI-added lines, for the context switch of reviewing it (`fileWeight`)
+    pub file: f64,
+    /// Per extra commit touching such a file, since reworked code needs a second look
+    /// (`churnWeight`)
+    pub churn: f64,
+}
+
+impl Default for EffortWeights {
+    fn default() -> Self {
+        EffortWeights {
+            line: 1.0,
+            file: 10.0,
+            churn: 5.0,
+        }
+    }
+}
+
+/// The configured effort weights, falling back to the defaults for unset keys
+pub fn effort_weights(repo: &Repository) -> Result<EffortWeights, GitAiError> {
+    let mut weights = EffortWeights::default();
+    let Ok(config) = repo.config() else {
+        return Ok(weights);
+    };
+    for (key, weight) in [
+        ("lineWeight", &mut weights.line),
+        ("fileWeight", &mut weights.file),
+        ("churnWeight", &mut weights.churn),
+    ] {
+        let key = format!("git-ai.effort.{}", key);
+        if let Ok(value) = config.get_string(&key) {
+            *weight = value
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .filter(|value| value.is_finite() && *value >= 0.0)
+                .ok_or_else(|| {
+                    GitAiError::Generic(format!(
+                        "{} must be a non-negative number, got {}",
+                        key, value
+                    ))
+                })?;
+        }
+    }
+    Ok(weights)
+}
+
+/// `--human-effort`: a rough proxy for the review attention a range's AI code needs. It is
+/// opinionated on purpose and only comparable between runs with the same weights:
+///
+/// ```text
+/// score = line * ai_lines + file * ai_files + churn * rework_commits
+/// ```
+///
+/// where `ai_files` counts files with any AI-added line and `rework_commits` the commits
+/// beyond the first that touched each of them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HumanEffort {
+    pub ai_lines: u32,
+    pub ai_files: u32,
+    pub rework_commits: u32,
+    pub score: f64,
+}
+
+pub fn human_effort(report: &StatsReport, weights: &EffortWeights) -> HumanEffort {
+    let ai_files: Vec<&FileStats> = report
+        .file_stats
+        .values()
+        .filter(|stats| stats.ai_additions > 0)
+        .collect();
+    let ai_lines = ai_files.iter().map(|stats| stats.ai_additions).sum();
+    let rework_commits = ai_files
+        .iter()
+        .map(|stats| stats.commits.saturating_sub(1))
+        .sum();
+    let ai_files = ai_files.len() as u32;
+    HumanEffort {
+        ai_lines,
+        ai_files,
+        rework_commits,
+        score: weights.line * f64::from(ai_lines)
+            + weights.file * f64::from(ai_files)
+            + weights.churn * f64::from(rework_commits),
+    }
+}
+
+pub fn render_human_effort(effort: &HumanEffort, weights: &EffortWeights) -> String {
+    format!(
+        "Human effort: {:.0} ({} AI line(s) x{} + {} file(s) x{} + {} rework commit(s) x{})",
+        effort.score,
+        effort.ai_lines,
+        weights.line,
+        effort.ai_files,
+        weights.file,
+        effort.rework_commits,
+        weights.churn
+    )
+}
+
+/// `--accept-rate`: what became of every line an agent suggested in the checkpoints the
+/// range's commits were built from (each commit's parent working log). Each suggested line
+/// lands in exactly one bucket:
+///
+/// ```text
+/// suggested   = retained + removed_by_humans + revised_by_ai + not_committed
+/// accept rate = retained / suggested * 100
+/// ```
+///
+/// A line rewritten by a later checkpoint counts against whoever rewrote it, and the
+/// rewrite is a suggestion of its own when an agent made it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AcceptRate {
+    pub suggested: u32,
+    /// Still attributed to an agent in the commit
+    pub retained: u32,
+    /// Deleted or rewritten by a human checkpoint
+    pub removed_by_humans: u32,
+    /// Deleted or rewritten by a later AI checkpoint
+    pub revised_by_ai: u32,
+    /// In files the commit doesn't contain
+    pub not_committed: u32,
+}
+
+impl AcceptRate {
+    pub fn percentage(&self) -> f64 {
+        percentage(self.retained, self.suggested)
+    }
+}
+
+pub fn accept_rate(
+    repo: &Repository,
+    revspec: Option<&str>,
+    options: &StatsOptions,
+) -> Result<AcceptRate, GitAiError> {
+    let revspec = effective_revspec(revspec, options)?;
+    let mut rate = AcceptRate::default();
+    for oid in walk_report_commits(repo, &revspec, options)? {
+        let commit = repo.find_commit(oid?)?;
+        if commit.parent_count() > 1 && !options.include_merges {
+            continue;
+        }
+        let parent_sha = match commit.parent_id(0) {
+            Ok(parent) => parent.to_string(),
+            Err(_) => "initial".to_string(),
+        };
+        let working_log =
+            get_reference_as_working_log(repo, &format!("ai-working-log/{}", parent_sha))
+                .unwrap_or_default();
+        tally_accept_rate(&working_log, &commit.tree()?, &mut rate);
+    }
+    Ok(rate)
+}
+
+/// Replay a working log the way the authorship log is built from it, following which lines
+/// are an agent's
+fn tally_accept_rate(working_log: &[Checkpoint], tree: &git2::Tree, rate: &mut AcceptRate) {
+    let mut ai_lines: BTreeMap<&str, HashSet<u32>> = BTreeMap::new();
+    for checkpoint in working_log
+        .iter()
+        .filter(|checkpoint| !checkpoint.touch_only)
+    {
+        let is_ai = checkpoint.agent_metadata.is_some();
+        for entry in &checkpoint.entries {
+            let lines = ai_lines.entry(entry.file.as_str()).or_default();
+            let expand = |ranges: &[Line]| {
+                ranges
+                    .iter()
+                    .flat_map(|line| line.start()..=line.end())
+                    .collect::<Vec<u32>>()
+            };
+            let added = expand(&entry.added_lines);
+            for line in expand(&entry.deleted_lines).iter().chain(&added) {
+                if lines.remove(line) {
+                    if is_ai {
+                        rate.revised_by_ai += 1;
+                    } else {
+                        rate.removed_by_humans += 1;
+                    }
+                }
+            }
+            if is_ai {
+                rate.suggested += added.len() as u32;
+                lines.extend(added);
+            }
+        }
+    }
+    for (file, lines) in ai_lines {
+        let count = lines.len() as u32;
+        if tree.get_path(std::path::Path::new(file)).is_ok() {
+            rate.retained += count;
+        } else {
+            rate.not_committed += count;
+        }
+    }
+}
+
+pub fn render_accept_rate(rate: &AcceptRate, percent: &PercentFormat) -> String {
+    let mut output = format!(
+        "Accept rate: {} of {} AI-suggested line(s) retained (retained / suggested)\n",
+        percent.format(rate.percentage()),
+        rate.suggested
+    );
+    for (label, count) in [
+        ("retained", rate.retained),
+        ("removed by humans", rate.removed_by_humans),
+        ("revised by AI", rate.revised_by_ai),
+        ("not committed", rate.not_committed),
+    ] {
+        output.push_str(&format!("    {:<18} {}\n", label, count));
+    }
+    output
+}
+
+/// AI-added lines as a percentage of the diff size, i.e. every line the range added or
+/// deleted (a modified line counts once as deleted and once as added):
+///
+/// ```text
+/// ai_lines / (added_lines + deleted_lines) * 100
+/// ```
+///
+/// The default ratio divides by added lines only, so a PR that mostly deletes code shows
+/// the same AI share as one that only adds. Dividing by the whole diff makes ratios
+/// comparable across PRs of different sizes and shapes.
+pub fn diff_size_ai_percentage(report: &StatsReport) -> f64 {
+    percentage(
+        report.ai_additions,
+        report.total_additions() + report.total_deletions,
+    )
+}
+
+/// One-line split such as "git-ai: 62% human / 38% AI across 4 files"
+pub fn summary_line(report: &StatsReport, percent: &PercentFormat) -> String {
+    let total = report.total_additions();
+    let files = report.file_stats.len();
+    let split = percent.format_split(&[
+        percentage(report.human_additions, total),
+        percentage(report.ai_additions, total),
+    ]);
+    format!(
+        "git-ai: {} human / {} AI across {} file{}",
+        split[0],
+        split[1],
+        files,
+        if files == 1 { "" } else { "s" }
+    )
+}
+
+/// Summary line for a single commit from local refs only, as printed after `git-ai commit`
+pub fn commit_summary_line(repo: &Repository, commit: &Commit) -> Result<String, GitAiError> {
+    let options = StatsOptions {
+        no_fetch: true,
+        include_merges: true,
+        ..Default::default()
+    };
+    let report = build_report(repo, std::iter::once(Ok(commit.id())), &options)?;
+    Ok(summary_line(&report, &PercentFormat::default()))
+}
+
+/// Bumped whenever the cached report's shape or meaning changes, orphaning older entries
+const STATS_CACHE_VERSION: u32 = 1;
+
+fn cache_enabled(repo: &Repository, options: &StatsOptions) -> bool {
+    !options.no_cache
+        && (options.cache
+            || repo
+                .config()
+                .and_then(|config| config.get_bool("git-ai.statsCache"))
+                .unwrap_or(false))
+}
+
+fn stats_cache_path(
+    repo: &Repository,
+    revspec: &str,
+    options: &StatsOptions,
+) -> Result<std::path::PathBuf, GitAiError> {
+    Ok(repo
+        .path()
+        .join("git-ai")
+        .join("stats-cache")
+        .join(format!("{}.json", stats_cache_key(repo, revspec, options)?)))
+}
+
+/// Identifies a query's report: the resolved SHAs, the authorship ref each commit had, and
+/// every option that changes the report; output-only options (`--output`, `--format`, ...)
+/// are left out so they share an entry. Commits are immutable but their attribution is
+/// not (`checkpoint --link-commit --overwrite`, `reset-attribution`, `import`), so a
+/// rewritten log gets a new key rather than the old report.
+fn stats_cache_key(
+    repo: &Repository,
+    revspec: &str,
+    options: &StatsOptions,
+) -> Result<String, GitAiError> {
+    let range = if revspec.contains("..") {
+        let spec = resolve_range(repo, revspec)?;
+        format!(
+            "{}{}{}",
+            spec.from()
+                .map(|from| from.id().to_string())
+                .unwrap_or_default(),
+            if spec.mode().contains(git2::RevparseMode::MERGE_BASE) {
+                "..."
+            } else {
+                ".."
+            },
+            spec.to().map(|to| to.id().to_string()).unwrap_or_default()
+        )
+    } else {
+        resolve_commit(repo, revspec)?.id().to_string()
+    };
+    let me = if options.author_me {
+        Some(current_identity(repo)?)
+    } else {
+        None
+    };
+    let key = format!(
+        "v{}|{}|{:?}|{:?}|{}|{:?}|{:?}|{}|{:?}|{}|{}|{}|{}|{}|{:?}|{:?}|{}",
+        STATS_CACHE_VERSION,
+        range,
+        options.since,
+        options.until,
+        authorship_state(repo, revspec, options)?,
+        me,
+        options.count_reformat_as,
+        options.include_merges,
+        options.min_lines,
+        options.exclude_small,
+        options.per_commit,
+        options.no_mailmap,
+        options.author_domain,
+        authorship_namespace(repo),
+        options.min_confidence,
+        options.author_regex.as_ref().map(Regex::as_str),
+        Attributes::load(repo)?.source()
+    );
+    Ok(format!("{:x}", Sha256::digest(key.as_bytes())))
+}
+
+/// Digest of the authorship ref every commit stats counts points at ("none" without one)
+fn authorship_state(
+    repo: &Repository,
+    revspec: &str,
+    options: &StatsOptions,
+) -> Result<String, GitAiError> {
+    let mut hasher = Sha256::new();
+    for oid in walk_report_commits(repo, revspec, options)? {
+        let oid = oid?;
+        let log = repo
+            .refname_to_id(&format!(
+                "refs/{}",
+                authorship_ref_name(repo, &oid.to_string())
+            ))
+            .map(|log| log.to_string())
+            .unwrap_or_else(|_| "none".to_string());
+        hasher.update(format!("{} {}\n", oid, log).as_bytes());
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+fn read_cached_report(path: &std::path::Path) -> Option<StatsReport> {
+    let content = std::fs::read_to_string(path).ok()?;
+    let report = serde_json::from_str(&content).ok()?;
+    debug_log(&format!("Using cached stats from {}", path.display()));
+    Some(report)
+}
+
+fn write_cached_report(path: &std::path::Path, report: &StatsReport) -> Result<(), GitAiError> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, serde_json::to_string(report)?)?;
+    Ok(())
+}
+
+/// Starts the machine-readable part of a stats note, followed by the query's cache key
+const STATS_NOTE_MARKER: &str = "git-ai-stats";
+
+/// Write `report` as the note on `revspec`'s commit: the summary line for `git log
+/// --notes=git-ai`, then the key and the report as JSON so a later run can reuse it.
+/// Re-annotating replaces the note, and does nothing when it would not change.
+fn annotate_commit(
+    repo: &Repository,
+    revspec: &str,
+    options: &StatsOptions,
+    report: &StatsReport,
+) -> Result<(), GitAiError> {
+    let commit = resolve_commit(repo, revspec)?.id();
+    let note = format!(
+        "{}\n\n{} {}\n{}\n",
+        summary_line(report, &PercentFormat::default()),
+        STATS_NOTE_MARKER,
+        stats_cache_key(repo, revspec, options)?,
+        serde_json::to_string(report)?
+    );
+    if write_note(repo, commit, &note)? {
+        eprintln!(
+            "Annotated {} in {}",
+            &commit.to_string()[..7],
+            GIT_AI_NOTES_REF
+        );
+    }
+    Ok(())
+}
+
+/// The report a stats note holds, when it was written for the same query
+fn read_stats_note(repo: &Repository, commit: Oid, key: &str) -> Option<StatsReport> {
+    let note = read_note(repo, commit)?;
+    let mut lines = note
+        .lines()
+        .skip_while(|line| !line.starts_with(STATS_NOTE_MARKER));
+    let noted_key = lines.next()?.strip_prefix(STATS_NOTE_MARKER)?.trim();
+    if noted_key != key {
+        return None;
+    }
+    let report = serde_json::from_str(lines.next()?).ok()?;
+    debug_log(&format!("Using stats noted on {}", commit));
+    Some(report)
+}
+
+/// `--fetch` or `git-ai.fetchAuthorship = true`, unless `--no-fetch` or
+/// `git-ai.offline = true` rules the network out
+fn fetch_enabled(repo: &Repository, options: &StatsOptions) -> bool {
+    let config = |key: &str| {
+        repo.config()
+            .and_then(|config| config.get_bool(key))
+            .unwrap_or(false)
+    };
+    (options.fetch || config("git-ai.fetchAuthorship"))
+        && !options.no_fetch
+        && !config("git-ai.offline")
+}
+
+/// One pass over the walk that stops at the first commit without a local authorship ref
+fn fetch_missing_authorship(
+    repo: &Repository,
+    mut commits: impl Iterator<Item = Result<Oid, GitAiError>>,
+) -> Result<(), GitAiError> {
+    let mut missing = None;
+    for oid in commits.by_ref() {
+        let oid = oid?;
+        let ref_name = format!("refs/{}", authorship_ref_name(repo, &oid.to_string()));
+        if repo.find_reference(&ref_name).is_err() {
+            missing = Some(oid);
+            break;
+        }
+    }
+    let Some(missing) = missing else {
+        return Ok(());
+    };
+    let Some(remote) = get_default_remote(repo) else {
+        return Ok(());
+    };
+
+    debug_log(&format!(
+        "{} (and possibly more) missing authorship, fetching from {}",
+        missing, remote
+    ));
+    // Best effort: stats still reports from local refs if the fetch fails
+    let _ = std::process::Command::new("git")
+        .arg("-C")
+        .arg(repo.path())
+        .args(["fetch", "--quiet", &remote, &authorship_refspec(repo)])
+        .stdin(std::process::Stdio::null())
+        .output();
+    Ok(())
+}
+
+/// The commit's author name, canonicalized through the mailmap when one is loaded
+fn commit_author_name(commit: &Commit, mailmap: Option<&Mailmap>) -> String {
+    let author = commit.author();
+    mailmap_name(
+        mailmap,
+        &String::from_utf8_lossy(author.name_bytes()),
+        &String::from_utf8_lossy(author.email_bytes()),
+    )
+}
+
+/// Lowercased domain of the commit's author email, canonicalized through the mailmap when
+/// one is loaded; "unknown" when the email has none
+fn commit_author_domain(commit: &Commit, mailmap: Option<&Mailmap>) -> String {
+    let author = commit.author();
+    let email = mailmap
+        .and_then(|mailmap| mailmap.resolve_signature(&author).ok())
+        .and_then(|signature| signature.email().map(str::to_string))
+        .or_else(|| author.email().map(str::to_string))
+        .unwrap_or_default();
+    match email.rsplit_once('@') {
+        Some((_, domain)) if !domain.trim().is_empty() => domain.trim().to_lowercase(),
+        _ => "unknown".to_string(),
+    }
+}
+
+/// Walk a single revision or a `base..head` range lazily; only the revwalk's own state
+/// is held, never the list of commits
+pub(crate) fn walk_commits<'a>(
+    repo: &'a Repository,
+    revspec: &str,
+) -> Result<Box<dyn Iterator<Item = Result<Oid, GitAiError>> + 'a>, GitAiError> {
+    if !revspec.contains("..") {
+        let oid = resolve_commit(repo, revspec)?.id();
+        return Ok(Box::new(std::iter::once(Ok(oid))));
+    }
+
+    let spec = resolve_range(repo, revspec)?;
+    let (from, to) = match (spec.from(), spec.to()) {
+        (Some(from), Some(to)) => (from.id(), to.id()),
+        _ => {
+            return Err(GitAiError::Generic(format!(
+                "Invalid range: {}. Expected <base>..<head>",
+                revspec
+            )));
+        }
+    };
+
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push(to)?;
+    if spec.mode().contains(git2::RevparseMode::MERGE_BASE) {
+        // base...head: exclude everything reachable from the merge base
+        revwalk.hide(repo.merge_base(from, to)?)?;
+    } else {
+        revwalk.hide(from)?;
+    }
+
+    Ok(Box::new(revwalk.map(|oid| oid.map_err(GitAiError::from))))
+}
+
+/// `--since` or `--until` was given
+fn has_window(options: &StatsOptions) -> bool {
+    options.since.is_some() || options.until.is_some()
+}
+
+/// The commits a report counts: those of `revspec` as `walk_commits` yields them, or with a
+/// `--since`/`--until` window, the commits of its range or, for a single revision, of its
+/// history whose commit time falls inside the window
+fn walk_report_commits<'a>(
+    repo: &'a Repository,
+    revspec: &str,
+    options: &StatsOptions,
+) -> Result<Box<dyn Iterator<Item = Result<Oid, GitAiError>> + 'a>, GitAiError> {
+    if !has_window(options) {
+        return walk_commits(repo, revspec);
+    }
+    let commits = if revspec.contains("..") {
+        walk_commits(repo, revspec)?
+    } else {
+        let mut revwalk = repo.revwalk()?;
+        revwalk.push(resolve_commit(repo, revspec)?.id())?;
+        Box::new(revwalk.map(|oid| oid.map_err(GitAiError::from)))
+    };
+    let (since, until) = (options.since, options.until);
+    Ok(Box::new(commits.filter(move |oid| {
+        let Ok(oid) = oid else {
+            return true;
+        };
+        let time = repo
+            .find_commit(*oid)
+            .map(|commit| commit.time().seconds())
+            .unwrap_or_default();
+        since.is_none_or(|since| time >= since) && until.is_none_or(|until| time <= until)
+    })))
+}
+
+/// A `--since`/`--until` value as unix seconds: a date (`2025-06-01`, midnight UTC), an
+/// RFC 3339 time, `@<unix seconds>`, or `<n> <unit> ago` (`2 weeks ago`, `3.days.ago`)
+/// with units from seconds to weeks
+pub fn parse_time(value: &str) -> Option<i64> {
+    if let Some(seconds) = value.strip_prefix('@') {
+        return seconds.parse().ok();
+    }
+    if let Ok(time) = chrono::DateTime::parse_from_rfc3339(value) {
+        return Some(time.timestamp());
+    }
+    if let Ok(date) = chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Some(date.and_hms_opt(0, 0, 0)?.and_utc().timestamp());
+    }
+    let words: Vec<&str> = value
+        .split(|c: char| c == '.' || c.is_whitespace())
+        .filter(|word| !word.is_empty())
+        .collect();
+    let [count, unit, "ago"] = words[..] else {
+        return None;
+    };
+    let count: i64 = count.parse().ok()?;
+    let unit_seconds = match unit.strip_suffix('s').unwrap_or(unit) {
+        "second" => 1,
+        "minute" => 60,
+        "hour" => 60 * 60,
+        "day" => 24 * 60 * 60,
+        "week" => 7 * 24 * 60 * 60,
+        _ => return None,
+    };
+    Some(chrono::Utc::now().timestamp() - count.checked_mul(unit_seconds)?)
+}
+
+/// Read the current user's identity from git config
+fn current_identity(repo: &Repository) -> Result<(Option<String>, Option<String>), GitAiError> {
+    let config = repo.config()?;
+    let name = config_string_lossy(&config, "user.name");
+    let email = config_string_lossy(&config, "user.email");
+    if name.is_none() && email.is_none() {
+        return Err(GitAiError::Generic(
+            "--me requires git user.name or user.email to be configured".to_string(),
+        ));
+    }
+    Ok((name, email))
+}
+
+fn is_authored_by(commit: &Commit, name: Option<&str>, email: Option<&str>) -> bool {
+    let author = commit.author();
+    let author_name = String::from_utf8_lossy(author.name_bytes());
+    let author_email = String::from_utf8_lossy(author.email_bytes());
+    let name_matches = name.is_some_and(|name| name == author_name);
+    let email_matches = email.is_some_and(|email| email.eq_ignore_ascii_case(&author_email));
+    name_matches || email_matches
+}
+
+/// Lines added and removed by a single diff hunk. Only added line numbers are stored;
+/// content is folded into digests, so a huge hunk costs four bytes per added line.
+#[derive(Default)]
+struct HunkLines {
+    added: Vec<u32>,
+    deleted: u32,
+    added_digest: Sha256,
+    deleted_digest: Sha256,
+}
+
+impl HunkLines {
+    fn add(&mut self, line_num: u32, content: &[u8]) {
+        self.added.push(line_num);
+        digest_without_whitespace(&mut self.added_digest, content);
+    }
+
+    fn delete(&mut self, content: &[u8]) {
+        self.deleted += 1;
+        digest_without_whitespace(&mut self.deleted_digest, content);
+    }
+
+    /// A hunk is a reformat when it removes and re-adds the same content, ignoring whitespace
+    /// (re-indentation, re-wrapping, trailing whitespace)
+    fn is_reformat(&self) -> bool {
+        if self.added.is_empty() || self.deleted == 0 {
+            return false;
+        }
+        self.added_digest.clone().finalize() == self.deleted_digest.clone().finalize()
+    }
+}
+
+fn digest_without_whitespace(digest: &mut Sha256, content: &[u8]) {
+    let mut buf = [0; 4];
+    for c in String::from_utf8_lossy(content)
+        .chars()
+        .filter(|c| !c.is_whitespace())
+    {
+        digest.update(c.encode_utf8(&mut buf).as_bytes());
+    }
+}
+
+/// What every hunk of one commit is attributed against
+struct CommitAttribution<'a> {
+    /// Lines without AI authorship data belong to the commit's git author
+    authorship_log: Option<AuthorshipLog>,
+    commit_author: String,
+    commit_email: String,
+    /// Only resolved with --author-domain
+    commit_domain: Option<String>,
+    mailmap: Option<&'a Mailmap>,
+    options: &'a StatsOptions,
+    /// `.git-ai/attributes` as it is in the work tree now, applied to every commit
+    attributes: &'a Attributes,
+}
+
+/// Attribute the lines a commit added using that commit's authorship log. Hunks are
+/// attributed as the diff streams past, so no more than one is held at a time.
+fn accumulate_commit_stats(
+    repo: &Repository,
+    commit: &Commit,
+    options: &StatsOptions,
+    mailmap: Option<&Mailmap>,
+    attributes: &Attributes,
+    report: &mut StatsReport,
+) -> Result<(), GitAiError> {
+    // Get the diff between parent and commit (root commits diff against an empty tree)
+    let tree = commit.tree()?;
+    let parent_tree = match commit.parent(0) {
+        Ok(parent) => Some(parent.tree()?),
+        Err(_) => None,
+    };
+
+    let mut diff_opts = DiffOptions::new();
+    diff_opts.context_lines(0); // No context lines
+
+    let diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), Some(&mut diff_opts))?;
+
+    let attribution = CommitAttribution {
+        authorship_log: get_reference_as_authorship_log(
+            repo,
+            &authorship_ref_name(repo, &commit.id().to_string()),
+        )
+        .ok(),
+        commit_author: commit_author_name(commit, mailmap),
+        commit_email: commit.author().email().unwrap_or("").to_string(),
+        commit_domain: options
+            .author_domain
+            .then(|| commit_author_domain(commit, mailmap)),
+        mailmap,
+        options,
+        attributes,
+    };
+
+    // The hunk being read: (file, old_start, new_start) and its lines so far
+    let mut current: Option<((String, u32, u32), HunkLines)> = None;
+    let mut touched: HashSet<String> = HashSet::new();
+
+    diff.foreach(
+        &mut |_delta, _| true,
+        None,
+        None,
+        Some(&mut |delta, hunk, line| {
+            let file_path = delta
+                .new_file()
+                .path()
+                .or_else(|| delta.old_file().path())
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let hunk_key = (
+                file_path,
+                hunk.as_ref().map(|h| h.old_start()).unwrap_or(0),
+                hunk.as_ref().map(|h| h.new_start()).unwrap_or(0),
+            );
+            if current.as_ref().map(|(key, _)| key) != Some(&hunk_key) {
+                if let Some(((file_path, _, _), lines)) = current.take() {
+                    attribute_hunk(&attribution, &file_path, &lines, report);
+                }
+                touched.insert(hunk_key.0.clone());
+                current = Some((hunk_key, HunkLines::default()));
+            }
+            let (_, lines) = current.as_mut().expect("hunk was just started");
+            match line.origin() {
+                '+' => lines.add(line.new_lineno().unwrap_or(0), line.content()),
+                '-' => lines.delete(line.content()),
+                _ => {}
+            }
+            true
+        }),
+    )?;
+    if let Some(((file_path, _, _), lines)) = current.take() {
+        attribute_hunk(&attribution, &file_path, &lines, report);
+    }
+    for file_path in touched {
+        if !attributes.policy(&file_path).ignore {
+            report.file_stats.entry(file_path).or_default().commits += 1;
+        }
+    }
+
+    Ok(())
+}
+
+fn attribute_hunk(
+    attribution: &CommitAttribution,
+    file_path: &str,
+    hunk: &HunkLines,
+    report: &mut StatsReport,
+) {
+    let policy = attribution.attributes.policy(file_path);
+    if policy.ignore {
+        return;
+    }
+    // A path's confidence floor applies on top of --min-confidence
+    let min_confidence = attribution
+        .options
+        .min_confidence
+        .max(policy.min_confidence);
+    let file_authorship = attribution
+        .authorship_log
+        .as_ref()
+        .and_then(|log| log.files.get(file_path));
+    let stats = report.file_stats.entry(file_path.to_string()).or_default();
+    let commit_author = &attribution.commit_author;
+    let keep = |author: &str| {
+        attribution
+            .options
+            .author_regex
+            .as_ref()
+            .is_none_or(|regex| regex.is_match(author))
+    };
+
+    let reformat = hunk.is_reformat();
+    for line_num in &hunk.added {
+        let entry = file_authorship
+            .and_then(|f| f.get_author_entry(*line_num))
+            // Guesses below --min-confidence fall back to the commit author
+            .filter(|e| match (&e.agent_metadata, min_confidence) {
+                (Some(metadata), Some(min_confidence)) => metadata.confidence >= min_confidence,
+                _ => true,
+            });
+        let mut author = match entry {
+            // Human checkpoints are canonicalized like the commit author; agents aren't
+            Some(e) if e.agent_metadata.is_none() => {
+                mailmap_name(attribution.mailmap, &e.author, &attribution.commit_email)
+            }
+            Some(e) => e.author.clone(),
+            None => commit_author.clone(),
+        };
+        let generated = match policy.force {
+            Some(force) => force == ForcedAttribution::Generated,
+            None => entry.is_some_and(|e| e.generated),
+        };
+        if generated {
+            if keep(&author) {
+                stats.generated_additions += 1;
+                report.generated_additions += 1;
+            }
+            continue;
+        }
+        let mut is_ai = entry.is_some_and(|e| e.agent_metadata.is_some());
+        // force-human hands an agent's lines to the commit author, as the floor above does
+        match policy.force {
+            Some(ForcedAttribution::Human) if is_ai => {
+                author = commit_author.clone();
+                is_ai = false;
+            }
+            Some(ForcedAttribution::Ai) => is_ai = true,
+            _ => {}
+        }
+
+        if reformat {
+            match attribution.options.count_reformat_as {
+                ReformatMode::Exclude => {
+                    if keep(&author) {
+                        report.reformat_additions += 1;
+                    }
+                    continue;
+                }
+                ReformatMode::Human => {
+                    author = commit_author.clone();
+                    is_ai = false;
+                }
+                ReformatMode::Ai => is_ai = true,
+                ReformatMode::Attributed => {}
+            }
+        }
+        if !keep(&author) {
+            continue;
+        }
+        if reformat {
+            report.reformat_additions += 1;
+        }
+
+        *stats.additions.entry(author.clone()).or_insert(0) += 1;
+        *report.total_additions_by_author.entry(author).or_insert(0) += 1;
+        stats.total_additions += 1;
+        // AI lines count toward the domain of whoever committed the agent's work
+        let domain = attribution
+            .commit_domain
+            .as_ref()
+            .map(|domain| stats.domains.entry(domain.clone()).or_default());
+        if is_ai {
+            stats.ai_additions += 1;
+            report.ai_additions += 1;
+            if let Some(domain) = domain {
+                domain.ai_additions += 1;
+            }
+        } else {
+            report.human_additions += 1;
+            if let Some(domain) = domain {
+                domain.human_additions += 1;
+            }
+        }
+    }
+
+    // Deleted lines have no author of their own, so they follow the commit's
+    if keep(commit_author) {
+        stats.deletions += hunk.deleted;
+        report.total_deletions += hunk.deleted;
+    }
 }
 
-#[allow(dead_code)]
-pub fn run(repo: &Repository, sha: &str) -> Result<(), GitAiError> {
-    // Find the commit
-    let commit = repo.find_commit(repo.revparse_single(sha)?.id())?;
+/// Attribution of uncommitted changes, folded from the working log of the current base commit
+pub fn working_tree_stats(repo: &Repository) -> Result<StatsReport, GitAiError> {
+    let base_commit = resolve_base_commit(repo)?;
+    // No working log yet means nothing has been checkpointed
+    let working_log =
+        get_reference_as_working_log(repo, &format!("ai-working-log/{}", base_commit))
+            .unwrap_or_default();
+    let authorship_log = AuthorshipLog::from_working_log(&working_log);
 
-    // Get the parent commit (for diff)
-    let parent = if let Ok(parent) = commit.parent(0) {
-        parent
-    } else {
-        return Err(GitAiError::Generic("Commit has no parent".to_string()));
-    };
+    let mut report = StatsReport::default();
+    for (file_path, file_authorship) in &authorship_log.files {
+        let stats = report.file_stats.entry(file_path.clone()).or_default();
+        for entry in &file_authorship.authors {
+            let count: u32 = entry.lines.iter().map(LineRange::len).sum();
+            if count == 0 {
+                continue;
+            }
+            if entry.generated {
+                stats.generated_additions += count;
+                report.generated_additions += count;
+                continue;
+            }
+            *stats.additions.entry(entry.author.clone()).or_insert(0) += count;
+            *report
+                .total_additions_by_author
+                .entry(entry.author.clone())
+                .or_insert(0) += count;
+            stats.total_additions += count;
+            if entry.agent_metadata.is_some() {
+                stats.ai_additions += count;
+                report.ai_additions += count;
+            } else {
+                report.human_additions += count;
+            }
+        }
+    }
+    Ok(report)
+}
 
-    // Get the diff between parent and commit
-    let tree = commit.tree()?;
-    let parent_tree = parent.tree()?;
+/// Redraw the working-tree bar chart until interrupted (Ctrl-C)
+fn watch(repo: &Repository, options: &StatsOptions) -> Result<(), GitAiError> {
+    let interval = Duration::from_secs(options.watch_interval.unwrap_or(2).max(1));
+    loop {
+        let report = working_tree_stats(repo)?;
+        // Clear the screen and move the cursor home before redrawing
+        print!("\x1b[2J\x1b[H");
+        println!("git-ai stats --watch (Ctrl-C to exit)\n");
+        print_bar_chart(&report, &options.percent);
+        std::io::stdout().flush()?;
+        std::thread::sleep(interval);
+    }
+}
 
-    let mut diff_opts = DiffOptions::new();
-    diff_opts.context_lines(0); // No context lines
+fn print_bar_chart(report: &StatsReport, percent: &PercentFormat) {
+    const WIDTH: u32 = 40;
+    let total = report.total_additions();
+    println!("Uncommitted lines: {}", total);
+    if total == 0 {
+        println!("No checkpointed changes yet.");
+        return;
+    }
 
-    let diff = repo.diff_tree_to_tree(Some(&parent_tree), Some(&tree), Some(&mut diff_opts))?;
+    let human_width =
+        ((report.human_additions as f64 / total as f64) * WIDTH as f64).round() as u32;
+    let ai_width = WIDTH - human_width;
+    println!(
+        "Human {}{} AI",
+        "\u{2588}".repeat(human_width as usize),
+        "\u{2591}".repeat(ai_width as usize)
+    );
+    let split = percent.format_split(&[
+        percentage(report.human_additions, total),
+        percentage(report.ai_additions, total),
+    ]);
+    println!(
+        "      {:<width$}{:>5}",
+        split[0],
+        split[1],
+        width = (WIDTH - 5) as usize
+    );
+}
 
-    // Process the diff and collect statistics
-    let mut file_stats = HashMap::new();
-    let mut total_additions_by_author = HashMap::new();
-    let mut total_deletions = 0;
+pub(crate) fn percentage(part: u32, total: u32) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        (part as f64 / total as f64) * 100.0
+    }
+}
 
-    // For each file, collect added and deleted lines
-    let mut file_added_lines: HashMap<String, Vec<u32>> = HashMap::new();
-    let mut file_deleted_counts: HashMap<String, u32> = HashMap::new();
+/// Remove files below the threshold from the report entirely, totals included
+fn exclude_small_files(report: &mut StatsReport, min_lines: u32) {
+    let small: Vec<String> = report
+        .file_stats
+        .iter()
+        .filter(|(_, stats)| stats.total_additions < min_lines)
+        .map(|(file_path, _)| file_path.clone())
+        .collect();
 
-    diff.foreach(
-        &mut |_delta, _| true,
-        None,
-        None,
-        Some(&mut |delta, _hunk, line| {
-            let file_path = delta
-                .new_file()
-                .path()
-                .unwrap()
-                .to_string_lossy()
-                .to_string();
-            match line.origin() {
-                '+' => {
-                    let line_num = line.new_lineno().unwrap_or(0) as u32;
-                    file_added_lines
-                        .entry(file_path)
-                        .or_default()
-                        .push(line_num);
-                }
-                '-' => {
-                    *file_deleted_counts.entry(file_path).or_default() += 1;
-                    total_deletions += 1;
+    for file_path in small {
+        let Some(stats) = report.file_stats.remove(&file_path) else {
+            continue;
+        };
+        for (author, count) in &stats.additions {
+            if let Some(total) = report.total_additions_by_author.get_mut(author) {
+                *total -= count;
+                if *total == 0 {
+                    report.total_additions_by_author.remove(author);
                 }
-                _ => {}
             }
-            true
-        }),
+        }
+        report.total_deletions -= stats.deletions;
+        report.ai_additions -= stats.ai_additions;
+        report.human_additions -= stats.total_additions - stats.ai_additions;
+    }
+}
+
+fn write_stats(
+    out: &mut dyn Write,
+    report: &StatsReport,
+    options: &StatsOptions,
+) -> std::io::Result<()> {
+    let min_lines = options.min_lines.unwrap_or(0);
+    writeln!(out, "{}", "=".repeat(50))?;
+
+    // Print per-file statistics, skipping files under --min-lines
+    let mut omitted = 0;
+    for (file_path, stats) in report.file_stats.iter() {
+        if stats.total_additions < min_lines {
+            omitted += 1;
+            continue;
+        }
+        write_file_stats(out, file_path, stats)?;
+    }
+    if omitted > 0 {
+        writeln!(
+            out,
+            "\n({} file(s) with fewer than {} added lines not shown)",
+            omitted, min_lines
+        )?;
+    }
+
+    // Print totals
+    writeln!(out, "\nTotal Additions:")?;
+    for (author, count) in report.total_additions_by_author.iter() {
+        writeln!(out, "    {} +{}", author, count)?;
+    }
+
+    writeln!(out, "\nTotal Deletions: -{}", report.total_deletions)?;
+
+    let total = report.total_additions();
+    let percent = &options.percent;
+    let split = percent.format_split(&[
+        percentage(report.human_additions, total),
+        percentage(report.ai_additions, total),
+    ]);
+    writeln!(
+        out,
+        "\nHuman: +{} ({})  AI: +{} ({})",
+        report.human_additions, split[0], report.ai_additions, split[1]
     )?;
+    if options.normalize_by == Some(Normalization::DiffSize) {
+        writeln!(
+            out,
+            "Normalized by diff size: AI {} of {} changed lines (added + deleted)",
+            percent.format(diff_size_ai_percentage(report)),
+            report.total_additions() + report.total_deletions
+        )?;
+    }
+    if options.weighted_by == Some(Weighting::Churn) {
+        let ai = churn_weighted_ai_percentage(report);
+        let split = percent.format_split(&[100.0 - ai, ai]);
+        writeln!(
+            out,
+            "Churn-weighted: Human {}  AI {} (lines per file divided by commits touching it)",
+            split[0], split[1]
+        )?;
+    }
+    if report.reformat_additions > 0 {
+        writeln!(out, "Reformat: +{}", report.reformat_additions)?;
+    }
+    if report.generated_additions > 0 {
+        writeln!(
+            out,
+            "Generated: +{} (neither human nor AI)",
+            report.generated_additions
+        )?;
+    }
+    if report.merges_skipped > 0 {
+        writeln!(
+            out,
+            "Skipped {} merge commit(s) (use --include-merges to count them)",
+            report.merges_skipped
+        )?;
+    }
+    Ok(())
+}
 
-    // For each file, use blame overlay logic to attribute added lines
-    for (file_path, added_lines) in file_added_lines.iter() {
-        // Get blame hunks for the file (for all lines, since we may not know the exact range)
-        let total_lines = {
-            // Try to get the file from the new tree
-            if let Ok(entry) = tree.get_path(std::path::Path::new(file_path)) {
-                if let Ok(blob) = repo.find_blob(entry.id()) {
-                    let content = std::str::from_utf8(blob.content()).unwrap_or("");
-                    content.lines().count() as u32
-                } else {
-                    0
-                }
-            } else {
-                0
+/// Totals per email domain across the report's files, so files dropped by --exclude-small
+/// drop out here too
+pub fn domain_breakdown(report: &StatsReport) -> BTreeMap<String, DomainStats> {
+    let mut domains: BTreeMap<String, DomainStats> = BTreeMap::new();
+    for stats in report.file_stats.values() {
+        for (domain, counts) in &stats.domains {
+            let total = domains.entry(domain.clone()).or_default();
+            total.human_additions += counts.human_additions;
+            total.ai_additions += counts.ai_additions;
+        }
+    }
+    domains
+}
+
+fn write_domain_stats(
+    out: &mut dyn Write,
+    report: &StatsReport,
+    percent: &PercentFormat,
+) -> std::io::Result<()> {
+    let domains = domain_breakdown(report);
+    let width = domains
+        .keys()
+        .map(|domain| domain.chars().count())
+        .chain(std::iter::once("Domain".len()))
+        .max()
+        .unwrap_or(0);
+    writeln!(
+        out,
+        "{:<width$}  {:>8}  {:>8}  {:>5}",
+        "Domain",
+        "Human",
+        "AI",
+        "AI %",
+        width = width
+    )?;
+    for (domain, counts) in &domains {
+        writeln!(
+            out,
+            "{:<width$}  {:>8}  {:>8}  {:>5}",
+            domain,
+            format!("+{}", counts.human_additions),
+            format!("+{}", counts.ai_additions),
+            percent.format(percentage(
+                counts.ai_additions,
+                counts.human_additions + counts.ai_additions
+            )),
+            width = width
+        )?;
+    }
+    if report.merges_skipped > 0 {
+        writeln!(
+            out,
+            "Skipped {} merge commit(s) (use --include-merges to count them)",
+            report.merges_skipped
+        )?;
+    }
+    Ok(())
+}
+
+fn write_per_commit(
+    out: &mut dyn Write,
+    commits: &[CommitStats],
+    format: StatsFormat,
+    percent: &PercentFormat,
+    style: &TableStyle,
+) -> Result<(), GitAiError> {
+    match format {
+        StatsFormat::Table => {
+            let rows: Vec<Vec<String>> = commits
+                .iter()
+                .map(|commit| {
+                    vec![
+                        commit.sha[..7].to_string(),
+                        commit.human_additions.to_string(),
+                        commit.ai_additions.to_string(),
+                        percent.format(commit.ai_percentage()),
+                        commit.author.clone(),
+                        commit.summary.clone(),
+                    ]
+                })
+                .collect();
+            write!(
+                out,
+                "{}",
+                render_table(
+                    &["Commit", "Human", "AI", "AI %", "Author", "Summary"],
+                    &[rows],
+                    style
+                )
+            )?;
+        }
+        StatsFormat::Text => {
+            for commit in commits {
+                writeln!(
+                    out,
+                    "{} {:>5} AI  +{} human +{} ai  {}  {}",
+                    &commit.sha[..7],
+                    percent.format(commit.ai_percentage()),
+                    commit.human_additions,
+                    commit.ai_additions,
+                    commit.author,
+                    commit.summary
+                )?;
             }
-        };
-        if total_lines == 0 {
-            continue;
         }
-        let blame_hunks = match get_git_blame_hunks(
-            repo,
-            file_path,
-            1,
-            total_lines,
-            &GitAiBlameOptions::default(),
-        ) {
-            Ok(hunks) => hunks,
-            Err(_) => continue,
+        StatsFormat::Json => {
+            let rows: Vec<serde_json::Value> = commits
+                .iter()
+                .map(|commit| {
+                    serde_json::json!({
+                        "sha": commit.sha,
+                        "summary": commit.summary,
+                        "author": commit.author,
+                        "human_additions": commit.human_additions,
+                        "ai_additions": commit.ai_additions,
+                        "ai_percentage": commit.ai_percentage(),
+                    })
+                })
+                .collect();
+            writeln!(out, "{}", serde_json::to_string_pretty(&rows)?)?;
+        }
+        StatsFormat::Csv => {
+            writeln!(
+                out,
+                "sha,summary,author,human_additions,ai_additions,ai_percentage"
+            )?;
+            for commit in commits {
+                writeln!(
+                    out,
+                    "{},{},{},{},{},{:.1}",
+                    commit.sha,
+                    csv_field(&commit.summary),
+                    csv_field(&commit.author),
+                    commit.human_additions,
+                    commit.ai_additions,
+                    commit.ai_percentage()
+                )?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Quote a CSV field when it contains a separator, quote or newline
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// `--by-author`: each author's added lines and share of the total, most lines first
+pub fn by_author(report: &StatsReport, percent: &PercentFormat) -> String {
+    let mut authors: Vec<(&String, &u32)> = report.total_additions_by_author.iter().collect();
+    authors.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+    let total: u32 = authors.iter().map(|(_, count)| **count).sum();
+    let width = authors
+        .iter()
+        .map(|(author, _)| author.chars().count())
+        .chain(std::iter::once("Author".len()))
+        .max()
+        .unwrap_or(0);
+
+    let mut output = format!(
+        "{:<width$}  {:>8}  {:>5}\n",
+        "Author",
+        "Lines",
+        "Share",
+        width = width
+    );
+    let shares = percent.format_split(
+        &authors
+            .iter()
+            .map(|(_, count)| percentage(**count, total))
+            .collect::<Vec<_>>(),
+    );
+    for ((author, count), share) in authors.into_iter().zip(shares) {
+        output.push_str(&format!(
+            "{:<width$}  {:>8}  {:>5}\n",
+            author,
+            format!("+{}", count),
+            share,
+            width = width
+        ));
+    }
+    output
+}
+
+/// By-file and by-author tables, columns padded so the raw Markdown reads aligned too.
+/// Files under `min_lines` are left out of the file table, as in the text output.
+pub fn markdown_table(report: &StatsReport, min_lines: u32, percent: &PercentFormat) -> String {
+    let (mut file_rows, mut author_rows) = breakdown_rows(report, min_lines, percent);
+    let total_row = file_rows.len() - 1;
+    for (i, row) in file_rows.iter_mut().enumerate() {
+        row[0] = if i == total_row {
+            "**Total**".to_string()
+        } else {
+            format!("`{}`", row[0].replace('|', "\\|"))
         };
-        let line_authors = match overlay_ai_authorship(repo, &blame_hunks, file_path) {
-            Ok(authors) => authors,
-            Err(_) => continue,
+    }
+    let total_row = author_rows.len() - 1;
+    for (i, row) in author_rows.iter_mut().enumerate() {
+        row[0] = if i == total_row {
+            "**Total**".to_string()
+        } else {
+            row[0].replace('|', "\\|")
         };
-        let stats = file_stats.entry(file_path.clone()).or_insert(FileStats {
-            additions: HashMap::new(),
-            deletions: *file_deleted_counts.get(file_path).unwrap_or(&0),
-            total_additions: 0,
-        });
-        for &line_num in added_lines {
-            let author = line_authors
-                .get(&line_num)
-                .cloned()
-                .unwrap_or("unknown".to_string());
-            *stats.additions.entry(author.clone()).or_insert(0) += 1;
-            *total_additions_by_author.entry(author).or_insert(0) += 1;
-            stats.total_additions += 1;
-        }
-    }
-    // For files with only deletions
-    for (file_path, &del_count) in file_deleted_counts.iter() {
-        file_stats.entry(file_path.clone()).or_insert(FileStats {
-            additions: HashMap::new(),
-            deletions: del_count,
-            total_additions: 0,
-        });
     }
 
-    // Print the statistics
-    print_stats(&file_stats, &total_additions_by_author, total_deletions);
+    let mut output = render_markdown_table(&FILE_HEADERS, &file_rows);
+    output.push('\n');
+    output.push_str(&render_markdown_table(&AUTHOR_HEADERS, &author_rows));
+    output
+}
+
+const FILE_HEADERS: [&str; 5] = ["File", "Human", "AI", "AI %", "Deleted"];
+const AUTHOR_HEADERS: [&str; 3] = ["Author", "Lines", "Share"];
 
-    Ok(())
+/// The by-file rows (files under `min_lines` left out) and the by-author rows, most lines
+/// first, each ending in a "Total" row
+fn breakdown_rows(
+    report: &StatsReport,
+    min_lines: u32,
+    percent: &PercentFormat,
+) -> (Vec<Vec<String>>, Vec<Vec<String>>) {
+    let mut file_rows: Vec<Vec<String>> = report
+        .file_stats
+        .iter()
+        .filter(|(_, stats)| stats.total_additions >= min_lines)
+        .map(|(file_path, stats)| {
+            let human = stats.total_additions - stats.ai_additions;
+            vec![
+                file_path.clone(),
+                human.to_string(),
+                stats.ai_additions.to_string(),
+                percent.format(percentage(stats.ai_additions, stats.total_additions)),
+                stats.deletions.to_string(),
+            ]
+        })
+        .collect();
+    let total = report.total_additions();
+    file_rows.push(vec![
+        "Total".to_string(),
+        report.human_additions.to_string(),
+        report.ai_additions.to_string(),
+        percent.format(percentage(report.ai_additions, total)),
+        report.total_deletions.to_string(),
+    ]);
+
+    let mut authors: Vec<(&String, &u32)> = report.total_additions_by_author.iter().collect();
+    authors.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+    let author_total: u32 = authors.iter().map(|(_, count)| **count).sum();
+    let shares = percent.format_split(
+        &authors
+            .iter()
+            .map(|(_, count)| percentage(**count, author_total))
+            .collect::<Vec<_>>(),
+    );
+    let mut author_rows: Vec<Vec<String>> = authors
+        .into_iter()
+        .zip(shares)
+        .map(|((author, count), share)| vec![author.clone(), count.to_string(), share])
+        .collect();
+    author_rows.push(vec![
+        "Total".to_string(),
+        author_total.to_string(),
+        percent.format(100.0),
+    ]);
+    (file_rows, author_rows)
 }
 
-#[allow(dead_code)]
-fn print_stats(
-    file_stats: &HashMap<String, FileStats>,
-    total_additions_by_author: &HashMap<String, u32>,
-    total_deletions: u32,
-) {
-    println!("{}", "=".repeat(50));
+/// How `--format table` is laid out: the columns it must fit in, and the theme that colors
+/// its Human and AI columns (none when colors are off or the output isn't a terminal)
+pub struct TableStyle<'a> {
+    pub width: usize,
+    pub theme: Option<&'a ColorTheme>,
+}
 
-    // Print per-file statistics
-    for (file_path, stats) in file_stats.iter() {
-        print_file_stats(file_path, stats);
+fn table_style(options: &StatsOptions) -> TableStyle<'static> {
+    let theme = ColorTheme::load();
+    let colored = !options.no_color
+        && theme.enabled
+        && options.output_file.is_none()
+        && std::io::stdout().is_terminal();
+    TableStyle {
+        width: terminal_width(),
+        theme: colored.then_some(theme),
     }
+}
 
-    // Print totals
-    println!("\nTotal Additions:");
-    for (author, count) in total_additions_by_author.iter() {
-        println!("    {} +{}", author, count);
+/// The by-file and by-author breakdown as bordered tables, totals set off below a rule
+pub fn stats_table(
+    report: &StatsReport,
+    min_lines: u32,
+    percent: &PercentFormat,
+    style: &TableStyle,
+) -> String {
+    let (mut file_rows, mut author_rows) = breakdown_rows(report, min_lines, percent);
+    let file_total = file_rows.split_off(file_rows.len() - 1);
+    let author_total = author_rows.split_off(author_rows.len() - 1);
+    let mut output = render_table(&FILE_HEADERS, &[file_rows, file_total], style);
+    output.push('\n');
+    output.push_str(&render_table(
+        &AUTHOR_HEADERS,
+        &[author_rows, author_total],
+        style,
+    ));
+    output
+}
+
+/// Narrowest a text column is cut to when the table is too wide for the terminal
+const MIN_TABLE_COLUMN: usize = 8;
+
+/// A bordered table with each column as wide as its content. Columns of counts and
+/// percentages are right-aligned. When the table is wider than `style.width`, the widest
+/// text column is cut (with "...") to make it fit. Sections are separated by a rule.
+fn render_table(headers: &[&str], sections: &[Vec<Vec<String>>], style: &TableStyle) -> String {
+    let rows: Vec<&Vec<String>> = sections.iter().flatten().collect();
+    let numeric: Vec<bool> = (0..headers.len())
+        .map(|column| {
+            !rows.is_empty()
+                && rows.iter().all(|row| {
+                    row[column]
+                        .chars()
+                        .all(|c| c.is_ascii_digit() || ".%+-".contains(c))
+                })
+        })
+        .collect();
+    let mut widths: Vec<usize> = headers
+        .iter()
+        .enumerate()
+        .map(|(column, header)| {
+            rows.iter()
+                .map(|row| row[column].chars().count())
+                .chain(std::iter::once(header.chars().count()))
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    // Each column takes its width plus a space either side and a border
+    let table_width = widths.iter().map(|width| width + 3).sum::<usize>() + 1;
+    if table_width > style.width
+        && let Some(column) = (0..headers.len())
+            .filter(|column| !numeric[*column])
+            .max_by_key(|column| widths[*column])
+    {
+        let floor = MIN_TABLE_COLUMN.max(headers[column].chars().count());
+        widths[column] = widths[column]
+            .saturating_sub(table_width - style.width)
+            .max(floor)
+            .min(widths[column]);
+    }
+
+    let role = |column: usize| match headers[column] {
+        "AI" | "AI %" => Some(ColorRole::AiLine),
+        "Human" => Some(ColorRole::HumanLine),
+        _ => None,
+    };
+    let border = format!(
+        "+{}+\n",
+        widths
+            .iter()
+            .map(|width| "-".repeat(width + 2))
+            .collect::<Vec<_>>()
+            .join("+")
+    );
+    let render_row = |cells: &[&str], paint: bool| {
+        let cells: Vec<String> = cells
+            .iter()
+            .enumerate()
+            .map(|(column, cell)| {
+                let width = widths[column];
+                let cell = if cell.chars().count() > width {
+                    let mut cut: String = cell.chars().take(width.saturating_sub(3)).collect();
+                    cut.push_str("...");
+                    cut
+                } else {
+                    cell.to_string()
+                };
+                let padded = if numeric[column] {
+                    format!("{:>width$}", cell, width = width)
+                } else {
+                    format!("{:<width$}", cell, width = width)
+                };
+                match (style.theme, role(column)) {
+                    (Some(theme), Some(role)) if paint => theme.paint(role, &padded),
+                    _ => padded,
+                }
+            })
+            .collect();
+        format!("| {} |\n", cells.join(" | "))
+    };
+
+    let mut output = border.clone();
+    output.push_str(&render_row(headers, false));
+    for section in sections {
+        output.push_str(&border);
+        for row in section {
+            let cells: Vec<&str> = row.iter().map(String::as_str).collect();
+            output.push_str(&render_row(&cells, true));
+        }
+    }
+    output.push_str(&border);
+    output
+}
+
+/// Files with at least one AI-authored line, for reviewer-assignment scripts. Files under
+/// `min_lines` are left out, as in the text output.
+pub fn ai_files(report: &StatsReport, min_lines: u32, with_counts: bool) -> String {
+    report
+        .file_stats
+        .iter()
+        .filter(|(_, stats)| stats.ai_additions > 0 && stats.total_additions >= min_lines)
+        .map(|(file_path, stats)| {
+            if with_counts {
+                format!("{}\t{}\n", file_path, stats.ai_additions)
+            } else {
+                format!("{}\n", file_path)
+            }
+        })
+        .collect()
+}
+
+/// The `n` files with the most AI-added lines and the `n` with the most human-added lines,
+/// as two short lists. Files under `min_lines` are left out, as in the text output.
+pub fn top_files(
+    report: &StatsReport,
+    n: usize,
+    min_lines: u32,
+    percent: &PercentFormat,
+) -> String {
+    let files: Vec<(&String, &FileStats)> = report
+        .file_stats
+        .iter()
+        .filter(|(_, stats)| stats.total_additions >= min_lines)
+        .collect();
+
+    let mut output = String::new();
+    for (title, label, is_ai) in [
+        ("Most AI-authored files", "AI", true),
+        ("Most human-authored files", "human", false),
+    ] {
+        let lines = |stats: &FileStats| {
+            if is_ai {
+                stats.ai_additions
+            } else {
+                stats.total_additions - stats.ai_additions
+            }
+        };
+        let mut ranked: Vec<&(&String, &FileStats)> =
+            files.iter().filter(|(_, stats)| lines(stats) > 0).collect();
+        // Most lines first; the higher share breaks ties, then the path
+        ranked.sort_by(|(a_path, a), (b_path, b)| {
+            lines(b).cmp(&lines(a)).then_with(|| {
+                let share = |stats: &FileStats| percentage(lines(stats), stats.total_additions);
+                share(b).total_cmp(&share(a)).then(a_path.cmp(b_path))
+            })
+        });
+
+        output.push_str(&format!("{}:\n", title));
+        if ranked.is_empty() {
+            output.push_str("  (none)\n");
+        }
+        for (file_path, stats) in ranked.into_iter().take(n) {
+            output.push_str(&format!(
+                "  {}  +{} {} ({})\n",
+                file_path,
+                lines(stats),
+                label,
+                percent.format(percentage(lines(stats), stats.total_additions))
+            ));
+        }
     }
+    output
+}
+
+/// `repo` and `branch` labels for metrics: the work tree's directory name and the checked
+/// out branch (`HEAD` when detached)
+pub fn metric_labels(repo: &Repository) -> (String, String) {
+    let repo_name = repo
+        .workdir()
+        .unwrap_or_else(|| repo.path())
+        .file_name()
+        .map(|name| name.to_string_lossy().trim_end_matches(".git").to_string())
+        .unwrap_or_default();
+    let branch = repo
+        .head()
+        .ok()
+        .filter(|head| head.is_branch())
+        .and_then(|head| head.shorthand().map(str::to_string))
+        .unwrap_or_else(|| "HEAD".to_string());
+    (repo_name, branch)
+}
+
+/// The report as Prometheus gauges, e.g. `git_ai_lines{repo="x",branch="main",type="ai"} 12`,
+/// for a cron job to push to a gateway
+pub fn prometheus_metrics(report: &StatsReport, repo_name: &str, branch: &str) -> String {
+    let labels = format!(
+        "repo=\"{}\",branch=\"{}\"",
+        escape_label(repo_name),
+        escape_label(branch)
+    );
+    let total = report.total_additions();
+    let mut output = String::new();
+    let mut gauge = |name: &str, help: &str, samples: &[(&str, String)]| {
+        output.push_str(&format!(
+            "# HELP {} {}\n# TYPE {} gauge\n",
+            name, help, name
+        ));
+        for (extra, value) in samples {
+            output.push_str(&format!("{}{{{}{}}} {}\n", name, labels, extra, value));
+        }
+    };
+    gauge(
+        "git_ai_lines",
+        "Added lines by authorship.",
+        &[
+            (",type=\"ai\"", report.ai_additions.to_string()),
+            (",type=\"human\"", report.human_additions.to_string()),
+        ],
+    );
+    gauge(
+        "git_ai_deleted_lines",
+        "Deleted lines.",
+        &[("", report.total_deletions.to_string())],
+    );
+    gauge(
+        "git_ai_ai_ratio",
+        "Share of added lines authored by AI, from 0 to 1.",
+        &[(
+            "",
+            format!("{}", percentage(report.ai_additions, total) / 100.0),
+        )],
+    );
+    gauge(
+        "git_ai_files",
+        "Files with changes.",
+        &[("", report.file_stats.len().to_string())],
+    );
+    gauge(
+        "git_ai_commits",
+        "Commits counted.",
+        &[("", report.commits.to_string())],
+    );
+    output
+}
 
-    println!("\nTotal Deletions: -{}", total_deletions);
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
 }
 
-#[allow(dead_code)]
-fn print_file_stats(file_path: &str, stats: &FileStats) {
+/// First column left-aligned, the rest (counts and percentages) right-aligned
+fn render_markdown_table(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let widths: Vec<usize> = headers
+        .iter()
+        .enumerate()
+        .map(|(column, header)| {
+            rows.iter()
+                .map(|row| row[column].chars().count())
+                .chain(std::iter::once(header.chars().count()))
+                .max()
+                .unwrap_or(0)
+                .max(3)
+        })
+        .collect();
+
+    let render_row = |cells: Vec<&str>| {
+        let cells: Vec<String> = cells
+            .iter()
+            .enumerate()
+            .map(|(column, cell)| {
+                if column == 0 {
+                    format!("{:<width$}", cell, width = widths[column])
+                } else {
+                    format!("{:>width$}", cell, width = widths[column])
+                }
+            })
+            .collect();
+        format!("| {} |\n", cells.join(" | "))
+    };
+
+    let mut output = render_row(headers.to_vec());
+    let separator: Vec<String> = widths
+        .iter()
+        .enumerate()
+        .map(|(column, width)| {
+            if column == 0 {
+                format!(":{}", "-".repeat(width - 1))
+            } else {
+                format!("{}:", "-".repeat(width - 1))
+            }
+        })
+        .collect();
+    output.push_str(&format!("| {} |\n", separator.join(" | ")));
+    for row in rows {
+        output.push_str(&render_row(row.iter().map(String::as_str).collect()));
+    }
+    output
+}
+
+fn write_file_stats(
+    out: &mut dyn Write,
+    file_path: &str,
+    stats: &FileStats,
+) -> std::io::Result<()> {
     // Calculate total changes for the file
     let total_additions = stats.total_additions;
     let total_deletions = stats.deletions;
 
     // Print file header with total changes
-    println!(
+    writeln!(
+        out,
         "\n{} (+{} -{})",
         file_path, total_additions, total_deletions
-    );
+    )?;
 
     // Print additions by author
     for (author, count) in stats.additions.iter() {
-        println!("   {} (+{})", author, count);
+        writeln!(out, "   {} (+{})", author, count)?;
+    }
+    if stats.generated_additions > 0 {
+        writeln!(out, "   generated (+{})", stats.generated_additions)?;
     }
 
     // Print deletions (no author attribution)
     if stats.deletions > 0 {
-        println!("   -{}", stats.deletions);
+        writeln!(out, "   -{}", stats.deletions)?;
+    }
+    Ok(())
+}
+
+pub fn parse_stats_args(args: &[String]) -> Result<(Option<String>, StatsOptions), GitAiError> {
+    let mut options = StatsOptions::default();
+    let mut revspec = None;
+    let mut i = 0;
+
+    while i < args.len() {
+        match args[i].as_str() {
+            "--me" | "--author-me" => {
+                options.author_me = true;
+                i += 1;
+            }
+            "--since" | "--after" | "--until" | "--before" => {
+                let flag = args[i].as_str();
+                let Some(value) = args.get(i + 1) else {
+                    return Err(GitAiError::Generic(format!("{} requires a time", flag)));
+                };
+                let Some(time) = parse_time(value) else {
+                    return Err(GitAiError::Generic(format!(
+                        "Invalid {} {}: expected a date like 2025-06-01, an RFC 3339 time, @<unix seconds> or <n> <unit> ago",
+                        flag, value
+                    )));
+                };
+                if matches!(flag, "--since" | "--after") {
+                    options.since = Some(time);
+                } else {
+                    options.until = Some(time);
+                }
+                i += 2;
+            }
+            "--author-regex" => {
+                let Some(pattern) = args.get(i + 1) else {
+                    return Err(GitAiError::Generic(
+                        "--author-regex requires a pattern".to_string(),
+                    ));
+                };
+                options.author_regex = Some(Regex::new(pattern).map_err(|e| {
+                    GitAiError::Generic(format!("Invalid --author-regex {}: {}", pattern, e))
+                })?);
+                i += 2;
+            }
+            "--by-author" => {
+                options.output = StatsOutput::ByAuthor;
+                i += 1;
+            }
+            "--round" => {
+                let Some(round) = args.get(i + 1).and_then(|value| Rounding::parse(value)) else {
+                    return Err(GitAiError::Generic(
+                        "--round requires floor, ceil, nearest or banker".to_string(),
+                    ));
+                };
+                options.percent.round = round;
+                i += 2;
+            }
+            "--decimals" => {
+                let Some(decimals) = args
+                    .get(i + 1)
+                    .and_then(|value| value.parse::<u8>().ok())
+                    .filter(|decimals| *decimals <= 6)
+                else {
+                    return Err(GitAiError::Generic(
+                        "--decimals requires a number from 0 to 6".to_string(),
+                    ));
+                };
+                options.percent.decimals = decimals;
+                i += 2;
+            }
+            "--sum-to-100" => {
+                options.percent.sum_to_100 = true;
+                i += 1;
+            }
+            "--min-lines" => {
+                if i + 1 >= args.len() {
+                    return Err(GitAiError::Generic(
+                        "Missing argument for --min-lines".to_string(),
+                    ));
+                }
+                match args[i + 1].parse::<u32>() {
+                    Ok(n) => options.min_lines = Some(n),
+                    Err(_) => {
+                        return Err(GitAiError::Generic(
+                            "Invalid number for --min-lines".to_string(),
+                        ));
+                    }
+                }
+                i += 2;
+            }
+            "--per-commit" => {
+                options.per_commit = true;
+                i += 1;
+            }
+            "--no-color" => {
+                options.no_color = true;
+                i += 1;
+            }
+            "--format" => {
+                options.format = match args.get(i + 1).map(String::as_str) {
+                    Some("text") => StatsFormat::Text,
+                    Some("json") => StatsFormat::Json,
+                    Some("csv") => StatsFormat::Csv,
+                    Some("table") => StatsFormat::Table,
+                    _ => {
+                        return Err(GitAiError::Generic(
+                            "--format requires text, json, csv or table".to_string(),
+                        ));
+                    }
+                };
+                i += 2;
+            }
+            "--output" => {
+                options.output = match args.get(i + 1).map(String::as_str) {
+                    Some("full") => StatsOutput::Full,
+                    Some("summary-line") => StatsOutput::SummaryLine,
+                    Some("markdown-table") => StatsOutput::MarkdownTable,
+                    Some("ai-files") => StatsOutput::AiFiles,
+                    Some("prometheus") => StatsOutput::Prometheus,
+                    Some("by-author") => StatsOutput::ByAuthor,
+                    _ => {
+                        return Err(GitAiError::Generic(
+                            "--output requires full, summary-line, markdown-table, ai-files, prometheus or by-author"
+                                .to_string(),
+                        ));
+                    }
+                };
+                i += 2;
+            }
+            "--markdown-table" => {
+                options.output = StatsOutput::MarkdownTable;
+                i += 1;
+            }
+            "--top" => {
+                match args
+                    .get(i + 1)
+                    .and_then(|value| value.parse::<usize>().ok())
+                {
+                    Some(n) if n > 0 => options.output = StatsOutput::Top(n),
+                    _ => {
+                        return Err(GitAiError::Generic(
+                            "--top requires a positive number of files".to_string(),
+                        ));
+                    }
+                }
+                i += 2;
+            }
+            "--emit-prometheus" => {
+                options.output = StatsOutput::Prometheus;
+                i += 1;
+            }
+            "--only-ai-files" => {
+                options.output = StatsOutput::AiFiles;
+                i += 1;
+            }
+            "--with-counts" => {
+                options.with_counts = true;
+                i += 1;
+            }
+            "--human-effort" => {
+                options.human_effort = true;
+                i += 1;
+            }
+            "--accept-rate" => {
+                options.accept_rate = true;
+                i += 1;
+            }
+            "--delta-from-parent" => {
+                options.delta_from_parent = true;
+                i += 1;
+            }
+            "--weighted-by" => {
+                options.weighted_by = match args.get(i + 1).map(String::as_str) {
+                    Some("churn") => Some(Weighting::Churn),
+                    _ => {
+                        return Err(GitAiError::Generic(
+                            "--weighted-by requires churn".to_string(),
+                        ));
+                    }
+                };
+                i += 2;
+            }
+            "--normalize-by" => {
+                options.normalize_by = match args.get(i + 1).map(String::as_str) {
+                    Some("diff-size") => Some(Normalization::DiffSize),
+                    _ => {
+                        return Err(GitAiError::Generic(
+                            "--normalize-by requires diff-size".to_string(),
+                        ));
+                    }
+                };
+                i += 2;
+            }
+            "--json-schema" => {
+                options.json_schema = true;
+                i += 1;
+            }
+            "--exclude-small" => {
+                options.exclude_small = true;
+                i += 1;
+            }
+            "--include-merges" => {
+                options.include_merges = true;
+                i += 1;
+            }
+            "--resolve-only" => {
+                options.resolve_only = true;
+                i += 1;
+            }
+            "--output-file" => {
+                let Some(path) = args.get(i + 1) else {
+                    return Err(GitAiError::Generic(
+                        "--output-file requires a path".to_string(),
+                    ));
+                };
+                options.output_file = Some(path.clone());
+                i += 2;
+            }
+            "--baseline-branch" => {
+                if i + 1 >= args.len() {
+                    return Err(GitAiError::Generic(
+                        "Missing argument for --baseline-branch".to_string(),
+                    ));
+                }
+                options.baseline_branch = Some(args[i + 1].clone());
+                i += 2;
+            }
+            "--min-confidence" => {
+                match args.get(i + 1).and_then(|value| Confidence::parse(value)) {
+                    Some(confidence) => options.min_confidence = Some(confidence),
+                    None => {
+                        return Err(GitAiError::Generic(
+                            "--min-confidence requires low or high".to_string(),
+                        ));
+                    }
+                }
+                i += 2;
+            }
+            "--author-domain" => {
+                options.author_domain = true;
+                i += 1;
+            }
+            "--no-mailmap" => {
+                options.no_mailmap = true;
+                i += 1;
+            }
+            "--cache" => {
+                options.cache = true;
+                i += 1;
+            }
+            "--no-cache" => {
+                options.no_cache = true;
+                i += 1;
+            }
+            "--annotate-commit" => {
+                options.annotate_commit = true;
+                i += 1;
+            }
+            "--fetch" => {
+                options.fetch = true;
+                i += 1;
+            }
+            "--no-fetch" => {
+                options.no_fetch = true;
+                i += 1;
+            }
+            "--zero-when-empty" => {
+                options.zero_when_empty = true;
+                i += 1;
+            }
+            "--watch" => {
+                options.watch = true;
+                i += 1;
+            }
+            "--count-reformat-as" => {
+                if i + 1 >= args.len() {
+                    return Err(GitAiError::Generic(
+                        "Missing argument for --count-reformat-as".to_string(),
+                    ));
+                }
+                match ReformatMode::parse(&args[i + 1]) {
+                    Some(mode) => options.count_reformat_as = mode,
+                    None => {
+                        return Err(GitAiError::Generic(format!(
+                            "Invalid value for --count-reformat-as: {} (expected attributed, human, ai or exclude)",
+                            args[i + 1]
+                        )));
+                    }
+                }
+                i += 2;
+            }
+            "--interval" => {
+                if i + 1 >= args.len() {
+                    return Err(GitAiError::Generic(
+                        "Missing argument for --interval".to_string(),
+                    ));
+                }
+                match args[i + 1].parse::<u64>() {
+                    Ok(secs) => options.watch_interval = Some(secs),
+                    Err(_) => {
+                        return Err(GitAiError::Generic(
+                            "Invalid number for --interval".to_string(),
+                        ));
+                    }
+                }
+                i += 2;
+            }
+
+            // Commit or range (non-option argument)
+            arg if !arg.starts_with('-') => {
+                if revspec.is_none() {
+                    revspec = Some(arg.to_string());
+                } else {
+                    return Err(GitAiError::Generic(
+                        "Multiple revisions specified".to_string(),
+                    ));
+                }
+                i += 1;
+            }
+
+            // Unknown option
+            _ => {
+                return Err(GitAiError::Generic(format!("Unknown option: {}", args[i])));
+            }
+        }
+    }
+
+    if matches!(options.format, StatsFormat::Json | StatsFormat::Csv) && !options.per_commit {
+        return Err(GitAiError::Generic(
+            "--format json and csv apply to --per-commit output".to_string(),
+        ));
+    }
+    if options.format == StatsFormat::Table
+        && (options.output != StatsOutput::Full
+            || options.watch
+            || options.author_domain
+            || options.delta_from_parent
+            || options.weighted_by.is_some()
+            || options.normalize_by.is_some())
+    {
+        return Err(GitAiError::Generic(
+            "--format table applies to the full breakdown and --per-commit output".to_string(),
+        ));
     }
+    if options.no_color && options.format != StatsFormat::Table {
+        return Err(GitAiError::Generic(
+            "--no-color applies to --format table".to_string(),
+        ));
+    }
+    if options.output != StatsOutput::Full && options.per_commit {
+        return Err(GitAiError::Generic(
+            "--output summary-line, --markdown-table, --only-ai-files, --top, --emit-prometheus and --by-author cannot be combined with --per-commit"
+                .to_string(),
+        ));
+    }
+    if (options.weighted_by.is_some()
+        || options.normalize_by.is_some()
+        || options.human_effort
+        || options.accept_rate)
+        && (options.output != StatsOutput::Full || options.per_commit)
+    {
+        return Err(GitAiError::Generic(
+            "--weighted-by, --normalize-by, --human-effort and --accept-rate apply to the full breakdown"
+                .to_string(),
+        ));
+    }
+    if options.author_domain
+        && (options.output != StatsOutput::Full
+            || options.per_commit
+            || options.watch
+            || options.weighted_by.is_some()
+            || options.normalize_by.is_some()
+            || options.human_effort
+            || options.accept_rate)
+    {
+        return Err(GitAiError::Generic(
+            "--author-domain replaces the full breakdown and cannot be combined with other output options"
+                .to_string(),
+        ));
+    }
+    if options.delta_from_parent
+        && (options.output != StatsOutput::Full
+            || options.per_commit
+            || options.watch
+            || options.author_domain
+            || options.baseline_branch.is_some()
+            || options.weighted_by.is_some()
+            || options.normalize_by.is_some()
+            || options.human_effort
+            || options.accept_rate)
+    {
+        return Err(GitAiError::Generic(
+            "--delta-from-parent replaces the full breakdown for a single commit and cannot be combined with other output options or --baseline-branch"
+                .to_string(),
+        ));
+    }
+    if has_window(&options) && (options.watch || options.delta_from_parent) {
+        return Err(GitAiError::Generic(
+            "--since and --until filter committed history and cannot be combined with --watch or --delta-from-parent"
+                .to_string(),
+        ));
+    }
+    if options.author_regex.is_some() && options.watch {
+        return Err(GitAiError::Generic(
+            "--author-regex filters commits and cannot be combined with --watch".to_string(),
+        ));
+    }
+    if options.output_file.is_some() && options.watch {
+        return Err(GitAiError::Generic(
+            "--output-file cannot be combined with --watch".to_string(),
+        ));
+    }
+    if options.resolve_only && options.watch {
+        return Err(GitAiError::Generic(
+            "--resolve-only cannot be combined with --watch".to_string(),
+        ));
+    }
+    if options.baseline_branch.is_some() && options.watch {
+        return Err(GitAiError::Generic(
+            "--baseline-branch reports on commits and cannot be combined with --watch".to_string(),
+        ));
+    }
+    if options.fetch && options.no_fetch {
+        return Err(GitAiError::Generic(
+            "--fetch and --no-fetch cannot be combined".to_string(),
+        ));
+    }
+    if options.cache && options.no_cache {
+        return Err(GitAiError::Generic(
+            "--cache and --no-cache cannot be combined".to_string(),
+        ));
+    }
+    if options.with_counts && options.output != StatsOutput::AiFiles {
+        return Err(GitAiError::Generic(
+            "--with-counts applies to --only-ai-files output".to_string(),
+        ));
+    }
+    if options.annotate_commit
+        && (revspec
+            .as_deref()
+            .is_some_and(|revspec| revspec.contains(".."))
+            || options.baseline_branch.is_some()
+            || has_window(&options)
+            || options.watch
+            || options.delta_from_parent
+            || options.resolve_only
+            || options.json_schema)
+    {
+        return Err(GitAiError::Generic(
+            "--annotate-commit writes a note on a single analyzed commit and cannot be combined with a range, --baseline-branch, --since, --until, --watch, --delta-from-parent, --resolve-only or --json-schema"
+                .to_string(),
+        ));
+    }
+    if options.exclude_small && options.min_lines.is_none() {
+        return Err(GitAiError::Generic(
+            "--exclude-small requires --min-lines".to_string(),
+        ));
+    }
+
+    Ok((revspec, options))
 }
@@ -0,0 +1,144 @@
+use crate::commands::checkpoint::resolve_base_commit;
+use crate::error::GitAiError;
+use crate::git::refs::get_reference_as_working_log;
+use crate::log_fmt::authorship_log::{AuthorshipLog, LineRange};
+use git2::{Repository, Status};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StatusOptions {
+    pub porcelain: bool,
+}
+
+/// One uncommitted path with the lines checkpoints attributed in it so far
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathStatus {
+    /// Two-letter index/work tree code, as in `git status --porcelain` (`??` when untracked)
+    pub code: String,
+    pub path: String,
+    pub ai_lines: u32,
+    pub human_lines: u32,
+}
+
+/// Every changed path in the work tree, sorted, with the AI and human line counts the
+/// working log holds for it. Lines no checkpoint has seen yet count for neither.
+pub fn run(repo: &Repository) -> Result<Vec<PathStatus>, GitAiError> {
+    let base_commit = resolve_base_commit(repo)?;
+    let working_log =
+        get_reference_as_working_log(repo, &format!("ai-working-log/{}", base_commit))
+            .unwrap_or_default();
+    let authorship = AuthorshipLog::from_working_log(&working_log);
+
+    let mut status_opts = git2::StatusOptions::new();
+    status_opts
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .include_ignored(false);
+    let mut paths: Vec<PathStatus> = repo
+        .statuses(Some(&mut status_opts))?
+        .iter()
+        .filter_map(|entry| {
+            let path = entry.path()?.to_string();
+            let (mut ai_lines, mut human_lines) = (0, 0);
+            if let Some(file) = authorship.files.get(&path) {
+                // A code generator's lines are neither
+                for author in file.authors.iter().filter(|author| !author.generated) {
+                    let lines: u32 = author.lines.iter().map(LineRange::len).sum();
+                    if author.agent_metadata.is_some() {
+                        ai_lines += lines;
+                    } else {
+                        human_lines += lines;
+                    }
+                }
+            }
+            Some(PathStatus {
+                code: status_code(entry.status()),
+                path,
+                ai_lines,
+                human_lines,
+            })
+        })
+        .collect();
+    paths.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(paths)
+}
+
+fn status_code(status: Status) -> String {
+    if status.is_conflicted() {
+        return "UU".to_string();
+    }
+    if status.is_wt_new() && !status.is_index_new() {
+        return "??".to_string();
+    }
+    let index = if status.is_index_new() {
+        'A'
+    } else if status.is_index_modified() {
+        'M'
+    } else if status.is_index_deleted() {
+        'D'
+    } else if status.is_index_renamed() {
+        'R'
+    } else if status.is_index_typechange() {
+        'T'
+    } else {
+        ' '
+    };
+    let worktree = if status.is_wt_modified() {
+        'M'
+    } else if status.is_wt_deleted() {
+        'D'
+    } else if status.is_wt_renamed() {
+        'R'
+    } else if status.is_wt_typechange() {
+        'T'
+    } else {
+        ' '
+    };
+    format!("{}{}", index, worktree)
+}
+
+/// `--porcelain` form, kept stable across versions for scripts and editor extensions: one
+/// `XY <ai> <human> <path>` line per path, with the path last so it may contain spaces
+pub fn porcelain(paths: &[PathStatus]) -> String {
+    paths
+        .iter()
+        .map(|path| {
+            format!(
+                "{} {} {} {}\n",
+                path.code, path.ai_lines, path.human_lines, path.path
+            )
+        })
+        .collect()
+}
+
+pub fn print_status(paths: &[PathStatus]) {
+    if paths.is_empty() {
+        println!("No uncommitted changes");
+        return;
+    }
+    let width = paths.iter().map(|path| path.path.len()).max().unwrap_or(0);
+    for path in paths {
+        println!(
+            "{} {:<width$}  {} AI, {} human",
+            path.code,
+            path.path,
+            path.ai_lines,
+            path.human_lines,
+            width = width
+        );
+    }
+}
+
+/// Parse `status --ai [--porcelain]`; `--ai` is what routes `status` to git-ai at all
+pub fn parse_status_args(args: &[String]) -> Result<StatusOptions, GitAiError> {
+    let mut options = StatusOptions::default();
+    for arg in args {
+        match arg.as_str() {
+            "--ai" => {}
+            "--porcelain" => options.porcelain = true,
+            _ => {
+                return Err(GitAiError::Generic(format!("Unknown option: {}", arg)));
+            }
+        }
+    }
+    Ok(options)
+}
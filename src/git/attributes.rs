@@ -0,0 +1,148 @@
+use crate::error::GitAiError;
+use crate::log_fmt::working_log::Confidence;
+use crate::utils::glob_to_regex;
+use git2::Repository;
+use regex::Regex;
+
+/// Where the per-path policies live, relative to the work tree root. Being a file in the
+/// repository, they are versioned and reviewed like the code they apply to.
+pub const ATTRIBUTES_PATH: &str = ".git-ai/attributes";
+
+/// Who a path's added lines are credited to, whatever the checkpoints recorded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForcedAttribution {
+    Human,
+    Ai,
+    /// A code generator's output (protoc, a build script), counted as neither
+    Generated,
+}
+
+/// What `.git-ai/attributes` says about one path
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PathPolicy {
+    /// Never tracked by checkpoints and left out of stats (`ignore`)
+    pub ignore: bool,
+    /// `force-human`, `force-ai` or `generated`
+    pub force: Option<ForcedAttribution>,
+    /// AI lines recorded with less confidence than this count as human (`min-confidence=`)
+    pub min_confidence: Option<Confidence>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Attribute {
+    Ignore(bool),
+    Force(Option<ForcedAttribution>),
+    MinConfidence(Option<Confidence>),
+}
+
+/// The rules of a `.git-ai/attributes` file, in file order
+#[derive(Debug, Clone, Default)]
+pub struct Attributes {
+    rules: Vec<(Regex, Vec<Attribute>)>,
+    source: String,
+}
+
+impl Attributes {
+    /// The work tree's `.git-ai/attributes`; none (every path unaffected) when it is missing
+    pub fn load(repo: &Repository) -> Result<Self, GitAiError> {
+        let Some(workdir) = repo.workdir() else {
+            return Ok(Self::default());
+        };
+        match std::fs::read_to_string(workdir.join(ATTRIBUTES_PATH)) {
+            Ok(source) => Self::parse(&source),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// gitattributes-style lines: a pattern, then attributes separated by whitespace, e.g.
+    /// `vendor/** ignore`, `docs/ force-human`, `*.pb.go generated` or
+    /// `src/gen/*.rs force-ai min-confidence=high`.
+    /// A `-` in front of an attribute unsets it. Patterns are globs as in `.gitignore`: one
+    /// without a `/` (other than a trailing one) matches at any depth, and a match on a
+    /// directory covers everything under it. `#` starts a comment line.
+    pub fn parse(source: &str) -> Result<Self, GitAiError> {
+        let mut rules = Vec::new();
+        for (number, line) in source.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut fields = line.split_whitespace();
+            let pattern = fields.next().unwrap_or_default();
+            let attributes = fields
+                .map(parse_attribute)
+                .collect::<Option<Vec<_>>>()
+                .filter(|attributes| !attributes.is_empty())
+                .ok_or_else(|| {
+                    GitAiError::Generic(format!(
+                        "{}:{}: expected a pattern followed by ignore, force-human, force-ai, generated or min-confidence=<high|low>, got '{}'",
+                        ATTRIBUTES_PATH,
+                        number + 1,
+                        line
+                    ))
+                })?;
+            rules.push((pattern_regex(pattern)?, attributes));
+        }
+        Ok(Self {
+            rules,
+            source: source.to_string(),
+        })
+    }
+
+    /// The file's text, so cached results can tell when the policies changed
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// The policy for a repository-relative path; later lines override earlier ones
+    pub fn policy(&self, path: &str) -> PathPolicy {
+        let mut policy = PathPolicy::default();
+        for (regex, attributes) in &self.rules {
+            if !regex.is_match(path) {
+                continue;
+            }
+            for attribute in attributes {
+                match attribute {
+                    Attribute::Ignore(ignore) => policy.ignore = *ignore,
+                    Attribute::Force(force) => policy.force = *force,
+                    Attribute::MinConfidence(floor) => policy.min_confidence = *floor,
+                }
+            }
+        }
+        policy
+    }
+}
+
+fn parse_attribute(field: &str) -> Option<Attribute> {
+    Some(match field {
+        "ignore" => Attribute::Ignore(true),
+        "-ignore" => Attribute::Ignore(false),
+        "force-human" => Attribute::Force(Some(ForcedAttribution::Human)),
+        "force-ai" => Attribute::Force(Some(ForcedAttribution::Ai)),
+        "generated" => Attribute::Force(Some(ForcedAttribution::Generated)),
+        "-force-human" | "-force-ai" | "-generated" => Attribute::Force(None),
+        "-min-confidence" => Attribute::MinConfidence(None),
+        _ => Attribute::MinConfidence(Some(Confidence::parse(
+            field.strip_prefix("min-confidence=")?,
+        )?)),
+    })
+}
+
+fn pattern_regex(pattern: &str) -> Result<Regex, GitAiError> {
+    let trimmed = pattern.trim_end_matches('/');
+    let glob = match trimmed.strip_prefix('/') {
+        Some(anchored) => anchored.to_string(),
+        None if trimmed.contains('/') => trimmed.to_string(),
+        None => format!("**/{}", trimmed),
+    };
+    let regex = glob_to_regex(&glob);
+    // Also everything under a matching directory
+    let regex = format!("{}(?:/.*)?$", regex.strip_suffix('$').unwrap_or(&regex));
+    Regex::new(&regex).map_err(|e| {
+        GitAiError::Generic(format!(
+            "{}: invalid pattern '{}': {}",
+            ATTRIBUTES_PATH, pattern, e
+        ))
+    })
+}
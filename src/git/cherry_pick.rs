@@ -0,0 +1,210 @@
+use crate::commands::stats::walk_commits;
+use crate::error::GitAiError;
+use crate::git::refs::{authorship_ref_name, get_reference_as_authorship_log, put_reference};
+use crate::log_fmt::authorship_log::AuthorshipLog;
+use crate::utils::debug_log;
+use git2::{Commit, Oid, Repository};
+use similar::{DiffOp, TextDiff};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// `git cherry-pick` options that take their value as the next argument
+const OPTIONS_WITH_VALUE: [&str; 6] = [
+    "-m",
+    "--mainline",
+    "-X",
+    "--strategy-option",
+    "--strategy",
+    "--cleanup",
+];
+
+/// The commits a `git cherry-pick <args>` run may pick, gathered before it runs: the
+/// revisions on the command line (ranges expanded), or when resuming with `--continue` or
+/// `--skip`, the stopped pick and whatever is left in the sequencer's todo list
+pub fn cherry_pick_sources(repo: &Repository, args: &[String]) -> Vec<Oid> {
+    if args
+        .iter()
+        .any(|arg| arg == "--continue" || arg == "--skip")
+    {
+        return sequencer_sources(repo);
+    }
+
+    let mut sources = Vec::new();
+    let mut i = 0;
+    while i < args.len() {
+        let arg = args[i].as_str();
+        if OPTIONS_WITH_VALUE.contains(&arg) {
+            i += 2;
+            continue;
+        }
+        i += 1;
+        if arg.starts_with('-') {
+            continue;
+        }
+        // Unresolvable arguments are git's to report
+        if let Ok(commits) = walk_commits(repo, arg) {
+            sources.extend(commits.flatten());
+        }
+    }
+    sources
+}
+
+fn sequencer_sources(repo: &Repository) -> Vec<Oid> {
+    let mut sources = Vec::new();
+    if let Ok(head) = std::fs::read_to_string(repo.path().join("CHERRY_PICK_HEAD"))
+        && let Ok(oid) = Oid::from_str(head.trim())
+    {
+        sources.push(oid);
+    }
+    let todo =
+        std::fs::read_to_string(repo.path().join("sequencer").join("todo")).unwrap_or_default();
+    for line in todo.lines() {
+        let mut fields = line.split_whitespace();
+        if let (Some("pick" | "p"), Some(rev)) = (fields.next(), fields.next())
+            && let Ok(object) = repo.revparse_single(rev)
+        {
+            sources.push(object.id());
+        }
+    }
+    sources
+}
+
+/// Give the commits cherry-picked on top of `old_head` the attribution of the commits they
+/// were picked from. A picked commit is recognized by its source's author, author date and
+/// subject, which cherry-pick keeps.
+///
+/// The source's lines are followed into the new commit's version of each file, so a pick
+/// landing at other line numbers keeps its attribution. Where a conflict was resolved
+/// differently from the source, those lines don't survive the mapping and are left to the
+/// committer, so only what applied as it was picked stays attributed. A new commit that
+/// already has a non-empty log keeps it. Returns the number of logs written.
+pub fn carry_cherry_picks(
+    repo: &Repository,
+    old_head: Option<Oid>,
+    sources: &[Oid],
+) -> Result<usize, GitAiError> {
+    let sources: Vec<Commit> = sources
+        .iter()
+        .filter_map(|oid| repo.find_commit(*oid).ok())
+        .collect();
+    let Some(mut commit) = repo.head().ok().and_then(|head| head.peel_to_commit().ok()) else {
+        return Ok(0);
+    };
+
+    let mut carried = 0;
+    // Each pick adds one commit, so there are never more new commits than sources
+    for _ in 0..sources.len() {
+        if Some(commit.id()) == old_head {
+            break;
+        }
+        if let Some(source) = sources.iter().find(|source| is_pick_of(&commit, source))
+            && carry_one(repo, source, &commit)?
+        {
+            carried += 1;
+        }
+        match commit.parent(0) {
+            Ok(parent) => commit = parent,
+            Err(_) => break,
+        }
+    }
+    debug_log(&format!(
+        "Carried {} authorship log(s) across cherry-pick",
+        carried
+    ));
+    Ok(carried)
+}
+
+fn is_pick_of(commit: &Commit, source: &Commit) -> bool {
+    let (author, source_author) = (commit.author(), source.author());
+    commit.id() != source.id()
+        && author.name_bytes() == source_author.name_bytes()
+        && author.email_bytes() == source_author.email_bytes()
+        && author.when().seconds() == source_author.when().seconds()
+        && commit.summary_bytes() == source.summary_bytes()
+}
+
+fn carry_one(repo: &Repository, source: &Commit, commit: &Commit) -> Result<bool, GitAiError> {
+    let new_ref = authorship_ref_name(repo, &commit.id().to_string());
+    if get_reference_as_authorship_log(repo, &new_ref).is_ok_and(|log| !log.files.is_empty()) {
+        return Ok(false);
+    }
+    let Ok(source_log) =
+        get_reference_as_authorship_log(repo, &authorship_ref_name(repo, &source.id().to_string()))
+    else {
+        return Ok(false);
+    };
+
+    let (source_tree, tree) = (source.tree()?, commit.tree()?);
+    let mut authorship_log = AuthorshipLog::new();
+    for (path, file) in &source_log.files {
+        let (Some(old), Some(new)) = (
+            blob_text(repo, &source_tree, path),
+            blob_text(repo, &tree, path),
+        ) else {
+            continue;
+        };
+        let line_map = unchanged_lines(&old, &new);
+        for author in &file.authors {
+            let lines: Vec<u32> = author
+                .lines
+                .iter()
+                .flat_map(|range| range.expand())
+                .filter_map(|line| line_map.get(&line).copied())
+                .collect();
+            if !lines.is_empty() {
+                authorship_log
+                    .get_or_create_file(path)
+                    .add_lines_like(author, &lines);
+            }
+        }
+    }
+    authorship_log.files.retain(|_, file| !file.is_empty());
+
+    let authorship_json = if cfg!(debug_assertions) {
+        serde_json::to_string_pretty(&authorship_log)?
+    } else {
+        serde_json::to_string(&authorship_log)?
+    };
+    put_reference(
+        repo,
+        &new_ref,
+        &authorship_json,
+        &format!(
+            "AI authorship carried over from cherry-picked commit {}",
+            source.id()
+        ),
+    )?;
+    Ok(true)
+}
+
+fn blob_text(repo: &Repository, tree: &git2::Tree, path: &str) -> Option<String> {
+    let blob = tree
+        .get_path(Path::new(path))
+        .ok()?
+        .to_object(repo)
+        .ok()?
+        .peel_to_blob()
+        .ok()?;
+    Some(String::from_utf8_lossy(blob.content()).into_owned())
+}
+
+/// 1-based line numbers in `old` that come through to `new` unchanged, and where they land
+fn unchanged_lines(old: &str, new: &str) -> HashMap<u32, u32> {
+    let mut map = HashMap::new();
+    for op in TextDiff::from_lines(old, new).ops() {
+        if let DiffOp::Equal {
+            old_index,
+            new_index,
+            len,
+        } = *op
+        {
+            for offset in 0..len {
+                map.insert(
+                    (old_index + offset + 1) as u32,
+                    (new_index + offset + 1) as u32,
+                );
+            }
+        }
+    }
+    map
+}
@@ -1,5 +1,10 @@
+pub mod attributes;
+pub mod cherry_pick;
+pub mod notes;
 pub mod post_commit;
+pub mod post_rewrite;
 pub mod pre_commit;
 pub mod refs;
 pub mod repository;
+pub mod revspec;
 pub use repository::find_repository;
@@ -0,0 +1,59 @@
+use crate::error::GitAiError;
+use git2::{Oid, Repository, Signature};
+
+/// Notes ref git-ai annotates commits under; `git log --notes=git-ai` shows them
+pub const GIT_AI_NOTES_REF: &str = "refs/notes/git-ai";
+
+/// The git-ai note on `commit`, if it has one
+pub fn read_note(repo: &Repository, commit: Oid) -> Option<String> {
+    let note = repo.find_note(Some(GIT_AI_NOTES_REF), commit).ok()?;
+    note.message().map(str::to_string)
+}
+
+/// Set the git-ai note on `commit` to `message`, replacing any earlier one. An identical note
+/// is left alone, so re-annotating doesn't grow the notes history. Returns whether it wrote.
+pub fn write_note(repo: &Repository, commit: Oid, message: &str) -> Result<bool, GitAiError> {
+    if read_note(repo, commit).as_deref() == Some(message) {
+        return Ok(false);
+    }
+    let signature = notes_signature(repo)?;
+    repo.note(
+        &signature,
+        &signature,
+        Some(GIT_AI_NOTES_REF),
+        commit,
+        message,
+        true,
+    )?;
+    Ok(true)
+}
+
+/// Drop the git-ai note on `commit`, for when the attribution it summarizes changed.
+/// Returns whether there was one.
+pub fn remove_note(repo: &Repository, commit: Oid) -> Result<bool, GitAiError> {
+    if read_note(repo, commit).is_none() {
+        return Ok(false);
+    }
+    let signature = notes_signature(repo)?;
+    repo.note_delete(commit, Some(GIT_AI_NOTES_REF), &signature, &signature)?;
+    Ok(true)
+}
+
+/// Drop every git-ai note at once; returns whether there were any
+pub fn remove_all_notes(repo: &Repository) -> Result<bool, GitAiError> {
+    match repo.find_reference(GIT_AI_NOTES_REF) {
+        Ok(mut reference) => {
+            reference.delete()?;
+            Ok(true)
+        }
+        Err(e) if e.code() == git2::ErrorCode::NotFound => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Notes are commits too; without a configured identity they are git-ai's
+fn notes_signature(repo: &Repository) -> Result<Signature<'static>, GitAiError> {
+    Ok(repo
+        .signature()
+        .or_else(|_| Signature::now("git-ai", "git-ai@localhost"))?)
+}
@@ -1,5 +1,5 @@
 use crate::error::GitAiError;
-use crate::git::refs::put_reference;
+use crate::git::refs::{authorship_ref_name, put_reference};
 use crate::log_fmt::authorship_log::AuthorshipLog;
 use crate::log_fmt::working_log::Checkpoint;
 use crate::utils::debug_log;
@@ -54,7 +54,7 @@ pub fn post_commit(repo: &Repository, force: bool) -> Result<(String, Authorship
         filtered_working_log.len()
     ));
 
-    // --- NEW: Serialize authorship log and store it in refs/ai/authorship/{commit_sha} ---
+    // --- NEW: Serialize authorship log and store it in refs/<namespace>/{commit_sha} ---
     let authorship_log = AuthorshipLog::from_working_log(&filtered_working_log);
 
     // Use pretty formatting in debug builds, single-line in release builds
@@ -64,7 +64,7 @@ pub fn post_commit(repo: &Repository, force: bool) -> Result<(String, Authorship
         serde_json::to_string(&authorship_log)?
     };
 
-    let ref_name = format!("ai/authorship/{}", commit_sha);
+    let ref_name = authorship_ref_name(repo, &commit_sha);
     put_reference(
         repo,
         &ref_name,
@@ -72,12 +72,74 @@ pub fn post_commit(repo: &Repository, force: bool) -> Result<(String, Authorship
         &format!("AI authorship attestation for commit {}", commit_sha),
     )?;
 
+    debug_log(&format!("Authorship log written to refs/{}", ref_name));
+
+    // Opt-in one-line feedback on the commit that was just made
+    if summary_enabled(repo) {
+        match crate::commands::stats::commit_summary_line(repo, &current_commit) {
+            Ok(line) => println!("{}", line),
+            Err(e) => debug_log(&format!("Failed to summarize commit: {}", e)),
+        }
+    }
+
+    Ok((ref_name, authorship_log))
+}
+
+/// `git commit --amend` replaced `amended_sha` with a new commit on the same parent. The
+/// original's authorship was built from the parent's working log and everything checkpointed
+/// since lives in the original's own log, so fold that into the parent's log, attribute the
+/// rewritten commit from the result, and drop the refs keyed on the original SHA.
+pub fn post_amend(
+    repo: &Repository,
+    amended_sha: &str,
+    force: bool,
+) -> Result<(String, AuthorshipLog), GitAiError> {
+    let head = repo.head()?.peel_to_commit()?;
+    let parent_sha = match head.parent(0) {
+        Ok(parent) => parent.id().to_string(),
+        Err(_) => "initial".to_string(),
+    };
+
+    let amended_log_ref = format!("ai-working-log/{}", amended_sha);
+    let amended_working_log = get_working_log(repo, amended_sha)?;
+    if !amended_working_log.is_empty() {
+        let mut working_log = get_working_log(repo, &parent_sha)?;
+        working_log.extend(amended_working_log);
+        let working_log_json = if cfg!(debug_assertions) {
+            serde_json::to_string_pretty(&working_log)?
+        } else {
+            serde_json::to_string(&working_log)?
+        };
+        put_reference(
+            repo,
+            &format!("ai-working-log/{}", parent_sha),
+            &working_log_json,
+            &format!("Fold working log of amended commit {}", amended_sha),
+        )?;
+    }
+
+    let result = post_commit(repo, force)?;
+
+    // The original commit is no longer in history; its refs would only go stale
+    for ref_name in [amended_log_ref, authorship_ref_name(repo, amended_sha)] {
+        if let Ok(mut reference) = repo.find_reference(&format!("refs/{}", ref_name)) {
+            reference.delete()?;
+        }
+    }
     debug_log(&format!(
-        "Authorship log written to refs/ai/authorship/{}",
-        commit_sha
+        "Moved attribution from amended commit {} to {}",
+        amended_sha,
+        head.id()
     ));
 
-    Ok((ref_name, authorship_log))
+    Ok(result)
+}
+
+/// `git-ai.postCommitSummary = true` prints the AI/human split after each commit
+fn summary_enabled(repo: &Repository) -> bool {
+    repo.config()
+        .and_then(|config| config.get_bool("git-ai.postCommitSummary"))
+        .unwrap_or(false)
 }
 
 /// Filter out working log entries for untracked files
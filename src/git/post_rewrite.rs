@@ -0,0 +1,46 @@
+use crate::error::GitAiError;
+use crate::git::notes::remove_note;
+use crate::git::refs::{authorship_ref_name, get_reference, put_reference};
+use crate::utils::debug_log;
+use git2::{Oid, Repository};
+
+/// Copy authorship logs from rewritten commits to their replacements, given the
+/// `<old-sha> <new-sha> [<extra>]` lines git passes to the post-rewrite hook on stdin.
+///
+/// A replacement that already has a log keeps it, so running the hook twice changes nothing
+/// and the log `git-ai commit --amend` writes for an amend is never clobbered. When several
+/// commits are squashed into one, the first one's log is kept. The old logs stay in place
+/// for branches that still point at the original commits. Returns the number of logs copied.
+pub fn post_rewrite(repo: &Repository, rewrites: &str) -> Result<usize, GitAiError> {
+    let mut copied = 0;
+    for line in rewrites.lines() {
+        let mut fields = line.split_whitespace();
+        let (Some(old_sha), Some(new_sha)) = (fields.next(), fields.next()) else {
+            continue;
+        };
+        let new_ref = authorship_ref_name(repo, new_sha);
+        if old_sha == new_sha || get_reference(repo, &new_ref).is_ok() {
+            continue;
+        }
+        let Ok(authorship_json) = get_reference(repo, &authorship_ref_name(repo, old_sha)) else {
+            continue;
+        };
+        put_reference(
+            repo,
+            &new_ref,
+            &authorship_json,
+            &format!(
+                "AI authorship carried over from rewritten commit {}",
+                old_sha
+            ),
+        )?;
+        // A note git copied along (notes.rewriteRef) summarized the old commit's log
+        remove_note(repo, Oid::from_str(new_sha)?)?;
+        copied += 1;
+    }
+    debug_log(&format!(
+        "Carried {} authorship log(s) across rewrite",
+        copied
+    ));
+    Ok(copied)
+}
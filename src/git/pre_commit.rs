@@ -3,7 +3,13 @@ use git2::Repository;
 
 pub fn pre_commit(repo: &Repository, default_user_name: String) -> Result<(), GitAiError> {
     // Run checkpoint as human editor.
-    let result =
-        crate::commands::checkpoint::run(repo, &default_user_name, false, false, true, None, None);
+    let result = crate::commands::checkpoint::run(
+        repo,
+        &default_user_name,
+        &crate::commands::checkpoint::CheckpointOptions {
+            quiet: true,
+            ..Default::default()
+        },
+    );
     result.map(|_| ())
 }
@@ -5,10 +5,35 @@ use git2::Repository;
 use serde_json;
 use std::fs;
 
-pub const AI_AUTHORSHIP_REFSPEC: &str = "+refs/ai/authorship/*:refs/ai/authorship/*";
+pub const DEFAULT_AUTHORSHIP_NAMESPACE: &str = "ai/authorship";
 pub const DEFAULT_REFSPEC: &str = "+refs/heads/*:refs/heads/*";
 
-///
+/// Namespace (relative to refs/) holding authorship logs, overridable with
+/// `git-ai.refNamespace` for teams whose tooling already owns refs/ai/*
+pub fn authorship_namespace(repo: &Repository) -> String {
+    repo.config()
+        .and_then(|config| config.get_string("git-ai.refNamespace"))
+        .ok()
+        .map(|ns| {
+            ns.trim()
+                .trim_start_matches("refs/")
+                .trim_matches('/')
+                .to_string()
+        })
+        .filter(|ns| !ns.is_empty())
+        .unwrap_or_else(|| DEFAULT_AUTHORSHIP_NAMESPACE.to_string())
+}
+
+/// Authorship log ref for a commit, relative to refs/ like the other helpers here
+pub fn authorship_ref_name(repo: &Repository, commit_sha: &str) -> String {
+    format!("{}/{}", authorship_namespace(repo), commit_sha)
+}
+
+/// Refspec that syncs every authorship log on fetch/push
+pub fn authorship_refspec(repo: &Repository) -> String {
+    format!("+refs/{0}/*:refs/{0}/*", authorship_namespace(repo))
+}
+
 pub fn put_reference(
     repo: &Repository,
     ref_name: &str,
@@ -47,7 +72,6 @@ pub fn get_reference(repo: &Repository, ref_name: &str) -> Result<String, GitAiE
     Ok(content.to_string())
 }
 
-#[allow(dead_code)]
 pub fn get_reference_as_working_log(
     repo: &Repository,
     ref_name: &str,
@@ -57,6 +81,31 @@ pub fn get_reference_as_working_log(
     Ok(working_log)
 }
 
+/// Every working log in the repo keyed by its base commit, committed or not
+/// (per-file snapshots under ai-working-log/diffs/ are skipped)
+pub fn list_working_logs(repo: &Repository) -> Result<Vec<(String, Vec<Checkpoint>)>, GitAiError> {
+    let mut working_logs = Vec::new();
+    for reference in repo.references_glob("refs/ai-working-log/*")? {
+        let reference = reference?;
+        let Some(base_commit) = reference
+            .name()
+            .and_then(|name| name.strip_prefix("refs/ai-working-log/"))
+        else {
+            continue;
+        };
+        if base_commit.contains('/') {
+            continue;
+        }
+        let base_commit = base_commit.to_string();
+
+        let blob = reference.peel_to_blob()?;
+        if let Ok(working_log) = serde_json::from_slice::<Vec<Checkpoint>>(blob.content()) {
+            working_logs.push((base_commit, working_log));
+        }
+    }
+    Ok(working_logs)
+}
+
 pub fn get_reference_as_authorship_log(
     repo: &Repository,
     ref_name: &str,
@@ -1,6 +1,163 @@
 use crate::error::GitAiError;
-use git2::Repository;
+use git2::{Config, Mailmap, Repository, Signature, Time};
+use std::path::{Path, PathBuf};
 
+/// Discover the repository from the current directory, with symlinks resolved first so a
+/// work tree reached through a link opens at the same root as one reached directly. When
+/// `GIT_DIR` is set, as `git --git-dir=<dir> ai ...` does, it is honored the way git would,
+/// along with `GIT_WORK_TREE`.
 pub fn find_repository() -> Result<Repository, GitAiError> {
-    Repository::discover(".").map_err(GitAiError::GitError)
+    if std::env::var_os("GIT_DIR").is_some() {
+        return Repository::open_from_env().map_err(GitAiError::GitError);
+    }
+    let start = std::env::current_dir()
+        .and_then(|dir| dir.canonicalize())
+        .unwrap_or_else(|_| PathBuf::from("."));
+    Repository::discover(start).map_err(GitAiError::GitError)
+}
+
+/// Open the repository at `path` exactly, without searching parent directories
+pub fn open_repository(path: &str) -> Result<Repository, GitAiError> {
+    let resolved = Path::new(path)
+        .canonicalize()
+        .unwrap_or_else(|_| PathBuf::from(path));
+    Repository::open(resolved)
+        .map_err(|_| GitAiError::Generic(format!("Not a git repository: {}", path)))
+}
+
+/// A path from the command line as the work-tree-relative path git-ai records. Absolute
+/// paths and paths that exist relative to the current directory are resolved through any
+/// symlinks and made relative to the work tree root; anything else is taken to be relative
+/// to the root already, which is how paths were always read.
+pub fn repo_relative_path(repo: &Repository, path: &str) -> Result<String, GitAiError> {
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| GitAiError::Generic("Repository has no working directory".to_string()))?;
+    let workdir = workdir
+        .canonicalize()
+        .unwrap_or_else(|_| workdir.to_path_buf());
+
+    let given = Path::new(path);
+    let candidate = if given.is_absolute() {
+        given.to_path_buf()
+    } else {
+        match std::env::current_dir() {
+            Ok(cwd) if cwd.join(given).exists() => cwd.join(given),
+            _ => return Ok(path.to_string()),
+        }
+    };
+    // A file that doesn't exist yet (or any more) is resolved through its directory
+    let resolved =
+        candidate
+            .canonicalize()
+            .or_else(|e| match (candidate.parent(), candidate.file_name()) {
+                (Some(parent), Some(name)) => parent.canonicalize().map(|dir| dir.join(name)),
+                _ => Err(e),
+            });
+
+    match resolved.ok().and_then(|resolved| {
+        resolved
+            .strip_prefix(&workdir)
+            .ok()
+            .map(|relative| relative.to_string_lossy().replace('\\', "/"))
+    }) {
+        Some(relative) => Ok(relative),
+        None if given.is_absolute() => Err(GitAiError::Generic(format!(
+            "{} is outside the repository at {}",
+            path,
+            workdir.display()
+        ))),
+        // Exists here but not under the work tree, e.g. with --repo from another directory
+        None => Ok(path.to_string()),
+    }
+}
+
+/// A git config value, decoded lossily. `Config::get_string` fails outright on bytes that
+/// aren't UTF-8 (a latin-1 `user.name`, say); reading the raw entry keeps the value with
+/// only the undecodable bytes replaced.
+pub fn config_string_lossy(config: &Config, key: &str) -> Option<String> {
+    let entry = config.get_entry(key).ok()?;
+    entry
+        .has_value()
+        .then(|| String::from_utf8_lossy(entry.value_bytes()).into_owned())
+}
+
+/// A git config value's raw bytes, whatever their encoding
+pub fn config_bytes(config: &Config, key: &str) -> Option<Vec<u8>> {
+    let entry = config.get_entry(key).ok()?;
+    entry.has_value().then(|| entry.value_bytes().to_vec())
+}
+
+/// Whether git-ai records attribution here. `git-ai.enabled = false` opts one repository out
+/// while the global hooks stay installed; a non-empty `GIT_AI_DISABLE` (other than `0` or
+/// `false`) does the same for a shell or CI job.
+pub fn tracking_enabled(repo: &Repository) -> bool {
+    if let Some(value) = std::env::var_os("GIT_AI_DISABLE")
+        && !matches!(value.to_str(), Some("" | "0" | "false"))
+    {
+        return false;
+    }
+    repo.config()
+        .and_then(|config| config.get_bool("git-ai.enabled"))
+        .unwrap_or(true)
+}
+
+/// The configured `user.name`, if any, decoded for display
+pub fn user_name(repo: &Repository) -> Option<String> {
+    let config = repo.config().ok()?;
+    config_string_lossy(&config, "user.name")
+}
+
+/// The configured `user.name` as stored, for recording next to its display form
+pub fn user_name_bytes(repo: &Repository) -> Option<Vec<u8>> {
+    let config = repo.config().ok()?;
+    config_bytes(&config, "user.name")
+}
+
+/// The remote git-ai syncs authorship refs with, as a bare `git fetch` would pick it: the
+/// current branch's upstream remote (`branch.<name>.remote`), then 'origin', then the first
+/// remote
+pub fn get_default_remote(repo: &Repository) -> Option<String> {
+    let remotes = repo.remotes().ok()?;
+    let names: Vec<&str> = remotes.iter().flatten().collect();
+    if let Some(upstream) = upstream_remote(repo)
+        && names.contains(&upstream.as_str())
+    {
+        return Some(upstream);
+    }
+    if names.contains(&"origin") {
+        return Some("origin".to_string());
+    }
+    names.first().map(|name| name.to_string())
+}
+
+/// `branch.<name>.remote` for the checked-out branch; "." (a local upstream) names no remote
+/// and is skipped by the caller
+fn upstream_remote(repo: &Repository) -> Option<String> {
+    // HEAD's symbolic target, so an unborn branch counts too
+    let head = repo.find_reference("HEAD").ok()?;
+    let branch = head.symbolic_target()?.strip_prefix("refs/heads/")?;
+    let config = repo.config().ok()?;
+    config_string_lossy(&config, &format!("branch.{}.remote", branch))
+}
+
+/// The repository's `.mailmap`, unless disabled with `--no-mailmap`
+pub fn load_mailmap(repo: &Repository, enabled: bool) -> Option<Mailmap> {
+    if !enabled {
+        return None;
+    }
+    repo.mailmap().ok()
+}
+
+/// Canonical name for an author under the mailmap. Checkpoint authors carry no email of
+/// their own, so callers pass the email of the commit the lines landed in.
+pub fn mailmap_name(mailmap: Option<&Mailmap>, name: &str, email: &str) -> String {
+    let Some(mailmap) = mailmap else {
+        return name.to_string();
+    };
+    Signature::new(name, email, &Time::new(0, 0))
+        .and_then(|signature| mailmap.resolve_signature(&signature))
+        .ok()
+        .and_then(|signature| signature.name().map(str::to_string))
+        .unwrap_or_else(|| name.to_string())
 }
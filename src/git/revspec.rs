@@ -0,0 +1,115 @@
+use crate::error::GitAiError;
+use crate::git::repository::get_default_remote;
+use git2::{Commit, Repository, Revspec};
+
+const EXPECTED_FORMAT: &str = "Expected a commit SHA, a branch or tag name, or an expression such as HEAD~1 (ranges use <base>..<head>)";
+
+/// Resolve a single revision to a commit, explaining what went wrong when it can't be found
+pub fn resolve_commit<'a>(repo: &'a Repository, spec: &str) -> Result<Commit<'a>, GitAiError> {
+    repo.revparse_single(spec)
+        .and_then(|object| object.peel_to_commit())
+        .map_err(|_| unknown_revision_error(repo, spec))
+}
+
+/// Resolve a `base..head` or `base...head` range, pointing at whichever side failed
+pub fn resolve_range<'a>(repo: &'a Repository, spec: &str) -> Result<Revspec<'a>, GitAiError> {
+    repo.revparse(spec).map_err(|_| {
+        let (base, head) = spec
+            .split_once("...")
+            .or_else(|| spec.split_once(".."))
+            .unwrap_or((spec, ""));
+        // An empty side means HEAD, exactly as git reads it
+        for side in [base, head] {
+            if !side.is_empty() && repo.revparse_single(side).is_err() {
+                return unknown_revision_error(repo, side);
+            }
+        }
+        GitAiError::Generic(format!("Invalid range: {}. {}", spec, EXPECTED_FORMAT))
+    })
+}
+
+fn unknown_revision_error(repo: &Repository, spec: &str) -> GitAiError {
+    let mut message = format!("Unknown revision: {}.", spec);
+    let suggestions = suggest_revisions(repo, spec);
+    if !suggestions.is_empty() {
+        message.push_str(&format!(" Did you mean {}?", suggestions.join(" or ")));
+    }
+    message.push('\n');
+    message.push_str(EXPECTED_FORMAT);
+    message.push('.');
+
+    if suggestions.is_empty() && get_default_remote(repo).is_some() {
+        let what = if looks_like_sha(spec) {
+            "commit"
+        } else {
+            "ref"
+        };
+        message.push_str(&format!(
+            "\nIf the {} only exists on a remote, run `git fetch` first.",
+            what
+        ));
+    }
+    GitAiError::Generic(message)
+}
+
+/// Known names close to the ref part of `spec`, with any `~N`/`^N` suffix carried over
+fn suggest_revisions(repo: &Repository, spec: &str) -> Vec<String> {
+    let split = spec.find(['~', '^', '@', ':']).unwrap_or(spec.len());
+    let (name, suffix) = spec.split_at(split);
+    if name.is_empty() {
+        return Vec::new();
+    }
+
+    let mut candidates = vec!["HEAD".to_string()];
+    if let Ok(references) = repo.references() {
+        for reference in references.flatten() {
+            // Only branches, tags and remote branches; git-ai's own refs are never meant
+            if let Some(shorthand) = reference.shorthand()
+                && (reference.is_branch() || reference.is_tag() || reference.is_remote())
+            {
+                candidates.push(shorthand.to_string());
+            }
+        }
+    }
+
+    let max_distance = if name.len() <= 4 { 1 } else { 2 };
+    let mut scored: Vec<(usize, String)> = candidates
+        .into_iter()
+        .filter(|candidate| candidate != name)
+        .filter_map(|candidate| {
+            let distance = if candidate.eq_ignore_ascii_case(name) {
+                0
+            } else {
+                edit_distance(&candidate, name)
+            };
+            (distance <= max_distance).then(|| (distance, format!("{}{}", candidate, suffix)))
+        })
+        .filter(|(_, suggestion)| repo.revparse_single(suggestion).is_ok())
+        .collect();
+    scored.sort();
+    scored.dedup_by(|a, b| a.1 == b.1);
+    scored
+        .into_iter()
+        .take(3)
+        .map(|(_, suggestion)| suggestion)
+        .collect()
+}
+
+fn looks_like_sha(spec: &str) -> bool {
+    spec.len() >= 4 && spec.len() <= 40 && spec.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Levenshtein distance between two strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
@@ -130,6 +130,15 @@ impl LineRange {
             LineRange::Range(start, end) => (*start..=*end).collect(),
         }
     }
+
+    /// Number of lines the range covers (never zero, hence no `is_empty`)
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> u32 {
+        match self {
+            LineRange::Single(_) => 1,
+            LineRange::Range(start, end) => end - start + 1,
+        }
+    }
 }
 
 impl fmt::Display for LineRange {
@@ -251,11 +260,17 @@ pub struct AuthorEntry {
     pub author: String,
     pub lines: Vec<LineRange>,
     pub agent_metadata: Option<AgentMetadata>,
+    /// Written by a code generator (protoc, a build script): neither a person nor an agent,
+    /// so counted on neither side of the human/AI split
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub generated: bool,
 }
 
 impl PartialEq for AuthorEntry {
     fn eq(&self, other: &Self) -> bool {
-        self.author == other.author && self.lines == other.lines
+        self.author == other.author
+            && self.lines == other.lines
+            && self.generated == other.generated
         // Note: We don't compare agent_metadata since AgentMetadata doesn't implement Eq
     }
 }
@@ -267,6 +282,7 @@ impl AuthorEntry {
             author,
             lines: Vec::new(),
             agent_metadata: None,
+            generated: false,
         }
     }
 
@@ -275,6 +291,7 @@ impl AuthorEntry {
             author,
             lines: Vec::new(),
             agent_metadata,
+            generated: false,
         }
     }
 
@@ -361,6 +378,31 @@ impl FileAuthorship {
         author: &str,
         lines: &[u32],
         agent_metadata: Option<AgentMetadata>,
+    ) {
+        self.insert_lines(author, lines, agent_metadata, false);
+    }
+
+    /// Add lines a code generator wrote, removing them from all other authors
+    pub fn add_generated_lines(&mut self, author: &str, lines: &[u32]) {
+        self.insert_lines(author, lines, None, true);
+    }
+
+    /// Add lines with the same attribution as `entry`, whoever or whatever it credits
+    pub fn add_lines_like(&mut self, entry: &AuthorEntry, lines: &[u32]) {
+        self.insert_lines(
+            &entry.author,
+            lines,
+            entry.agent_metadata.clone(),
+            entry.generated,
+        );
+    }
+
+    fn insert_lines(
+        &mut self,
+        author: &str,
+        lines: &[u32],
+        agent_metadata: Option<AgentMetadata>,
+        generated: bool,
     ) {
         // Create a single range to remove from all other authors
         let lines_to_remove = LineRange::compress_lines(lines);
@@ -370,8 +412,19 @@ impl FileAuthorship {
             other_author.remove_lines(&lines_to_remove);
         }
 
-        // Add to this author with compression
-        if let Some(entry) = self.authors.iter_mut().find(|a| a.author == author) {
+        // Add to this author with compression. Lines an agent wrote under another model or
+        // confidence stay in separate entries so neither is relabeled.
+        let same_agent = |entry: &AuthorEntry| match (&entry.agent_metadata, &agent_metadata) {
+            (Some(existing), Some(new)) => {
+                existing.model == new.model && existing.confidence == new.confidence
+            }
+            _ => true,
+        };
+        if let Some(entry) = self
+            .authors
+            .iter_mut()
+            .find(|a| a.author == author && a.generated == generated && same_agent(a))
+        {
             entry.add_lines(&lines_to_remove);
             // Update agent metadata if provided and not already set
             if agent_metadata.is_some() && entry.agent_metadata.is_none() {
@@ -380,6 +433,7 @@ impl FileAuthorship {
         } else {
             // Create new author entry
             let mut new_entry = AuthorEntry::new_with_metadata(author.to_string(), agent_metadata);
+            new_entry.generated = generated;
             new_entry.add_lines(&lines_to_remove);
             self.authors.push(new_entry);
         }
@@ -390,14 +444,19 @@ impl FileAuthorship {
         self.authors.iter().all(|a| a.is_empty())
     }
 
+    #[allow(dead_code)]
     pub fn get_author(&self, line: u32) -> Option<&str> {
+        self.get_author_entry(line)
+            .map(|entry| entry.author.as_str())
+    }
+
+    /// Get the full author entry (including agent metadata) for a line
+    pub fn get_author_entry(&self, line: u32) -> Option<&AuthorEntry> {
         // Check authors in reverse order (most recent first)
-        for author in self.authors.iter().rev() {
-            if let Some(author) = author.get_author_for_line(line) {
-                return Some(author);
-            }
-        }
-        None
+        self.authors
+            .iter()
+            .rev()
+            .find(|author| author.get_author_for_line(line).is_some())
     }
 }
 
@@ -406,11 +465,17 @@ impl fmt::Display for FileAuthorship {
         write!(f, "{}", self.file)?;
         for author in &self.authors {
             write!(f, "\n  Author: {}", author.author)?;
+            if author.generated {
+                write!(f, " (generated)")?;
+            }
             if let Some(ref metadata) = author.agent_metadata {
                 write!(f, " (model: {}", metadata.model)?;
                 if let Some(ref human_author) = metadata.human_author {
                     write!(f, ", human: {}", human_author)?;
                 }
+                if !metadata.confidence.is_high() {
+                    write!(f, ", low confidence")?;
+                }
                 write!(f, ")")?;
             }
             for range in &author.lines {
@@ -457,7 +522,7 @@ impl AuthorshipLog {
                     };
 
                     for author in &mut file_auth.authors {
-                        author.remove_lines(&[to_remove.clone()]);
+                        author.remove_lines(std::slice::from_ref(&to_remove));
                     }
                 }
 
@@ -473,7 +538,12 @@ impl AuthorshipLog {
                         }
                     }
                 }
-                if !added_lines.is_empty() {
+                if added_lines.is_empty() {
+                    continue;
+                }
+                if checkpoint.generated {
+                    file_auth.add_generated_lines(&checkpoint.author, &added_lines);
+                } else {
                     file_auth.add_lines(
                         &checkpoint.author,
                         &added_lines,
@@ -496,7 +566,7 @@ impl Default for AuthorshipLog {
 
 impl fmt::Display for AuthorshipLog {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        for (_file_path, file_auth) in &self.files {
+        for file_auth in self.files.values() {
             writeln!(f, "{}", file_auth)?;
         }
         Ok(())
@@ -854,6 +924,16 @@ mod tests {
         assert_eq!(deserialized, ranges);
     }
 
+    #[test]
+    fn test_line_range_len() {
+        assert_eq!(LineRange::Single(7).len(), 1);
+        assert_eq!(LineRange::Range(2, 4).len(), 3);
+        assert_eq!(
+            LineRange::Range(2, 4).len() as usize,
+            LineRange::Range(2, 4).expand().len()
+        );
+    }
+
     #[test]
     fn test_new_authorship_format_serialization() {
         use serde_json;
@@ -885,6 +965,31 @@ mod tests {
         assert_eq!(aidan_entry.lines.len(), 1); // Should be compressed to one range
     }
 
+    #[test]
+    fn test_models_of_one_agent_keep_separate_entries() {
+        use crate::log_fmt::working_log::AgentMetadata;
+
+        let metadata = |model: &str| AgentMetadata {
+            model: model.to_string(),
+            human_author: None,
+            confidence: Default::default(),
+        };
+        let mut file_auth = FileAuthorship::new("src/test.rs".to_string());
+        file_auth.add_lines("claude", &[1, 2], Some(metadata("claude-4-sonnet")));
+        file_auth.add_lines("claude", &[3], Some(metadata("claude-4-opus")));
+        file_auth.add_lines("claude", &[4], Some(metadata("claude-4-sonnet")));
+
+        assert_eq!(file_auth.authors.len(), 2);
+        let model = |line| {
+            file_auth
+                .get_author_entry(line)
+                .and_then(|entry| entry.agent_metadata.as_ref())
+                .map(|metadata| metadata.model.clone())
+        };
+        assert_eq!(model(3).as_deref(), Some("claude-4-opus"));
+        assert_eq!(model(4).as_deref(), Some("claude-4-sonnet"));
+    }
+
     #[test]
     fn test_agent_metadata_integration() {
         use crate::log_fmt::working_log::AgentMetadata;
@@ -895,6 +1000,7 @@ mod tests {
         let agent_metadata = AgentMetadata {
             model: "claude-3-sonnet".to_string(),
             human_author: Some("john.doe".to_string()),
+            confidence: Default::default(),
         };
 
         let checkpoint = Checkpoint::new_with_metadata(
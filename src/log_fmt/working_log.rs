@@ -27,6 +27,11 @@ impl Line {
         }
     }
 
+    /// Number of lines covered
+    pub fn line_count(&self) -> u32 {
+        self.end() - self.start() + 1
+    }
+
     /// Check if this line/range contains a given line number
     pub fn contains(&self, line_number: u32) -> bool {
         match self {
@@ -54,6 +59,10 @@ pub struct WorkingLogEntry {
     pub added_lines: Vec<Line>,
     /// List of lines or line ranges that were deleted
     pub deleted_lines: Vec<Line>,
+    /// The file was over the checkpoint size limit, so only that it changed is recorded and
+    /// no lines are
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub file_level: bool,
 }
 
 #[allow(dead_code)]
@@ -64,6 +73,15 @@ impl WorkingLogEntry {
             file,
             added_lines,
             deleted_lines,
+            file_level: false,
+        }
+    }
+
+    /// An entry that records the file changed without saying which lines
+    pub fn file_level(file: String) -> Self {
+        Self {
+            file_level: true,
+            ..Self::new(file, Vec::new(), Vec::new())
         }
     }
 
@@ -107,10 +125,38 @@ impl WorkingLogEntry {
     }
 }
 
+/// How sure the recorder was that an edit came from the agent
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Confidence {
+    /// Inferred, e.g. forced to AI without knowing which agent made the edit
+    Low,
+    /// Reported by the agent's own tool-use hook
+    #[default]
+    High,
+}
+
+impl Confidence {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "low" => Some(Confidence::Low),
+            "high" => Some(Confidence::High),
+            _ => None,
+        }
+    }
+
+    pub fn is_high(&self) -> bool {
+        *self == Confidence::High
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AgentMetadata {
     pub model: String,
     pub human_author: Option<String>,
+    /// Records written before confidence existed were all explicit, so absent means high
+    #[serde(default, skip_serializing_if = "Confidence::is_high")]
+    pub confidence: Confidence,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -121,6 +167,26 @@ pub struct Checkpoint {
     pub entries: Vec<WorkingLogEntry>,
     pub timestamp: u64,
     pub agent_metadata: Option<AgentMetadata>,
+    /// Prompt or message recorded with the checkpoint (`--message`, or the `--hook-input` payload)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>,
+    /// Written by `checkpoint --touch-only`: the entries name files the agent announced it
+    /// would edit and carry no lines until a later checkpoint finalizes them
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub touch_only: bool,
+    /// Written by `checkpoint --snapshot`: a tree with the full content of every file in
+    /// `entries` as the checkpoint saw it. Unlike the line ranges this costs a blob per file
+    /// per snapshot (packing only deduplicates identical content), so it is opt-in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_tree: Option<String>,
+    /// Written by `checkpoint --author-type generated`, or for paths `.git-ai/attributes`
+    /// marks `generated`: the edits came from a code generator rather than a person or agent
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub generated: bool,
+    /// The author's name exactly as configured, hex-encoded, when it is not valid UTF-8 and
+    /// `author` only holds its lossy decoding
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author_raw: Option<String>,
 }
 
 impl Checkpoint {
@@ -142,6 +208,27 @@ impl Checkpoint {
             entries,
             timestamp,
             agent_metadata: None,
+            prompt: None,
+            touch_only: false,
+            content_tree: None,
+            generated: false,
+            author_raw: None,
+        }
+    }
+
+    /// Record `raw` as the author's name as configured when `author` cannot hold it
+    pub fn set_author_bytes(&mut self, raw: &[u8]) {
+        self.author_raw = std::str::from_utf8(raw)
+            .is_err()
+            .then(|| raw.iter().map(|byte| format!("{:02x}", byte)).collect());
+    }
+
+    /// Author label for display, e.g. "Claude [AI: claude-4-sonnet]" or "protoc [generated]"
+    pub fn attribution(&self) -> String {
+        match &self.agent_metadata {
+            Some(metadata) => format!("{} [AI: {}]", self.author, metadata.model),
+            None if self.generated => format!("{} [generated]", self.author),
+            None => self.author.clone(),
         }
     }
 
@@ -164,6 +251,11 @@ impl Checkpoint {
             entries,
             timestamp,
             agent_metadata: Some(agent_metadata),
+            prompt: None,
+            touch_only: false,
+            content_tree: None,
+            generated: false,
+            author_raw: None,
         }
     }
 }
@@ -302,6 +394,7 @@ mod tests {
         let agent_metadata = AgentMetadata {
             model: "claude-3-sonnet".to_string(),
             human_author: Some("john.doe".to_string()),
+            confidence: Confidence::Low,
         };
 
         let checkpoint = Checkpoint::new_with_metadata(
@@ -326,5 +419,11 @@ mod tests {
             deserialized_metadata.human_author.as_deref(),
             Some("john.doe")
         );
+        assert_eq!(deserialized_metadata.confidence, Confidence::Low);
+
+        // Older records carry no confidence and were all explicit
+        let legacy: AgentMetadata =
+            serde_json::from_str(r#"{"model":"claude","human_author":null}"#).unwrap();
+        assert_eq!(legacy.confidence, Confidence::High);
     }
 }
@@ -6,7 +6,8 @@ mod utils;
 
 use clap::Parser;
 use git::find_repository;
-use git::refs::AI_AUTHORSHIP_REFSPEC;
+use git::refs::authorship_refspec;
+use git::repository::get_default_remote;
 use std::io::{IsTerminal, Write};
 use std::process::Command;
 use utils::debug_log;
@@ -20,7 +21,7 @@ use crate::git::refs::DEFAULT_REFSPEC;
 #[command(disable_help_flag = true)]
 struct Cli {
     /// Git command and arguments
-    #[arg(trailing_var_arg = true)]
+    #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
     args: Vec<String>,
 }
 
@@ -39,7 +40,16 @@ fn main() {
 
     let cli = Cli::parse();
 
-    if cli.args.is_empty() {
+    // `git ai <command>` reaches a git-named install as `ai <command>`. Handle it here: git
+    // would only dispatch it back to git-ai.
+    let (binary_name, cli_args) = match cli.args.split_first() {
+        Some((first, rest)) if binary_name == "git" && first == "ai" => {
+            ("git-ai".to_string(), rest)
+        }
+        _ => (binary_name, cli.args.as_slice()),
+    };
+
+    if cli_args.is_empty() {
         // No arguments provided, show appropriate help
         if binary_name == "git" {
             // User called 'git' (via alias), show git help
@@ -48,169 +58,1304 @@ fn main() {
             // User called 'git-ai', show git-ai specific help
             print_help();
         }
-        return;
+        return;
+    }
+
+    let command = &cli_args[0];
+    let args = &cli_args[1..];
+
+    // As git-ai (also `git ai`, which git runs as git-ai), help and version are git-ai's own.
+    // `git ai --help` never gets here: git turns it into `git help ai` and looks for a man page.
+    if binary_name != "git" {
+        match command.as_str() {
+            "-h" | "--help" => {
+                print_help();
+                return;
+            }
+            "help" if args.is_empty() => {
+                print_help();
+                return;
+            }
+            "version" => {
+                println!("git-ai {}", env!("CARGO_PKG_VERSION"));
+                return;
+            }
+            _ => {}
+        }
+    }
+
+    match command.as_str() {
+        "checkpoint" => {
+            handle_checkpoint(args);
+        }
+        "blame" => {
+            debug_log("overriding: git blame");
+            handle_blame(args);
+        }
+        "stats" => {
+            handle_stats(args);
+        }
+        "log" if commands::log::is_git_ai_log(args) => {
+            handle_log(args);
+        }
+        "status" if args.iter().any(|arg| arg == "--ai") => {
+            handle_status(args);
+        }
+        "search" => {
+            handle_search(args);
+        }
+        "completions" => {
+            handle_completions(args);
+        }
+        "mark-ai" => {
+            handle_mark_ai(args);
+        }
+        "doctor" => {
+            handle_doctor(args);
+        }
+        "hook-exec" => {
+            handle_hook_exec(args);
+        }
+        "hooks" => {
+            handle_hooks(args);
+        }
+        "gate" => {
+            handle_gate(args);
+        }
+        "export" => {
+            handle_export(args);
+        }
+        "import" => {
+            handle_import(args);
+        }
+        "apply" => {
+            handle_apply(args);
+        }
+        "reset-attribution" => {
+            handle_reset_attribution(args);
+        }
+        "diff-checkpoints" => {
+            handle_diff_checkpoints(args);
+        }
+        "commit" => {
+            // debug_log(&format!("wrapping: git commit"));
+            handle_commit(args);
+        }
+        "cherry-pick" => {
+            handle_cherry_pick(args);
+        }
+        "pre-commit" => {
+            // Backwards compatibility: do nothing and exit 0
+            std::process::exit(0);
+        }
+        "post-commit" => {
+            // Backwards compatibility: do nothing and exit 0
+            std::process::exit(0);
+        }
+        "post-rewrite" => {
+            handle_post_rewrite();
+        }
+        "init"
+            if args
+                .iter()
+                .any(|arg| arg.starts_with("--with-presets") || arg == "--install-hooks") =>
+        {
+            handle_init_presets(args);
+        }
+        "fetch" => {
+            handle_fetch_or_pull("fetch", args);
+        }
+        "push" => {
+            handle_fetch_or_pull("push", args);
+        }
+        _ => {
+            debug_log(&format!("proxying: git {}", command));
+            // Proxy all other commands to git
+            proxy_to_git(cli_args);
+        }
+    }
+}
+
+fn handle_checkpoint(args: &[String]) {
+    // Parse checkpoint-specific arguments
+    let mut author = None;
+    let mut show_working_log = false;
+    let mut reset = false;
+    let mut model = None;
+    let mut author_type = None;
+    let mut confidence = None;
+    let mut message = None;
+    let mut scope = commands::checkpoint::ChangeScope::All;
+    let mut expire_working_log = None;
+    let mut max_ms = None;
+    let mut max_file_size = None;
+    let mut json = false;
+    let mut repo_path = None;
+    let mut verify_clean = false;
+    let mut allow = Vec::new();
+    let mut link_commit = None;
+    let mut overwrite = false;
+    let mut touch_only = false;
+    let mut snapshot = false;
+    let mut detect_model = false;
+    let mut touch_files: Vec<String> = Vec::new();
+    let mut on_branch = Vec::new();
+    let mut skip_branch = Vec::new();
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--author" => {
+                if i + 1 < args.len() {
+                    author = Some(args[i + 1].clone());
+                    i += 2;
+                } else {
+                    eprintln!("Error: --author requires a value");
+                    std::process::exit(1);
+                }
+            }
+            "--show-working-log" => {
+                show_working_log = true;
+                i += 1;
+            }
+            "--verify-clean" => {
+                verify_clean = true;
+                i += 1;
+            }
+            "--allow" => {
+                if i + 1 < args.len() {
+                    allow.push(args[i + 1].clone());
+                    i += 2;
+                } else {
+                    eprintln!("Error: --allow requires a glob");
+                    std::process::exit(1);
+                }
+            }
+            "--on-branch" | "--skip-branch" => {
+                let Some(glob) = args.get(i + 1) else {
+                    eprintln!("Error: {} requires a branch glob", args[i]);
+                    std::process::exit(1);
+                };
+                if args[i] == "--on-branch" {
+                    on_branch.push(glob.clone());
+                } else {
+                    skip_branch.push(glob.clone());
+                }
+                i += 2;
+            }
+            "--link-commit" => {
+                if i + 1 < args.len() {
+                    link_commit = Some(args[i + 1].clone());
+                    i += 2;
+                } else {
+                    eprintln!("Error: --link-commit requires a commit");
+                    std::process::exit(1);
+                }
+            }
+            "--overwrite" => {
+                overwrite = true;
+                i += 1;
+            }
+            "--touch-only" => {
+                touch_only = true;
+                i += 1;
+            }
+            "--snapshot" => {
+                snapshot = true;
+                i += 1;
+            }
+            "--detect-model" => {
+                detect_model = true;
+                i += 1;
+            }
+            "--files" => {
+                if i + 1 < args.len() {
+                    touch_files.extend(
+                        args[i + 1]
+                            .split(',')
+                            .filter(|file| !file.trim().is_empty())
+                            .map(str::to_string),
+                    );
+                    i += 2;
+                } else {
+                    eprintln!("Error: --files requires a comma-separated list of paths");
+                    std::process::exit(1);
+                }
+            }
+            "--reset" => {
+                reset = true;
+                i += 1;
+            }
+            "--json" => {
+                json = true;
+                i += 1;
+            }
+            "--repo" => {
+                if i + 1 < args.len() {
+                    repo_path = Some(args[i + 1].clone());
+                    i += 2;
+                } else {
+                    eprintln!("Error: --repo requires a path");
+                    std::process::exit(1);
+                }
+            }
+            "--no-index" | "--staged-only" => {
+                let requested = if args[i] == "--no-index" {
+                    commands::checkpoint::ChangeScope::Unstaged
+                } else {
+                    commands::checkpoint::ChangeScope::Staged
+                };
+                if scope != commands::checkpoint::ChangeScope::All && scope != requested {
+                    eprintln!("Error: --no-index and --staged-only cannot be combined");
+                    std::process::exit(1);
+                }
+                scope = requested;
+                i += 1;
+            }
+            "--expire-working-log" => {
+                if i + 1 < args.len() {
+                    expire_working_log = Some(args[i + 1].clone());
+                    i += 2;
+                } else {
+                    eprintln!("Error: --expire-working-log requires a duration (e.g. 30d)");
+                    std::process::exit(1);
+                }
+            }
+            "--max-ms" => match args.get(i + 1).and_then(|value| value.parse::<u64>().ok()) {
+                Some(value) => {
+                    max_ms = Some(value);
+                    i += 2;
+                }
+                None => {
+                    eprintln!("Error: --max-ms requires a number of milliseconds");
+                    std::process::exit(1);
+                }
+            },
+            "--max-file-size" => {
+                match args.get(i + 1).and_then(|value| value.parse::<u64>().ok()) {
+                    Some(value) => {
+                        max_file_size = Some(value);
+                        i += 2;
+                    }
+                    None => {
+                        eprintln!("Error: --max-file-size requires a number of bytes");
+                        std::process::exit(1);
+                    }
+                }
+            }
+            "--model" => {
+                if i + 1 < args.len() {
+                    model = Some(args[i + 1].clone());
+                    i += 2;
+                } else {
+                    eprintln!("Error: --model requires a value");
+                    std::process::exit(1);
+                }
+            }
+            "--message" => {
+                if i + 1 < args.len() {
+                    message = Some(args[i + 1].clone());
+                    i += 2;
+                } else {
+                    eprintln!("Error: --message requires a value");
+                    std::process::exit(1);
+                }
+            }
+            "--hook-input" | "--hook-input-file" => {
+                // Editor hook payload: inline JSON, "-" for stdin, or a file path. Large
+                // payloads should come through stdin or a file to stay clear of argv limits.
+                let Some(value) = args.get(i + 1) else {
+                    eprintln!("Error: {} requires a value", args[i]);
+                    std::process::exit(1);
+                };
+                let payload = if args[i] == "--hook-input-file" {
+                    std::fs::read_to_string(value)
+                } else if value == "-" {
+                    let mut payload = String::new();
+                    std::io::Read::read_to_string(&mut std::io::stdin(), &mut payload)
+                        .map(|_| payload)
+                } else {
+                    Ok(value.clone())
+                };
+                match payload {
+                    Ok(payload) => {
+                        message = commands::checkpoint::prompt_from_hook_payload(&payload);
+                    }
+                    Err(e) => {
+                        eprintln!("Error: failed to read hook input: {}", e);
+                        std::process::exit(1);
+                    }
+                }
+                i += 2;
+            }
+            "--author-type" => {
+                match args
+                    .get(i + 1)
+                    .and_then(|value| commands::checkpoint::AuthorType::parse(value))
+                {
+                    Some(value) => {
+                        author_type = Some(value);
+                        i += 2;
+                    }
+                    None => {
+                        eprintln!("Error: --author-type requires 'human', 'ai' or 'generated'");
+                        std::process::exit(1);
+                    }
+                }
+            }
+            "--confidence" => {
+                match args
+                    .get(i + 1)
+                    .and_then(|value| log_fmt::working_log::Confidence::parse(value))
+                {
+                    Some(value) => {
+                        confidence = Some(value);
+                        i += 2;
+                    }
+                    None => {
+                        eprintln!("Error: --confidence requires 'high' or 'low'");
+                        std::process::exit(1);
+                    }
+                }
+            }
+
+            _ => {
+                eprintln!("Unknown checkpoint argument: {}", args[i]);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    // --detect-model fills in the model a wrapper didn't pass (--model still wins). When
+    // nothing identifies the agent the edit is still its own, with an unknown model.
+    let mut detected_args = Vec::new();
+    if detect_model && model.is_none() {
+        match commands::checkpoint::detect_model() {
+            Some(detected) => {
+                detected_args = vec!["--model".to_string(), detected.clone()];
+                model = Some(detected);
+            }
+            None if author_type.is_none() => {
+                detected_args = vec!["--author-type".to_string(), "ai".to_string()];
+                author_type = Some(commands::checkpoint::AuthorType::Ai);
+            }
+            None => {}
+        }
+    }
+
+    // --repo opens that repository as given instead of discovering one from the cwd
+    let repo = match &repo_path {
+        Some(path) => git::repository::open_repository(path),
+        None => find_repository(),
+    };
+    let repo = match repo {
+        Ok(repo) => repo,
+        Err(e) => {
+            eprintln!("Failed to find repository: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    if json && show_working_log {
+        eprintln!("Error: --json cannot be combined with --show-working-log");
+        std::process::exit(1);
+    }
+    if !allow.is_empty() && !verify_clean {
+        eprintln!("Error: --allow requires --verify-clean");
+        std::process::exit(1);
+    }
+    if overwrite && link_commit.is_none() {
+        eprintln!("Error: --overwrite requires --link-commit");
+        std::process::exit(1);
+    }
+    if link_commit.is_some() && (show_working_log || reset || verify_clean || max_ms.is_some()) {
+        eprintln!(
+            "Error: --link-commit cannot be combined with --show-working-log, --reset, --verify-clean or --max-ms"
+        );
+        std::process::exit(1);
+    }
+    if !touch_files.is_empty() && !touch_only {
+        eprintln!("Error: --files requires --touch-only");
+        std::process::exit(1);
+    }
+    if touch_only
+        && (show_working_log || reset || verify_clean || max_ms.is_some() || link_commit.is_some())
+    {
+        eprintln!(
+            "Error: --touch-only cannot be combined with --show-working-log, --reset, --verify-clean, --max-ms or --link-commit"
+        );
+        std::process::exit(1);
+    }
+    if snapshot && (touch_only || link_commit.is_some()) {
+        eprintln!(
+            "Error: --snapshot records file content and cannot be combined with --touch-only or --link-commit"
+        );
+        std::process::exit(1);
+    }
+
+    // Scope check for agent edits: refuse to checkpoint when anything outside the allowed
+    // globs changed, so the offenders can be reverted before they are attributed
+    if verify_clean {
+        match commands::checkpoint::verify_clean(&repo, &allow, scope) {
+            Ok(offenders) if offenders.is_empty() => {}
+            Ok(offenders) => {
+                eprintln!("Error: changes outside the allowed paths:");
+                for path in offenders {
+                    eprintln!("  {}", path);
+                }
+                std::process::exit(1);
+            }
+            Err(e) => {
+                eprintln!("Checkpoint failed: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    // After the scope check, which still guards a disabled repository
+    if !git::repository::tracking_enabled(&repo) {
+        debug_log("git-ai is disabled for this repository; skipping checkpoint");
+        if json {
+            println!("{}", serde_json::json!({ "status": "disabled" }));
+        }
+        std::process::exit(0);
+    }
+
+    // Branch guard: edits on excluded branches (release, hotfix) go unrecorded. Reading
+    // the log, resetting it and linking a named commit aren't edits, so they still work.
+    if !show_working_log && !reset && link_commit.is_none() {
+        match commands::checkpoint::branch_excluded(&repo, &on_branch, &skip_branch) {
+            Ok(false) => {}
+            Ok(true) => {
+                let branch = commands::checkpoint::current_branch(&repo);
+                debug_log(&format!(
+                    "Branch {} is excluded from tracking; skipping checkpoint",
+                    branch.as_deref().unwrap_or("(detached HEAD)")
+                ));
+                if json {
+                    println!(
+                        "{}",
+                        serde_json::json!({ "status": "skipped", "branch": branch })
+                    );
+                }
+                std::process::exit(0);
+            }
+            Err(e) => {
+                eprintln!("Checkpoint failed: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    // Time-bounded hook path: hand the same checkpoint to a background process, with the
+    // prompt already read from stdin passed along as --message
+    if let Some(max_ms) = max_ms {
+        let mut child_args = Vec::new();
+        let mut i = 0;
+        while i < args.len() {
+            match args[i].as_str() {
+                // The scope check already ran here, so the child skips --verify-clean and --allow
+                "--max-ms" | "--message" | "--hook-input" | "--hook-input-file" | "--allow" => {
+                    i += 2
+                }
+                // Detected here, where the agent is still an ancestor
+                "--json" | "--verify-clean" | "--detect-model" => i += 1,
+                _ => {
+                    child_args.push(args[i].clone());
+                    i += 1;
+                }
+            }
+        }
+        child_args.extend(detected_args);
+        if let Some(message) = &message {
+            child_args.push("--message".to_string());
+            child_args.push(message.clone());
+        }
+        match commands::checkpoint::run_with_budget(
+            &child_args,
+            std::time::Duration::from_millis(max_ms),
+        ) {
+            Ok(Some(status)) if !status.success() => {
+                eprintln!("Checkpoint failed: exited with {}", status);
+                std::process::exit(status.code().unwrap_or(1));
+            }
+            Ok(Some(_)) => {
+                if json {
+                    print_checkpoint_json(&repo);
+                }
+                return;
+            }
+            // Still writing in the background, so there is nothing to report yet
+            Ok(None) => {
+                if json {
+                    println!("{}", serde_json::json!({ "status": "pending" }));
+                }
+                return;
+            }
+            Err(e) => {
+                eprintln!("Checkpoint failed: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    // Get the current user name from git config
+    let default_user_name = git::repository::user_name(&repo).unwrap_or_else(|| {
+        eprintln!("Warning: git user.name not configured. Using 'unknown' as author.");
+        "unknown".to_string()
+    });
+
+    // Self-trimming for always-on hooks: the flag wins over git-ai.expireWorkingLog
+    let expire_working_log = expire_working_log.or_else(|| {
+        repo.config()
+            .and_then(|config| config.get_string("git-ai.expireWorkingLog"))
+            .ok()
+    });
+    let expire_after = expire_working_log.map(|value| {
+        utils::parse_duration(&value).unwrap_or_else(|| {
+            eprintln!(
+                "Error: invalid --expire-working-log duration '{}' (e.g. 30d, 12h)",
+                value
+            );
+            std::process::exit(1);
+        })
+    });
+
+    let final_author = author.as_ref().unwrap_or(&default_user_name);
+    let generated = author_type == Some(commands::checkpoint::AuthorType::Generated);
+    if generated && link_commit.is_some() {
+        eprintln!(
+            "Error: --author-type generated applies to work-tree checkpoints, not --link-commit"
+        );
+        std::process::exit(1);
+    }
+    let confidence =
+        commands::checkpoint::resolve_confidence(model.as_deref(), author_type, confidence);
+    let model = commands::checkpoint::resolve_model(model.as_deref(), author_type);
+
+    // Retroactive attribution: credit an existing commit's additions instead of the work tree
+    if let Some(commit) = &link_commit {
+        match commands::checkpoint::link_commit(
+            &repo,
+            commit,
+            final_author,
+            model.as_deref(),
+            Some(&default_user_name),
+            confidence,
+            overwrite,
+        ) {
+            Ok((ref_name, authorship_log)) => {
+                if json {
+                    println!(
+                        "{}",
+                        serde_json::json!({
+                            "status": "linked",
+                            "ref": format!("refs/{}", ref_name),
+                            "author": final_author,
+                            "model": model,
+                            "files": authorship_log.files.len(),
+                        })
+                    );
+                } else {
+                    eprintln!(
+                        "Linked attribution for {} file(s) to {}",
+                        authorship_log.files.len(),
+                        commit
+                    );
+                }
+            }
+            Err(e) => {
+                eprintln!("Checkpoint failed: {}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    // Intent only: name the files the agent is about to edit, for its next checkpoint to finalize
+    if touch_only {
+        let Some(model) = &model else {
+            eprintln!("Error: --touch-only records AI intent; pass --model or --author-type ai");
+            std::process::exit(1);
+        };
+        let agent_metadata = log_fmt::working_log::AgentMetadata {
+            model: model.clone(),
+            human_author: Some(default_user_name.clone()),
+            confidence,
+        };
+        let touch_files: Vec<String> = match touch_files
+            .iter()
+            .map(|file| git::repository::repo_relative_path(&repo, file.trim()))
+            .collect()
+        {
+            Ok(files) => files,
+            Err(e) => {
+                eprintln!("Checkpoint failed: {}", e);
+                std::process::exit(1);
+            }
+        };
+        match commands::checkpoint::touch(&repo, &touch_files, final_author, agent_metadata) {
+            Ok(touched) => {
+                if json {
+                    print_checkpoint_json(&repo);
+                } else {
+                    eprintln!("{} touched {} file(s)", final_author, touched);
+                }
+            }
+            Err(e) => {
+                eprintln!("Checkpoint failed: {}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    let options = commands::checkpoint::CheckpointOptions {
+        show_working_log,
+        reset,
+        quiet: json,
+        model,
+        human_author: Some(default_user_name.clone()),
+        confidence,
+        prompt: message,
+        scope,
+        snapshot,
+        max_file_size,
+        generated,
+        author_raw: author
+            .is_none()
+            .then(|| git::repository::user_name_bytes(&repo))
+            .flatten(),
+    };
+    if let Err(e) = commands::checkpoint(&repo, final_author, &options) {
+        eprintln!("Checkpoint failed: {}", e);
+        std::process::exit(1);
+    }
+    if json {
+        print_checkpoint_json(&repo);
+    }
+
+    if let Some(max_age) = expire_after {
+        let expired = commands::checkpoint::resolve_base_commit(&repo).and_then(|base| {
+            commands::checkpoint::expire_committed_working_logs(&repo, &base, max_age)
+        });
+        if let Err(e) = expired {
+            eprintln!("Failed to expire working log entries: {}", e);
+        }
+    }
+}
+
+fn print_checkpoint_json(repo: &git2::Repository) {
+    let summary = commands::checkpoint::last_checkpoint_json(repo)
+        .and_then(|summary| Ok(serde_json::to_string(&summary)?));
+    match summary {
+        Ok(summary) => println!("{}", summary),
+        Err(e) => {
+            eprintln!("Failed to read checkpoint: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn handle_blame(args: &[String]) {
+    if args.is_empty() {
+        eprintln!("Error: blame requires a file argument");
+        std::process::exit(1);
+    }
+
+    // Find the git repository
+    let repo = match find_repository() {
+        Ok(repo) => repo,
+        Err(e) => {
+            eprintln!("Failed to find repository: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    // Parse blame arguments
+    let (file_path, options) = match commands::blame::parse_blame_args(args) {
+        Ok(result) => result,
+        Err(e) => {
+            eprintln!("Failed to parse blame arguments: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let file_path = match git::repository::repo_relative_path(&repo, &file_path) {
+        Ok(path) => path,
+        Err(e) => {
+            eprintln!("Blame failed: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    // Check if this is an interactive terminal
+    let is_interactive = std::io::stdout().is_terminal();
+
+    if is_interactive && options.incremental {
+        // For incremental mode in interactive terminal, we need special handling
+        // This would typically involve a pager like less
+        let mut full_args = vec!["blame".to_string()];
+        full_args.extend_from_slice(args);
+        proxy_to_git(&full_args);
+        return;
+    }
+
+    if let Err(e) = commands::blame::run(&repo, &file_path, &options) {
+        eprintln!("Blame failed: {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn handle_stats(args: &[String]) {
+    // Find the git repository
+    let repo = match find_repository() {
+        Ok(repo) => repo,
+        Err(e) => {
+            eprintln!("Failed to find repository: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    // Parse stats arguments
+    let (revspec, options) = match commands::stats::parse_stats_args(args) {
+        Ok(result) => result,
+        Err(e) => {
+            eprintln!("Failed to parse stats arguments: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    if let Err(e) = commands::stats::run(&repo, revspec.as_deref(), &options) {
+        eprintln!("Stats failed: {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn handle_log(args: &[String]) {
+    // Find the git repository
+    let repo = match find_repository() {
+        Ok(repo) => repo,
+        Err(e) => {
+            eprintln!("Failed to find repository: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let options = match commands::log::parse_log_args(args) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("Failed to parse log arguments: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    if let Err(e) = commands::log::print(&repo, &options) {
+        eprintln!("Log failed: {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn handle_status(args: &[String]) {
+    let repo = match find_repository() {
+        Ok(repo) => repo,
+        Err(e) => {
+            eprintln!("Failed to find repository: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let options = match commands::status::parse_status_args(args) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("Failed to parse status arguments: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    match commands::status::run(&repo) {
+        Ok(paths) if options.porcelain => print!("{}", commands::status::porcelain(&paths)),
+        Ok(paths) => commands::status::print_status(&paths),
+        Err(e) => {
+            eprintln!("Status failed: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Entry point for editor hooks: `hook-exec <preset> [<event>]` with the payload on stdin
+fn handle_hook_exec(args: &[String]) {
+    let Some(preset) = args
+        .first()
+        .and_then(|preset| commands::init::Preset::parse(preset))
+    else {
+        eprintln!("Error: hook-exec requires a preset (cursor or claude)");
+        std::process::exit(1);
+    };
+    let event = args.get(1).map(String::as_str);
+
+    // Editors pipe the payload in; never wait on an interactive terminal
+    let mut payload = String::new();
+    if !std::io::stdin().is_terminal()
+        && let Err(e) = std::io::Read::read_to_string(&mut std::io::stdin(), &mut payload)
+    {
+        eprintln!("Error: failed to read hook input: {}", e);
+        std::process::exit(1);
+    }
+
+    if let Err(e) = commands::hook_exec::run(preset, &payload, event) {
+        eprintln!("Hook failed: {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn handle_doctor(args: &[String]) {
+    let mut json = false;
+    for arg in args {
+        match arg.as_str() {
+            "--json" => json = true,
+            _ => {
+                eprintln!("Unknown doctor argument: {}", arg);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    // Not being in a repository is one of the things doctor reports on
+    let repo = find_repository().ok();
+    let home = std::env::var_os("HOME").map(std::path::PathBuf::from);
+    let checks = commands::doctor::run_checks(repo.as_ref(), home.as_deref());
+
+    if json {
+        let status = checks
+            .iter()
+            .map(|check| check.status)
+            .max()
+            .unwrap_or(commands::doctor::CheckStatus::Pass);
+        let report = serde_json::json!({ "status": status, "checks": checks });
+        match serde_json::to_string_pretty(&report) {
+            Ok(report) => println!("{}", report),
+            Err(e) => {
+                eprintln!("Doctor failed: {}", e);
+                std::process::exit(2);
+            }
+        }
+    } else {
+        commands::doctor::print_checks(&checks);
+    }
+    std::process::exit(commands::doctor::exit_code(&checks));
+}
+
+fn handle_hooks(args: &[String]) {
+    let json = match commands::hooks::parse_hooks_args(args) {
+        Ok(json) => json,
+        Err(e) => {
+            eprintln!("Failed to parse hooks arguments: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let repo = match find_repository() {
+        Ok(repo) => repo,
+        Err(e) => {
+            eprintln!("Failed to find repository: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let hooks_dir = commands::hooks::hooks_dir(&repo);
+    let hooks = commands::hooks::run(&repo);
+    if json {
+        let report = serde_json::json!({
+            "hooks_dir": hooks_dir.display().to_string(),
+            "hooks": hooks,
+        });
+        match serde_json::to_string_pretty(&report) {
+            Ok(report) => println!("{}", report),
+            Err(e) => {
+                eprintln!("Hooks status failed: {}", e);
+                std::process::exit(1);
+            }
+        }
+    } else {
+        println!("Hooks in {}:", hooks_dir.display());
+        print!("{}", commands::hooks::render(&hooks));
+    }
+}
+
+fn handle_search(args: &[String]) {
+    // Find the git repository
+    let repo = match find_repository() {
+        Ok(repo) => repo,
+        Err(e) => {
+            eprintln!("Failed to find repository: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let (query, options) = match commands::search::parse_search_args(args) {
+        Ok(result) => result,
+        Err(e) => {
+            eprintln!("Failed to parse search arguments: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    match commands::search::run(&repo, &query, &options) {
+        Ok(matches) => commands::search::print_matches(&matches),
+        Err(e) => {
+            eprintln!("Search failed: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn handle_mark_ai(args: &[String]) {
+    // Find the git repository
+    let repo = match find_repository() {
+        Ok(repo) => repo,
+        Err(e) => {
+            eprintln!("Failed to find repository: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let mut options = match commands::mark_ai::parse_mark_ai_args(args) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("Failed to parse mark-ai arguments: {}", e);
+            std::process::exit(1);
+        }
+    };
+    if !git::repository::tracking_enabled(&repo) {
+        eprintln!("git-ai is disabled for this repository; nothing marked");
+        return;
+    }
+    options.file = match git::repository::repo_relative_path(&repo, &options.file) {
+        Ok(path) => path,
+        Err(e) => {
+            eprintln!("Mark-ai failed: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let human_author = git::repository::user_name(&repo).unwrap_or_else(|| "unknown".to_string());
+
+    match commands::mark_ai::run(&repo, &options, &human_author) {
+        Ok(marked) => eprintln!(
+            "Marked {} line(s) in {} as AI-authored ({})",
+            marked, options.file, options.model
+        ),
+        Err(e) => {
+            eprintln!("Mark-ai failed: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn handle_gate(args: &[String]) {
+    let repo = match find_repository() {
+        Ok(repo) => repo,
+        Err(e) => {
+            eprintln!("Failed to find repository: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let (revspec, options) = match commands::gate::parse_gate_args(args) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            eprintln!("Failed to parse gate arguments: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    match commands::gate::run(&repo, revspec.as_deref(), &options) {
+        Ok(outcome) => {
+            print!("{}", commands::gate::render(&outcome, &options));
+            if !outcome.passed() {
+                std::process::exit(1);
+            }
+        }
+        Err(e) => {
+            eprintln!("Gate failed: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn handle_export(args: &[String]) {
+    let repo = match find_repository() {
+        Ok(repo) => repo,
+        Err(e) => {
+            eprintln!("Failed to find repository: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let options = match commands::bundle::parse_export_args(args) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("Failed to parse export arguments: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    match commands::bundle::export(&repo, &options) {
+        Ok(logs) => eprintln!("Exported {} authorship log(s) to {}", logs, options.output),
+        Err(e) => {
+            eprintln!("Export failed: {}", e);
+            std::process::exit(1);
+        }
     }
+}
 
-    let command = &cli.args[0];
-    let args = &cli.args[1..];
+fn handle_import(args: &[String]) {
+    let repo = match find_repository() {
+        Ok(repo) => repo,
+        Err(e) => {
+            eprintln!("Failed to find repository: {}", e);
+            std::process::exit(1);
+        }
+    };
 
-    match command.as_str() {
-        "checkpoint" => {
-            handle_checkpoint(args);
+    let options = match commands::bundle::parse_import_args(args) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("Failed to parse import arguments: {}", e);
+            std::process::exit(1);
         }
-        "blame" => {
-            debug_log(&format!("overriding: git blame"));
-            handle_blame(args);
+    };
+
+    match commands::bundle::import(&repo, &options) {
+        Ok(summary) => eprintln!(
+            "Imported authorship logs from {} ({} new, {} merged, {} total)",
+            options.bundle, summary.added, summary.merged, summary.total
+        ),
+        Err(e) => {
+            eprintln!("Import failed: {}", e);
+            std::process::exit(1);
         }
-        "commit" => {
-            // debug_log(&format!("wrapping: git commit"));
-            handle_commit(args);
+    }
+}
+
+fn handle_reset_attribution(args: &[String]) {
+    let scope = match commands::reset_attribution::parse_reset_attribution_args(args) {
+        Ok(scope) => scope,
+        Err(e) => {
+            eprintln!("Failed to parse reset-attribution arguments: {}", e);
+            std::process::exit(1);
         }
-        "pre-commit" => {
-            // Backwards compatibility: do nothing and exit 0
-            std::process::exit(0);
+    };
+
+    let repo = match find_repository() {
+        Ok(repo) => repo,
+        Err(e) => {
+            eprintln!("Failed to find repository: {}", e);
+            std::process::exit(1);
         }
-        "post-commit" => {
-            // Backwards compatibility: do nothing and exit 0
-            std::process::exit(0);
+    };
+
+    match commands::reset_attribution::run(&repo, &scope) {
+        Ok(deleted) if deleted.is_empty() => eprintln!("No attribution to reset"),
+        Ok(deleted) => {
+            for ref_name in &deleted {
+                println!("deleted {}", ref_name);
+            }
+            eprintln!("Deleted {} attribution ref(s)", deleted.len());
         }
-        "fetch" => {
-            handle_fetch_or_pull("fetch", args);
+        Err(e) => {
+            eprintln!("Reset failed: {}", e);
+            std::process::exit(1);
         }
-        "push" => {
-            handle_fetch_or_pull("push", args);
+    }
+}
+
+fn handle_diff_checkpoints(args: &[String]) {
+    let (a, b) = match commands::diff_checkpoints::parse_diff_checkpoints_args(args) {
+        Ok(sides) => sides,
+        Err(e) => {
+            eprintln!("Failed to parse diff-checkpoints arguments: {}", e);
+            std::process::exit(1);
         }
-        _ => {
-            debug_log(&format!("proxying: git {}", command));
-            // Proxy all other commands to git
-            proxy_to_git(&cli.args);
+    };
+
+    let repo = match find_repository() {
+        Ok(repo) => repo,
+        Err(e) => {
+            eprintln!("Failed to find repository: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    match commands::diff_checkpoints::run(&repo, &a, &b) {
+        Ok(output) => print!("{}", output),
+        Err(e) => {
+            eprintln!("diff-checkpoints failed: {}", e);
+            std::process::exit(1);
         }
     }
 }
 
-fn handle_checkpoint(args: &[String]) {
-    // Parse checkpoint-specific arguments
-    let mut author = None;
-    let mut show_working_log = false;
-    let mut reset = false;
-    let mut model = None;
+fn handle_completions(args: &[String]) {
+    let Some(shell) = args.first() else {
+        eprintln!("Error: completions requires a shell (bash, zsh, fish or powershell)");
+        std::process::exit(1);
+    };
+
+    match commands::completions::run(shell) {
+        Ok(script) => print!("{}", script),
+        Err(e) => {
+            eprintln!("Completions failed: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
 
+fn handle_init_presets(args: &[String]) {
+    // Parse init-specific arguments (plain `git-ai init` is proxied to `git init`)
+    let mut presets_arg = None;
+    let mut install_hooks = false;
     let mut i = 0;
     while i < args.len() {
         match args[i].as_str() {
-            "--author" => {
+            "--install-hooks" => {
+                install_hooks = true;
+                i += 1;
+            }
+            "--with-presets" => {
                 if i + 1 < args.len() {
-                    author = Some(args[i + 1].clone());
+                    presets_arg = Some(args[i + 1].clone());
                     i += 2;
                 } else {
-                    eprintln!("Error: --author requires a value");
+                    eprintln!("Error: --with-presets requires a value (e.g. cursor,claude)");
                     std::process::exit(1);
                 }
             }
-            "--show-working-log" => {
-                show_working_log = true;
-                i += 1;
-            }
-            "--reset" => {
-                reset = true;
+            arg if arg.starts_with("--with-presets=") => {
+                presets_arg = Some(arg["--with-presets=".len()..].to_string());
                 i += 1;
             }
-            "--model" => {
-                if i + 1 < args.len() {
-                    model = Some(args[i + 1].clone());
-                    i += 2;
-                } else {
-                    eprintln!("Error: --model requires a value");
-                    std::process::exit(1);
-                }
+            _ => {
+                eprintln!("Unknown init argument: {}", args[i]);
+                std::process::exit(1);
             }
+        }
+    }
 
-            _ => {
-                eprintln!("Unknown checkpoint argument: {}", args[i]);
+    if install_hooks {
+        let installed = find_repository().and_then(|repo| commands::init::install_git_hooks(&repo));
+        match installed {
+            Ok(path) => println!("Installed git hook {}", path.display()),
+            Err(e) => {
+                eprintln!("Init failed: {}", e);
                 std::process::exit(1);
             }
         }
+        if presets_arg.is_none() {
+            return;
+        }
     }
 
-    // Find the git repository
-    let repo = match find_repository() {
-        Ok(repo) => repo,
+    let presets = match commands::init::parse_presets(presets_arg.as_deref().unwrap_or("")) {
+        Ok(presets) => presets,
         Err(e) => {
-            eprintln!("Failed to find repository: {}", e);
+            eprintln!("Failed to parse init arguments: {}", e);
             std::process::exit(1);
         }
     };
 
-    // Get the current user name from git config
-    let default_user_name = match repo.config() {
-        Ok(config) => match config.get_string("user.name") {
-            Ok(name) => name,
-            Err(_) => {
-                eprintln!("Warning: git user.name not configured. Using 'unknown' as author.");
-                "unknown".to_string()
-            }
-        },
-        Err(_) => {
-            eprintln!("Warning: Failed to get git config. Using 'unknown' as author.");
-            "unknown".to_string()
+    let home = match std::env::var_os("HOME") {
+        Some(home) => std::path::PathBuf::from(home),
+        None => {
+            eprintln!("Error: HOME is not set");
+            std::process::exit(1);
         }
     };
 
-    let final_author = author.as_ref().unwrap_or(&default_user_name);
-
-    if let Err(e) = commands::checkpoint(
-        &repo,
-        final_author,
-        show_working_log,
-        reset,
-        false,
-        model.as_deref(),
-        Some(&default_user_name),
-    ) {
-        eprintln!("Checkpoint failed: {}", e);
-        std::process::exit(1);
+    match commands::init::run(&home, &presets) {
+        Ok(written) => {
+            for config in written {
+                match &config.backup {
+                    Some(backup) if config.kept_backup => println!(
+                        "Kept the earlier backup of {} at {}",
+                        config.path.display(),
+                        backup.display()
+                    ),
+                    Some(backup) => println!(
+                        "Backed up {} to {}",
+                        config.path.display(),
+                        backup.display()
+                    ),
+                    None => {}
+                }
+                println!(
+                    "Wrote {:?} hooks to {}:",
+                    config.preset,
+                    config.path.display()
+                );
+                println!("{}", config.contents);
+            }
+        }
+        Err(e) => {
+            eprintln!("Init failed: {}", e);
+            std::process::exit(1);
+        }
     }
 }
 
-fn handle_blame(args: &[String]) {
-    if args.is_empty() {
-        eprintln!("Error: blame requires a file argument");
-        std::process::exit(1);
-    }
-
-    // Find the git repository
+/// Called by the post-rewrite git hook with the rewritten SHAs on stdin. Never fails the
+/// rewrite itself: errors are reported and the hook still exits 0.
+fn handle_post_rewrite() {
     let repo = match find_repository() {
         Ok(repo) => repo,
         Err(e) => {
             eprintln!("Failed to find repository: {}", e);
-            std::process::exit(1);
+            return;
         }
     };
+    if !git::repository::tracking_enabled(&repo) {
+        return;
+    }
 
-    // Parse blame arguments
-    let (file_path, options) = match commands::blame::parse_blame_args(args) {
-        Ok(result) => result,
+    let mut rewrites = String::new();
+    if let Err(e) = std::io::Read::read_to_string(&mut std::io::stdin(), &mut rewrites) {
+        eprintln!("Failed to read rewritten commits: {}", e);
+        return;
+    }
+    if let Err(e) = git::post_rewrite::post_rewrite(&repo, &rewrites) {
+        eprintln!("Post-rewrite failed: {}", e);
+    }
+}
+
+fn handle_apply(args: &[String]) {
+    let options = match commands::apply::parse_apply_args(args) {
+        Ok(options) => options,
         Err(e) => {
-            eprintln!("Failed to parse blame arguments: {}", e);
+            eprintln!("Failed to parse apply arguments: {}", e);
             std::process::exit(1);
         }
     };
+    // git apply works outside a repository too; there is just nothing to attribute
+    let repo = match find_repository() {
+        Ok(repo) if git::repository::tracking_enabled(&repo) => repo,
+        _ => {
+            let mut full_args = vec!["apply".to_string()];
+            full_args.extend_from_slice(&options.git_args);
+            proxy_to_git(&full_args);
+            return;
+        }
+    };
 
-    // Check if this is an interactive terminal
-    let is_interactive = std::io::stdout().is_terminal();
-
-    if is_interactive && options.incremental {
-        // For incremental mode in interactive terminal, we need special handling
-        // This would typically involve a pager like less
-        let mut full_args = vec!["blame".to_string()];
-        full_args.extend_from_slice(args);
-        proxy_to_git(&full_args);
-        return;
-    }
+    let human_author = git::repository::user_name(&repo).unwrap_or_else(|| {
+        eprintln!("Warning: git user.name not configured. Using 'unknown' as author.");
+        "unknown".to_string()
+    });
 
-    if let Err(e) = commands::blame::run(&repo, &file_path, &options) {
-        eprintln!("Blame failed: {}", e);
-        std::process::exit(1);
+    match commands::apply::run(&repo, &options, &human_author) {
+        Ok(status) => std::process::exit(status.code().unwrap_or(1)),
+        Err(e) => {
+            eprintln!("Failed to execute git apply: {}", e);
+            std::process::exit(1);
+        }
     }
 }
 
@@ -224,27 +1369,33 @@ fn handle_commit(args: &[String]) {
         }
     };
 
+    // A disabled repository still commits, just without the pre- and post-commit steps
+    let enabled = git::repository::tracking_enabled(&repo);
+
     // Get the current user name from git config
-    let default_user_name = match repo.config() {
-        Ok(config) => match config.get_string("user.name") {
-            Ok(name) => name,
-            Err(_) => {
-                eprintln!("Warning: git user.name not configured. Using 'unknown' as author.");
-                "unknown".to_string()
-            }
-        },
-        Err(_) => {
-            eprintln!("Warning: Failed to get git config. Using 'unknown' as author.");
+    if enabled {
+        let default_user_name = git::repository::user_name(&repo).unwrap_or_else(|| {
+            eprintln!("Warning: git user.name not configured. Using 'unknown' as author.");
             "unknown".to_string()
-        }
-    };
+        });
 
-    // Run pre-commit logic
-    if let Err(e) = git::pre_commit::pre_commit(&repo, default_user_name.clone()) {
-        eprintln!("Pre-commit failed: {}", e);
-        std::process::exit(1);
+        // Run pre-commit logic
+        if let Err(e) = git::pre_commit::pre_commit(&repo, default_user_name) {
+            eprintln!("Pre-commit failed: {}", e);
+            std::process::exit(1);
+        }
     }
 
+    // An amend rewrites HEAD, so remember which commit it replaces
+    let amended_sha = if args.iter().any(|arg| arg == "--amend") {
+        repo.head()
+            .ok()
+            .and_then(|head| head.target())
+            .map(|oid| oid.to_string())
+    } else {
+        None
+    };
+
     // Proxy to git commit with interactive support
     let mut full_args = vec!["commit".to_string()];
     full_args.extend_from_slice(args);
@@ -259,8 +1410,14 @@ fn handle_commit(args: &[String]) {
                 Ok(status) => {
                     let code = status.code().unwrap_or(1);
                     // If commit succeeded, run post-commit
-                    if code == 0 {
-                        if let Err(e) = git::post_commit::post_commit(&repo, false) {
+                    if code == 0 && enabled {
+                        let result = match &amended_sha {
+                            Some(amended_sha) => {
+                                git::post_commit::post_amend(&repo, amended_sha, false)
+                            }
+                            None => git::post_commit::post_commit(&repo, false),
+                        };
+                        if let Err(e) = result {
                             eprintln!("Post-commit failed: {}", e);
                         }
                     }
@@ -279,6 +1436,35 @@ fn handle_commit(args: &[String]) {
     }
 }
 
+fn handle_cherry_pick(args: &[String]) {
+    let mut full_args = vec!["cherry-pick".to_string()];
+    full_args.extend_from_slice(args);
+    let repo = match find_repository() {
+        Ok(repo) if git::repository::tracking_enabled(&repo) => repo,
+        _ => {
+            proxy_to_git(&full_args);
+            return;
+        }
+    };
+
+    // What will be picked has to be known before git moves HEAD or clears the sequencer
+    let old_head = repo.head().ok().and_then(|head| head.target());
+    let sources = git::cherry_pick::cherry_pick_sources(&repo, args);
+
+    let code = match std::process::Command::new("git").args(&full_args).status() {
+        Ok(status) => status.code().unwrap_or(1),
+        Err(e) => {
+            eprintln!("Failed to execute git cherry-pick: {}", e);
+            std::process::exit(1);
+        }
+    };
+    // A pick that stops on a conflict may already have committed the ones before it
+    if let Err(e) = git::cherry_pick::carry_cherry_picks(&repo, old_head, &sources) {
+        eprintln!("Cherry-pick attribution failed: {}", e);
+    }
+    std::process::exit(code);
+}
+
 fn handle_fetch_or_pull(cmd: &str, args: &[String]) {
     // Find the git repository
     let repo = match find_repository() {
@@ -303,7 +1489,7 @@ fn handle_fetch_or_pull(cmd: &str, args: &[String]) {
             proxy_to_git(&[
                 cmd.to_string(),
                 default_remote,
-                AI_AUTHORSHIP_REFSPEC.to_string(),
+                authorship_refspec(&repo),
                 DEFAULT_REFSPEC.to_string(),
             ]);
         } else {
@@ -314,11 +1500,7 @@ fn handle_fetch_or_pull(cmd: &str, args: &[String]) {
     }
     if args.len() == 1 && remote_names.contains(&args[0]) {
         // git fetch <remote> or git pull <remote>: inject refspec after remote
-        proxy_to_git(&[
-            cmd.to_string(),
-            args[0].clone(),
-            AI_AUTHORSHIP_REFSPEC.to_string(),
-        ]);
+        proxy_to_git(&[cmd.to_string(), args[0].clone(), authorship_refspec(&repo)]);
         return;
     }
     // More complex: just proxy as-is
@@ -327,26 +1509,8 @@ fn handle_fetch_or_pull(cmd: &str, args: &[String]) {
     proxy_to_git(&full_args);
 }
 
-fn get_default_remote(repo: &git2::Repository) -> Option<String> {
-    if let Ok(remotes) = repo.remotes() {
-        if remotes.len() == 0 {
-            return None;
-        }
-        // Prefer 'origin' if it exists
-        for i in 0..remotes.len() {
-            if let Some(name) = remotes.get(i) {
-                if name == "origin" {
-                    return Some("origin".to_string());
-                }
-            }
-        }
-        // Otherwise, just use the first remote
-        remotes.get(0).map(|s| s.to_string())
-    } else {
-        None
-    }
-}
-
+/// Run the real git with `args`. The child inherits our environment as-is (GIT_DIR,
+/// GIT_AUTHOR_*, GIT_PAGER, ...), so nothing is copied or overridden here.
 fn proxy_to_git(args: &[String]) {
     // Check if this is an interactive command that needs special handling
     let interactive_commands = [
@@ -461,17 +1625,81 @@ fn parse_file_with_line_range(file_arg: &str) -> (String, Option<(u32, u32)>) {
 
 fn print_help() {
     eprintln!("git-ai - git proxy with AI authorship tracking");
-    eprintln!("");
+    eprintln!();
     eprintln!("Usage: git-ai <git or git-ai command> [args...]");
-    eprintln!("");
+    eprintln!("       git ai <git-ai command> [args...]   (git runs git-ai from PATH)");
+    eprintln!();
+    eprintln!("Use 'git ai -h' for this help: git answers 'git ai --help' with a man page.");
+    eprintln!();
     eprintln!("Commands:");
     eprintln!("  checkpoint    [new] checkpoint working changes and specify author");
+    eprintln!(
+        "                --snapshot also stores the changed files' full content (a blob per file per checkpoint)"
+    );
+    eprintln!(
+        "                --on-branch/--skip-branch <glob> skip checkpoints on other branches (git-ai.onBranch, git-ai.skipBranch)"
+    );
     eprintln!("  blame         [override] git blame with AI authorship tracking");
+    eprintln!("  stats         [new] human vs AI line stats for a commit or <base>..<head> range");
+    eprintln!(
+        "                offline unless --fetch (or git-ai.fetchAuthorship) pulls missing authorship refs first"
+    );
+    eprintln!(
+        "                --count-reformat-as attributed|human|ai|exclude counts whitespace-only re-adds (default: attributed)"
+    );
+    eprintln!(
+        "  log           [extended] [--ai] --file <path|dir|glob>... [-L a,b] [--author] [--model] [--limit n] [--reverse] [--oneline] [--model-breakdown] [--between <from> <to>] [--json|--json-lines] lists checkpoints that touched the paths"
+    );
+    eprintln!(
+        "                without --ai or a git-ai-only flag (--file, --between, --json, ...), runs git log"
+    );
+    eprintln!(
+        "  status        [extended] --ai [--porcelain] lists uncommitted paths with AI/human line counts"
+    );
+    eprintln!("  search        [new] search checkpoint prompts (case-insensitive, or --regex)");
+    eprintln!(
+        "  mark-ai       [new] <file> --lines 3-5,10 --model <model> tags pasted lines as AI"
+    );
+    eprintln!(
+        "  doctor        [new] check git-ai setup (--json for CI); exits 1 on warnings, 2 on failures"
+    );
+    eprintln!("  completions   [new] print a bash, zsh, fish or powershell completion script");
+    eprintln!(
+        "  hooks         [new] status [--json] shows which git hooks run git-ai and which are your own"
+    );
+    eprintln!(
+        "  hook-exec     [new] <preset> [<event>] runs an editor hook with its payload on stdin"
+    );
+    eprintln!(
+        "  gate          [new] [<range>] --max <pct> / --per-file-max <pct> [--min-lines n] [--fetch] exits 1 over the AI threshold"
+    );
+    eprintln!(
+        "  export        [new] --output <file> [--format git-bundle] bundles authorship refs for offline transfer"
+    );
+    eprintln!(
+        "  import        [new] <file> [--merge-strategy highest-confidence|prefer-local|prefer-incoming|union] fetches authorship refs from an exported bundle"
+    );
+    eprintln!(
+        "  diff-checkpoints [new] <a> [<b>|worktree] diffs two checkpoints (<n> or <base>:<n>) by who changed what"
+    );
+    eprintln!(
+        "  reset-attribution [new] --working | --commits <range> | --all, with --confirm; deletes attribution refs, never history"
+    );
+    eprintln!("  init          [extended] --with-presets cursor,claude writes editor hook configs");
+    eprintln!(
+        "                --install-hooks adds a post-rewrite hook that follows rebased commits"
+    );
+    eprintln!(
+        "  apply         [wrapper] git apply, attributing the hunks [--author-type human|ai|generated] [--model]"
+    );
     eprintln!(
         "  commit        [wrapper] pass through to 'git commit' with git-ai before/after hooks"
     );
+    eprintln!(
+        "  cherry-pick   [wrapper] git cherry-pick, carrying attribution to the picked commits (also --continue)"
+    );
     eprintln!("  fetch         [rewritten] Fetch from remote with AI authorship refs appended");
     eprintln!("  push          [rewritten] Push to remote with AI authorship refs appended");
-    eprintln!("");
+    eprintln!();
     std::process::exit(0);
 }
@@ -1,11 +1,12 @@
+use crate::commands::checkpoint::CheckpointOptions;
 use crate::commands::{blame, checkpoint};
 use crate::error::GitAiError;
-use crate::git::post_commit::post_commit;
+use crate::git::post_commit::{post_amend, post_commit};
 use git2::{Repository, Signature};
 use std::collections::BTreeMap;
 use std::fs;
 use std::path::PathBuf;
-use std::process::Command;
+use std::process::{Command, Output};
 
 pub struct TmpFile {
     repo: TmpRepo,
@@ -173,7 +174,7 @@ impl TmpFile {
 
         // Add to git index using the filename directly
         let mut index = self.repo.repo.index()?;
-        index.add_path(&std::path::Path::new(&self.filename))?;
+        index.add_path(std::path::Path::new(&self.filename))?;
         index.write()?;
 
         Ok(())
@@ -245,7 +246,7 @@ impl TmpRepo {
 
         if add_to_git {
             let mut index = self.repo.index()?;
-            index.add_path(&file_path.strip_prefix(&self.path).unwrap())?;
+            index.add_path(file_path.strip_prefix(&self.path).unwrap())?;
             index.write()?;
         }
 
@@ -265,15 +266,88 @@ impl TmpRepo {
         author: &str,
     ) -> Result<(usize, usize, usize), GitAiError> {
         checkpoint(
-            &self.repo, author, false, // show_working_log
-            false, // reset
-            true, None, // model
-            None, // human_author
+            &self.repo,
+            author,
+            &CheckpointOptions {
+                quiet: true,
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Triggers a checkpoint attributed to an AI agent running the given model
+    pub fn trigger_checkpoint_with_ai(
+        &self,
+        author: &str,
+        model: &str,
+    ) -> Result<(usize, usize, usize), GitAiError> {
+        checkpoint(
+            &self.repo,
+            author,
+            &CheckpointOptions {
+                quiet: true,
+                model: Some(model.to_string()),
+                human_author: Some("Test User".to_string()),
+                ..Default::default()
+            },
         )
     }
 
+    /// The built git-ai binary, ready to run in the repository. Tracking is on unless the
+    /// caller sets `GIT_AI_DISABLE` again.
+    pub fn git_ai_command(&self) -> Command {
+        let mut command =
+            Command::new(bin_dir().join(format!("git-ai{}", std::env::consts::EXE_SUFFIX)));
+        command.current_dir(&self.path).env_remove("GIT_AI_DISABLE");
+        command
+    }
+
+    /// Real git, ready to run in the repository, with the built git-ai first on PATH so
+    /// `git ai` dispatches to it
+    pub fn git_command(&self) -> Command {
+        let path = std::env::var_os("PATH").unwrap_or_default();
+        let path =
+            std::env::join_paths(std::iter::once(bin_dir()).chain(std::env::split_paths(&path)))
+                .expect("PATH holds an unjoinable entry");
+        let mut command = Command::new("git");
+        command.current_dir(&self.path).env("PATH", path);
+        command
+    }
+
+    /// Runs `git <args>` in the repository
+    pub fn git(&self, args: &[&str]) -> Output {
+        self.git_command()
+            .args(args)
+            .output()
+            .expect("Failed to run git")
+    }
+
+    /// Runs `git-ai <args>` in the repository
+    pub fn git_ai(&self, args: &[&str]) -> Output {
+        self.git_ai_with_env(args, &[])
+    }
+
+    /// Runs `git-ai <args>` in the repository with extra environment variables
+    pub fn git_ai_with_env(&self, args: &[&str], env: &[(&str, &str)]) -> Output {
+        self.git_ai_command()
+            .envs(env.iter().copied())
+            .args(args)
+            .output()
+            .expect("Failed to run git-ai")
+    }
+
     /// Commits all changes with the given message and runs post-commit hook
     pub fn commit_with_message(&self, message: &str) -> Result<(), GitAiError> {
+        self.commit_with_author(message, "Test User", "test@example.com")
+    }
+
+    /// Like `commit_with_message`, authored and committed as `name <email>`
+    pub fn commit_with_author(
+        &self,
+        message: &str,
+        name: &str,
+        email: &str,
+    ) -> Result<(), GitAiError> {
         // Add all files to the index
         let mut index = self.repo.index()?;
         index.add_all(["*"].iter(), git2::IndexAddOption::DEFAULT, None)?;
@@ -286,7 +360,7 @@ impl TmpRepo {
         // Use a fixed timestamp for stable test results
         // Unix timestamp for 2023-01-01 12:00:00 UTC
         let fixed_time = git2::Time::new(1672574400, 0);
-        let signature = Signature::new("Test User", "test@example.com", &fixed_time)?;
+        let signature = Signature::new(name, email, &fixed_time)?;
 
         // Check if there's a parent commit before we use it
         let _has_parent = if let Ok(head) = self.repo.head() {
@@ -312,7 +386,7 @@ impl TmpRepo {
 
         let _commit_id = if let Some(parent) = parent_commit {
             self.repo.commit(
-                Some(&"HEAD"),
+                Some("HEAD"),
                 &signature,
                 &signature,
                 message,
@@ -321,7 +395,7 @@ impl TmpRepo {
             )?
         } else {
             self.repo
-                .commit(Some(&"HEAD"), &signature, &signature, message, &tree, &[])?
+                .commit(Some("HEAD"), &signature, &signature, message, &tree, &[])?
         };
 
         println!("Commit ID: {}", _commit_id);
@@ -332,6 +406,23 @@ impl TmpRepo {
         Ok(())
     }
 
+    /// Amends HEAD with everything in the working directory, like `git-ai commit --amend`
+    pub fn amend_with_message(&self, message: &str) -> Result<(), GitAiError> {
+        let mut index = self.repo.index()?;
+        index.add_all(["*"].iter(), git2::IndexAddOption::DEFAULT, None)?;
+        index.write()?;
+        let tree = self.repo.find_tree(index.write_tree()?)?;
+
+        let head = self.repo.head()?.peel_to_commit()?;
+        let amended_sha = head.id().to_string();
+        let commit_id = head.amend(Some("HEAD"), None, None, None, Some(message), Some(&tree))?;
+        println!("Amended commit ID: {}", commit_id);
+
+        post_amend(&self.repo, &amended_sha, false)?;
+
+        Ok(())
+    }
+
     /// Creates a new branch and switches to it
     pub fn create_branch(&self, branch_name: &str) -> Result<(), GitAiError> {
         let head = self.repo.head()?;
@@ -370,7 +461,7 @@ impl TmpRepo {
     pub fn merge_branch(&self, branch_name: &str, message: &str) -> Result<(), GitAiError> {
         let output = Command::new("git")
             .current_dir(&self.path)
-            .args(&["merge", branch_name, "-m", message, "-X", "theirs"])
+            .args(["merge", branch_name, "-m", message, "-X", "theirs"])
             .output()
             .map_err(|e| GitAiError::Generic(format!("Failed to run git merge: {}", e)))?;
 
@@ -394,7 +485,7 @@ impl TmpRepo {
 
         let mut rebase = Command::new("git")
             .current_dir(&self.path)
-            .args(&["rebase", onto_branch])
+            .args(["rebase", onto_branch])
             .output()
             .map_err(|e| GitAiError::Generic(format!("Failed to run git rebase: {}", e)))?;
 
@@ -407,19 +498,19 @@ impl TmpRepo {
             // Overwrite with theirs (the branch we're rebasing onto)
             let theirs_content = Command::new("git")
                 .current_dir(&self.path)
-                .args(&["show", &format!("{}:lines.md", onto_branch)])
+                .args(["show", &format!("{}:lines.md", onto_branch)])
                 .output()
                 .map_err(|e| GitAiError::Generic(format!("Failed to get theirs: {}", e)))?;
             fs::write(&conflicted_file, &theirs_content.stdout)?;
             // Add and continue
             Command::new("git")
                 .current_dir(&self.path)
-                .args(&["add", "lines.md"])
+                .args(["add", "lines.md"])
                 .output()
                 .map_err(|e| GitAiError::Generic(format!("Failed to git add: {}", e)))?;
             rebase = Command::new("git")
                 .current_dir(&self.path)
-                .args(&["rebase", "--continue"])
+                .args(["rebase", "--continue"])
                 .output()
                 .map_err(|e| {
                     GitAiError::Generic(format!("Failed to git rebase --continue: {}", e))
@@ -475,12 +566,12 @@ impl TmpRepo {
         let refs = self.repo.references()?;
         for reference in refs {
             let reference = reference?;
-            if let Some(name) = reference.name() {
-                if name.starts_with("refs/heads/") {
-                    let branch_name = name.strip_prefix("refs/heads/").unwrap();
-                    if branch_name != current {
-                        return Ok(branch_name.to_string());
-                    }
+            if let Some(name) = reference.name()
+                && name.starts_with("refs/heads/")
+            {
+                let branch_name = name.strip_prefix("refs/heads/").unwrap();
+                if branch_name != current {
+                    return Ok(branch_name.to_string());
                 }
             }
         }
@@ -523,6 +614,24 @@ impl TmpRepo {
     }
 }
 
+/// The directory holding the built git-ai binary. Test binaries live in
+/// target/<profile>/deps, next to nothing but other tests.
+fn bin_dir() -> PathBuf {
+    let exe = std::env::current_exe().expect("Failed to locate the test binary");
+    let dir = exe.parent().expect("Test binary has no directory");
+    let dir = if dir.ends_with("deps") {
+        dir.parent().unwrap_or(dir)
+    } else {
+        dir
+    };
+    dir.to_path_buf()
+}
+
+/// Owned command-line arguments, as the `parse_*_args` functions take them
+pub fn args(values: &[&str]) -> Vec<String> {
+    values.iter().map(|value| value.to_string()).collect()
+}
+
 const ALPHABET: &str = "A
 B
 C
@@ -8,6 +8,271 @@
 /// * `msg` - The debug message to print
 pub fn debug_log(msg: &str) {
     if cfg!(debug_assertions) {
-        eprintln!("\x1b[1;33m[git-ai]\x1b[0m {}", msg);
+        eprintln!(
+            "{} {}",
+            ColorTheme::load().paint(ColorRole::DebugPrefix, "[git-ai]"),
+            msg
+        );
     }
 }
+
+/// What git-ai colors. Each role is configured as `git-ai.colors.<key>`, i.e. in a
+/// `[git-ai "colors"]` section of git config.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorRole {
+    /// The `[git-ai]` prefix of debug messages
+    DebugPrefix,
+    /// Blame lines written by an agent
+    AiLine,
+    /// Blame lines written by a person
+    HumanLine,
+    /// Human blame lines in a commit hunk that also has AI lines
+    MixedLine,
+    /// The blame gutter (commit, author, date, line number). Uncolored, it takes the
+    /// line's color along with the content.
+    Gutter,
+}
+
+impl ColorRole {
+    pub const ALL: [ColorRole; 5] = [
+        ColorRole::DebugPrefix,
+        ColorRole::AiLine,
+        ColorRole::HumanLine,
+        ColorRole::MixedLine,
+        ColorRole::Gutter,
+    ];
+
+    pub fn key(&self) -> &'static str {
+        match self {
+            ColorRole::DebugPrefix => "debug-prefix",
+            ColorRole::AiLine => "ai-line",
+            ColorRole::HumanLine => "human-line",
+            ColorRole::MixedLine => "mixed-line",
+            ColorRole::Gutter => "gutter",
+        }
+    }
+
+    /// Only the debug prefix is colored out of the box; blame lines stay plain until a
+    /// theme asks for them
+    fn default_sgr(&self) -> Option<&'static str> {
+        match self {
+            ColorRole::DebugPrefix => Some("1;33"),
+            _ => None,
+        }
+    }
+}
+
+/// A named set of colors for the blame roles, chosen with `blame --theme` or
+/// `git-ai.colors.theme`. Roles configured one by one still override it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThemePreset {
+    Dark,
+    Light,
+    HighContrast,
+    /// No hues at all, only bold, underline and dim
+    Mono,
+}
+
+impl ThemePreset {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "dark" => Some(ThemePreset::Dark),
+            "light" => Some(ThemePreset::Light),
+            "high-contrast" => Some(ThemePreset::HighContrast),
+            "mono" => Some(ThemePreset::Mono),
+            _ => None,
+        }
+    }
+
+    /// The preset's color for a role; the debug prefix keeps its default in every preset
+    fn sgr(&self, role: ColorRole) -> Option<&'static str> {
+        let [ai, human, mixed, gutter] = match self {
+            // Bright hues stand out on a dark background
+            ThemePreset::Dark => [Some("95"), None, Some("93"), Some("90")],
+            // Yellow washes out on a light one
+            ThemePreset::Light => [Some("35"), None, Some("34"), Some("90")],
+            // Backgrounds, so attribution reads at a glance whatever the terminal's colors
+            ThemePreset::HighContrast => [Some("1;97;45"), None, Some("1;30;43"), Some("1")],
+            ThemePreset::Mono => [Some("1"), None, Some("4"), Some("2")],
+        };
+        match role {
+            ColorRole::DebugPrefix => role.default_sgr(),
+            ColorRole::AiLine => ai,
+            ColorRole::HumanLine => human,
+            ColorRole::MixedLine => mixed,
+            ColorRole::Gutter => gutter,
+        }
+    }
+}
+
+/// Parse a color such as `yellow`, `bold bright-blue`, `dim`, or raw SGR parameters
+/// like `38;5;208`, into SGR parameters. `none` (or `normal`) turns the role off.
+pub fn parse_color(value: &str) -> Option<Option<String>> {
+    let value = value.trim().to_lowercase();
+    if value == "none" || value == "normal" {
+        return Some(None);
+    }
+    let mut codes = Vec::new();
+    for token in value.split_whitespace() {
+        let code = match token {
+            "bold" => "1".to_string(),
+            "dim" => "2".to_string(),
+            "italic" => "3".to_string(),
+            "underline" => "4".to_string(),
+            "reverse" => "7".to_string(),
+            raw if raw.chars().all(|c| c.is_ascii_digit() || c == ';') => raw.to_string(),
+            name => {
+                let (base, name) = match name.strip_prefix("bright-") {
+                    Some(name) => (90, name),
+                    None => (30, name),
+                };
+                let offset = [
+                    "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+                ]
+                .iter()
+                .position(|color| *color == name)?;
+                (base + offset).to_string()
+            }
+        };
+        codes.push(code);
+    }
+    if codes.is_empty() {
+        return None;
+    }
+    Some(Some(codes.join(";")))
+}
+
+/// Resolved colors for every role
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColorTheme {
+    /// False under `NO_COLOR`: nothing is colored, not even fixed highlights
+    pub enabled: bool,
+    sgr: [Option<String>; 5],
+}
+
+impl ColorTheme {
+    /// Theme from `git-ai.colors.*`, starting from `preset` or else the one
+    /// `git-ai.colors.theme` names. Unparseable values, an unknown preset name among them,
+    /// keep the role's default.
+    pub fn from_config(
+        config: Option<&git2::Config>,
+        no_color: bool,
+        preset: Option<ThemePreset>,
+    ) -> Self {
+        let preset = preset.or_else(|| {
+            let name = config?.get_string("git-ai.colors.theme").ok()?;
+            ThemePreset::parse(&name)
+        });
+        let sgr = ColorRole::ALL.map(|role| {
+            if no_color {
+                return None;
+            }
+            config
+                .and_then(|config| {
+                    config
+                        .get_string(&format!("git-ai.colors.{}", role.key()))
+                        .ok()
+                })
+                .and_then(|value| parse_color(&value))
+                .unwrap_or_else(|| match preset {
+                    Some(preset) => preset.sgr(role).map(str::to_string),
+                    None => role.default_sgr().map(str::to_string),
+                })
+        });
+        Self {
+            enabled: !no_color,
+            sgr,
+        }
+    }
+
+    /// The theme for this process: the current repository's git config (or the global one
+    /// outside a repository), honoring https://no-color.org
+    pub fn load() -> &'static ColorTheme {
+        static THEME: std::sync::OnceLock<ColorTheme> = std::sync::OnceLock::new();
+        THEME.get_or_init(|| Self::load_with_preset(None))
+    }
+
+    /// As `load`, but starting from `preset` (`None` for the configured one)
+    pub fn load_with_preset(preset: Option<ThemePreset>) -> ColorTheme {
+        let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+        let config = git2::Repository::discover(".")
+            .and_then(|repo| repo.config())
+            .or_else(|_| git2::Config::open_default())
+            .ok();
+        ColorTheme::from_config(config.as_ref(), no_color, preset)
+    }
+
+    /// SGR parameters for a role, if it is colored
+    pub fn sgr(&self, role: ColorRole) -> Option<&str> {
+        self.sgr[role as usize].as_deref()
+    }
+
+    /// `text` wrapped in the role's color, or unchanged when the role has none
+    pub fn paint(&self, role: ColorRole, text: &str) -> String {
+        match self.sgr(role) {
+            Some(sgr) => format!("\x1b[{}m{}\x1b[0m", sgr, text),
+            None => text.to_string(),
+        }
+    }
+}
+
+/// Width of the terminal: `COLUMNS`, else what `tput cols` reports for the terminal, else 80
+pub fn terminal_width() -> usize {
+    std::env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.trim().parse().ok())
+        .or_else(|| {
+            let output = std::process::Command::new("tput")
+                .arg("cols")
+                .stderr(std::process::Stdio::inherit())
+                .output()
+                .ok()?;
+            String::from_utf8_lossy(&output.stdout).trim().parse().ok()
+        })
+        .filter(|width: &usize| *width > 0)
+        .unwrap_or(80)
+}
+
+/// Parse a duration such as `30d`, `12h`, `45m`, `2w` or `90s` into seconds
+pub fn parse_duration(value: &str) -> Option<u64> {
+    let value = value.trim();
+    let split = value.find(|c: char| !c.is_ascii_digit())?;
+    let (amount, unit) = value.split_at(split);
+    let amount: u64 = amount.parse().ok()?;
+    let unit_seconds = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => return None,
+    };
+    amount.checked_mul(unit_seconds)
+}
+
+/// Anchored regex for a path glob matched against the whole repository-relative path: `*`
+/// and `?` stay within one directory, `**` crosses directories (`src/**` is everything
+/// under src)
+pub fn glob_to_regex(glob: &str) -> String {
+    let mut pattern = String::from("^");
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                // `**/` also matches no directory at all
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    pattern.push_str("(?:.*/)?");
+                } else {
+                    pattern.push_str(".*");
+                }
+            }
+            '*' => pattern.push_str("[^/]*"),
+            '?' => pattern.push_str("[^/]"),
+            c => pattern.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    pattern.push('$');
+    pattern
+}
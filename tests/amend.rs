@@ -0,0 +1,45 @@
+use git_ai::commands::stats;
+use git_ai::git::refs::{authorship_ref_name, get_reference};
+use git_ai::tmp_repo::TmpRepo;
+use tempfile::tempdir;
+
+#[test]
+fn test_amend_moves_attribution_to_rewritten_commit() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    let mut file = tmp_repo
+        .write_file("amended.txt", "human 1\nhuman 2\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("test_user")
+        .unwrap();
+    tmp_repo.commit_with_message("Add amended.txt").unwrap();
+    let original_sha = tmp_repo.head_commit_sha().unwrap();
+
+    file.append("ai 1\nai 2\n").unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo.amend_with_message("Add amended.txt").unwrap();
+    let amended_sha = tmp_repo.head_commit_sha().unwrap();
+    assert_ne!(original_sha, amended_sha);
+
+    let blame = tmp_repo.blame_for_file(&file, None).unwrap();
+    let authors: Vec<&str> = blame.values().map(String::as_str).collect();
+    assert_eq!(authors, ["test_user", "test_user", "Claude", "Claude"]);
+
+    let options = stats::StatsOptions {
+        no_fetch: true,
+        ..Default::default()
+    };
+    let report = stats::run(tmp_repo.repo(), None, &options).unwrap();
+    assert_eq!(report.ai_additions, 2);
+    assert_eq!(report.human_additions, 2);
+
+    // Nothing stays keyed on the commit the amend replaced
+    let repo = tmp_repo.repo();
+    assert!(get_reference(repo, &authorship_ref_name(repo, &original_sha)).is_err());
+    assert!(get_reference(repo, &format!("ai-working-log/{}", original_sha)).is_err());
+}
@@ -0,0 +1,109 @@
+use assert_cmd::Command;
+use git_ai::commands::checkpoint::resolve_base_commit;
+use git_ai::commands::stats;
+use git_ai::git::refs::get_reference_as_working_log;
+use git_ai::tmp_repo::TmpRepo;
+use tempfile::tempdir;
+
+const PATCH: &str = "diff --git a/patched.rs b/patched.rs
+new file mode 100644
+--- /dev/null
++++ b/patched.rs
+@@ -0,0 +1,2 @@
++fn one() {}
++fn two() {}
+";
+
+#[test]
+fn test_apply_attributes_applied_hunks() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    std::fs::write(repo_path.join("change.patch"), PATCH).unwrap();
+    let working_log_len = || {
+        let base_commit = resolve_base_commit(tmp_repo.repo()).unwrap();
+        get_reference_as_working_log(tmp_repo.repo(), &format!("ai-working-log/{}", base_commit))
+            .unwrap_or_default()
+            .len()
+    };
+
+    // Report-only applies touch nothing, so nothing is recorded
+    let output = tmp_repo.git_ai(&["apply", "--check", "change.patch"]);
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(working_log_len(), 0);
+
+    // A human edit already pending stays the human's
+    std::fs::write(repo_path.join("human.rs"), "fn mine() {}\n").unwrap();
+    let output = tmp_repo.git_ai(&[
+        "apply",
+        "--author-type",
+        "ai",
+        "--model",
+        "gpt-4o",
+        "change.patch",
+    ]);
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(
+        std::fs::read_to_string(repo_path.join("patched.rs")).unwrap(),
+        "fn one() {}\nfn two() {}\n"
+    );
+
+    std::fs::remove_file(repo_path.join("change.patch")).unwrap();
+    tmp_repo.commit_with_message("apply patch").unwrap();
+    let report = stats::run(
+        tmp_repo.repo(),
+        None,
+        &stats::StatsOptions {
+            no_fetch: true,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert_eq!(report.ai_additions, 2);
+    assert_eq!(report.human_additions, 1);
+    assert_eq!(
+        report.file_stats["patched.rs"].additions.get("gpt-4o"),
+        Some(&2)
+    );
+
+    // A patch that doesn't apply fails like git; only the usual checkpoint of pending edits ran
+    let before = working_log_len();
+    std::fs::write(repo_path.join("change.patch"), PATCH).unwrap();
+    let output = tmp_repo.git_ai(&["apply", "change.patch"]);
+    assert!(!output.status.success());
+    assert_eq!(working_log_len(), before + 1);
+}
+
+#[test]
+fn test_apply_without_tracking_is_plain_git_apply() {
+    let tmp_dir = tempdir().unwrap();
+    let outside = tmp_dir.path().join("outside");
+    std::fs::create_dir_all(&outside).unwrap();
+    std::fs::write(outside.join("change.patch"), PATCH).unwrap();
+
+    // No repository: git applies the patch, git-ai flags and all
+    let output = Command::cargo_bin("git-ai")
+        .unwrap()
+        .current_dir(&outside)
+        .env("GIT_CEILING_DIRECTORIES", tmp_dir.path())
+        .args(["apply", "--author-type", "ai", "change.patch"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    assert!(outside.join("patched.rs").exists());
+
+    // Tracking disabled: applied, but nothing recorded
+    let repo_path = tmp_dir.path().join("repo");
+    std::fs::create_dir_all(&repo_path).unwrap();
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    std::fs::write(repo_path.join("change.patch"), PATCH).unwrap();
+    let output = tmp_repo.git_ai_with_env(&["apply", "change.patch"], &[("GIT_AI_DISABLE", "1")]);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(repo_path.join("patched.rs").exists());
+    let base_commit = resolve_base_commit(tmp_repo.repo()).unwrap();
+    assert!(
+        get_reference_as_working_log(tmp_repo.repo(), &format!("ai-working-log/{}", base_commit))
+            .unwrap_or_default()
+            .is_empty()
+    );
+}
@@ -0,0 +1,209 @@
+use assert_cmd::Command;
+use git_ai::commands::checkpoint::{self, CheckpointOptions, resolve_base_commit};
+use git_ai::commands::stats::{self, StatsOptions};
+use git_ai::git::attributes::{Attributes, ForcedAttribution, PathPolicy};
+use git_ai::git::refs::get_reference_as_working_log;
+use git_ai::log_fmt::working_log::Confidence;
+use git_ai::tmp_repo::TmpRepo;
+use tempfile::tempdir;
+
+#[test]
+fn test_attributes_patterns_and_precedence() {
+    let attributes = Attributes::parse(
+        "\
+# policies
+vendor/ ignore
+docs/** force-human
+*.gen.rs force-ai min-confidence=high
+/src/gen.rs force-human
+docs/agents.md -force-human
+",
+    )
+    .unwrap();
+
+    assert!(attributes.policy("vendor/lib/a.js").ignore);
+    assert!(!attributes.policy("src/vendor.rs").ignore);
+    assert_eq!(
+        attributes.policy("docs/guide/intro.md").force,
+        Some(ForcedAttribution::Human)
+    );
+    assert_eq!(attributes.policy("docs/agents.md"), PathPolicy::default());
+    assert_eq!(
+        attributes.policy("deep/dir/parser.gen.rs"),
+        PathPolicy {
+            ignore: false,
+            force: Some(ForcedAttribution::Ai),
+            min_confidence: Some(Confidence::High),
+        }
+    );
+    // Anchored to the root, and later lines win
+    assert_eq!(
+        attributes.policy("src/gen.rs").force,
+        Some(ForcedAttribution::Human)
+    );
+    assert_eq!(attributes.policy("lib/src/gen.rs").force, None);
+
+    assert!(Attributes::parse("docs/\n").is_err());
+    assert!(Attributes::parse("docs/ min-confidence=medium\n").is_err());
+}
+
+#[test]
+fn test_attributes_in_checkpoint_and_stats() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+
+    // Committed by the agent before any policy existed
+    tmp_repo
+        .write_file("docs/guide.md", "written by the agent\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo.commit_with_message("Docs").unwrap();
+
+    tmp_repo
+        .write_file(
+            ".git-ai/attributes",
+            "vendor/ ignore\ndocs/ force-human\n*.gen.rs force-ai\n",
+            true,
+        )
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("Test User")
+        .unwrap();
+
+    tmp_repo
+        .write_file("vendor/lib.js", "third party\n", true)
+        .unwrap();
+    tmp_repo
+        .write_file("docs/guide.md", "written by the agent\nmore\n", true)
+        .unwrap();
+    tmp_repo
+        .write_file("src/app.rs", "fn app() {}\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo
+        .write_file("src/parser.gen.rs", "generated\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("Test User")
+        .unwrap();
+
+    let base_commit = resolve_base_commit(tmp_repo.repo()).unwrap();
+    let working_log =
+        get_reference_as_working_log(tmp_repo.repo(), &format!("ai-working-log/{}", base_commit))
+            .unwrap();
+    let summary: Vec<(String, bool, Vec<&str>)> = working_log
+        .iter()
+        .map(|checkpoint| {
+            (
+                checkpoint.author.clone(),
+                checkpoint.agent_metadata.is_some(),
+                checkpoint
+                    .entries
+                    .iter()
+                    .map(|entry| entry.file.as_str())
+                    .collect(),
+            )
+        })
+        .collect();
+    // vendor/ is never tracked; the agent's docs edit and the person's generated file are
+    // split off into checkpoints credited to the other side
+    assert_eq!(
+        summary,
+        [
+            ("Test User".to_string(), false, vec![".git-ai/attributes"]),
+            ("Test User".to_string(), false, vec!["docs/guide.md"]),
+            ("Claude".to_string(), true, vec!["src/app.rs"]),
+            ("Test User".to_string(), true, vec!["src/parser.gen.rs"]),
+            ("Test User".to_string(), false, vec![]),
+        ]
+    );
+    let forced = working_log[3].agent_metadata.as_ref().unwrap();
+    assert_eq!(forced.model, "unknown");
+    assert_eq!(forced.confidence, Confidence::Low);
+
+    tmp_repo.commit_with_message("Policies").unwrap();
+    let options = StatsOptions {
+        no_fetch: true,
+        ..Default::default()
+    };
+    let report = stats::report(tmp_repo.repo(), Some("HEAD~2..HEAD"), &options).unwrap();
+    assert!(!report.file_stats.contains_key("vendor/lib.js"));
+    // The agent's earlier commit to docs/ counts as human too
+    assert_eq!(report.file_stats["docs/guide.md"].total_additions, 2);
+    assert_eq!(report.file_stats["docs/guide.md"].ai_additions, 0);
+    assert_eq!(report.file_stats["src/app.rs"].ai_additions, 1);
+    assert_eq!(report.file_stats["src/parser.gen.rs"].ai_additions, 1);
+}
+
+#[test]
+fn test_generated_code_is_neither_human_nor_ai() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+
+    tmp_repo
+        .write_file(".git-ai/attributes", "*.pb.go generated\n", true)
+        .unwrap();
+    tmp_repo
+        .write_file("api.pb.go", "package api\ntype Msg struct{}\n", true)
+        .unwrap();
+    tmp_repo
+        .write_file("main.go", "package main\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    // A codegen script checkpoints its own output
+    tmp_repo
+        .write_file("schema.sql", "CREATE TABLE t ();\n", true)
+        .unwrap();
+    checkpoint::run(
+        tmp_repo.repo(),
+        "sqlgen",
+        &CheckpointOptions {
+            quiet: true,
+            generated: true,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    tmp_repo.commit_with_message("Codegen").unwrap();
+
+    let options = StatsOptions {
+        no_fetch: true,
+        ..Default::default()
+    };
+    let report = stats::report(tmp_repo.repo(), None, &options).unwrap();
+    assert_eq!(report.generated_additions, 3);
+    // main.go and the attributes file
+    assert_eq!(report.ai_additions, 2);
+    assert_eq!(report.file_stats["api.pb.go"].generated_additions, 2);
+    assert_eq!(report.file_stats["api.pb.go"].total_additions, 0);
+    assert_eq!(report.file_stats["schema.sql"].generated_additions, 1);
+    assert!(!report.total_additions_by_author.contains_key("sqlgen"));
+
+    let output = Command::cargo_bin("git-ai")
+        .unwrap()
+        .current_dir(&repo_path)
+        .args(["blame", "schema.sql"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("sqlgen [generated]"));
+
+    let output = Command::cargo_bin("git-ai")
+        .unwrap()
+        .current_dir(&repo_path)
+        .args(["blame", "--json", "api.pb.go"])
+        .output()
+        .unwrap();
+    let document: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let line = &document["lines"][0];
+    assert_eq!(line["generated"], true);
+    assert_eq!(line["ai"], false);
+}
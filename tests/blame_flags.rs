@@ -1,5 +1,4 @@
-use git_ai::tmp_repo::TmpRepo;
-use insta::assert_debug_snapshot;
+use git_ai::tmp_repo::{TmpRepo, args};
 use std::collections::BTreeMap;
 use std::process::Command;
 use tempfile::tempdir;
@@ -15,23 +14,13 @@ fn run_git_blame(repo_path: &std::path::Path, file_path: &str, args: &[&str]) ->
     let mut i = 0;
     while i < args.len() {
         match args[i] {
-            "--abbrev" => {
-                if i + 1 < args.len() {
-                    processed_args.push(format!("--abbrev={}", args[i + 1]));
-                    i += 2;
-                } else {
-                    processed_args.push(args[i].to_string());
-                    i += 1;
-                }
+            "--abbrev" if i + 1 < args.len() => {
+                processed_args.push(format!("--abbrev={}", args[i + 1]));
+                i += 2;
             }
-            "--date" => {
-                if i + 1 < args.len() {
-                    processed_args.push(format!("--date={}", args[i + 1]));
-                    i += 2;
-                } else {
-                    processed_args.push(args[i].to_string());
-                    i += 1;
-                }
+            "--date" if i + 1 < args.len() => {
+                processed_args.push(format!("--date={}", args[i + 1]));
+                i += 2;
             }
             _ => {
                 processed_args.push(args[i].to_string());
@@ -93,11 +82,9 @@ fn extract_authors(output: &str) -> Vec<String> {
             // Extract author name from blame line format
             // Format: sha (author date line) code
             if let Some(start) = line.find('(') {
-                if let Some(end) = line[start..].find(' ') {
-                    Some(line[start + 1..start + end].trim().to_string())
-                } else {
-                    None
-                }
+                line[start..]
+                    .find(' ')
+                    .map(|end| line[start + 1..start + end].trim().to_string())
             } else {
                 None
             }
@@ -148,8 +135,8 @@ fn normalize_for_snapshot(output: &str) -> String {
         })
         .map(|line| {
             // Remove the ^ prefix that git adds for boundary commits
-            if line.starts_with('^') {
-                line[1..].to_string()
+            if let Some(stripped) = line.strip_prefix('^') {
+                stripped.to_string()
             } else {
                 line
             }
@@ -225,7 +212,7 @@ fn test_blame_basic_format() {
     let git_output = run_git_blame(&repo_path, "test.txt", &[]);
     let git_ai_output = run_git_ai_blame(&repo_path, "test.txt", &[]);
 
-    let comparison = create_blame_comparison(&git_output, &git_ai_output, "basic_format");
+    let _comparison = create_blame_comparison(&git_output, &git_ai_output, "basic_format");
 
     // Use insta to capture the comparison and catch drift
     let git_norm = normalize_for_snapshot(&git_output);
@@ -259,7 +246,7 @@ fn test_blame_line_range() {
     let git_output = run_git_blame(&repo_path, "test.txt", &["-L", "2,4"]);
     let git_ai_output = run_git_ai_blame(&repo_path, "test.txt", &["-L", "2,4"]);
 
-    let comparison = create_blame_comparison(&git_output, &git_ai_output, "line_range");
+    let _comparison = create_blame_comparison(&git_output, &git_ai_output, "line_range");
     let git_norm = normalize_for_snapshot(&git_output);
     let git_ai_norm = normalize_for_snapshot(&git_ai_output);
     println!("\n[DEBUG] Normalized git blame output:\n{}", git_norm);
@@ -288,7 +275,7 @@ fn test_blame_porcelain_format() {
     let git_output = run_git_blame(&repo_path, "test.txt", &["--porcelain"]);
     let git_ai_output = run_git_ai_blame(&repo_path, "test.txt", &["--porcelain"]);
 
-    let comparison = create_blame_comparison(&git_output, &git_ai_output, "porcelain_format");
+    let _comparison = create_blame_comparison(&git_output, &git_ai_output, "porcelain_format");
     let git_norm = normalize_for_snapshot(&git_output);
     let git_ai_norm = normalize_for_snapshot(&git_ai_output);
     println!("\n[DEBUG] Normalized git blame output:\n{}", git_norm);
@@ -317,7 +304,7 @@ fn test_blame_show_email() {
     let git_output = run_git_blame(&repo_path, "test.txt", &["-e"]);
     let git_ai_output = run_git_ai_blame(&repo_path, "test.txt", &["-e"]);
 
-    let comparison = create_blame_comparison(&git_output, &git_ai_output, "show_email");
+    let _comparison = create_blame_comparison(&git_output, &git_ai_output, "show_email");
     let git_norm = normalize_for_snapshot(&git_output);
     let git_ai_norm = normalize_for_snapshot(&git_ai_output);
     println!("\n[DEBUG] Normalized git blame output:\n{}", git_norm);
@@ -353,7 +340,7 @@ fn test_blame_show_name() {
     let git_output = run_git_blame(&repo_path, "test.txt", &["-f"]);
     let git_ai_output = run_git_ai_blame(&repo_path, "test.txt", &["-f"]);
 
-    let comparison = create_blame_comparison(&git_output, &git_ai_output, "show_name");
+    let _comparison = create_blame_comparison(&git_output, &git_ai_output, "show_name");
     let git_norm = normalize_for_snapshot(&git_output);
     let git_ai_norm = normalize_for_snapshot(&git_ai_output);
     println!("\n[DEBUG] Normalized git blame output:\n{}", git_norm);
@@ -392,7 +379,7 @@ fn test_blame_show_number() {
     let git_output = run_git_blame(&repo_path, "test.txt", &["-n"]);
     let git_ai_output = run_git_ai_blame(&repo_path, "test.txt", &["-n"]);
 
-    let comparison = create_blame_comparison(&git_output, &git_ai_output, "show_number");
+    let _comparison = create_blame_comparison(&git_output, &git_ai_output, "show_number");
     let git_norm = normalize_for_snapshot(&git_output);
     let git_ai_norm = normalize_for_snapshot(&git_ai_output);
     println!("\n[DEBUG] Normalized git blame output:\n{}", git_norm);
@@ -421,7 +408,7 @@ fn test_blame_suppress_author() {
     let git_output = run_git_blame(&repo_path, "test.txt", &["-s"]);
     let git_ai_output = run_git_ai_blame(&repo_path, "test.txt", &["-s"]);
 
-    let comparison = create_blame_comparison(&git_output, &git_ai_output, "suppress_author");
+    let _comparison = create_blame_comparison(&git_output, &git_ai_output, "suppress_author");
     let git_norm = normalize_for_snapshot(&git_output);
     let git_ai_norm = normalize_for_snapshot(&git_ai_output);
     println!("\n[DEBUG] Normalized git blame output:\n{}", git_norm);
@@ -460,7 +447,7 @@ fn test_blame_long_rev() {
     let git_output = run_git_blame(&repo_path, "test.txt", &["-l"]);
     let git_ai_output = run_git_ai_blame(&repo_path, "test.txt", &["-l"]);
 
-    let comparison = create_blame_comparison(&git_output, &git_ai_output, "long_rev");
+    let _comparison = create_blame_comparison(&git_output, &git_ai_output, "long_rev");
     let git_norm = normalize_for_snapshot(&git_output);
     let git_ai_norm = normalize_for_snapshot(&git_ai_output);
     println!("\n[DEBUG] Normalized git blame output:\n{}", git_norm);
@@ -510,7 +497,7 @@ fn test_blame_raw_timestamp() {
     let git_output = run_git_blame(&repo_path, "test.txt", &["-t"]);
     let git_ai_output = run_git_ai_blame(&repo_path, "test.txt", &["-t"]);
 
-    let comparison = create_blame_comparison(&git_output, &git_ai_output, "raw_timestamp");
+    let _comparison = create_blame_comparison(&git_output, &git_ai_output, "raw_timestamp");
     let git_norm = normalize_for_snapshot(&git_output);
     let git_ai_norm = normalize_for_snapshot(&git_ai_output);
     println!("\n[DEBUG] Normalized git blame output:\n{}", git_norm);
@@ -549,7 +536,7 @@ fn test_blame_abbrev() {
     let git_output = run_git_blame(&repo_path, "test.txt", &["--abbrev", "4"]);
     let git_ai_output = run_git_ai_blame(&repo_path, "test.txt", &["--abbrev", "4"]);
 
-    let comparison = create_blame_comparison(&git_output, &git_ai_output, "abbrev");
+    let _comparison = create_blame_comparison(&git_output, &git_ai_output, "abbrev");
     let git_norm = normalize_for_snapshot(&git_output);
     let git_ai_norm = normalize_for_snapshot(&git_ai_output);
     println!("\n[DEBUG] Normalized git blame output:\n{}", git_norm);
@@ -606,7 +593,7 @@ fn test_blame_show_root() {
     let git_output = run_git_blame(&repo_path, "test.txt", &["--root"]);
     let git_ai_output = run_git_ai_blame(&repo_path, "test.txt", &["--root"]);
 
-    let comparison = create_blame_comparison(&git_output, &git_ai_output, "show_root");
+    let _comparison = create_blame_comparison(&git_output, &git_ai_output, "show_root");
     let git_norm = normalize_for_snapshot(&git_output);
     let git_ai_norm = normalize_for_snapshot(&git_ai_output);
     println!("\n[DEBUG] Normalized git blame output:\n{}", git_norm);
@@ -683,7 +670,7 @@ fn test_blame_date_format() {
     let git_output = run_git_blame(&repo_path, "test.txt", &["--date", "short"]);
     let git_ai_output = run_git_ai_blame(&repo_path, "test.txt", &["--date", "short"]);
 
-    let comparison = create_blame_comparison(&git_output, &git_ai_output, "date_format");
+    let _comparison = create_blame_comparison(&git_output, &git_ai_output, "date_format");
     let git_norm = normalize_for_snapshot(&git_output);
     let git_ai_norm = normalize_for_snapshot(&git_ai_output);
     println!("\n[DEBUG] Normalized git blame output:\n{}", git_norm);
@@ -722,7 +709,7 @@ fn test_blame_multiple_flags() {
     let git_output = run_git_blame(&repo_path, "test.txt", &["-L", "2,4", "-e", "-n"]);
     let git_ai_output = run_git_ai_blame(&repo_path, "test.txt", &["-L", "2,4", "-e", "-n"]);
 
-    let comparison = create_blame_comparison(&git_output, &git_ai_output, "multiple_flags");
+    let _comparison = create_blame_comparison(&git_output, &git_ai_output, "multiple_flags");
     let git_norm = normalize_for_snapshot(&git_output);
     let git_ai_norm = normalize_for_snapshot(&git_ai_output);
     println!("\n[DEBUG] Normalized git blame output:\n{}", git_norm);
@@ -768,7 +755,7 @@ fn test_blame_incremental_format() {
     let git_output = run_git_blame(&repo_path, "test.txt", &["--incremental"]);
     let git_ai_output = run_git_ai_blame(&repo_path, "test.txt", &["--incremental"]);
 
-    let comparison = create_blame_comparison(&git_output, &git_ai_output, "incremental_format");
+    let _comparison = create_blame_comparison(&git_output, &git_ai_output, "incremental_format");
     let git_norm = normalize_for_snapshot(&git_output);
     let git_ai_norm = normalize_for_snapshot(&git_ai_output);
     println!("\n[DEBUG] Normalized git blame output:\n{}", git_norm);
@@ -797,7 +784,7 @@ fn test_blame_line_porcelain() {
     let git_output = run_git_blame(&repo_path, "test.txt", &["--line-porcelain"]);
     let git_ai_output = run_git_ai_blame(&repo_path, "test.txt", &["--line-porcelain"]);
 
-    let comparison = create_blame_comparison(&git_output, &git_ai_output, "line_porcelain");
+    let _comparison = create_blame_comparison(&git_output, &git_ai_output, "line_porcelain");
     let git_norm = normalize_for_snapshot(&git_output);
     let git_ai_norm = normalize_for_snapshot(&git_ai_output);
     println!("\n[DEBUG] Normalized git blame output:\n{}", git_norm);
@@ -839,7 +826,7 @@ fn test_blame_with_ai_authorship() {
     let git_output = run_git_blame(&repo_path, "test.txt", &[]);
     let git_ai_output = run_git_ai_blame(&repo_path, "test.txt", &[]);
 
-    let comparison = create_blame_comparison(&git_output, &git_ai_output, "ai_authorship");
+    let _comparison = create_blame_comparison(&git_output, &git_ai_output, "ai_authorship");
     let git_norm = normalize_for_snapshot(&git_output);
     let git_ai_norm = normalize_for_snapshot(&git_ai_output);
     println!("\n[DEBUG] Normalized git blame output:\n{}", git_norm);
@@ -866,3 +853,769 @@ fn test_blame_with_ai_authorship() {
         "Should show Claude as author"
     );
 }
+
+#[test]
+fn test_blame_web_no_open() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let tmp_repo = TmpRepo::new(repo_path.clone()).unwrap();
+    let mut file = tmp_repo
+        .write_file("web.rs", "fn human() {}\n", true)
+        .unwrap();
+
+    tmp_repo
+        .trigger_checkpoint_with_author("test_user")
+        .unwrap();
+    file.append("let s = \"<ai>\"; // generated\n").unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo.commit_with_message("Initial commit").unwrap();
+
+    let output = run_git_ai_blame(&repo_path, "web.rs", &["--web", "--no-open"]);
+    let html_path = output.trim();
+    assert!(
+        html_path.ends_with(".html"),
+        "unexpected output: {}",
+        output
+    );
+
+    let html = std::fs::read_to_string(html_path).unwrap();
+    assert!(html.contains("class=\"legend\""));
+    assert!(html.contains("AI 1 (50%)"));
+    assert!(html.contains("<tr class=\"ai\">"));
+    assert!(html.contains("&lt;ai&gt;"));
+    assert!(html.contains("<span class=\"cmt\">// generated</span>"));
+}
+
+#[test]
+fn test_blame_compare_with_git_matches() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let tmp_repo = TmpRepo::new(repo_path.clone()).unwrap();
+    let mut lines = tmp_repo
+        .write_file("cmp.txt", "HUMAN LINE\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("test_user")
+        .unwrap();
+    tmp_repo.commit_with_message("human commit").unwrap();
+
+    lines.append("AI LINE\n").unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo.commit_with_message("agent commit").unwrap();
+
+    let output = run_git_ai_blame(&repo_path, lines.filename(), &["--compare-with-git"]);
+    assert!(
+        output.contains("match git blame"),
+        "unexpected output: {}",
+        output
+    );
+
+    let repo = tmp_repo.repo();
+    let options = git_ai::commands::blame::GitAiBlameOptions::default();
+    let hunks =
+        git_ai::commands::blame::get_git_blame_hunks(repo, lines.filename(), 1, 2, &options)
+            .unwrap();
+    let discrepancies =
+        git_ai::commands::blame::compare_with_git(repo, lines.filename(), &hunks, &[(1, 2)])
+            .unwrap();
+    assert!(discrepancies.is_empty());
+
+    // Dropping git-ai's hunks surfaces every line as a discrepancy
+    let discrepancies =
+        git_ai::commands::blame::compare_with_git(repo, lines.filename(), &[], &[(1, 2)]).unwrap();
+    assert_eq!(discrepancies.len(), 2);
+    assert_eq!(discrepancies[0].git_ai_commit, None);
+}
+
+#[test]
+fn test_blame_output_writes_file() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let tmp_repo = TmpRepo::new(repo_path.clone()).unwrap();
+    let mut file = tmp_repo
+        .write_file("out.txt", "HUMAN LINE\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("test_user")
+        .unwrap();
+    file.append("AI LINE\n").unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo.commit_with_message("Initial commit").unwrap();
+
+    let porcelain_path = repo_path.join("artifacts/nested/blame.txt");
+    let stdout = run_git_ai_blame(
+        &repo_path,
+        "out.txt",
+        &["--porcelain", "--output", porcelain_path.to_str().unwrap()],
+    );
+    assert!(stdout.is_empty(), "unexpected stdout: {}", stdout);
+    let porcelain = std::fs::read_to_string(&porcelain_path).unwrap();
+    assert!(porcelain.contains("filename out.txt"));
+    assert!(porcelain.contains("\tAI LINE"));
+
+    let json_path = repo_path.join("artifacts/blame.json");
+    run_git_ai_blame(
+        &repo_path,
+        "out.txt",
+        &["--json", "--output", json_path.to_str().unwrap()],
+    );
+    let json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&json_path).unwrap()).unwrap();
+    assert_eq!(json["file"], "out.txt");
+    assert_eq!(json["lines"][0]["ai"], false);
+    assert_eq!(json["lines"][1]["ai"], true);
+    assert_eq!(json["lines"][1]["author"], "Claude");
+    assert_eq!(json["lines"][1]["model"], "claude-4-sonnet");
+}
+
+#[test]
+fn test_blame_since_marks_baseline_lines() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let tmp_repo = TmpRepo::new(repo_path.clone()).unwrap();
+    let mut file = tmp_repo
+        .write_file("since.txt", "OLD LINE\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo.commit_with_message("Initial commit").unwrap();
+    let baseline = tmp_repo.head_commit_sha().unwrap();
+
+    file.append("NEW LINE\n").unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo.commit_with_message("Second commit").unwrap();
+
+    let output = run_git_ai_blame(&repo_path, "since.txt", &["--json", "--since", &baseline]);
+    let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(json["lines"][0]["author"], "baseline");
+    assert_eq!(json["lines"][0]["ai"], false);
+    assert_eq!(json["lines"][1]["author"], "Claude");
+    assert_eq!(json["lines"][1]["ai"], true);
+}
+
+#[test]
+fn test_blame_applies_mailmap_unless_disabled() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let tmp_repo = TmpRepo::new(repo_path.clone()).unwrap();
+    tmp_repo
+        .write_file(
+            ".mailmap",
+            "Canonical Name <canonical@example.com> <test@example.com>\n",
+            true,
+        )
+        .unwrap();
+    tmp_repo
+        .write_file("mapped.txt", "HUMAN LINE\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("test_user")
+        .unwrap();
+    tmp_repo.commit_with_message("Initial commit").unwrap();
+
+    let output = run_git_ai_blame(&repo_path, "mapped.txt", &["--json"]);
+    let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(json["lines"][0]["author"], "Canonical Name");
+
+    let porcelain = run_git_ai_blame(&repo_path, "mapped.txt", &["--porcelain"]);
+    assert!(porcelain.contains("author-mail <canonical@example.com>"));
+
+    let output = run_git_ai_blame(&repo_path, "mapped.txt", &["--json", "--no-mailmap"]);
+    let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(json["lines"][0]["author"], "test_user");
+}
+
+#[test]
+fn test_blame_group_hunks_collapses_runs() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let tmp_repo = TmpRepo::new(repo_path.clone()).unwrap();
+    let mut file = tmp_repo
+        .write_file("runs.txt", "h1\nh2\nh3\nh4\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("test_user")
+        .unwrap();
+    file.append("a1\na2\n").unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo.commit_with_message("Initial commit").unwrap();
+
+    let output = run_git_ai_blame(&repo_path, "runs.txt", &["--group-hunks"]);
+    assert_eq!(
+        output,
+        "test_user (lines 1-4, 4 lines)\nClaude [AI: claude-4-sonnet] (lines 5-6, 2 lines)\n"
+    );
+
+    let output = run_git_ai_blame(&repo_path, "runs.txt", &["--group-hunks", "--group-edges"]);
+    assert_eq!(
+        output,
+        "test_user (lines 1-4, 4 lines)\n     1  h1\n   ...\n     4  h4\nClaude [AI: claude-4-sonnet] (lines 5-6, 2 lines)\n     5  a1\n     6  a2\n"
+    );
+}
+
+#[test]
+fn test_blame_contents_from_stdin() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let tmp_repo = TmpRepo::new(repo_path.clone()).unwrap();
+    let mut file = tmp_repo.write_file("buffer.txt", "h1\nh2\n", true).unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("test_user")
+        .unwrap();
+    tmp_repo.commit_with_message("Initial commit").unwrap();
+
+    // Checkpointed but not committed, then one more line only in the editor buffer
+    file.append("a1\n").unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+
+    let binary_path = std::env::current_dir().unwrap().join("target/debug/git-ai");
+    let mut child = Command::new(binary_path)
+        .current_dir(&repo_path)
+        .args(["blame", "--contents", "-", "buffer.txt"])
+        .env("GIT_PAGER", "cat")
+        .env("PAGER", "cat")
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+    use std::io::Write;
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"h1\nh2\na1\nunsaved\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    println!("{}", stdout);
+
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 4);
+    assert!(lines[0].contains("test_user"));
+    assert!(lines[2].contains("Claude"));
+    assert!(lines[3].contains("Not Committed Yet"));
+    assert!(lines[3].starts_with("0000000"));
+}
+
+#[test]
+fn test_blame_at_revision_of_deleted_file() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    let mut file = tmp_repo.write_file("gone.txt", "h1\n", true).unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("test_user")
+        .unwrap();
+    file.append("a1\n").unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo.commit_with_message("Add gone.txt").unwrap();
+    let added_at = tmp_repo.head_commit_sha().unwrap();
+
+    std::fs::remove_file(repo_path.join("gone.txt")).unwrap();
+    let mut index = tmp_repo.repo().index().unwrap();
+    index.remove_path(std::path::Path::new("gone.txt")).unwrap();
+    index.write().unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("test_user")
+        .unwrap();
+    tmp_repo.commit_with_message("Remove gone.txt").unwrap();
+
+    let output = run_git_ai_blame(&repo_path, "gone.txt", &["--at", &added_at]);
+    let authors = extract_authors(&output);
+    assert_eq!(authors, ["test_user", "Claude"]);
+
+    let options = git_ai::commands::blame::GitAiBlameOptions {
+        revision: Some("HEAD".to_string()),
+        ..Default::default()
+    };
+    let error = git_ai::commands::blame::run(tmp_repo.repo(), "gone.txt", &options).unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .contains("gone.txt does not exist at HEAD")
+    );
+}
+
+#[test]
+fn test_blame_relative_to_marks_other_authors() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let tmp_repo = TmpRepo::new(repo_path.clone()).unwrap();
+    let mut file = tmp_repo.write_file("review.txt", "h1\nh2\n", true).unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("test_user")
+        .unwrap();
+    file.append("a1\n").unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo.commit_with_message("Initial commit").unwrap();
+
+    let output = run_git_ai_blame(&repo_path, "review.txt", &["--relative-to", "test_user"]);
+    let markers: Vec<&str> = output.lines().map(|line| &line[..2]).collect();
+    assert_eq!(markers, ["  ", "  ", "* "]);
+    assert!(output.lines().nth(2).unwrap().contains("Claude"));
+}
+
+#[test]
+fn test_blame_marks_low_confidence_ai_lines() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    let mut file = tmp_repo
+        .write_file("guess.txt", "HUMAN LINE\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("test_user")
+        .unwrap();
+    file.append("FORCED AI LINE\n").unwrap();
+
+    // Forced to AI without naming the agent's model: recorded as a guess
+    let binary_path = std::env::current_dir().unwrap().join("target/debug/git-ai");
+    let status = Command::new(binary_path)
+        .current_dir(&repo_path)
+        .args(["checkpoint", "--author", "Claude", "--author-type", "ai"])
+        .status()
+        .unwrap();
+    assert!(status.success());
+    tmp_repo.commit_with_message("Guessed commit").unwrap();
+
+    let output = run_git_ai_blame(&repo_path, "guess.txt", &["--json"]);
+    let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(json["lines"][0]["confidence"], serde_json::Value::Null);
+    assert_eq!(json["lines"][1]["ai"], true);
+    assert_eq!(json["lines"][1]["confidence"], "low");
+
+    let output = run_git_ai_blame(&repo_path, "guess.txt", &[]);
+    assert!(output.lines().nth(1).unwrap().contains("(Claude? "));
+
+    let output = run_git_ai_blame(&repo_path, "guess.txt", &["--group-hunks"]);
+    assert!(output.contains("Claude [AI: unknown, low confidence] (lines 2, 1 line)"));
+}
+
+#[test]
+fn test_blame_model_filter() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    let mut file = tmp_repo
+        .write_file("models.txt", "HUMAN LINE\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("test_user")
+        .unwrap();
+    file.append("SONNET LINE\n").unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    file.append("OPUS LINE\n").unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-opus")
+        .unwrap();
+    file.append("SONNET AGAIN\n").unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo.commit_with_message("Model commit").unwrap();
+
+    let lines = |args: &[&str]| -> Vec<u64> {
+        let output = run_git_ai_blame(&repo_path, "models.txt", args);
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+        json["lines"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|line| line["line"].as_u64().unwrap())
+            .collect()
+    };
+    assert_eq!(lines(&["--json", "--model", "SONNET"]), [2, 4]);
+    assert_eq!(lines(&["--json", "--model", "claude"]), [2, 3, 4]);
+    assert_eq!(
+        lines(&["--json", "--model-exact", "claude-4"]),
+        Vec::<u64>::new()
+    );
+    assert_eq!(
+        lines(&["--json", "--model-exact", "claude-4-opus", "-L", "1,3"]),
+        [3]
+    );
+
+    let output = run_git_ai_blame(&repo_path, "models.txt", &["--model", "opus"]);
+    assert_eq!(output.lines().count(), 1);
+    assert!(output.contains("OPUS LINE"));
+}
+
+#[test]
+fn test_blame_only_category() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    let mut file = tmp_repo
+        .write_file("only.txt", "BASE LINE\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("test_user")
+        .unwrap();
+    tmp_repo.commit_with_message("Base").unwrap();
+
+    // One commit, so the human fix-up and the generated line share a hunk
+    file.append("HUMAN FIX\n").unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("test_user")
+        .unwrap();
+    file.append("AI LINE\n").unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo.commit_with_message("Mixed commit").unwrap();
+
+    let lines = |args: &[&str]| -> Vec<u64> {
+        let output = run_git_ai_blame(&repo_path, "only.txt", args);
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+        json["lines"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|line| line["line"].as_u64().unwrap())
+            .collect()
+    };
+    assert_eq!(lines(&["--json", "--only", "ai"]), [3]);
+    assert_eq!(lines(&["--json", "--only", "mixed"]), [2]);
+    assert_eq!(lines(&["--json", "--only", "human"]), [1]);
+    assert_eq!(
+        lines(&["--json", "--only", "ai", "-L", "1,2"]),
+        Vec::<u64>::new()
+    );
+
+    let output = run_git_ai_blame(&repo_path, "only.txt", &["--only", "ai"]);
+    assert_eq!(output.lines().count(), 1);
+    assert!(output.contains("   3) AI LINE"), "{}", output);
+
+    let output = run_git_ai_blame(&repo_path, "only.txt", &["--only", "ai", "--group-hunks"]);
+    assert_eq!(
+        output.trim(),
+        "Claude [AI: claude-4-sonnet] (lines 3, 1 line)"
+    );
+}
+
+#[test]
+fn test_blame_tabwidth_expands_tabs() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    tmp_repo
+        .write_file("tabs.go", "\tfoo()\n  \tbar()\nx\ty\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("test_user")
+        .unwrap();
+    tmp_repo.commit_with_message("Tabs").unwrap();
+
+    let contents = |args: &[&str]| -> Vec<String> {
+        run_git_ai_blame(&repo_path, "tabs.go", args)
+            .lines()
+            .map(|line| line.split_once(") ").unwrap().1.to_string())
+            .collect()
+    };
+    // Mixed spaces then a tab still reach the same stop
+    assert_eq!(
+        contents(&["--tabwidth", "4"]),
+        ["    foo()", "    bar()", "x   y"]
+    );
+    assert_eq!(contents(&[])[0], format!("{}foo()", " ".repeat(8)));
+
+    tmp_repo
+        .repo()
+        .config()
+        .unwrap()
+        .set_str("core.whitespace", "trailing-space,tabwidth=2")
+        .unwrap();
+    assert_eq!(contents(&[])[0], "  foo()");
+    assert_eq!(contents(&["--tabwidth", "3"])[2], "x  y");
+}
+
+#[test]
+fn test_blame_fields_selects_and_orders_gutter() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    tmp_repo
+        .write_file("fields.rs", "fn human() {}\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("test_user")
+        .unwrap();
+    tmp_repo
+        .write_file("fields.rs", "fn human() {}\nfn agent() {}\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo.commit_with_message("Fields").unwrap();
+
+    // Columns come in the order asked for, each padded to its widest value
+    let output = run_git_ai_blame(&repo_path, "fields.rs", &["--fields", "line,author,model"]);
+    assert_eq!(
+        output,
+        "(1 test_user -              ) fn human() {}\n(2 Claude    claude-4-sonnet) fn agent() {}\n"
+    );
+
+    // "default" expands to the usual gutter, so extra columns can be appended to it
+    let output = run_git_ai_blame(&repo_path, "fields.rs", &["--fields", "default,model"]);
+    let head = tmp_repo.head_commit_sha().unwrap();
+    let second = output.lines().nth(1).unwrap();
+    assert!(
+        second.starts_with(&format!("({} Claude    ", &head[..7])),
+        "{}",
+        output
+    );
+    assert!(
+        second.ends_with(" 2 claude-4-sonnet) fn agent() {}"),
+        "{}",
+        output
+    );
+}
+
+#[test]
+fn test_blame_porcelain_v2_streams_records() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    tmp_repo
+        .write_file("stream.rs", "fn human() {}\nfn also_human() {}\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("test_user")
+        .unwrap();
+    tmp_repo
+        .write_file(
+            "stream.rs",
+            "fn human() {}\nfn also_human() {}\nfn agent() {}\n",
+            true,
+        )
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo.commit_with_message("Stream").unwrap();
+    let head = tmp_repo.head_commit_sha().unwrap();
+
+    // One record per run of equal attribution within a commit, then the line count
+    let output = run_git_ai_blame(&repo_path, "stream.rs", &["--porcelain-v2"]);
+    let records: Vec<&str> = output.split("\n\n").collect();
+    assert_eq!(records.len(), 3, "{}", output);
+    assert!(records[0].starts_with(&format!("hunk 1 2 {}\n", head)));
+    assert!(records[0].ends_with("\nattribution test_user"));
+    assert!(!records[0].contains("model "));
+    assert!(records[1].starts_with(&format!("hunk 3 3 {}\n", head)));
+    assert!(records[1].ends_with("\nattribution Claude\nmodel claude-4-sonnet"));
+    assert_eq!(records[2], "done 3\n");
+
+    // Large files arrive in chunks, each its own record
+    let big: String = (1..=600).map(|i| format!("line {}\n", i)).collect();
+    tmp_repo.write_file("big.txt", &big, true).unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("test_user")
+        .unwrap();
+    tmp_repo.commit_with_message("Big").unwrap();
+    let output = run_git_ai_blame(&repo_path, "big.txt", &["--porcelain-v2"]);
+    let ranges: Vec<String> = output
+        .lines()
+        .filter_map(|line| line.strip_prefix("hunk "))
+        .map(|hunk| hunk.rsplit_once(' ').unwrap().0.to_string())
+        .collect();
+    assert_eq!(ranges, ["1 256", "257 512", "513 600"]);
+    assert!(output.ends_with("done 600\n"));
+}
+
+#[test]
+fn test_blame_wrap_keeps_gutter_aligned() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    let long_line = format!("let v = [{}];", "1, ".repeat(40));
+    tmp_repo
+        .write_file("long.rs", &format!("short\n{}\n", long_line), true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo.commit_with_message("Long line").unwrap();
+
+    let blame = |args: &[&str]| {
+        let output = Command::new(std::env::current_dir().unwrap().join("target/debug/git-ai"))
+            .current_dir(&repo_path)
+            .arg("blame")
+            .args(args)
+            .arg("long.rs")
+            .env("GIT_PAGER", "cat")
+            .env("COLUMNS", "70")
+            .output()
+            .unwrap();
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+    let unwrapped = blame(&[]);
+    assert_eq!(unwrapped.lines().count(), 2);
+
+    let wrapped = blame(&["--wrap"]);
+    let rows: Vec<&str> = wrapped.lines().collect();
+    assert!(rows.len() > 3, "{}", wrapped);
+    assert_eq!(rows[0], unwrapped.lines().next().unwrap());
+    let gutter = rows[1].find(") ").unwrap() + 2;
+    assert!(rows.iter().all(|row| row.chars().count() <= 70));
+    for row in &rows[2..] {
+        assert!(row[..gutter].trim().is_empty(), "{:?}", row);
+    }
+    let content: String = rows[1..].iter().map(|row| &row[gutter..]).collect();
+    assert_eq!(content, long_line);
+
+    // Machine-readable formats refuse to wrap
+    assert!(blame(&["--wrap", "--porcelain"]).is_empty());
+}
+
+#[test]
+fn test_blame_show_prompt() {
+    use git_ai::commands::checkpoint::{self, CheckpointOptions};
+
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    tmp_repo.write_file("notes.md", "by hand\n", true).unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("Test User")
+        .unwrap();
+    // The prompt is recorded before the agent's edits, as the prompt-submit hook does
+    checkpoint::run(
+        tmp_repo.repo(),
+        "Test User",
+        &CheckpointOptions {
+            quiet: true,
+            prompt: Some("Write a haiku about\nautumn leaves falling in the wind".to_string()),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    tmp_repo
+        .write_file("notes.md", "by hand\nleaves drift down\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo.commit_with_message("Haiku").unwrap();
+
+    let blame = |args: &[&str]| {
+        let output = Command::new(std::env::current_dir().unwrap().join("target/debug/git-ai"))
+            .current_dir(&repo_path)
+            .arg("blame")
+            .args(args)
+            .arg("notes.md")
+            .env("GIT_PAGER", "cat")
+            .output()
+            .unwrap();
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+
+    let output = blame(&["--show-prompt"]);
+    let rows: Vec<&str> = output.lines().collect();
+    assert_eq!(rows.len(), 2, "{}", output);
+    assert!(
+        rows[1].contains(" Write a haiku about a... ("),
+        "{}",
+        output
+    );
+    assert!(
+        rows[0].contains(&format!(" {} (", " ".repeat(24))),
+        "{}",
+        output
+    );
+
+    // A single line shows the whole prompt beneath it
+    let output = blame(&["--show-prompt", "-L", "2,2"]);
+    assert!(
+        output.ends_with(
+            "leaves drift down\n\nPrompt:\n    Write a haiku about\n    autumn leaves falling in the wind\n"
+        ),
+        "{}",
+        output
+    );
+    let output = blame(&["--show-prompt", "-L", "1,1"]);
+    assert!(!output.contains("Prompt:"), "{}", output);
+
+    assert!(blame(&["--show-prompt", "--json"]).is_empty());
+}
+
+#[test]
+fn test_blame_count_with_threshold() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    tmp_repo.write_file("gate.rs", "human\n", true).unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("Test User")
+        .unwrap();
+    tmp_repo
+        .write_file("gate.rs", "human\nai 1\nai 2\nai 3\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo.commit_with_message("Mostly agent").unwrap();
+
+    let count = |args: &[&str]| {
+        let output = Command::new(std::env::current_dir().unwrap().join("target/debug/git-ai"))
+            .current_dir(&repo_path)
+            .args(["blame", "--count"])
+            .args(args)
+            .arg("gate.rs")
+            .output()
+            .unwrap();
+        (
+            output.status.success(),
+            String::from_utf8_lossy(&output.stdout).to_string(),
+        )
+    };
+    assert_eq!(count(&[]), (true, "75.0\n".to_string()));
+    assert_eq!(count(&["--threshold", "80"]), (true, "75.0\n".to_string()));
+    assert_eq!(count(&["--threshold", "50"]), (false, "75.0\n".to_string()));
+    assert_eq!(
+        count(&["-L", "1,2", "--threshold", "50"]),
+        (true, "50.0\n".to_string())
+    );
+
+    assert!(
+        git_ai::commands::blame::parse_blame_args(&args(&["--threshold", "50", "gate.rs"]))
+            .is_err()
+    );
+}
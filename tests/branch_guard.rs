@@ -0,0 +1,59 @@
+use git_ai::commands::checkpoint::{branch_excluded, resolve_base_commit};
+use git_ai::git::refs::get_reference_as_working_log;
+use git_ai::tmp_repo::TmpRepo;
+use tempfile::tempdir;
+
+#[test]
+fn test_checkpoints_skip_excluded_branches() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    let base_commit = resolve_base_commit(tmp_repo.repo()).unwrap();
+    let working_log = || {
+        get_reference_as_working_log(tmp_repo.repo(), &format!("ai-working-log/{}", base_commit))
+            .unwrap_or_default()
+    };
+    let mut config = tmp_repo.repo().config().unwrap();
+    config.set_str("git-ai.skipBranch", "release/*").unwrap();
+    tmp_repo.create_branch("release/1.0").unwrap();
+    let recorded_before = working_log().len();
+
+    std::fs::write(repo_path.join("agent.rs"), "fn a() {}\n").unwrap();
+    let checkpoint = [
+        "checkpoint",
+        "--author",
+        "Claude",
+        "--model",
+        "claude-4-sonnet",
+    ];
+    let output = tmp_repo.git_ai(&[&checkpoint[..], &["--json"]].concat());
+    assert!(output.status.success(), "{:?}", output);
+    let summary: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(summary["status"], "skipped");
+    assert_eq!(summary["branch"], "release/1.0");
+    assert_eq!(working_log().len(), recorded_before);
+
+    // A flag replaces the configured list for the run
+    let output = tmp_repo.git_ai(&[&checkpoint[..], &["--skip-branch", "main"]].concat());
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(working_log().len(), recorded_before + 1);
+
+    // An allow list skips every branch it doesn't name
+    config.remove("git-ai.skipBranch").unwrap();
+    config
+        .set_multivar("git-ai.onBranch", "^$", "feature/**, fix/*")
+        .unwrap();
+    let repo = tmp_repo.repo();
+    assert!(branch_excluded(repo, &[], &[]).unwrap());
+    tmp_repo.create_branch("feature/parser/lexer").unwrap();
+    assert!(!branch_excluded(repo, &[], &[]).unwrap());
+    assert!(branch_excluded(repo, &[], &["feature/**".to_string()]).unwrap());
+    assert!(branch_excluded(repo, &["fix/*".to_string()], &[]).unwrap());
+
+    // Detached, HEAD is on no branch, which only an allow list excludes
+    let head = repo.head().unwrap().target().unwrap();
+    repo.set_head_detached(head).unwrap();
+    assert!(branch_excluded(repo, &[], &[]).unwrap());
+    config.remove_multivar("git-ai.onBranch", ".*").unwrap();
+    assert!(!branch_excluded(repo, &[], &[]).unwrap());
+}
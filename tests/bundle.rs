@@ -0,0 +1,184 @@
+use assert_cmd::Command;
+use git_ai::git::refs::{authorship_ref_name, get_reference_as_authorship_log, put_reference};
+use git_ai::log_fmt::authorship_log::AuthorshipLog;
+use git_ai::log_fmt::working_log::{AgentMetadata, Confidence};
+use git_ai::tmp_repo::TmpRepo;
+use tempfile::tempdir;
+
+#[test]
+fn test_export_bundle_round_trips_authorship() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().join("source");
+    std::fs::create_dir_all(&repo_path).unwrap();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    tmp_repo
+        .write_file("agent.rs", "fn generated() {}\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-sonnet")
+        .unwrap();
+    tmp_repo.commit_with_message("AI commit").unwrap();
+    let head = tmp_repo.head_commit_sha().unwrap();
+    let ref_name = authorship_ref_name(tmp_repo.repo(), &head);
+
+    let bundle = tmp_dir.path().join("attribution.bundle");
+    let output = Command::cargo_bin("git-ai")
+        .unwrap()
+        .current_dir(&repo_path)
+        .args(["export", "--format", "git-bundle", "--output"])
+        .arg(&bundle)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    let output = tmp_repo.git(&["bundle", "list-heads", bundle.to_str().unwrap()]);
+    assert!(output.status.success(), "{:?}", output);
+    let heads = String::from_utf8_lossy(&output.stdout);
+    assert!(heads.contains(&format!("refs/{}", ref_name)), "{}", heads);
+
+    // Plain git can clone the commits out of the bundle; import adds the attribution
+    let clone_path = tmp_dir.path().join("clone");
+    let output = tmp_repo.git(&[
+        "clone",
+        "--quiet",
+        bundle.to_str().unwrap(),
+        clone_path.to_str().unwrap(),
+    ]);
+    assert!(output.status.success(), "{:?}", output);
+    let output = Command::cargo_bin("git-ai")
+        .unwrap()
+        .current_dir(&clone_path)
+        .arg("import")
+        .arg(&bundle)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+
+    let clone = git2::Repository::open(&clone_path).unwrap();
+    let log = get_reference_as_authorship_log(&clone, &ref_name).unwrap();
+    assert!(log.files.contains_key("agent.rs"));
+
+    let output = Command::cargo_bin("git-ai")
+        .unwrap()
+        .current_dir(&repo_path)
+        .args(["export", "--format", "json", "--output", "x"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn test_import_merge_strategies_reconcile_overlapping_logs() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().join("source");
+    std::fs::create_dir_all(&repo_path).unwrap();
+
+    // The bundle credits both lines to Claude with high confidence
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    tmp_repo
+        .write_file("agent.rs", "fn one() {}\nfn two() {}\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-sonnet")
+        .unwrap();
+    tmp_repo.commit_with_message("AI commit").unwrap();
+    let head = tmp_repo.head_commit_sha().unwrap();
+    let ref_name = authorship_ref_name(tmp_repo.repo(), &head);
+    let bundle = tmp_dir.path().join("attribution.bundle");
+    let output = Command::cargo_bin("git-ai")
+        .unwrap()
+        .current_dir(&repo_path)
+        .args(["export", "--format", "git-bundle", "--output"])
+        .arg(&bundle)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+
+    let clone_path = tmp_dir.path().join("clone");
+    let output = tmp_repo.git(&[
+        "clone",
+        "--quiet",
+        bundle.to_str().unwrap(),
+        clone_path.to_str().unwrap(),
+    ]);
+    assert!(output.status.success(), "{:?}", output);
+    let clone = git2::Repository::open(&clone_path).unwrap();
+
+    // Locally, line 1 is Alice's and line 2 an AI guess recorded with low confidence
+    let mut local = AuthorshipLog::new();
+    let file = local.get_or_create_file("agent.rs");
+    file.add_lines("Alice", &[1], None);
+    file.add_lines(
+        "Claude",
+        &[2],
+        Some(AgentMetadata {
+            model: "claude-sonnet".to_string(),
+            human_author: None,
+            confidence: Confidence::Low,
+        }),
+    );
+    let local_json = serde_json::to_string(&local).unwrap();
+
+    let high = Some(Confidence::High);
+    let low = Some(Confidence::Low);
+    for (strategy, expected) in [
+        ("prefer-local", [("Alice", None), ("Claude", low)]),
+        ("prefer-incoming", [("Claude", high), ("Claude", high)]),
+        ("highest-confidence", [("Alice", None), ("Claude", high)]),
+        ("union", [("Claude", high), ("Claude", low)]),
+    ] {
+        put_reference(&clone, &ref_name, &local_json, "local attribution").unwrap();
+        let output = Command::cargo_bin("git-ai")
+            .unwrap()
+            .current_dir(&clone_path)
+            .args(["import", "--merge-strategy", strategy])
+            .arg(&bundle)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{}: {:?}", strategy, output);
+
+        let log = get_reference_as_authorship_log(&clone, &ref_name).unwrap();
+        let file = &log.files["agent.rs"];
+        for (line, (author, confidence)) in (1..).zip(expected) {
+            let entry = file.get_author_entry(line).unwrap();
+            assert_eq!(entry.author, author, "{}: line {}", strategy, line);
+            assert_eq!(
+                entry.agent_metadata.as_ref().map(|m| m.confidence),
+                confidence,
+                "{}: line {}",
+                strategy,
+                line
+            );
+        }
+        assert_eq!(
+            clone.references_glob("refs/ai-import/*").unwrap().count(),
+            0
+        );
+    }
+
+    // Without the flag, highest-confidence applies
+    put_reference(&clone, &ref_name, &local_json, "local attribution").unwrap();
+    let output = Command::cargo_bin("git-ai")
+        .unwrap()
+        .current_dir(&clone_path)
+        .arg("import")
+        .arg(&bundle)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    let log = get_reference_as_authorship_log(&clone, &ref_name).unwrap();
+    assert_eq!(
+        log.files["agent.rs"].get_author_entry(1).unwrap().author,
+        "Alice"
+    );
+    assert!(String::from_utf8_lossy(&output.stderr).contains("1 merged"));
+
+    let output = Command::cargo_bin("git-ai")
+        .unwrap()
+        .current_dir(&clone_path)
+        .args(["import", "--merge-strategy", "newest"])
+        .arg(&bundle)
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+}
@@ -0,0 +1,36 @@
+use assert_cmd::Command;
+use git_ai::tmp_repo::TmpRepo;
+use tempfile::tempdir;
+
+#[test]
+fn test_checkpoint_repo_targets_another_repository() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+    let elsewhere = tempdir().unwrap();
+
+    let (_tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    std::fs::write(repo_path.join("remote.rs"), "one\ntwo\n").unwrap();
+
+    let output = Command::cargo_bin("git-ai")
+        .unwrap()
+        .current_dir(elsewhere.path())
+        .args(["checkpoint", "--json", "--repo"])
+        .arg(&repo_path)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    let recorded: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(recorded["files"], 1);
+    assert_eq!(recorded["added_lines"], 2);
+
+    let output = Command::cargo_bin("git-ai")
+        .unwrap()
+        .current_dir(&repo_path)
+        .args(["checkpoint", "--repo"])
+        .arg(elsewhere.path())
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Not a git repository"), "{}", stderr);
+}
@@ -0,0 +1,140 @@
+use git_ai::git::refs::{authorship_ref_name, get_reference_as_authorship_log};
+use git_ai::tmp_repo::TmpRepo;
+use tempfile::tempdir;
+
+/// git-ai with the environment a non-interactive `git cherry-pick` needs
+fn cherry_pick(tmp_repo: &TmpRepo, args: &[&str]) -> std::process::Output {
+    let env = [
+        ("GIT_EDITOR", "true"),
+        ("GIT_COMMITTER_NAME", "Test User"),
+        ("GIT_COMMITTER_EMAIL", "test@example.com"),
+    ];
+    tmp_repo.git_ai_with_env(&[&["cherry-pick"], args].concat(), &env)
+}
+
+/// Lines of `file` attributed to an agent in `sha`'s authorship log
+fn ai_lines(tmp_repo: &TmpRepo, sha: &str, file: &str) -> Vec<u32> {
+    let repo = tmp_repo.repo();
+    let log = get_reference_as_authorship_log(repo, &authorship_ref_name(repo, sha)).unwrap();
+    let mut lines: Vec<u32> = log.files[file]
+        .authors
+        .iter()
+        .filter(|author| author.agent_metadata.is_some())
+        .flat_map(|author| author.lines.iter().flat_map(|range| range.expand()))
+        .collect();
+    lines.sort();
+    lines
+}
+
+#[test]
+fn test_cherry_pick_carries_cleanly_applied_attribution() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+    let (tmp_repo, lines_file, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    let main = tmp_repo.current_branch().unwrap();
+    let base: Vec<String> = lines_file.contents().lines().map(str::to_string).collect();
+    let render = |lines: &[String]| lines.join("\n") + "\n";
+
+    // The agent rewrites line 2 and appends a tail on a feature branch
+    tmp_repo.create_branch("feature").unwrap();
+    let mut picked = base.clone();
+    picked[1] = "2 from the agent".to_string();
+    picked.push("tail from the agent".to_string());
+    tmp_repo
+        .write_file("lines.md", &render(&picked), true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo.commit_with_message("Agent edits").unwrap();
+    let source = tmp_repo.head_commit_sha().unwrap();
+    assert_eq!(
+        ai_lines(&tmp_repo, &source, "lines.md"),
+        [2, base.len() as u32 + 1]
+    );
+
+    // Meanwhile main gains a header, shifting every line, and its own line 2
+    tmp_repo.switch_branch(&main).unwrap();
+    let mut ours = vec!["header".to_string()];
+    ours.extend(base.iter().cloned());
+    ours[2] = "2 from a person".to_string();
+    tmp_repo
+        .write_file("lines.md", &render(&ours), true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("test_user")
+        .unwrap();
+    tmp_repo.commit_with_message("Header").unwrap();
+
+    // Line 2 conflicts; resolving it the person's way leaves only the tail to the agent
+    let output = cherry_pick(&tmp_repo, &[&source]);
+    assert!(!output.status.success(), "{:?}", output);
+    let mut resolved = ours.clone();
+    resolved.push("tail from the agent".to_string());
+    std::fs::write(repo_path.join("lines.md"), render(&resolved)).unwrap();
+    let output = tmp_repo.git(&["add", "lines.md"]);
+    assert!(output.status.success(), "{:?}", output);
+    let output = cherry_pick(&tmp_repo, &["--continue"]);
+    assert!(output.status.success(), "{:?}", output);
+
+    let new_sha = tmp_repo.head_commit_sha().unwrap();
+    assert_ne!(new_sha, source);
+    assert_eq!(
+        ai_lines(&tmp_repo, &new_sha, "lines.md"),
+        [resolved.len() as u32]
+    );
+    // The source keeps its own log
+    assert_eq!(ai_lines(&tmp_repo, &source, "lines.md").len(), 2);
+}
+
+#[test]
+fn test_cherry_pick_range_maps_each_commit() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    let main = tmp_repo.current_branch().unwrap();
+    let fork = tmp_repo.head_commit_sha().unwrap();
+
+    tmp_repo.create_branch("feature").unwrap();
+    tmp_repo
+        .write_file("one.rs", "fn one() {}\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo.commit_with_message("One").unwrap();
+    tmp_repo
+        .write_file("two.rs", "fn two() {}\nfn three() {}\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo.commit_with_message("Two").unwrap();
+
+    tmp_repo.switch_branch(&main).unwrap();
+    tmp_repo
+        .write_file("main.rs", "fn main() {}\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("test_user")
+        .unwrap();
+    tmp_repo.commit_with_message("Main").unwrap();
+
+    let range = format!("{}..feature", fork);
+    let output = cherry_pick(&tmp_repo, &[&range]);
+    assert!(output.status.success(), "{:?}", output);
+
+    let head = tmp_repo.head_commit_sha().unwrap();
+    assert_eq!(ai_lines(&tmp_repo, &head, "two.rs"), [1, 2]);
+    let parent = tmp_repo
+        .repo()
+        .head()
+        .unwrap()
+        .peel_to_commit()
+        .unwrap()
+        .parent(0)
+        .unwrap()
+        .id()
+        .to_string();
+    assert_eq!(ai_lines(&tmp_repo, &parent, "one.rs"), [1]);
+}
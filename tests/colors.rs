@@ -0,0 +1,81 @@
+use git_ai::utils::{ColorRole, ColorTheme, ThemePreset, parse_color};
+use tempfile::tempdir;
+
+#[test]
+fn test_color_theme_from_config() {
+    assert_eq!(parse_color("yellow"), Some(Some("33".to_string())));
+    assert_eq!(
+        parse_color("bold bright-blue"),
+        Some(Some("1;94".to_string()))
+    );
+    assert_eq!(parse_color("38;5;208"), Some(Some("38;5;208".to_string())));
+    assert_eq!(parse_color("none"), Some(None));
+    assert_eq!(parse_color("chartreuse"), None);
+
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("config");
+    std::fs::write(
+        &path,
+        "[git-ai \"colors\"]\n\tdebug-prefix = none\n\tai-line = magenta\n\thuman-line = chartreuse\n",
+    )
+    .unwrap();
+    let config = git2::Config::open(&path).unwrap();
+
+    let theme = ColorTheme::from_config(Some(&config), false, None);
+    assert_eq!(theme.paint(ColorRole::DebugPrefix, "[git-ai]"), "[git-ai]");
+    assert_eq!(theme.paint(ColorRole::AiLine, "x"), "\x1b[35mx\x1b[0m");
+    // Invalid values keep the default, which for blame lines is no color
+    assert_eq!(theme.sgr(ColorRole::HumanLine), None);
+
+    let defaults = ColorTheme::from_config(None, false, None);
+    assert_eq!(defaults.sgr(ColorRole::DebugPrefix), Some("1;33"));
+
+    // NO_COLOR wins over any configuration
+    let theme = ColorTheme::from_config(Some(&config), true, None);
+    assert!(!theme.enabled);
+    assert_eq!(theme.paint(ColorRole::AiLine, "x"), "x");
+}
+
+#[test]
+fn test_theme_presets_under_role_overrides() {
+    assert_eq!(
+        ThemePreset::parse("high-contrast"),
+        Some(ThemePreset::HighContrast)
+    );
+    assert_eq!(ThemePreset::parse("solarized"), None);
+
+    let mono = ColorTheme::from_config(None, false, Some(ThemePreset::Mono));
+    assert_eq!(mono.sgr(ColorRole::AiLine), Some("1"));
+    assert_eq!(mono.sgr(ColorRole::MixedLine), Some("4"));
+    assert_eq!(mono.sgr(ColorRole::Gutter), Some("2"));
+    assert_eq!(mono.sgr(ColorRole::HumanLine), None);
+    assert_eq!(mono.sgr(ColorRole::DebugPrefix), Some("1;33"));
+
+    // Without a preset the gutter takes the line's color, as before themes
+    assert_eq!(
+        ColorTheme::from_config(None, false, None).sgr(ColorRole::Gutter),
+        None
+    );
+
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("config");
+    std::fs::write(
+        &path,
+        "[git-ai \"colors\"]\n\ttheme = light\n\tmixed-line = cyan\n",
+    )
+    .unwrap();
+    let config = git2::Config::open(&path).unwrap();
+
+    // The configured preset applies, with individually configured roles on top
+    let theme = ColorTheme::from_config(Some(&config), false, None);
+    assert_eq!(theme.sgr(ColorRole::AiLine), Some("35"));
+    assert_eq!(theme.sgr(ColorRole::MixedLine), Some("36"));
+
+    // --theme replaces the configured preset, not the individual roles
+    let theme = ColorTheme::from_config(Some(&config), false, Some(ThemePreset::Dark));
+    assert_eq!(theme.sgr(ColorRole::AiLine), Some("95"));
+    assert_eq!(theme.sgr(ColorRole::MixedLine), Some("36"));
+
+    let theme = ColorTheme::from_config(Some(&config), true, Some(ThemePreset::Dark));
+    assert_eq!(theme.sgr(ColorRole::AiLine), None);
+}
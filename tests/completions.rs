@@ -0,0 +1,33 @@
+use git_ai::commands::completions;
+
+#[test]
+fn test_completions_cover_commands_and_flags() {
+    let bash = completions::run("bash").unwrap();
+    assert!(bash.contains("complete -o default -F _git_ai git-ai"));
+    assert!(bash.contains("--count-reformat-as"));
+    assert!(bash.contains("--compare-with-git"));
+    assert!(bash.contains("--with-presets"));
+
+    let status = std::process::Command::new("bash")
+        .args(["-n", "-c", &bash])
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    assert!(
+        completions::run("zsh")
+            .unwrap()
+            .starts_with("#compdef git-ai")
+    );
+    assert!(
+        completions::run("fish")
+            .unwrap()
+            .contains("__fish_seen_subcommand_from checkpoint' -l author-type")
+    );
+    assert!(
+        completions::run("powershell")
+            .unwrap()
+            .contains("'stats' = @(")
+    );
+    assert!(completions::run("tcsh").is_err());
+}
@@ -0,0 +1,74 @@
+use assert_cmd::Command;
+use git_ai::commands::checkpoint::{detect_model_from, resolve_base_commit};
+use git_ai::git::refs::get_reference_as_working_log;
+use git_ai::log_fmt::working_log::Confidence;
+use git_ai::tmp_repo::TmpRepo;
+use std::collections::HashMap;
+use tempfile::tempdir;
+
+#[test]
+fn test_detect_model_signal_order() {
+    let detect = |vars: &[(&str, &str)], processes: &[&str]| {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        let processes: Vec<String> = processes.iter().map(|name| name.to_string()).collect();
+        detect_model_from(|name| vars.get(name).cloned(), &processes)
+    };
+
+    assert_eq!(detect(&[], &["bash", "sshd"]), None);
+    assert_eq!(
+        detect(&[], &["sh", "/usr/local/bin/Cursor Helper", "claude"]).as_deref(),
+        Some("cursor")
+    );
+    assert_eq!(
+        detect(&[("CLAUDECODE", "1")], &["cursor"]).as_deref(),
+        Some("claude")
+    );
+    assert_eq!(
+        detect(
+            &[("CLAUDECODE", "1"), ("ANTHROPIC_MODEL", "claude-opus-4")],
+            &["cursor"]
+        )
+        .as_deref(),
+        Some("claude-opus-4")
+    );
+}
+
+#[test]
+fn test_checkpoint_detect_model() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    let base_commit = resolve_base_commit(tmp_repo.repo()).unwrap();
+    let last_checkpoint = || {
+        get_reference_as_working_log(tmp_repo.repo(), &format!("ai-working-log/{}", base_commit))
+            .unwrap()
+            .pop()
+            .unwrap()
+    };
+    let checkpoint = |args: &[&str]| {
+        let output = Command::cargo_bin("git-ai")
+            .unwrap()
+            .current_dir(&repo_path)
+            .arg("checkpoint")
+            .args(args)
+            .env("ANTHROPIC_MODEL", "claude-opus-4")
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{:?}", output);
+    };
+
+    std::fs::write(repo_path.join("agent.rs"), "fn a() {}\n").unwrap();
+    checkpoint(&["--author", "Claude", "--detect-model"]);
+    let detected = last_checkpoint();
+    let metadata = detected.agent_metadata.unwrap();
+    assert_eq!(metadata.model, "claude-opus-4");
+    assert_eq!(metadata.confidence, Confidence::High);
+
+    // An explicit --model wins over what the environment says
+    std::fs::write(repo_path.join("agent.rs"), "fn a() {}\nfn b() {}\n").unwrap();
+    checkpoint(&["--author", "Claude", "--detect-model", "--model", "gpt-5"]);
+    assert_eq!(last_checkpoint().agent_metadata.unwrap().model, "gpt-5");
+}
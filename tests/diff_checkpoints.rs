@@ -0,0 +1,74 @@
+use git_ai::tmp_repo::TmpRepo;
+use tempfile::tempdir;
+
+fn diff(tmp_repo: &TmpRepo, args: &[&str]) -> String {
+    let mut full_args = vec!["diff-checkpoints"];
+    full_args.extend_from_slice(args);
+    let output = tmp_repo.git_ai(&full_args);
+    assert!(output.status.success(), "{:?}", output);
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+#[test]
+fn test_diff_checkpoints_annotates_changes() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    let checkpoint = |args: &[&str]| {
+        let mut full_args = vec!["checkpoint"];
+        full_args.extend_from_slice(args);
+        let output = tmp_repo.git_ai(&full_args);
+        assert!(output.status.success(), "{:?}", output);
+    };
+    let agent = ["--author", "Claude", "--model", "claude-sonnet"];
+
+    tmp_repo
+        .write_file("lib.rs", "fn one() {}\n", true)
+        .unwrap();
+    checkpoint(&["--author", "Ada", "--snapshot"]);
+    tmp_repo
+        .write_file("lib.rs", "fn one() {}\nfn two() {}\n", true)
+        .unwrap();
+    checkpoint(&[agent.as_slice(), &["--snapshot"]].concat());
+    tmp_repo
+        .write_file("notes.rs", "fn three() {}\n", true)
+        .unwrap();
+    checkpoint(&agent);
+
+    // Both ends have snapshots: a unified diff headed by who made the change
+    assert_eq!(
+        diff(&tmp_repo, &["1", "2"]),
+        "=== lib.rs\n\
+         changed in #2 Claude [AI: claude-sonnet]\n\
+         --- #1 lib.rs\n\
+         +++ #2 lib.rs\n\
+         @@ -1 +1,2 @@\n \
+         fn one() {}\n\
+         +fn two() {}\n"
+    );
+
+    // #3 took no snapshot, so notes.rs falls back to the recorded line changes
+    let output = diff(&tmp_repo, &["2", "3"]);
+    assert_eq!(
+        output,
+        "=== notes.rs\n\
+         changed in #3 Claude [AI: claude-sonnet]\n\
+         no content snapshot; recorded line changes:\n  \
+         #3 Claude [AI: claude-sonnet]  +1\n"
+    );
+
+    // Against the work tree, uncheckpointed edits show up too
+    std::fs::write(repo_path.join("lib.rs"), "fn one() {}\n").unwrap();
+    let output = diff(&tmp_repo, &["2"]);
+    assert!(
+        output.contains("=== lib.rs\nchanged since the last checkpoint\n"),
+        "{}",
+        output
+    );
+    assert!(output.contains("-fn two() {}\n"), "{}", output);
+    assert!(output.contains("=== notes.rs\nchanged in #3"), "{}", output);
+
+    let output = tmp_repo.git_ai(&["diff-checkpoints", "9"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("No checkpoint #9"));
+}
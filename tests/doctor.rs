@@ -0,0 +1,84 @@
+use assert_cmd::Command;
+use git_ai::commands::doctor::{self, CheckStatus};
+use git_ai::commands::init;
+use git_ai::commands::json_schema::json_schema;
+use git_ai::tmp_repo::TmpRepo;
+use tempfile::tempdir;
+
+fn status_of(checks: &[doctor::Check], id: &str) -> CheckStatus {
+    checks.iter().find(|check| check.id == id).unwrap().status
+}
+
+#[test]
+fn test_doctor_checks_reflect_setup() {
+    let tmp_dir = tempdir().unwrap();
+    let home = tempdir().unwrap();
+    let tmp_repo = TmpRepo::new(tmp_dir.path().to_path_buf()).unwrap();
+
+    let checks = doctor::run_checks(Some(tmp_repo.repo()), Some(home.path()));
+    assert_eq!(status_of(&checks, "repository"), CheckStatus::Pass);
+    assert_eq!(status_of(&checks, "post-rewrite-hook"), CheckStatus::Warn);
+    assert_eq!(status_of(&checks, "editor-hooks"), CheckStatus::Warn);
+    assert_eq!(doctor::exit_code(&checks), 1);
+    assert!(
+        checks
+            .iter()
+            .filter(|check| check.status != CheckStatus::Pass)
+            .all(|check| check.remediation.is_some())
+    );
+
+    init::install_git_hooks(tmp_repo.repo()).unwrap();
+    init::run(home.path(), &[init::Preset::Claude]).unwrap();
+    let checks = doctor::run_checks(Some(tmp_repo.repo()), Some(home.path()));
+    assert_eq!(status_of(&checks, "post-rewrite-hook"), CheckStatus::Pass);
+    assert_eq!(status_of(&checks, "editor-hooks"), CheckStatus::Pass);
+
+    // With core.hooksPath set, doctor looks where git (and `hooks status`) does
+    tmp_repo
+        .repo()
+        .config()
+        .unwrap()
+        .set_str("core.hooksPath", ".husky")
+        .unwrap();
+    let checks = doctor::run_checks(Some(tmp_repo.repo()), Some(home.path()));
+    assert_eq!(status_of(&checks, "post-rewrite-hook"), CheckStatus::Warn);
+    init::install_git_hooks(tmp_repo.repo()).unwrap();
+    let checks = doctor::run_checks(Some(tmp_repo.repo()), Some(home.path()));
+    assert_eq!(status_of(&checks, "post-rewrite-hook"), CheckStatus::Pass);
+
+    let checks = doctor::run_checks(None, Some(home.path()));
+    assert_eq!(status_of(&checks, "repository"), CheckStatus::Fail);
+    assert_eq!(doctor::exit_code(&checks), 2);
+}
+
+#[test]
+fn test_doctor_json_output() {
+    let tmp_dir = tempdir().unwrap();
+    let home = tempdir().unwrap();
+    let _tmp_repo = TmpRepo::new(tmp_dir.path().to_path_buf()).unwrap();
+
+    let output = Command::cargo_bin("git-ai")
+        .unwrap()
+        .current_dir(tmp_dir.path())
+        .env("HOME", home.path())
+        .args(["doctor", "--json"])
+        .output()
+        .unwrap();
+    // No hooks are installed yet, so the worst status is a warning
+    assert_eq!(output.status.code(), Some(1));
+
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let schema = &json_schema()["$defs"]["doctor"];
+    assert_eq!(report["status"], "warn");
+    for field in schema["properties"]["checks"]["items"]["required"]
+        .as_array()
+        .unwrap()
+    {
+        let field = field.as_str().unwrap();
+        assert!(
+            report["checks"][0].get(field).is_some(),
+            "missing {}",
+            field
+        );
+    }
+}
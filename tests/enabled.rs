@@ -0,0 +1,102 @@
+use git_ai::commands::checkpoint::resolve_base_commit;
+use git_ai::git::refs::{authorship_ref_name, get_reference_as_authorship_log};
+use git_ai::tmp_repo::TmpRepo;
+use tempfile::tempdir;
+
+#[test]
+fn test_disabled_repository_skips_tracking() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    tmp_repo
+        .repo()
+        .config()
+        .unwrap()
+        .set_bool("git-ai.enabled", false)
+        .unwrap();
+    tmp_repo
+        .write_file("agent.rs", "fn generated() {}\n", true)
+        .unwrap();
+    let working_ref = format!(
+        "refs/ai-working-log/{}",
+        resolve_base_commit(tmp_repo.repo()).unwrap()
+    );
+
+    let output = tmp_repo.git_ai_with_env(
+        &[
+            "checkpoint",
+            "--author",
+            "Claude",
+            "--model",
+            "claude-sonnet",
+        ],
+        &[],
+    );
+    assert!(output.status.success(), "{:?}", output);
+    assert!(tmp_repo.repo().find_reference(&working_ref).is_err());
+
+    // The scope check still runs, and --json says why nothing was recorded
+    let output =
+        tmp_repo.git_ai_with_env(&["checkpoint", "--verify-clean", "--allow", "src/**"], &[]);
+    assert!(!output.status.success(), "{:?}", output);
+    let output = tmp_repo.git_ai_with_env(&["checkpoint", "--json"], &[]);
+    assert!(output.status.success(), "{:?}", output);
+    let summary: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(summary["status"], "disabled");
+
+    let output = tmp_repo.git_ai_with_env(
+        &["mark-ai", "agent.rs", "--lines", "1", "--model", "gpt-4o"],
+        &[],
+    );
+    assert!(output.status.success(), "{:?}", output);
+    assert!(tmp_repo.repo().find_reference(&working_ref).is_err());
+
+    // Commits still go through, just without an authorship log
+    let output = tmp_repo.git_ai_with_env(&["commit", "-q", "-m", "Disabled"], &[]);
+    assert!(output.status.success(), "{:?}", output);
+    let head = tmp_repo.head_commit_sha().unwrap();
+    assert!(
+        get_reference_as_authorship_log(
+            tmp_repo.repo(),
+            &authorship_ref_name(tmp_repo.repo(), &head)
+        )
+        .is_err()
+    );
+
+    // GIT_AI_DISABLE opts out too, even where the config enables tracking
+    tmp_repo
+        .repo()
+        .config()
+        .unwrap()
+        .set_bool("git-ai.enabled", true)
+        .unwrap();
+    tmp_repo
+        .write_file("agent.rs", "fn generated() {}\nfn more() {}\n", true)
+        .unwrap();
+    let working_ref = format!("refs/ai-working-log/{}", head);
+    let output = tmp_repo.git_ai_with_env(
+        &[
+            "checkpoint",
+            "--author",
+            "Claude",
+            "--model",
+            "claude-sonnet",
+        ],
+        &[("GIT_AI_DISABLE", "1")],
+    );
+    assert!(output.status.success(), "{:?}", output);
+    assert!(tmp_repo.repo().find_reference(&working_ref).is_err());
+
+    let output = tmp_repo.git_ai_with_env(
+        &[
+            "checkpoint",
+            "--author",
+            "Claude",
+            "--model",
+            "claude-sonnet",
+        ],
+        &[("GIT_AI_DISABLE", "0")],
+    );
+    assert!(output.status.success(), "{:?}", output);
+    assert!(tmp_repo.repo().find_reference(&working_ref).is_ok());
+}
@@ -0,0 +1,54 @@
+use git_ai::commands::checkpoint::expire_committed_working_logs;
+use git_ai::git::refs::{list_working_logs, put_reference};
+use git_ai::tmp_repo::TmpRepo;
+use git_ai::utils::parse_duration;
+use tempfile::tempdir;
+
+#[test]
+fn test_parse_duration() {
+    assert_eq!(parse_duration("30d"), Some(30 * 24 * 60 * 60));
+    assert_eq!(parse_duration("12h"), Some(12 * 60 * 60));
+    assert_eq!(parse_duration("2w"), Some(14 * 24 * 60 * 60));
+    assert_eq!(parse_duration("90s"), Some(90));
+    assert_eq!(parse_duration("30"), None);
+    assert_eq!(parse_duration("d"), None);
+    assert_eq!(parse_duration("3y"), None);
+}
+
+#[test]
+fn test_expire_drops_old_committed_checkpoints_only() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    // The base commit leaves a committed working log behind under "initial"
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    let head = tmp_repo.head_commit_sha().unwrap();
+    tmp_repo.write_file("new.txt", "pending\n", true).unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("test_user")
+        .unwrap();
+
+    // Age every checkpoint, including the current one, well past the cutoff
+    for (base_commit, mut working_log) in list_working_logs(tmp_repo.repo()).unwrap() {
+        for checkpoint in &mut working_log {
+            checkpoint.timestamp = 1_000;
+        }
+        put_reference(
+            tmp_repo.repo(),
+            &format!("ai-working-log/{}", base_commit),
+            &serde_json::to_string(&working_log).unwrap(),
+            "age checkpoints",
+        )
+        .unwrap();
+    }
+
+    let expired =
+        expire_committed_working_logs(tmp_repo.repo(), &head, parse_duration("30d").unwrap())
+            .unwrap();
+    assert!(expired > 0);
+
+    let remaining = list_working_logs(tmp_repo.repo()).unwrap();
+    assert_eq!(remaining.len(), 1);
+    assert_eq!(remaining[0].0, head);
+    assert_eq!(remaining[0].1.len(), 1);
+}
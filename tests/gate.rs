@@ -0,0 +1,52 @@
+use assert_cmd::Command;
+use git_ai::commands::gate;
+use git_ai::tmp_repo::{TmpRepo, args};
+use tempfile::tempdir;
+
+#[test]
+fn test_gate_per_file_max_flags_fully_ai_files() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    tmp_repo
+        .write_file("human.rs", &"h\n".repeat(10), true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("test_user")
+        .unwrap();
+    tmp_repo.write_file("agent.rs", "a1\na2\n", true).unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo.commit_with_message("mostly human").unwrap();
+
+    let check = |values: &[&str]| {
+        let (revspec, options) = gate::parse_gate_args(&args(values)).unwrap();
+        let outcome = gate::run(tmp_repo.repo(), revspec.as_deref(), &options).unwrap();
+        (outcome.passed(), gate::render(&outcome, &options))
+    };
+
+    // 2 of 12 lines overall is under --max, but agent.rs alone is entirely AI
+    assert!(check(&["--max", "50", "--no-fetch"]).0);
+    let (passed, output) = check(&["--max", "50", "--per-file-max", "80", "--no-fetch"]);
+    assert!(!passed);
+    assert_eq!(
+        output,
+        "git-ai gate failed: 1 file is over --per-file-max 80%:\n  agent.rs  100% AI (2 of 2 lines)\n"
+    );
+    // Too small to count under --min-lines
+    assert!(check(&["--per-file-max", "80", "--min-lines", "3", "--no-fetch"]).0);
+
+    let output = Command::cargo_bin("git-ai")
+        .unwrap()
+        .current_dir(&repo_path)
+        .args(["gate", "--per-file-max", "80", "--no-fetch"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("agent.rs"));
+
+    assert!(gate::parse_gate_args(&args(&["--min-lines", "3"])).is_err());
+    assert!(gate::parse_gate_args(&args(&["--per-file-max", "120"])).is_err());
+}
@@ -0,0 +1,98 @@
+use git_ai::tmp_repo::TmpRepo;
+use tempfile::tempdir;
+
+#[test]
+fn test_git_ai_routes_like_git_dash_ai() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().join("repo");
+    std::fs::create_dir_all(&repo_path).unwrap();
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    tmp_repo
+        .write_file("agent.rs", "fn generated() {}\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-sonnet")
+        .unwrap();
+    tmp_repo.commit_with_message("AI commit").unwrap();
+
+    for args in [
+        &["blame", "agent.rs"][..],
+        &["stats", "HEAD", "--no-fetch"],
+        &["--version"],
+        &["version"],
+    ] {
+        let direct = tmp_repo.git_ai(args);
+        let via_git = tmp_repo.git(&[&["ai"], args].concat());
+        assert!(via_git.status.success(), "{:?}: {:?}", args, via_git);
+        assert_eq!(via_git.stdout, direct.stdout, "{:?}", args);
+    }
+    let output = tmp_repo.git(&["ai", "version"]);
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        format!("git-ai {}\n", env!("CARGO_PKG_VERSION"))
+    );
+
+    // -h is git-ai's help, not a clap error or git's usage
+    let output = tmp_repo.git(&["ai", "-h"]);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("git ai <git-ai command>"));
+
+    // Options git handles before dispatching still pick the repository
+    let summary = ["stats", "HEAD", "--no-fetch", "--output", "summary-line"];
+    let repo = repo_path.to_str().unwrap();
+    let git_dir = format!("--git-dir={}/.git", repo);
+    let work_tree = format!("--work-tree={}", repo);
+    for prefix in [&["-C", repo][..], &[&git_dir, &work_tree]] {
+        let output = tmp_repo
+            .git_command()
+            .current_dir(tmp_dir.path())
+            .args([prefix, &["ai"], &summary].concat())
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{:?}: {:?}", prefix, output);
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            "git-ai: 0% human / 100% AI across 1 file\n"
+        );
+    }
+    assert!(
+        !tmp_repo
+            .git_command()
+            .current_dir(tmp_dir.path())
+            .args([&["ai"], &summary[..]].concat())
+            .output()
+            .unwrap()
+            .status
+            .success()
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_git_named_install_handles_git_ai() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().join("repo");
+    std::fs::create_dir_all(&repo_path).unwrap();
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    tmp_repo
+        .write_file("agent.rs", "fn generated() {}\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-sonnet")
+        .unwrap();
+    tmp_repo.commit_with_message("AI commit").unwrap();
+
+    // git-ai installed under the name git sees `git ai stats` as `ai stats`
+    let proxy = tmp_dir.path().join("git");
+    std::os::unix::fs::symlink(assert_cmd::cargo::cargo_bin("git-ai"), &proxy).unwrap();
+    let output = std::process::Command::new(&proxy)
+        .args(["ai", "stats", "HEAD", "--no-fetch"])
+        .current_dir(&repo_path)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(
+        output.stdout,
+        tmp_repo.git_ai(&["stats", "HEAD", "--no-fetch"]).stdout
+    );
+}
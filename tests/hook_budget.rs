@@ -0,0 +1,75 @@
+use git_ai::commands::checkpoint::resolve_base_commit;
+use git_ai::git::refs::get_reference_as_working_log;
+use git_ai::tmp_repo::TmpRepo;
+use std::process::Command;
+use std::time::{Duration, Instant};
+use tempfile::tempdir;
+
+fn run_checkpoint(repo_path: &std::path::Path, args: &[&str]) -> std::process::Output {
+    let binary_path = std::env::current_dir().unwrap().join("target/debug/git-ai");
+    Command::new(binary_path)
+        .current_dir(repo_path)
+        .arg("checkpoint")
+        .args(args)
+        .output()
+        .expect("Failed to run git-ai checkpoint")
+}
+
+fn working_log_len(tmp_repo: &TmpRepo) -> usize {
+    let base_commit = resolve_base_commit(tmp_repo.repo()).unwrap();
+    get_reference_as_working_log(tmp_repo.repo(), &format!("ai-working-log/{}", base_commit))
+        .map(|working_log| working_log.len())
+        .unwrap_or(0)
+}
+
+#[test]
+fn test_checkpoint_over_budget_finishes_in_background() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    tmp_repo.write_file("edit.txt", "edited\n", true).unwrap();
+    let before = working_log_len(&tmp_repo);
+
+    // A zero budget always runs out: the hook returns cleanly and the write still lands
+    let output = run_checkpoint(&repo_path, &["--max-ms", "0", "--author", "bg_user"]);
+    assert!(output.status.success());
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while working_log_len(&tmp_repo) == before && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(20));
+    }
+    assert_eq!(working_log_len(&tmp_repo), before + 1);
+}
+
+#[test]
+fn test_checkpoint_within_budget_passes_hook_prompt_through() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    tmp_repo.write_file("edit.txt", "edited\n", true).unwrap();
+
+    let output = run_checkpoint(
+        &repo_path,
+        &[
+            "--max-ms",
+            "10000",
+            "--hook-input",
+            r#"{"prompt":"add edit.txt"}"#,
+        ],
+    );
+    assert!(output.status.success());
+
+    let base_commit = resolve_base_commit(tmp_repo.repo()).unwrap();
+    let working_log =
+        get_reference_as_working_log(tmp_repo.repo(), &format!("ai-working-log/{}", base_commit))
+            .unwrap();
+    assert_eq!(
+        working_log.last().unwrap().prompt.as_deref(),
+        Some("add edit.txt")
+    );
+
+    let output = run_checkpoint(&repo_path, &["--max-ms", "soon"]);
+    assert!(!output.status.success());
+}
@@ -0,0 +1,104 @@
+use git_ai::commands::checkpoint::resolve_base_commit;
+use git_ai::commands::hook_exec::{HookAction, parse_hook_payload};
+use git_ai::commands::init::Preset;
+use git_ai::git::refs::get_reference_as_working_log;
+use git_ai::tmp_repo::TmpRepo;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+use tempfile::tempdir;
+
+#[test]
+fn test_hook_exec_checkpoints_in_payload_cwd() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    tmp_repo
+        .write_file("agent.txt", "from the agent\n", true)
+        .unwrap();
+
+    // Editors may launch hooks from anywhere; the payload says where the agent works
+    let elsewhere = tempdir().unwrap();
+    let payload = serde_json::json!({
+        "hook_event_name": "PostToolUse",
+        "cwd": repo_path,
+    });
+    let binary_path = std::env::current_dir().unwrap().join("target/debug/git-ai");
+    let mut child = Command::new(binary_path)
+        .current_dir(elsewhere.path())
+        .args(["hook-exec", "claude"])
+        .stdin(Stdio::piped())
+        .spawn()
+        .expect("Failed to run git-ai hook-exec");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(payload.to_string().as_bytes())
+        .unwrap();
+    assert!(child.wait().unwrap().success());
+
+    // Past the hook budget the checkpoint finishes in the background
+    let base_commit = resolve_base_commit(tmp_repo.repo()).unwrap();
+    let ref_name = format!("ai-working-log/{}", base_commit);
+    let deadline = Instant::now() + Duration::from_secs(10);
+    let working_log = loop {
+        match get_reference_as_working_log(tmp_repo.repo(), &ref_name) {
+            Ok(working_log) if !working_log.is_empty() => break working_log,
+            _ if Instant::now() < deadline => std::thread::sleep(Duration::from_millis(20)),
+            _ => panic!("hook-exec never checkpointed"),
+        }
+    };
+    let last = working_log.last().unwrap();
+    assert_eq!(last.author, "Claude");
+    assert_eq!(last.entries[0].file, "agent.txt");
+}
+
+#[test]
+fn test_hook_exec_outside_a_repository_is_quiet() {
+    let elsewhere = tempdir().unwrap();
+    let payload = serde_json::json!({
+        "hook_event_name": "PostToolUse",
+        "cwd": elsewhere.path(),
+    });
+    let binary_path = std::env::current_dir().unwrap().join("target/debug/git-ai");
+    let mut child = Command::new(binary_path)
+        .current_dir(elsewhere.path())
+        .args(["hook-exec", "claude"])
+        .stdin(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to run git-ai hook-exec");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(payload.to_string().as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    assert!(output.stderr.is_empty());
+}
+
+#[test]
+fn test_hook_exec_payload_events() {
+    let invocation = parse_hook_payload(
+        Preset::Cursor,
+        r#"{"prompt":"fix it","workspace_roots":["/work"]}"#,
+        Some("beforeSubmitPrompt"),
+    )
+    .unwrap();
+    assert_eq!(
+        invocation.action,
+        HookAction::Human {
+            prompt: Some("fix it".to_string())
+        }
+    );
+    assert_eq!(
+        invocation.cwd.as_deref(),
+        Some(std::path::Path::new("/work"))
+    );
+
+    assert!(parse_hook_payload(Preset::Claude, r#"{"hook_event_name":"Stop"}"#, None).is_err());
+    assert!(parse_hook_payload(Preset::Claude, "", None).is_err());
+}
@@ -0,0 +1,72 @@
+use git_ai::commands::init::install_git_hooks;
+use git_ai::commands::json_schema::json_schema;
+use git_ai::tmp_repo::TmpRepo;
+use tempfile::tempdir;
+
+#[test]
+fn test_hooks_status_reports_each_hook() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    install_git_hooks(tmp_repo.repo()).unwrap();
+    let hooks_dir = tmp_repo.repo().path().join("hooks");
+    std::fs::write(
+        hooks_dir.join("pre-commit"),
+        "#!/bin/sh\ncargo fmt --check\ngit-ai checkpoint --author \"$USER\"\n",
+    )
+    .unwrap();
+    std::fs::write(hooks_dir.join("post-merge"), "#!/bin/sh\nnpm install\n").unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let executable = std::fs::Permissions::from_mode(0o755);
+        std::fs::set_permissions(hooks_dir.join("pre-commit"), executable).unwrap();
+    }
+
+    let output = tmp_repo.git_ai(&["hooks", "status"]);
+    assert!(output.status.success(), "{:?}", output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let rows: Vec<&str> = stdout.lines().skip(1).collect();
+    assert_eq!(rows[0], "pre-commit    your hook, chained to git-ai");
+    assert_eq!(rows[1], "post-commit   not installed");
+    #[cfg(unix)]
+    assert_eq!(
+        rows[2],
+        "post-merge    your hook, does not run git-ai (not executable, so git skips it)"
+    );
+    assert_eq!(rows[3], "post-rewrite  installed by git-ai");
+
+    let output = tmp_repo.git_ai(&["hooks", "status", "--json"]);
+    assert!(output.status.success(), "{:?}", output);
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let schema = &json_schema()["$defs"]["hooks_status"];
+    for field in schema["required"].as_array().unwrap() {
+        assert!(report.get(field.as_str().unwrap()).is_some());
+    }
+    let rewrite = &report["hooks"][3];
+    assert_eq!(rewrite["name"], "post-rewrite");
+    assert_eq!(rewrite["installed_by_git_ai"], true);
+    assert_eq!(rewrite["chained"], false);
+    assert_eq!(report["hooks"][0]["chained"], true);
+    assert_eq!(report["hooks"][1]["installed"], false);
+
+    // core.hooksPath moves where git, and so the status, looks
+    tmp_repo
+        .repo()
+        .config()
+        .unwrap()
+        .set_str("core.hooksPath", "githooks")
+        .unwrap();
+    let output = tmp_repo.git_ai(&["hooks", "status", "--json"]);
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(report["hooks_dir"].as_str().unwrap().ends_with("githooks"));
+    assert!(
+        report["hooks"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .all(|hook| hook["installed"] == false)
+    );
+
+    assert!(!tmp_repo.git_ai(&["hooks", "install"]).status.success());
+}
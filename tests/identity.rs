@@ -0,0 +1,92 @@
+use assert_cmd::Command;
+use git_ai::git::repository::{config_bytes, config_string_lossy};
+use git_ai::tmp_repo::TmpRepo;
+use std::io::Write;
+use tempfile::tempdir;
+
+fn set_raw_user_name(repo_path: &std::path::Path, name: &[u8]) {
+    let mut config = std::fs::OpenOptions::new()
+        .append(true)
+        .open(repo_path.join(".git/config"))
+        .unwrap();
+    config.write_all(b"[user]\n\tname = ").unwrap();
+    config.write_all(name).unwrap();
+    config.write_all(b"\n").unwrap();
+}
+
+fn checkpoint_record(repo_path: &std::path::Path) -> serde_json::Value {
+    let output = Command::cargo_bin("git-ai")
+        .unwrap()
+        .current_dir(repo_path)
+        .args(["checkpoint", "--json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!stderr.contains("not configured"), "{}", stderr);
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+fn decode_hex(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+#[test]
+fn test_non_ascii_user_name_is_recorded() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+    let (_tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+
+    set_raw_user_name(&repo_path, "Zoë Łukasz".as_bytes());
+    std::fs::write(repo_path.join("notes.txt"), "first\n").unwrap();
+    let recorded = checkpoint_record(&repo_path);
+    assert_eq!(recorded["author"], "Zoë Łukasz");
+    // Valid UTF-8 needs no raw copy
+    assert!(recorded["author_raw"].is_null());
+}
+
+#[test]
+fn test_non_utf8_user_name_is_decoded_lossily() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+
+    // "Jörg" as latin-1
+    set_raw_user_name(&repo_path, b"J\xf6rg");
+    let config = tmp_repo.repo().config().unwrap();
+    assert!(config.get_string("user.name").is_err());
+    assert_eq!(
+        config_string_lossy(&config, "user.name").as_deref(),
+        Some("J\u{fffd}rg")
+    );
+    assert_eq!(
+        config_bytes(&config, "user.name").as_deref(),
+        Some(&b"J\xf6rg"[..])
+    );
+
+    std::fs::write(repo_path.join("notes.txt"), "first\n").unwrap();
+    let recorded = checkpoint_record(&repo_path);
+    assert_eq!(recorded["author"], "J\u{fffd}rg");
+    assert_eq!(recorded["author_raw"], "4af67267");
+    assert_eq!(
+        decode_hex(recorded["author_raw"].as_str().unwrap()),
+        b"J\xf6rg"
+    );
+
+    // The raw name survives in the stored working log, not just the printed record
+    let base = git_ai::commands::checkpoint::resolve_base_commit(tmp_repo.repo()).unwrap();
+    let working_log = git_ai::git::refs::get_reference_as_working_log(
+        tmp_repo.repo(),
+        &format!("ai-working-log/{}", base),
+    )
+    .unwrap();
+    let stored = working_log.last().unwrap();
+    assert_eq!(stored.author, "J\u{fffd}rg");
+    assert_eq!(
+        decode_hex(stored.author_raw.as_deref().unwrap()),
+        b"J\xf6rg"
+    );
+}
@@ -0,0 +1,97 @@
+use git_ai::commands::init::{self, Preset};
+use tempfile::tempdir;
+
+#[test]
+fn test_init_presets_merge_and_backup() {
+    let home = tempdir().unwrap();
+    let claude_dir = home.path().join(".claude");
+    std::fs::create_dir_all(&claude_dir).unwrap();
+    std::fs::write(claude_dir.join("settings.json"), "{\"model\": \"opus\"}\n").unwrap();
+
+    let presets = init::parse_presets("cursor,claude").unwrap();
+    assert_eq!(presets, vec![Preset::Cursor, Preset::Claude]);
+
+    let written = init::run(home.path(), &presets).unwrap();
+    assert_eq!(written.len(), 2);
+    assert!(written[0].backup.is_none());
+    assert_eq!(
+        written[1].backup.as_deref(),
+        Some(claude_dir.join("settings.json.bak").as_path())
+    );
+
+    let cursor: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(home.path().join(".cursor/hooks.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(cursor["version"], 1);
+    assert!(
+        cursor["hooks"]["afterFileEdit"][0]["command"]
+            .as_str()
+            .unwrap()
+            .ends_with("git-ai hook-exec cursor afterFileEdit")
+    );
+
+    assert!(!written[1].kept_backup);
+
+    // Existing settings survive and re-running does not duplicate hooks
+    let rerun = init::run(home.path(), &[Preset::Claude]).unwrap();
+    // The backup still holds the original, not the merged file
+    assert!(rerun[0].kept_backup);
+    assert_eq!(
+        std::fs::read_to_string(claude_dir.join("settings.json.bak")).unwrap(),
+        "{\"model\": \"opus\"}\n"
+    );
+    let claude: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(claude_dir.join("settings.json")).unwrap())
+            .unwrap();
+    assert_eq!(claude["model"], "opus");
+    assert_eq!(claude["hooks"]["PostToolUse"].as_array().unwrap().len(), 1);
+
+    assert!(init::parse_presets("vim").is_err());
+}
+
+#[test]
+fn test_init_replaces_hooks_from_another_install_path() {
+    let home = tempdir().unwrap();
+    let cursor_dir = home.path().join(".cursor");
+    std::fs::create_dir_all(&cursor_dir).unwrap();
+    std::fs::write(
+        cursor_dir.join("hooks.json"),
+        r#"{"version": 1, "hooks": {"afterFileEdit": [
+            {"command": "/old/bin/git-ai hook-exec cursor afterFileEdit"},
+            {"command": "prettier --write"}
+        ]}}"#,
+    )
+    .unwrap();
+    let claude_dir = home.path().join(".claude");
+    std::fs::create_dir_all(&claude_dir).unwrap();
+    std::fs::write(
+        claude_dir.join("settings.json"),
+        r#"{"hooks": {"PostToolUse": [
+            {"matcher": "Write", "hooks": [{"type": "command", "command": "\"/opt/my tools/git-ai\" hook-exec claude PostToolUse"}]}
+        ]}}"#,
+    )
+    .unwrap();
+
+    init::run(home.path(), &[Preset::Cursor, Preset::Claude]).unwrap();
+
+    let cursor: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(cursor_dir.join("hooks.json")).unwrap())
+            .unwrap();
+    let after_edit = cursor["hooks"]["afterFileEdit"].as_array().unwrap();
+    assert_eq!(after_edit.len(), 2);
+    assert_eq!(after_edit[0]["command"], "prettier --write");
+    assert!(
+        !after_edit[1]["command"]
+            .as_str()
+            .unwrap()
+            .starts_with("/old/")
+    );
+
+    let claude: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(claude_dir.join("settings.json")).unwrap())
+            .unwrap();
+    let post_tool_use = claude["hooks"]["PostToolUse"].as_array().unwrap();
+    assert_eq!(post_tool_use.len(), 1);
+    assert!(!post_tool_use[0].to_string().contains("/opt/my tools/"));
+}
@@ -0,0 +1,114 @@
+use assert_cmd::Command;
+use git_ai::commands::json_schema::{JSON_SCHEMA_VERSION, json_schema};
+use git_ai::tmp_repo::TmpRepo;
+use tempfile::tempdir;
+
+fn git_ai_json(repo_path: &std::path::Path, args: &[&str]) -> serde_json::Value {
+    let output = Command::cargo_bin("git-ai")
+        .unwrap()
+        .current_dir(repo_path)
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+fn assert_has_required(value: &serde_json::Value, schema: &serde_json::Value) {
+    for field in schema["required"].as_array().unwrap() {
+        let field = field.as_str().unwrap();
+        assert!(value.get(field).is_some(), "missing {} in {}", field, value);
+    }
+}
+
+#[test]
+fn test_json_outputs_match_schema() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    tmp_repo.write_file("notes.md", "AI LINE\n", true).unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo.commit_with_message("agent commit").unwrap();
+
+    let printed = git_ai_json(&repo_path, &["stats", "--json-schema"]);
+    assert_eq!(printed, json_schema());
+    assert_eq!(printed["version"], JSON_SCHEMA_VERSION);
+
+    let per_commit = git_ai_json(
+        &repo_path,
+        &[
+            "stats",
+            "HEAD",
+            "--per-commit",
+            "--format",
+            "json",
+            "--no-fetch",
+        ],
+    );
+    let row_schema = &printed["$defs"]["stats_per_commit"]["items"];
+    assert_has_required(&per_commit[0], row_schema);
+
+    let blame = git_ai_json(&repo_path, &["blame", "--json", "notes.md"]);
+    let blame_schema = &printed["$defs"]["blame"];
+    assert_has_required(&blame, blame_schema);
+    assert_has_required(
+        &blame["lines"][0],
+        &blame_schema["properties"]["lines"]["items"],
+    );
+
+    // --json-lines shares the per-line schema: each line is one element of the --json array
+    let output = Command::cargo_bin("git-ai")
+        .unwrap()
+        .current_dir(&repo_path)
+        .args(["blame", "--json-lines", "notes.md"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    let json_lines: Vec<serde_json::Value> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(&json_lines, blame["lines"].as_array().unwrap());
+    assert_eq!(
+        printed["$defs"]["blame_json_lines"]["$ref"],
+        "#/$defs/blame/properties/lines/items"
+    );
+}
+
+#[test]
+fn test_checkpoint_json_reports_what_was_recorded() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (_tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    std::fs::write(repo_path.join("hook.rs"), "one\ntwo\nthree\n").unwrap();
+
+    let recorded = git_ai_json(
+        &repo_path,
+        &[
+            "checkpoint",
+            "--json",
+            "--author",
+            "Claude",
+            "--model",
+            "claude-4-sonnet",
+        ],
+    );
+    assert_has_required(&recorded, &json_schema()["$defs"]["checkpoint"]);
+    assert_eq!(recorded["status"], "recorded");
+    assert_eq!(recorded["author"], "Claude");
+    assert_eq!(recorded["model"], "claude-4-sonnet");
+    assert_eq!(recorded["files"], 1);
+    assert_eq!(recorded["added_lines"], 3);
+    assert_eq!(recorded["deleted_lines"], 0);
+    let id = recorded["id"].as_str().unwrap();
+    let (base_commit, position) = id.rsplit_once(':').unwrap();
+    assert_eq!(
+        recorded["ref"],
+        format!("refs/ai-working-log/{}", base_commit)
+    );
+    assert!(position.parse::<usize>().unwrap() >= 1);
+}
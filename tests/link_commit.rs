@@ -0,0 +1,72 @@
+use git_ai::git::refs::{authorship_ref_name, get_reference_as_authorship_log};
+use git_ai::tmp_repo::TmpRepo;
+use tempfile::tempdir;
+
+#[test]
+fn test_link_commit_backfills_attribution() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+
+    // Committed with plain git, so no hook ever saw it
+    std::fs::write(repo_path.join("agent.rs"), "fn a() {}\nfn b() {}\n").unwrap();
+    for args in [
+        &["add", "agent.rs"][..],
+        &["commit", "-q", "-m", "outside the proxy"],
+    ] {
+        let status = std::process::Command::new("git")
+            .args(["-c", "user.name=Test", "-c", "user.email=t@example.com"])
+            .args(args)
+            .current_dir(&repo_path)
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+    let head = tmp_repo.head_commit_sha().unwrap();
+    let ref_name = authorship_ref_name(tmp_repo.repo(), &head);
+
+    let output = tmp_repo.git_ai(&[
+        "checkpoint",
+        "--link-commit",
+        "HEAD",
+        "--author",
+        "Claude",
+        "--model",
+        "claude-4-sonnet",
+    ]);
+    assert!(output.status.success(), "{:?}", output);
+    let log = get_reference_as_authorship_log(tmp_repo.repo(), &ref_name).unwrap();
+    let entry = log.files["agent.rs"].get_author_entry(2).unwrap();
+    assert_eq!(entry.author, "Claude");
+    assert_eq!(
+        entry.agent_metadata.as_ref().unwrap().model,
+        "claude-4-sonnet"
+    );
+
+    // Existing attribution is kept unless --overwrite
+    let output = tmp_repo.git_ai(&[
+        "checkpoint",
+        "--link-commit",
+        "HEAD",
+        "--author-type",
+        "human",
+    ]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--overwrite"));
+
+    let output = tmp_repo.git_ai(&[
+        "checkpoint",
+        "--link-commit",
+        "HEAD",
+        "--author-type",
+        "human",
+        "--author",
+        "Test",
+        "--overwrite",
+    ]);
+    assert!(output.status.success(), "{:?}", output);
+    let log = get_reference_as_authorship_log(tmp_repo.repo(), &ref_name).unwrap();
+    let entry = log.files["agent.rs"].get_author_entry(1).unwrap();
+    assert_eq!(entry.author, "Test");
+    assert!(entry.agent_metadata.is_none());
+}
@@ -0,0 +1,470 @@
+use git_ai::commands::log;
+use git_ai::tmp_repo::{TmpRepo, args};
+use tempfile::tempdir;
+
+fn collect_entries(repo: &git2::Repository, options: &log::LogOptions) -> Vec<log::FileLogEntry> {
+    let mut entries = Vec::new();
+    log::run(repo, options, |entry| {
+        entries.push(entry);
+        Ok(())
+    })
+    .unwrap();
+    entries
+}
+
+#[test]
+fn test_log_file_history_newest_first_with_line_range() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    let mut file = tmp_repo
+        .write_file("app.rs", "line 1\nline 2\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("test_user")
+        .unwrap();
+    tmp_repo
+        .write_file("other.rs", "unrelated\n", true)
+        .unwrap();
+    file.append("line 3\nline 4\n").unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+
+    let options = log::parse_log_args(&args(&["--file", "app.rs"])).unwrap();
+    let entries = collect_entries(tmp_repo.repo(), &options);
+    assert_eq!(entries.len(), 2);
+    assert!(entries[0].checkpoint.agent_metadata.is_some());
+    assert_eq!(entries[1].checkpoint.author, "test_user");
+
+    let options = log::parse_log_args(&args(&["--file", "app.rs", "-L", "1,2"])).unwrap();
+    let entries = collect_entries(tmp_repo.repo(), &options);
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].checkpoint.author, "test_user");
+
+    assert!(log::parse_log_args(&args(&["-L", "3,1"])).is_err());
+}
+
+#[test]
+fn test_log_reverse_and_oneline() {
+    use git_ai::commands::checkpoint::{self, CheckpointOptions};
+
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    let mut file = tmp_repo.write_file("app.rs", "line 1\n", true).unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("test_user")
+        .unwrap();
+    file.append("line 2\n").unwrap();
+    checkpoint::run(
+        tmp_repo.repo(),
+        "Claude",
+        &CheckpointOptions {
+            quiet: true,
+            model: Some("claude-4-sonnet".to_string()),
+            human_author: Some("Test User".to_string()),
+            prompt: Some(
+                "Split the parser into a lexer, an AST module and a pretty printer\nthen test"
+                    .to_string(),
+            ),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    let options =
+        log::parse_log_args(&args(&["--file", "app.rs", "--reverse", "--oneline"])).unwrap();
+    assert!(options.oneline);
+    let entries = collect_entries(tmp_repo.repo(), &options);
+    assert_eq!(entries[0].checkpoint.author, "test_user");
+    assert_eq!(entries[1].checkpoint.author, "Claude");
+
+    let short = &entries[1].base_commit[..7];
+    assert_eq!(
+        log::oneline(&entries[0]),
+        format!("{}:{} T human", short, entries[0].index)
+    );
+    assert_eq!(
+        log::oneline(&entries[1]),
+        format!(
+            "{}:{} C claude-4-sonnet Split the parser into a lexer, an AST module an...",
+            short, entries[1].index
+        )
+    );
+}
+
+#[test]
+fn test_log_path_globs_and_filters() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    std::fs::create_dir_all(repo_path.join("src/parser")).unwrap();
+    tmp_repo
+        .write_file("src/parser/lexer.rs", "fn lex() {}\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("test_user")
+        .unwrap();
+    tmp_repo
+        .write_file("src/parser/ast.rs", "struct Ast;\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo.write_file("docs.md", "# Docs\n", true).unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-opus")
+        .unwrap();
+
+    let run = |values: &[&str]| {
+        let options = log::parse_log_args(&args(values)).unwrap();
+        collect_entries(tmp_repo.repo(), &options)
+            .into_iter()
+            .map(|entry| {
+                entry
+                    .entries
+                    .iter()
+                    .map(|file| file.file.clone())
+                    .collect::<Vec<_>>()
+                    .join(",")
+            })
+            .collect::<Vec<_>>()
+    };
+
+    // A directory, with or without a trailing slash
+    assert_eq!(
+        run(&["--file", "src/parser/"]),
+        ["src/parser/ast.rs", "src/parser/lexer.rs"]
+    );
+    assert_eq!(
+        run(&["--file", "src/**/*.rs", "--limit", "1"]),
+        ["src/parser/ast.rs"]
+    );
+    assert_eq!(
+        run(&["--file", "src/*.rs", "--file", "doc?.md"]),
+        ["docs.md"]
+    );
+    assert_eq!(
+        run(&["--file", "src", "--model", "SONNET"]),
+        ["src/parser/ast.rs"]
+    );
+    assert_eq!(
+        run(&["--file", "**/*.rs", "--author", "TEST"]),
+        ["src/parser/lexer.rs"]
+    );
+
+    assert!(log::parse_log_args(&args(&["--file", "x", "--limit", "0"])).is_err());
+}
+
+#[test]
+fn test_log_author_stats_rollup() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    tmp_repo
+        .write_file("agent.rs", "fn a() {}\nfn b() {}\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo
+        .write_file("agent.rs", "fn a() {}\nfn c() {}\nfn d() {}\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo
+        .write_file(
+            "agent.rs",
+            "fn a() {}\nfn c() {}\nfn d() {}\nfn e() {}\n",
+            true,
+        )
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("test_user")
+        .unwrap();
+
+    let options = log::parse_log_args(&args(&["--file", "agent.rs", "--author-stats"])).unwrap();
+    assert!(options.author_stats);
+    // Rolled up the way `log --author-stats` prints them
+    let mut stats = Vec::new();
+    log::run(tmp_repo.repo(), &options, |entry| {
+        log::tally(&mut stats, &entry);
+        Ok(())
+    })
+    .unwrap();
+    log::sort_author_stats(&mut stats);
+    assert_eq!(
+        stats,
+        [
+            log::AuthorStats {
+                author: "Claude".to_string(),
+                model: Some("claude-4-sonnet".to_string()),
+                checkpoints: 2,
+                added_lines: 4,
+                deleted_lines: 1,
+            },
+            log::AuthorStats {
+                author: "test_user".to_string(),
+                model: None,
+                checkpoints: 1,
+                added_lines: 1,
+                deleted_lines: 0,
+            },
+        ]
+    );
+    assert_eq!(
+        log::render_author_stats(&stats),
+        "Checkpoints by author:\n    Claude [AI: claude-4-sonnet]  2 checkpoints  +4 -1\n    test_user                     1 checkpoint  +1 -0\n"
+    );
+}
+
+#[test]
+fn test_log_model_breakdown() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    tmp_repo
+        .write_file("agent.rs", "fn a() {}\nfn b() {}\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo
+        .write_file("agent.rs", "fn a() {}\nfn b() {}\nfn c() {}\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Codex", "gpt-5")
+        .unwrap();
+    tmp_repo
+        .write_file(
+            "agent.rs",
+            "fn a() {}\nfn b() {}\nfn c() {}\nfn d() {}\n",
+            true,
+        )
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("test_user")
+        .unwrap();
+
+    let args = args(&["--file", "agent.rs", "--oneline", "--model-breakdown"]);
+    let options = log::parse_log_args(&args).unwrap();
+    assert!(options.model_breakdown);
+    let entries = collect_entries(tmp_repo.repo(), &options);
+    assert_eq!(entries.len(), 3);
+
+    // The human checkpoint still shows the agents' lines it built on
+    assert_eq!(
+        log::model_breakdown(&entries[0]).as_deref(),
+        Some("claude-4-sonnet:50%,gpt-5:25%,human:25%")
+    );
+    assert_eq!(
+        log::model_breakdown(&entries[1]).as_deref(),
+        Some("claude-4-sonnet:67%,gpt-5:33%")
+    );
+    assert!(log::oneline(&entries[1]).contains(" C claude-4-sonnet:67%,gpt-5:33%"));
+    // A single model keeps the plain column
+    assert_eq!(log::model_breakdown(&entries[2]), None);
+    assert!(log::oneline(&entries[2]).contains(" C claude-4-sonnet"));
+
+    // Without the flag nothing is counted
+    let options = log::parse_log_args(&args[..3]).unwrap();
+    let entries = collect_entries(tmp_repo.repo(), &options);
+    assert!(entries.iter().all(|entry| entry.model_lines.is_empty()));
+}
+
+#[test]
+fn test_log_between_tags() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    let tag = |name: &str| {
+        let head = tmp_repo.repo().head().unwrap().peel_to_commit().unwrap();
+        tmp_repo
+            .repo()
+            .tag_lightweight(name, head.as_object(), false)
+            .unwrap();
+    };
+    tmp_repo.write_file("early.rs", "before\n", true).unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo.commit_with_message("Before the session").unwrap();
+    tag("session-start");
+
+    tmp_repo.write_file("notes.md", "plan\n", true).unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("test_user")
+        .unwrap();
+    tmp_repo
+        .write_file("app.rs", "fn app() {}\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo.commit_with_message("Session work").unwrap();
+    tmp_repo
+        .write_file("app.rs", "fn app() {}\nfn more() {}\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Codex", "gpt-5")
+        .unwrap();
+    tmp_repo.commit_with_message("More session work").unwrap();
+    tag("session-end");
+
+    tmp_repo.write_file("late.rs", "after\n", true).unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+
+    let between = |extra: &[&str]| {
+        let flags = [
+            &["--between", "session-start", "session-end", "--reverse"],
+            extra,
+        ]
+        .concat();
+        let options = log::parse_log_args(&args(&flags)).unwrap();
+        collect_entries(tmp_repo.repo(), &options)
+            .iter()
+            .map(|entry| {
+                format!(
+                    "{} {}",
+                    entry.checkpoint.author,
+                    entry
+                        .entries
+                        .iter()
+                        .map(|file| file.file.as_str())
+                        .collect::<Vec<_>>()
+                        .join(",")
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        between(&[]),
+        ["test_user notes.md", "Claude app.rs", "Codex app.rs"]
+    );
+    assert_eq!(
+        between(&["--file", "app.rs"]),
+        ["Claude app.rs", "Codex app.rs"]
+    );
+    assert_eq!(
+        between(&["--file", "app.rs", "--model", "gpt"]),
+        ["Codex app.rs"]
+    );
+
+    assert!(log::parse_log_args(&args(&["--between", "session-start"])).is_err());
+    assert!(
+        log::parse_log_args(&args(&[
+            "--between",
+            "session-start",
+            "session-end",
+            "-L",
+            "1,2"
+        ]))
+        .is_err()
+    );
+}
+
+#[test]
+fn test_log_json_lines_streams_the_json_records() {
+    use git_ai::commands::json_schema::json_schema;
+
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    let mut file = tmp_repo.write_file("app.rs", "line 1\n", true).unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("test_user")
+        .unwrap();
+    file.append("line 2\nline 3\n").unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+
+    let git_ai = |args: &[&str]| {
+        let output = tmp_repo.git_ai(args);
+        assert!(output.status.success(), "{:?}", output);
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+    let printed = git_ai(&["log", "--file", "app.rs", "--json"]);
+    let array: serde_json::Value = serde_json::from_str(&printed).unwrap();
+    // Written one element at a time, but the same text as the whole array pretty-printed
+    assert_eq!(
+        printed,
+        format!("{}\n", serde_json::to_string_pretty(&array).unwrap())
+    );
+    let json_lines: Vec<serde_json::Value> = git_ai(&["log", "--file", "app.rs", "--json-lines"])
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(&json_lines, array.as_array().unwrap());
+    assert_eq!(json_lines.len(), 2);
+
+    let schema = &json_schema()["$defs"]["log"]["items"];
+    for field in schema["required"].as_array().unwrap() {
+        assert!(json_lines[0].get(field.as_str().unwrap()).is_some());
+    }
+    assert_eq!(json_lines[0]["model"], "claude-4-sonnet");
+    assert_eq!(json_lines[0]["index"], 2);
+    assert_eq!(json_lines[0]["files"][0]["added_lines"], 2);
+    assert_eq!(json_lines[1]["author"], "test_user");
+    assert!(json_lines[1]["model"].is_null());
+
+    // No matches is an empty array, or no lines at all
+    assert_eq!(
+        git_ai(&["log", "--file", "missing.rs", "--json"]).trim(),
+        "[]"
+    );
+    assert_eq!(git_ai(&["log", "--file", "missing.rs", "--json-lines"]), "");
+
+    let with_file = |flags: &[&str]| args(&[&["--file", "app.rs"][..], flags].concat());
+    assert!(log::parse_log_args(&with_file(&["--json", "--json-lines"])).is_err());
+    assert!(log::parse_log_args(&with_file(&["--json-lines", "--oneline"])).is_err());
+}
+
+#[test]
+fn test_log_routes_on_git_ai_flags() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    tmp_repo.write_file("app.rs", "line 1\n", true).unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+
+    let routed = |values: &[&str]| log::is_git_ai_log(&args(values));
+    assert!(routed(&["--file=app.rs"]));
+    assert!(routed(&["--json-lines", "--file", "app.rs"]));
+    assert!(routed(&["--ai", "--oneline"]));
+    assert!(!routed(&["--oneline", "--reverse"]));
+    assert!(!routed(&["--author=someone", "-n", "3"]));
+    assert!(!routed(&["--", "--file"]));
+
+    let stdout = |args: &[&str]| {
+        let output = tmp_repo.git_ai(args);
+        assert!(output.status.success(), "{:?}", output);
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+    // git-ai's own flags reach the checkpoint history in either form
+    let json_lines = stdout(&["log", "--json-lines", "--file=app.rs", "--model=claude"]);
+    assert_eq!(json_lines.lines().count(), 1);
+    assert!(json_lines.contains("\"claude-4-sonnet\""));
+    // With --ai, the flags git log shares list every checkpoint
+    let oneline = stdout(&["log", "--ai", "--reverse", "--oneline"]);
+    assert_eq!(oneline.lines().count(), 2, "{}", oneline);
+    assert!(oneline.lines().next().unwrap().contains(" human"));
+    assert!(oneline.lines().nth(1).unwrap().contains(" claude-4-sonnet"));
+    // Without any, it is git log
+    let git_log = stdout(&["log", "--oneline"]);
+    assert!(git_log.contains("initial commit"), "{}", git_log);
+}
@@ -0,0 +1,62 @@
+use git_ai::commands::mark_ai::{self, parse_mark_ai_args};
+use git_ai::commands::stats;
+use git_ai::tmp_repo::{TmpRepo, args};
+use tempfile::tempdir;
+
+#[test]
+fn test_mark_ai_attributes_pasted_lines() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    tmp_repo
+        .write_file(
+            "paste.rs",
+            "fn mine() {}\nfn pasted() {}\nfn pasted_too() {}\n",
+            true,
+        )
+        .unwrap();
+
+    let options =
+        parse_mark_ai_args(&args(&["paste.rs", "--lines", "2-3", "--model", "gpt-4o"])).unwrap();
+    assert_eq!(
+        mark_ai::run(tmp_repo.repo(), &options, "test_user").unwrap(),
+        2
+    );
+    tmp_repo.commit_with_message("paste from chat").unwrap();
+
+    let report = stats::run(
+        tmp_repo.repo(),
+        None,
+        &stats::StatsOptions {
+            no_fetch: true,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert_eq!(report.ai_additions, 2);
+    assert_eq!(report.human_additions, 1);
+    assert_eq!(
+        report.file_stats["paste.rs"].additions.get("gpt-4o"),
+        Some(&2)
+    );
+}
+
+#[test]
+fn test_mark_ai_rejects_ranges_outside_file() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    tmp_repo.write_file("short.rs", "one\ntwo\n", true).unwrap();
+
+    let options =
+        parse_mark_ai_args(&args(&["short.rs", "--lines", "2-5", "--model", "gpt-4o"])).unwrap();
+    let error = mark_ai::run(tmp_repo.repo(), &options, "test_user")
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("short.rs has 2 lines"), "{}", error);
+
+    assert!(parse_mark_ai_args(&args(&["short.rs", "--lines", "3-1", "--model", "m"])).is_err());
+    assert!(parse_mark_ai_args(&args(&["short.rs", "--lines", "1"])).is_err());
+}
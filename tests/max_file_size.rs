@@ -0,0 +1,55 @@
+use assert_cmd::Command;
+use git_ai::tmp_repo::TmpRepo;
+use tempfile::tempdir;
+
+fn checkpoint(repo_path: &std::path::Path, args: &[&str]) -> serde_json::Value {
+    let output = Command::cargo_bin("git-ai")
+        .unwrap()
+        .current_dir(repo_path)
+        .args(["checkpoint", "--json", "--model", "claude-sonnet"])
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+#[test]
+fn test_checkpoint_records_large_files_at_file_level() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+
+    let dump = "row,value\n".repeat(200);
+    std::fs::write(repo_path.join("dump.csv"), &dump).unwrap();
+    std::fs::write(repo_path.join("small.rs"), "one\ntwo\n").unwrap();
+    let recorded = checkpoint(&repo_path, &["--max-file-size", "1000"]);
+    assert_eq!(recorded["files"], 2);
+    assert_eq!(recorded["added_lines"], 2);
+    assert_eq!(recorded["file_level"], serde_json::json!(["dump.csv"]));
+
+    // The saved state keeps the blob id, not the content, and still tells changes apart
+    let recorded = checkpoint(&repo_path, &["--max-file-size", "1000"]);
+    assert_eq!(recorded["files"], 0);
+    std::fs::write(repo_path.join("dump.csv"), format!("{}extra,1\n", dump)).unwrap();
+    let recorded = checkpoint(&repo_path, &["--max-file-size", "1000"]);
+    assert_eq!(recorded["files"], 1);
+    assert_eq!(recorded["file_level"], serde_json::json!(["dump.csv"]));
+
+    // Under git-ai.maxFileSize = 0 there is no limit; lines come back once the saved state
+    // holds content again
+    tmp_repo
+        .repo()
+        .config()
+        .unwrap()
+        .set_i64("git-ai.maxFileSize", 0)
+        .unwrap();
+    std::fs::write(repo_path.join("dump.csv"), format!("{}extra,2\n", dump)).unwrap();
+    let recorded = checkpoint(&repo_path, &[]);
+    assert_eq!(recorded["file_level"], serde_json::json!(["dump.csv"]));
+    std::fs::write(repo_path.join("dump.csv"), format!("{}extra,3\n", dump)).unwrap();
+    let recorded = checkpoint(&repo_path, &[]);
+    assert_eq!(recorded["file_level"], serde_json::json!([]));
+    assert_eq!(recorded["added_lines"], 1);
+    assert_eq!(recorded["deleted_lines"], 1);
+}
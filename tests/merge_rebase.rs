@@ -219,3 +219,80 @@ fn test_blame_after_merge_conflict_resolution() {
     let blame = tmp_repo.blame_for_file(&lines, Some((10, 20))).unwrap();
     assert_debug_snapshot!(blame);
 }
+
+#[test]
+fn test_blame_no_merges_follows_conflict_resolution() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+    let tmp_repo = TmpRepo::new(repo_path.clone()).unwrap();
+    let mut file = tmp_repo
+        .write_file("total.rs", "fn total() {\n    0\n}\n", true)
+        .unwrap();
+    tmp_repo
+        .commit_with_author("initial commit", "Base Dev", "base@example.com")
+        .unwrap();
+    let default_branch = tmp_repo.current_branch().unwrap();
+
+    tmp_repo.create_branch("feature").unwrap();
+    file.update("fn total() {\n    compute(alpha, beta)\n}\n")
+        .unwrap();
+    tmp_repo
+        .commit_with_author("compute the total", "Feature Dev", "feature@example.com")
+        .unwrap();
+
+    tmp_repo.switch_branch(&default_branch).unwrap();
+    file.update("fn total() {\n    1\n}\n").unwrap();
+    tmp_repo
+        .commit_with_author("start at one", "Main Dev", "main@example.com")
+        .unwrap();
+
+    // Resolve the conflict by hand, keeping the feature's line with a tweak
+    let git = |args: &[&str]| {
+        std::process::Command::new("git")
+            .current_dir(&repo_path)
+            .args([
+                "-c",
+                "user.name=Merger",
+                "-c",
+                "user.email=merger@example.com",
+            ])
+            .args(args)
+            .output()
+            .unwrap()
+    };
+    assert!(!git(&["merge", "feature"]).status.success());
+    std::fs::write(
+        repo_path.join("total.rs"),
+        "fn total() {\n    compute(alpha, beta, gamma)\n}\n",
+    )
+    .unwrap();
+    assert!(git(&["commit", "-am", "merge feature"]).status.success());
+
+    let authors = |no_merges: bool| {
+        let options = git_ai::commands::blame::GitAiBlameOptions {
+            no_merges,
+            ..Default::default()
+        };
+        git_ai::commands::blame::get_git_blame_hunks(tmp_repo.repo(), "total.rs", 1, 3, &options)
+            .unwrap()
+            .into_iter()
+            .map(|hunk| (hunk.range, hunk.original_author))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        authors(false),
+        [
+            ((1, 1), "Base Dev".to_string()),
+            ((2, 2), "Merger".to_string()),
+            ((3, 3), "Base Dev".to_string()),
+        ]
+    );
+    assert_eq!(
+        authors(true),
+        [
+            ((1, 1), "Base Dev".to_string()),
+            ((2, 2), "Feature Dev".to_string()),
+            ((3, 3), "Base Dev".to_string()),
+        ]
+    );
+}
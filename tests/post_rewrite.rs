@@ -0,0 +1,138 @@
+use git_ai::commands::init::install_git_hooks;
+use git_ai::git::post_rewrite::post_rewrite;
+use git_ai::git::refs::{authorship_ref_name, get_reference};
+use git_ai::tmp_repo::TmpRepo;
+use std::process::Command;
+use tempfile::tempdir;
+
+fn commit_ai_file(tmp_repo: &TmpRepo) -> String {
+    tmp_repo
+        .write_file("rewritten.txt", "ai 1\nai 2\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo.commit_with_message("Add rewritten.txt").unwrap();
+    tmp_repo.head_commit_sha().unwrap()
+}
+
+#[test]
+fn test_post_rewrite_copies_authorship_once() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    let old_sha = commit_ai_file(&tmp_repo);
+    let repo = tmp_repo.repo();
+    let new_sha = repo
+        .head()
+        .unwrap()
+        .peel_to_commit()
+        .unwrap()
+        .amend(Some("HEAD"), None, None, None, Some("Reworded"), None)
+        .unwrap()
+        .to_string();
+
+    let rewrites = format!("{} {}\n", old_sha, new_sha);
+    assert_eq!(post_rewrite(repo, &rewrites).unwrap(), 1);
+    assert_eq!(post_rewrite(repo, &rewrites).unwrap(), 0);
+    assert_eq!(
+        get_reference(repo, &authorship_ref_name(repo, &new_sha)).unwrap(),
+        get_reference(repo, &authorship_ref_name(repo, &old_sha)).unwrap()
+    );
+}
+
+#[test]
+fn test_installed_hook_follows_git_amend() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    let old_sha = commit_ai_file(&tmp_repo);
+    let repo = tmp_repo.repo();
+
+    let hook = install_git_hooks(repo).unwrap();
+    // Re-running init refreshes git-ai's own hook, but never replaces someone else's
+    assert_eq!(install_git_hooks(repo).unwrap(), hook);
+
+    // The hook calls git-ai from PATH, so put the freshly built binary first
+    let binary_dir = std::env::current_dir().unwrap().join("target/debug");
+    let path = format!(
+        "{}:{}",
+        binary_dir.display(),
+        std::env::var("PATH").unwrap_or_default()
+    );
+    let status = Command::new("git")
+        .current_dir(&repo_path)
+        .args(["commit", "--amend", "-m", "Reworded"])
+        .env("PATH", path)
+        .env("GIT_AUTHOR_NAME", "Test User")
+        .env("GIT_AUTHOR_EMAIL", "test@example.com")
+        .env("GIT_COMMITTER_NAME", "Test User")
+        .env("GIT_COMMITTER_EMAIL", "test@example.com")
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let new_sha = tmp_repo.head_commit_sha().unwrap();
+    assert_ne!(old_sha, new_sha);
+    assert!(get_reference(repo, &authorship_ref_name(repo, &new_sha)).is_ok());
+
+    std::fs::write(&hook, "#!/bin/sh\necho custom\n").unwrap();
+    assert!(install_git_hooks(repo).is_err());
+}
+
+#[test]
+fn test_hook_installs_where_git_runs_hooks() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().join("repo");
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    let old_sha = commit_ai_file(&tmp_repo);
+    let repo = tmp_repo.repo();
+
+    // Shared hook directories, as husky sets up, replace .git/hooks entirely
+    repo.config()
+        .unwrap()
+        .set_str("core.hooksPath", ".husky")
+        .unwrap();
+    let hook = install_git_hooks(repo).unwrap();
+    assert_eq!(hook, repo_path.join(".husky").join("post-rewrite"));
+
+    let binary_dir = std::env::current_dir().unwrap().join("target/debug");
+    let path = format!(
+        "{}:{}",
+        binary_dir.display(),
+        std::env::var("PATH").unwrap_or_default()
+    );
+    let status = Command::new("git")
+        .current_dir(&repo_path)
+        .args(["commit", "--amend", "-m", "Reworded"])
+        .env("PATH", path)
+        .env("GIT_AUTHOR_NAME", "Test User")
+        .env("GIT_AUTHOR_EMAIL", "test@example.com")
+        .env("GIT_COMMITTER_NAME", "Test User")
+        .env("GIT_COMMITTER_EMAIL", "test@example.com")
+        .status()
+        .unwrap();
+    assert!(status.success());
+    let new_sha = tmp_repo.head_commit_sha().unwrap();
+    assert_ne!(old_sha, new_sha);
+    assert!(get_reference(repo, &authorship_ref_name(repo, &new_sha)).is_ok());
+
+    // A linked worktree runs the hooks of the repository it belongs to
+    repo.config().unwrap().remove("core.hooksPath").unwrap();
+    let worktree_path = tmp_dir.path().join("linked");
+    let status = Command::new("git")
+        .current_dir(&repo_path)
+        .args(["worktree", "add", "-q", "-b", "linked"])
+        .arg(&worktree_path)
+        .status()
+        .unwrap();
+    assert!(status.success());
+    let worktree = git2::Repository::open(&worktree_path).unwrap();
+    assert_eq!(
+        install_git_hooks(&worktree).unwrap(),
+        repo.path().join("hooks").join("post-rewrite")
+    );
+}
@@ -0,0 +1,36 @@
+use assert_cmd::Command;
+use git_ai::tmp_repo::TmpRepo;
+use tempfile::tempdir;
+
+#[test]
+fn test_proxy_passes_git_env_through() {
+    let tmp_dir = tempdir().unwrap();
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(tmp_dir.path().to_path_buf()).unwrap();
+
+    // Non-interactive path (captured output)
+    let output = Command::cargo_bin("git-ai")
+        .unwrap()
+        .current_dir(tmp_repo.path())
+        .env("GIT_AUTHOR_NAME", "Env Author")
+        .env("GIT_AUTHOR_EMAIL", "env@example.com")
+        .args(["var", "GIT_AUTHOR_IDENT"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("Env Author <env@example.com>"));
+
+    // Interactive path (spawned) still honors GIT_DIR from the environment
+    let elsewhere = tempdir().unwrap();
+    let output = Command::cargo_bin("git-ai")
+        .unwrap()
+        .current_dir(elsewhere.path())
+        .env("GIT_DIR", tmp_repo.path().join(".git"))
+        .args(["branch", "--show-current"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout).trim(),
+        tmp_repo.current_branch().unwrap()
+    );
+}
@@ -0,0 +1,136 @@
+use assert_cmd::Command;
+use git_ai::commands::stats::{self, StatsOptions};
+use git_ai::git::refs::{authorship_ref_name, authorship_refspec};
+use git_ai::git::repository::get_default_remote;
+use git_ai::tmp_repo::TmpRepo;
+use tempfile::tempdir;
+
+#[test]
+fn test_custom_ref_namespace_round_trips_through_push_and_fetch() {
+    let tmp_dir = tempdir().unwrap();
+    let remote_path = tmp_dir.path().join("remote.git");
+    git2::Repository::init_bare(&remote_path).unwrap();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(tmp_dir.path().join("source")).unwrap();
+    let repo = tmp_repo.repo();
+    repo.config()
+        .unwrap()
+        .set_str("git-ai.refNamespace", "refs/team-ai/authorship/")
+        .unwrap();
+    assert_eq!(
+        authorship_refspec(repo),
+        "+refs/team-ai/authorship/*:refs/team-ai/authorship/*"
+    );
+
+    tmp_repo.write_file("notes.md", "AI LINE\n", true).unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo.commit_with_message("agent commit").unwrap();
+
+    let head = tmp_repo.head_commit_sha().unwrap();
+    let ref_name = format!("refs/{}", authorship_ref_name(repo, &head));
+    assert_eq!(ref_name, format!("refs/team-ai/authorship/{}", head));
+    assert!(repo.find_reference(&ref_name).is_ok());
+    assert!(
+        repo.find_reference(&format!("refs/ai/authorship/{}", head))
+            .is_err()
+    );
+
+    repo.remote("origin", remote_path.to_str().unwrap())
+        .unwrap();
+    Command::cargo_bin("git-ai")
+        .unwrap()
+        .current_dir(tmp_repo.path())
+        .args(["push", "origin"])
+        .assert()
+        .success();
+
+    // A second clone with the same namespace picks the refs back up on fetch
+    let clone_path = tmp_dir.path().join("clone");
+    let clone = git2::Repository::init(&clone_path).unwrap();
+    clone
+        .config()
+        .unwrap()
+        .set_str("git-ai.refNamespace", "team-ai/authorship")
+        .unwrap();
+    clone
+        .remote("origin", remote_path.to_str().unwrap())
+        .unwrap();
+    Command::cargo_bin("git-ai")
+        .unwrap()
+        .current_dir(&clone_path)
+        .args(["fetch", "origin"])
+        .assert()
+        .success();
+
+    assert!(clone.find_reference(&ref_name).is_ok());
+}
+
+#[test]
+fn test_authorship_sync_uses_branch_upstream_remote() {
+    let tmp_dir = tempdir().unwrap();
+    let upstream_path = tmp_dir.path().join("upstream.git");
+    let origin_path = tmp_dir.path().join("origin.git");
+    git2::Repository::init_bare(&upstream_path).unwrap();
+    git2::Repository::init_bare(&origin_path).unwrap();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(tmp_dir.path().join("source")).unwrap();
+    tmp_repo.write_file("notes.md", "AI LINE\n", true).unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo.commit_with_message("agent commit").unwrap();
+    tmp_repo
+        .repo()
+        .remote("upstream", upstream_path.to_str().unwrap())
+        .unwrap();
+    Command::cargo_bin("git-ai")
+        .unwrap()
+        .current_dir(tmp_repo.path())
+        .args(["push", "upstream"])
+        .assert()
+        .success();
+    let status = std::process::Command::new("git")
+        .current_dir(tmp_repo.path())
+        .args(["push", "--quiet", "upstream", "HEAD"])
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    // The branch tracks a remote named upstream; an origin exists too but has nothing
+    let clone_path = tmp_dir.path().join("clone");
+    let status = std::process::Command::new("git")
+        .args(["clone", "--quiet", "-o", "upstream"])
+        .arg(&upstream_path)
+        .arg(&clone_path)
+        .status()
+        .unwrap();
+    assert!(status.success());
+    let clone = git2::Repository::open(&clone_path).unwrap();
+    clone
+        .remote("origin", origin_path.to_str().unwrap())
+        .unwrap();
+    assert_eq!(get_default_remote(&clone).as_deref(), Some("upstream"));
+
+    // Stats --fetch pulls the missing authorship refs from it
+    let head = tmp_repo.head_commit_sha().unwrap();
+    let ref_name = format!("refs/ai/authorship/{}", head);
+    assert!(clone.find_reference(&ref_name).is_err());
+    let fetch = StatsOptions {
+        fetch: true,
+        ..Default::default()
+    };
+    let report = stats::run(&clone, None, &fetch).unwrap();
+    assert_eq!(report.ai_additions, 1);
+    assert!(clone.find_reference(&ref_name).is_ok());
+
+    // Without upstream configuration, origin is still the default
+    let branch = clone.head().unwrap().shorthand().unwrap().to_string();
+    clone
+        .config()
+        .unwrap()
+        .remove(&format!("branch.{}.remote", branch))
+        .unwrap();
+    assert_eq!(get_default_remote(&clone).as_deref(), Some("origin"));
+}
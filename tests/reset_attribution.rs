@@ -0,0 +1,90 @@
+use git_ai::commands::checkpoint::resolve_base_commit;
+use git_ai::git::refs::{authorship_ref_name, get_reference_as_authorship_log};
+use git_ai::tmp_repo::TmpRepo;
+use tempfile::tempdir;
+
+#[test]
+fn test_reset_attribution_scopes() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    tmp_repo
+        .write_file("first.rs", "fn first() {}\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-sonnet")
+        .unwrap();
+    tmp_repo.commit_with_message("First").unwrap();
+    let first = tmp_repo.head_commit_sha().unwrap();
+    tmp_repo
+        .write_file("second.rs", "fn second() {}\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-sonnet")
+        .unwrap();
+    tmp_repo.commit_with_message("Second").unwrap();
+    let second = tmp_repo.head_commit_sha().unwrap();
+    tmp_repo
+        .write_file("pending.rs", "fn pending() {}\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-sonnet")
+        .unwrap();
+    let has_log = |sha: &str| {
+        get_reference_as_authorship_log(tmp_repo.repo(), &authorship_ref_name(tmp_repo.repo(), sha))
+            .is_ok()
+    };
+    let working_ref = format!(
+        "refs/ai-working-log/{}",
+        resolve_base_commit(tmp_repo.repo()).unwrap()
+    );
+
+    // Every scope is refused without --confirm, and scopes don't combine
+    let output = tmp_repo.git_ai(&["reset-attribution", "--all"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--confirm"));
+    let output = tmp_repo.git_ai(&["reset-attribution", "--working", "--all", "--confirm"]);
+    assert!(!output.status.success());
+    assert!(has_log(&first) && has_log(&second));
+
+    // --commits only deletes the logs of commits in the range
+    let output = tmp_repo.git_ai(&[
+        "reset-attribution",
+        "--commits",
+        "HEAD~1..HEAD",
+        "--confirm",
+    ]);
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        format!(
+            "deleted refs/{}\n",
+            authorship_ref_name(tmp_repo.repo(), &second)
+        )
+    );
+    assert!(has_log(&first) && !has_log(&second));
+
+    // --working leaves committed attribution alone
+    let output = tmp_repo.git_ai(&["reset-attribution", "--working", "--confirm"]);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(
+        String::from_utf8_lossy(&output.stdout).contains(&format!("deleted {}\n", working_ref))
+    );
+    assert!(tmp_repo.repo().find_reference(&working_ref).is_err());
+    assert!(has_log(&first));
+
+    // --all takes what is left; history itself is untouched
+    let output = tmp_repo.git_ai(&["reset-attribution", "--all", "--confirm"]);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(
+        String::from_utf8_lossy(&output.stdout)
+            .contains(&authorship_ref_name(tmp_repo.repo(), &first))
+    );
+    assert!(!has_log(&first));
+    assert_eq!(tmp_repo.head_commit_sha().unwrap(), second);
+
+    let output = tmp_repo.git_ai(&["reset-attribution", "--all", "--confirm"]);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("No attribution to reset"));
+}
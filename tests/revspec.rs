@@ -0,0 +1,71 @@
+use git_ai::git::revspec::{resolve_commit, resolve_range};
+use git_ai::tmp_repo::TmpRepo;
+use tempfile::tempdir;
+
+fn repo_with_two_commits(tmp_dir: &std::path::Path) -> TmpRepo {
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(tmp_dir.join("repo")).unwrap();
+    tmp_repo.write_file("second.txt", "second\n", true).unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("test_user")
+        .unwrap();
+    tmp_repo.commit_with_message("second commit").unwrap();
+    tmp_repo
+}
+
+#[test]
+fn test_lowercase_head_suggests_head() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_repo = repo_with_two_commits(tmp_dir.path());
+
+    let error = resolve_commit(tmp_repo.repo(), "head~1")
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("Unknown revision: head~1"), "{}", error);
+    assert!(error.contains("Did you mean HEAD~1?"), "{}", error);
+    assert!(error.contains("Expected a commit SHA"), "{}", error);
+}
+
+#[test]
+fn test_branch_typo_in_range_suggests_branch() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_repo = repo_with_two_commits(tmp_dir.path());
+    tmp_repo.create_branch("feature-login").unwrap();
+
+    let Err(error) = resolve_range(tmp_repo.repo(), "feature-logn..HEAD") else {
+        panic!("expected feature-logn to be unknown");
+    };
+    let error = error.to_string();
+    assert!(
+        error.contains("Unknown revision: feature-logn"),
+        "{}",
+        error
+    );
+    assert!(error.contains("Did you mean feature-login?"), "{}", error);
+
+    assert!(resolve_range(tmp_repo.repo(), "HEAD~1..feature-login").is_ok());
+}
+
+#[test]
+fn test_unknown_sha_mentions_fetch_when_remote_exists() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_repo = repo_with_two_commits(tmp_dir.path());
+    let missing = "deadbeefdeadbeefdeadbeefdeadbeefdeadbeef";
+
+    let error = resolve_commit(tmp_repo.repo(), missing)
+        .unwrap_err()
+        .to_string();
+    assert!(!error.contains("git fetch"), "{}", error);
+
+    tmp_repo
+        .repo()
+        .remote("origin", "https://example.com/repo.git")
+        .unwrap();
+    let error = resolve_commit(tmp_repo.repo(), missing)
+        .unwrap_err()
+        .to_string();
+    assert!(
+        error.contains("If the commit only exists on a remote, run `git fetch` first."),
+        "{}",
+        error
+    );
+}
@@ -0,0 +1,102 @@
+use git_ai::commands::checkpoint::{self, CheckpointOptions, prompt_from_hook_payload};
+use git_ai::commands::search::{self, SearchOptions};
+use git_ai::tmp_repo::TmpRepo;
+use tempfile::tempdir;
+
+#[test]
+fn test_search_finds_prompts_and_followup_edits() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+
+    // Prompt submitted: recorded on a checkpoint before the agent edits
+    let prompt = prompt_from_hook_payload(r#"{"prompt": "Refactor the parser into modules"}"#);
+    checkpoint::run(
+        tmp_repo.repo(),
+        "Test User",
+        &CheckpointOptions {
+            quiet: true,
+            prompt,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    tmp_repo
+        .write_file("parser.rs", "mod lexer;\nmod ast;\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+
+    let matches = search::run(tmp_repo.repo(), "REFACTOR", &SearchOptions::default()).unwrap();
+    assert_eq!(matches.len(), 1);
+    assert_eq!(
+        matches[0].checkpoint.prompt.as_deref(),
+        Some("Refactor the parser into modules")
+    );
+    assert_eq!(matches[0].followups.len(), 1);
+    assert_eq!(matches[0].followups[0].entries[0].file, "parser.rs");
+
+    // Prompts survive the commit
+    tmp_repo.commit_with_message("agent refactor").unwrap();
+    let options = SearchOptions { regex: true };
+    let matches = search::run(tmp_repo.repo(), r"parser\s+into", &options).unwrap();
+    assert_eq!(matches.len(), 1);
+
+    let matches = search::run(tmp_repo.repo(), "parser.into", &SearchOptions::default()).unwrap();
+    assert!(matches.is_empty());
+}
+
+#[test]
+fn test_prompt_from_hook_payload() {
+    assert_eq!(
+        prompt_from_hook_payload("{\"prompt\": \"fix tests\", \"session_id\": \"1\"}").as_deref(),
+        Some("fix tests")
+    );
+    assert_eq!(prompt_from_hook_payload("{\"other\": 1}"), None);
+    assert_eq!(
+        prompt_from_hook_payload("plain text\n").as_deref(),
+        Some("plain text")
+    );
+    assert_eq!(prompt_from_hook_payload("  "), None);
+}
+
+#[test]
+fn test_hook_input_from_stdin_and_file() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().join("repo");
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+
+    // Large payloads go through stdin instead of argv
+    let big_context = "x".repeat(512 * 1024);
+    let payload = format!(
+        "{{\"prompt\": \"Add retry logic\", \"context\": \"{}\"}}",
+        big_context
+    );
+    assert_cmd::Command::cargo_bin("git-ai")
+        .unwrap()
+        .current_dir(&repo_path)
+        .args(["checkpoint", "--hook-input", "-"])
+        .write_stdin(payload)
+        .assert()
+        .success();
+
+    let payload_path = tmp_dir.path().join("payload.json");
+    std::fs::write(&payload_path, "{\"prompt\": \"Add logging\"}").unwrap();
+    assert_cmd::Command::cargo_bin("git-ai")
+        .unwrap()
+        .current_dir(&repo_path)
+        .args(["checkpoint", "--hook-input-file"])
+        .arg(&payload_path)
+        .assert()
+        .success();
+
+    let matches = search::run(tmp_repo.repo(), "add", &SearchOptions::default()).unwrap();
+    let prompts: Vec<_> = matches
+        .iter()
+        .filter_map(|m| m.checkpoint.prompt.as_deref())
+        .collect();
+    assert_eq!(prompts, vec!["Add retry logic", "Add logging"]);
+}
@@ -26,6 +26,38 @@ fn test_simple_additions_empty_repo() {
     assert_debug_snapshot!(blame);
 }
 
+#[test]
+fn test_ai_checkpoint_before_first_commit() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let tmp_repo = TmpRepo::new(repo_path.clone()).unwrap();
+    assert!(tmp_repo.repo().head().is_err());
+
+    let file = tmp_repo
+        .write_file("main.rs", "fn main() {\n    println!(\"hi\");\n}\n", true)
+        .unwrap();
+
+    let (entries, files, checkpoints) = tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    assert_eq!((entries, files, checkpoints), (1, 1, 1));
+
+    // With no HEAD yet, the working log is keyed on the "initial" placeholder
+    assert!(
+        tmp_repo
+            .repo()
+            .find_reference("refs/ai-working-log/initial")
+            .is_ok()
+    );
+
+    tmp_repo.commit_with_message("Initial commit").unwrap();
+
+    let blame = tmp_repo.blame_for_file(&file, None).unwrap();
+    assert!(blame.values().all(|author| author == "Claude"));
+    assert_eq!(blame.len(), 3);
+}
+
 #[test]
 fn test_simple_additions_with_base_commit() {
     let tmp_dir = tempdir().unwrap();
@@ -72,3 +104,87 @@ fn test_simple_additions_on_top_of_ai_contributions() {
     let blame = tmp_repo.blame_for_file(&lines, Some((30, 35))).unwrap();
     assert_debug_snapshot!(blame);
 }
+
+#[test]
+fn test_author_type_overrides_model_classification() {
+    use git_ai::commands::checkpoint::{self, AuthorType, CheckpointOptions, resolve_model};
+    use git_ai::commands::stats;
+
+    assert_eq!(
+        resolve_model(Some("cursor"), None).as_deref(),
+        Some("cursor")
+    );
+    assert_eq!(
+        resolve_model(None, Some(AuthorType::Ai)).as_deref(),
+        Some("unknown")
+    );
+
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    tmp_repo
+        .write_file("notes.md", "HUMAN AFTER AGENT\n", true)
+        .unwrap();
+
+    // An AI hook fired, but the edit was the human's
+    let model = resolve_model(Some("cursor"), Some(AuthorType::Human));
+    assert_eq!(model, None);
+    checkpoint::run(
+        tmp_repo.repo(),
+        "Test User",
+        &CheckpointOptions {
+            quiet: true,
+            model,
+            human_author: Some("Test User".to_string()),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    let report = stats::working_tree_stats(tmp_repo.repo()).unwrap();
+    assert_eq!(report.human_additions, 1);
+    assert_eq!(report.ai_additions, 0);
+}
+
+#[test]
+fn test_checkpoint_scope_limits_scan_to_staged_or_unstaged_files() {
+    use git_ai::commands::checkpoint::{self, ChangeScope, CheckpointOptions};
+    use git_ai::git::refs::list_working_logs;
+
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    tmp_repo.write_file("staged.txt", "STAGED\n", true).unwrap();
+    tmp_repo
+        .write_file("unstaged.txt", "UNSTAGED\n", false)
+        .unwrap();
+
+    let checkpoint_files = |scope| {
+        checkpoint::run(
+            tmp_repo.repo(),
+            "Test User",
+            &CheckpointOptions {
+                quiet: true,
+                scope,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let (_, working_log) = list_working_logs(tmp_repo.repo()).unwrap().remove(0);
+        working_log
+            .last()
+            .unwrap()
+            .entries
+            .iter()
+            .map(|entry| entry.file.clone())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        checkpoint_files(ChangeScope::Unstaged),
+        vec!["unstaged.txt"]
+    );
+    assert_eq!(checkpoint_files(ChangeScope::Staged), vec!["staged.txt"]);
+}
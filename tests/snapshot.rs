@@ -0,0 +1,59 @@
+use git_ai::commands::checkpoint::resolve_base_commit;
+use git_ai::git::refs::get_reference_as_working_log;
+use git_ai::tmp_repo::TmpRepo;
+use tempfile::tempdir;
+
+#[test]
+fn test_checkpoint_snapshot_stores_file_content() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    let base_commit = resolve_base_commit(tmp_repo.repo()).unwrap();
+    let working_log = || {
+        get_reference_as_working_log(tmp_repo.repo(), &format!("ai-working-log/{}", base_commit))
+            .unwrap()
+    };
+
+    tmp_repo
+        .write_file("top.rs", "fn top() {}\n", true)
+        .unwrap();
+    let output = tmp_repo.git_ai(&["checkpoint", "--author", "Ada"]);
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(working_log()[0].content_tree, None);
+
+    std::fs::create_dir_all(repo_path.join("src")).unwrap();
+    tmp_repo
+        .write_file("src/lib.rs", "pub fn generated() {}\n", true)
+        .unwrap();
+    let output = tmp_repo.git_ai(&[
+        "checkpoint",
+        "--author",
+        "Claude",
+        "--model",
+        "claude-sonnet",
+        "--snapshot",
+    ]);
+    assert!(output.status.success(), "{:?}", output);
+
+    // The snapshot is an ordinary tree holding the files the checkpoint changed
+    let tree_id = working_log()[1].content_tree.clone().unwrap();
+    let tree = tmp_repo
+        .repo()
+        .find_tree(git2::Oid::from_str(&tree_id).unwrap())
+        .unwrap();
+    let blob = tree
+        .get_path(std::path::Path::new("src/lib.rs"))
+        .unwrap()
+        .to_object(tmp_repo.repo())
+        .unwrap()
+        .peel_to_blob()
+        .unwrap();
+    assert_eq!(blob.content(), b"pub fn generated() {}\n");
+    assert!(tree.get_path(std::path::Path::new("top.rs")).is_err());
+    let snapshot_ref = format!("refs/ai-working-log/snapshots/{}-{}", base_commit, tree_id);
+    assert!(tmp_repo.repo().find_reference(&snapshot_ref).is_ok());
+
+    let output = tmp_repo.git_ai(&["reset-attribution", "--working", "--confirm"]);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(String::from_utf8_lossy(&output.stdout).contains(&snapshot_ref));
+}
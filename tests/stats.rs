@@ -0,0 +1,1563 @@
+use git_ai::commands::stats::{self, PercentFormat, Rounding, StatsOptions};
+use git_ai::tmp_repo::{TmpRepo, args};
+use tempfile::tempdir;
+
+#[test]
+fn test_stats_splits_human_and_ai_additions() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+
+    let mut notes = tmp_repo
+        .write_file("notes.md", "HUMAN LINE\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("test_user")
+        .unwrap();
+    notes.append("AI LINE 1\nAI LINE 2\n").unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo.commit_with_message("mixed commit").unwrap();
+
+    let report = stats::run(tmp_repo.repo(), None, &StatsOptions::default()).unwrap();
+    assert_eq!(report.commits, 1);
+    assert_eq!(report.human_additions, 1);
+    assert_eq!(report.ai_additions, 2);
+    assert_eq!(report.file_stats["notes.md"].ai_additions, 2);
+}
+
+#[test]
+fn test_stats_range_with_author_me() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    let base = tmp_repo.head_commit_sha().unwrap();
+
+    let mut notes = tmp_repo
+        .write_file("notes.md", "AI LINE 1\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo.commit_with_message("first").unwrap();
+
+    notes.append("HUMAN LINE\n").unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("test_user")
+        .unwrap();
+    tmp_repo.commit_with_message("second").unwrap();
+
+    let range = format!("{}..HEAD", base);
+    let options = StatsOptions {
+        author_me: true,
+        ..Default::default()
+    };
+
+    let report = stats::run(tmp_repo.repo(), Some(&range), &options).unwrap();
+    assert_eq!(report.commits, 2);
+    assert_eq!(report.ai_additions, 1);
+    assert_eq!(report.human_additions, 1);
+
+    // Commits are authored by "Test User"; someone else sees nothing
+    let mut config = tmp_repo.repo().config().unwrap();
+    config.set_str("user.name", "Someone Else").unwrap();
+    config.set_str("user.email", "else@example.com").unwrap();
+
+    let report = stats::run(tmp_repo.repo(), Some(&range), &options).unwrap();
+    assert_eq!(report.commits, 0);
+    assert_eq!(report.total_additions(), 0);
+}
+
+#[test]
+fn test_stats_author_me_within_time_window() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    let base = tmp_repo.head_commit_sha().unwrap();
+    tmp_repo
+        .write_file("notes.md", "AI LINE 1\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo.commit_with_message("first").unwrap();
+    let range = format!("{}..HEAD", base);
+    // TmpRepo commits at 2023-01-01T12:00:00Z
+    let (_, options) = stats::parse_stats_args(&args(&[
+        "--me",
+        "--since",
+        "2022-12-31",
+        "--until",
+        "2023-01-01T13:00:00Z",
+    ]))
+    .unwrap();
+    assert!(options.author_me);
+    let report = stats::run(tmp_repo.repo(), Some(&range), &options).unwrap();
+    assert_eq!((report.commits, report.ai_additions), (1, 1));
+
+    // A single revision walks its history inside the window
+    let report = stats::run(tmp_repo.repo(), None, &options).unwrap();
+    assert_eq!(report.ai_additions, 1);
+
+    for window in [&["--until", "@1672531200"][..], &["--since", "1.week.ago"]] {
+        let (_, options) =
+            stats::parse_stats_args(&args(&[&["--me"][..], window].concat())).unwrap();
+        let report = stats::run(tmp_repo.repo(), Some(&range), &options).unwrap();
+        assert_eq!(report.commits, 0);
+    }
+
+    assert!(stats::parse_stats_args(&args(&["--since", "last tuesday"])).is_err());
+    assert!(
+        stats::parse_stats_args(&args(&["--since", "2025-06-01", "--annotate-commit"])).is_err()
+    );
+}
+
+#[test]
+fn test_working_tree_stats_before_commit() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+
+    let report = stats::working_tree_stats(tmp_repo.repo()).unwrap();
+    assert_eq!(report.total_additions(), 0);
+
+    let mut notes = tmp_repo
+        .write_file("notes.md", "HUMAN LINE\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("test_user")
+        .unwrap();
+    notes.append("AI LINE 1\nAI LINE 2\n").unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+
+    let report = stats::working_tree_stats(tmp_repo.repo()).unwrap();
+    assert_eq!(report.human_additions, 1);
+    assert_eq!(report.ai_additions, 2);
+}
+
+#[test]
+fn test_watch_rejects_revspec() {
+    let (revspec, options) = stats::parse_stats_args(&args(&["--watch", "HEAD~1"])).unwrap();
+    assert!(options.watch);
+
+    let tmp_dir = tempdir().unwrap();
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(tmp_dir.path().to_path_buf()).unwrap();
+    assert!(stats::run(tmp_repo.repo(), revspec.as_deref(), &options).is_err());
+}
+
+#[test]
+fn test_stats_reformat_hunks_counted_separately() {
+    use git_ai::commands::stats::ReformatMode;
+
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+
+    let mut code = tmp_repo
+        .write_file("code.rs", "fn a() {\nlet x = 1;\n}\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("test_user")
+        .unwrap();
+    tmp_repo.commit_with_message("human code").unwrap();
+
+    // Agent re-indents the body and adds one substantive line
+    code.update("fn a() {\n    let x = 1;\n}\n\nfn b() {}\n")
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo.commit_with_message("agent reformat").unwrap();
+
+    // By default the re-indented line is counted like any other, as the agent's
+    let report = stats::run(tmp_repo.repo(), None, &StatsOptions::default()).unwrap();
+    assert_eq!(report.reformat_additions, 1);
+    assert_eq!(report.ai_additions, 3);
+    assert_eq!(report.human_additions, 0);
+
+    let options = StatsOptions {
+        count_reformat_as: ReformatMode::Exclude,
+        ..Default::default()
+    };
+    let report = stats::run(tmp_repo.repo(), None, &options).unwrap();
+    assert_eq!(report.reformat_additions, 1);
+    assert_eq!(report.ai_additions, 2);
+    assert_eq!(report.human_additions, 0);
+
+    let options = StatsOptions {
+        count_reformat_as: ReformatMode::Human,
+        ..Default::default()
+    };
+    let report = stats::run(tmp_repo.repo(), None, &options).unwrap();
+    assert_eq!(report.ai_additions, 2);
+    assert_eq!(report.human_additions, 1);
+
+    let (_, options) = stats::parse_stats_args(&args(&["--count-reformat-as", "ai"])).unwrap();
+    let report = stats::run(tmp_repo.repo(), None, &options).unwrap();
+    assert_eq!(report.ai_additions, 3);
+}
+
+#[test]
+fn test_stats_fetches_missing_authorship_only_when_asked() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().join("work");
+    let remote_path = tmp_dir.path().join("remote.git");
+    git2::Repository::init_bare(&remote_path).unwrap();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    tmp_repo
+        .write_file("notes.md", "AI LINE 1\nAI LINE 2\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo.commit_with_message("agent commit").unwrap();
+
+    let repo = tmp_repo.repo();
+    repo.remote("origin", remote_path.to_str().unwrap())
+        .unwrap();
+    let status = std::process::Command::new("git")
+        .arg("-C")
+        .arg(&repo_path)
+        .args([
+            "push",
+            "--quiet",
+            "origin",
+            "refs/ai/authorship/*:refs/ai/authorship/*",
+        ])
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    // Simulate a clone that never fetched authorship refs
+    let head = tmp_repo.head_commit_sha().unwrap();
+    let ref_name = format!("refs/ai/authorship/{}", head);
+    repo.find_reference(&ref_name).unwrap().delete().unwrap();
+
+    // Offline by default
+    let report = stats::run(repo, None, &StatsOptions::default()).unwrap();
+    assert_eq!(report.ai_additions, 0);
+    assert!(repo.find_reference(&ref_name).is_err());
+
+    // Configured to fetch, but --no-fetch wins
+    let mut config = repo.config().unwrap();
+    config.set_bool("git-ai.fetchAuthorship", true).unwrap();
+    let offline = StatsOptions {
+        no_fetch: true,
+        ..Default::default()
+    };
+    let report = stats::run(repo, None, &offline).unwrap();
+    assert_eq!(report.ai_additions, 0);
+    config.remove("git-ai.fetchAuthorship").unwrap();
+
+    let fetch = StatsOptions {
+        fetch: true,
+        ..Default::default()
+    };
+    let report = stats::run(repo, None, &fetch).unwrap();
+    assert_eq!(report.ai_additions, 2);
+    assert!(repo.find_reference(&ref_name).is_ok());
+}
+
+#[test]
+fn test_stats_skips_merges_unless_included() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    let base = tmp_repo.head_commit_sha().unwrap();
+    let default_branch = tmp_repo.get_default_branch().unwrap();
+
+    tmp_repo.create_branch("feature").unwrap();
+    tmp_repo
+        .write_file("feature.md", "AI LINE 1\nAI LINE 2\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo.commit_with_message("feature work").unwrap();
+
+    tmp_repo.switch_branch(&default_branch).unwrap();
+    tmp_repo
+        .write_file("main.md", "HUMAN LINE\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("test_user")
+        .unwrap();
+    tmp_repo.commit_with_message("main work").unwrap();
+    tmp_repo.merge_branch("feature", "merge feature").unwrap();
+
+    // Each line counts once, on the side that introduced it
+    let range = format!("{}..HEAD", base);
+    let report = stats::run(tmp_repo.repo(), Some(&range), &StatsOptions::default()).unwrap();
+    assert_eq!(report.commits, 2);
+    assert_eq!(report.merges_skipped, 1);
+    assert_eq!(report.ai_additions, 2);
+    assert_eq!(report.human_additions, 1);
+
+    // A single merge SHA follows the same rule
+    let report = stats::run(tmp_repo.repo(), None, &StatsOptions::default()).unwrap();
+    assert_eq!(report.commits, 0);
+    assert_eq!(report.merges_skipped, 1);
+
+    let (revspec, options) = stats::parse_stats_args(&args(&["--include-merges", &range])).unwrap();
+    let report = stats::run(tmp_repo.repo(), revspec.as_deref(), &options).unwrap();
+    assert_eq!(report.commits, 3);
+    assert_eq!(report.merges_skipped, 0);
+    // The merge's first-parent diff brings the feature lines in again
+    assert_eq!(report.total_additions(), 5);
+}
+
+#[test]
+fn test_stats_min_lines_and_exclude_small() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    tmp_repo
+        .write_file("config.toml", "debug = true\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("test_user")
+        .unwrap();
+    tmp_repo
+        .write_file("main.rs", "fn main() {\n    run();\n}\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo.commit_with_message("mixed").unwrap();
+
+    // Small files are hidden from the breakdown but still counted
+    let (_, options) = stats::parse_stats_args(&args(&["--min-lines", "2"])).unwrap();
+    let report = stats::run(tmp_repo.repo(), None, &options).unwrap();
+    assert!(report.file_stats.contains_key("config.toml"));
+    assert_eq!(report.human_additions, 1);
+    assert_eq!(report.ai_additions, 3);
+
+    let (_, options) =
+        stats::parse_stats_args(&args(&["--min-lines", "2", "--exclude-small"])).unwrap();
+    let report = stats::run(tmp_repo.repo(), None, &options).unwrap();
+    assert!(!report.file_stats.contains_key("config.toml"));
+    assert_eq!(report.human_additions, 0);
+    assert_eq!(report.ai_additions, 3);
+    assert!(!report.total_additions_by_author.contains_key("test_user"));
+
+    assert!(stats::parse_stats_args(&args(&["--exclude-small"])).is_err());
+}
+
+#[test]
+fn test_stats_per_commit_split() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    let base = tmp_repo.head_commit_sha().unwrap();
+
+    tmp_repo
+        .write_file("agent.md", "AI LINE 1\nAI LINE 2\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo.commit_with_message("agent, heavy").unwrap();
+
+    tmp_repo
+        .write_file("human.md", "HUMAN LINE\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("test_user")
+        .unwrap();
+    tmp_repo.commit_with_message("human tweak").unwrap();
+
+    let (revspec, options) = stats::parse_stats_args(&args(&[
+        "--per-commit",
+        "--format",
+        "csv",
+        &format!("{}..HEAD", base),
+    ]))
+    .unwrap();
+    let report = stats::run(tmp_repo.repo(), revspec.as_deref(), &options).unwrap();
+
+    assert_eq!(report.per_commit.len(), 2);
+    assert_eq!(report.per_commit[0].summary, "human tweak");
+    assert_eq!(report.per_commit[0].ai_percentage(), 0.0);
+    assert_eq!(report.per_commit[1].summary, "agent, heavy");
+    assert_eq!(report.per_commit[1].ai_additions, 2);
+    assert_eq!(report.per_commit[1].ai_percentage(), 100.0);
+
+    assert!(stats::parse_stats_args(&args(&["--format", "json"])).is_err());
+}
+
+#[test]
+fn test_stats_applies_mailmap_unless_disabled() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    tmp_repo
+        .write_file(
+            ".mailmap",
+            "Canonical Name <canonical@example.com> <test@example.com>\n",
+            true,
+        )
+        .unwrap();
+    let mut notes = tmp_repo
+        .write_file("notes.md", "HUMAN LINE\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("test_user")
+        .unwrap();
+    notes.append("AI LINE\n").unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo.commit_with_message("mailmapped commit").unwrap();
+
+    let options = StatsOptions {
+        per_commit: true,
+        ..Default::default()
+    };
+    let report = stats::run(tmp_repo.repo(), None, &options).unwrap();
+    let authors = &report.file_stats["notes.md"].additions;
+    assert_eq!(authors.get("Canonical Name"), Some(&1));
+    assert_eq!(authors.get("Claude"), Some(&1));
+    assert!(!authors.contains_key("test_user"));
+    assert_eq!(report.per_commit[0].author, "Canonical Name");
+
+    let options = StatsOptions {
+        no_mailmap: true,
+        per_commit: true,
+        ..Default::default()
+    };
+    let report = stats::run(tmp_repo.repo(), None, &options).unwrap();
+    let authors = &report.file_stats["notes.md"].additions;
+    assert_eq!(authors.get("test_user"), Some(&1));
+    assert_eq!(report.per_commit[0].author, "Test User");
+}
+
+#[test]
+fn test_summary_line_for_commit() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    let mut notes = tmp_repo
+        .write_file("notes.md", "HUMAN LINE\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("test_user")
+        .unwrap();
+    notes.append("AI LINE 1\nAI LINE 2\nAI LINE 3\n").unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo.commit_with_message("mixed commit").unwrap();
+
+    let head = tmp_repo.repo().head().unwrap().peel_to_commit().unwrap();
+    assert_eq!(
+        stats::commit_summary_line(tmp_repo.repo(), &head).unwrap(),
+        "git-ai: 25% human / 75% AI across 1 file"
+    );
+
+    let (_, options) = stats::parse_stats_args(&args(&["--output", "summary-line"])).unwrap();
+    assert_eq!(options.output, stats::StatsOutput::SummaryLine);
+    assert!(stats::parse_stats_args(&args(&["--output", "summary-line", "--per-commit"])).is_err());
+}
+
+#[test]
+fn test_stats_streams_long_history() {
+    let tmp_dir = tempdir().unwrap();
+    let repo = git2::Repository::init(tmp_dir.path()).unwrap();
+    let signature = git2::Signature::now("Test User", "test@example.com").unwrap();
+
+    // Synthetic history written straight to the object database: one line rewritten per commit
+    let commit_count = 2000;
+    let mut parent: Option<git2::Oid> = None;
+    let mut root = None;
+    for i in 0..=commit_count {
+        let blob = repo.blob(format!("line {}\n", i).as_bytes()).unwrap();
+        let mut tree = repo.treebuilder(None).unwrap();
+        tree.insert("counter.txt", blob, 0o100644).unwrap();
+        let tree = repo.find_tree(tree.write().unwrap()).unwrap();
+        let parents: Vec<git2::Commit> = parent
+            .iter()
+            .map(|oid| repo.find_commit(*oid).unwrap())
+            .collect();
+        let parents: Vec<&git2::Commit> = parents.iter().collect();
+        let oid = repo
+            .commit(
+                Some("HEAD"),
+                &signature,
+                &signature,
+                &format!("commit {}", i),
+                &tree,
+                &parents,
+            )
+            .unwrap();
+        root.get_or_insert(oid);
+        parent = Some(oid);
+    }
+
+    let options = StatsOptions {
+        no_fetch: true,
+        ..Default::default()
+    };
+    let range = format!("{}..HEAD", root.unwrap());
+    let report = stats::run(&repo, Some(&range), &options).unwrap();
+    assert_eq!(report.commits, commit_count);
+    assert_eq!(report.human_additions, commit_count as u32);
+    assert_eq!(report.total_deletions, commit_count as u32);
+    // Nothing per commit is retained unless --per-commit asks for it
+    assert!(report.per_commit.is_empty());
+}
+
+#[test]
+fn test_stats_markdown_table() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    let mut notes = tmp_repo
+        .write_file("notes.md", "HUMAN LINE\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("test_user")
+        .unwrap();
+    notes.append("AI LINE 1\nAI LINE 2\nAI LINE 3\n").unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo.commit_with_message("mixed commit").unwrap();
+
+    let (_, options) = stats::parse_stats_args(&args(&["--markdown-table"])).unwrap();
+    assert_eq!(options.output, stats::StatsOutput::MarkdownTable);
+    let report = stats::run(tmp_repo.repo(), None, &options).unwrap();
+    assert_eq!(
+        stats::markdown_table(&report, 0, &PercentFormat::default()),
+        "\
+| File       | Human |  AI | AI % | Deleted |
+| :--------- | ----: | --: | ---: | ------: |
+| `notes.md` |     1 |   3 |  75% |       0 |
+| **Total**  |     1 |   3 |  75% |       0 |
+
+| Author    | Lines | Share |
+| :-------- | ----: | ----: |
+| Claude    |     3 |   75% |
+| test_user |     1 |   25% |
+| **Total** |     4 |  100% |
+"
+    );
+}
+
+#[test]
+fn test_stats_only_ai_files() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    tmp_repo
+        .write_file("human.md", "HUMAN LINE\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("test_user")
+        .unwrap();
+    tmp_repo
+        .write_file("ai.rs", "AI LINE 1\nAI LINE 2\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo.commit_with_message("mixed commit").unwrap();
+
+    let (_, options) =
+        stats::parse_stats_args(&args(&["--only-ai-files", "--with-counts"])).unwrap();
+    assert_eq!(options.output, stats::StatsOutput::AiFiles);
+    let report = stats::run(tmp_repo.repo(), None, &options).unwrap();
+    assert_eq!(stats::ai_files(&report, 0, false), "ai.rs\n");
+    assert_eq!(stats::ai_files(&report, 0, true), "ai.rs\t2\n");
+    assert_eq!(stats::ai_files(&report, 3, true), "");
+
+    assert!(stats::parse_stats_args(&args(&["--with-counts"])).is_err());
+}
+
+#[test]
+fn test_stats_weighted_by_churn() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    let base = tmp_repo.head_commit_sha().unwrap();
+
+    // One human file written once, one AI file reworked over two commits
+    tmp_repo
+        .write_file("once.rs", "h1\nh2\nh3\nh4\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("test_user")
+        .unwrap();
+    let mut reworked = tmp_repo
+        .write_file("reworked.rs", "a1\na2\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo.commit_with_message("first pass").unwrap();
+    reworked.append("a3\na4\n").unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo.commit_with_message("rework").unwrap();
+
+    let (_, mut options) = stats::parse_stats_args(&args(&["--weighted-by", "churn"])).unwrap();
+    assert_eq!(options.weighted_by, Some(stats::Weighting::Churn));
+    options.no_fetch = true;
+    let range = format!("{}..HEAD", base);
+    let report = stats::run(tmp_repo.repo(), Some(&range), &options).unwrap();
+
+    assert_eq!(report.ai_additions, 4);
+    assert_eq!(report.human_additions, 4);
+    assert_eq!(report.file_stats["reworked.rs"].commits, 2);
+    // (4 / 2) / (4 / 1 + 4 / 2) = 1/3
+    let weighted = stats::churn_weighted_ai_percentage(&report);
+    assert!((weighted - 100.0 / 3.0).abs() < 1e-9);
+
+    assert!(stats::parse_stats_args(&args(&["--weighted-by", "lines"])).is_err());
+}
+
+#[test]
+fn test_stats_cache_reuses_identical_queries() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    tmp_repo
+        .write_file("cached.rs", "ai 1\nai 2\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo.commit_with_message("agent commit").unwrap();
+
+    let (_, options) = stats::parse_stats_args(&args(&["--cache", "--no-fetch"])).unwrap();
+    let report = stats::run(tmp_repo.repo(), Some("HEAD"), &options).unwrap();
+    assert_eq!(report.ai_additions, 2);
+
+    let cache_dir = tmp_repo.repo().path().join("git-ai").join("stats-cache");
+    let entries: Vec<_> = std::fs::read_dir(&cache_dir).unwrap().flatten().collect();
+    assert_eq!(entries.len(), 1);
+
+    // Doctor the entry to prove the next identical query is served from it
+    let entry = entries[0].path();
+    let mut cached: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&entry).unwrap()).unwrap();
+    cached["ai_additions"] = 99.into();
+    std::fs::write(&entry, cached.to_string()).unwrap();
+    let report = stats::run(tmp_repo.repo(), Some("HEAD"), &options).unwrap();
+    assert_eq!(report.ai_additions, 99);
+
+    // A different option is a different entry; --no-cache bypasses the cache entirely
+    let (_, options) =
+        stats::parse_stats_args(&args(&["--cache", "--no-fetch", "--include-merges"])).unwrap();
+    let report = stats::run(tmp_repo.repo(), Some("HEAD"), &options).unwrap();
+    assert_eq!(report.ai_additions, 2);
+
+    let (_, options) = stats::parse_stats_args(&args(&["--no-cache", "--no-fetch"])).unwrap();
+    let report = stats::run(tmp_repo.repo(), Some("HEAD"), &options).unwrap();
+    assert_eq!(report.ai_additions, 2);
+
+    // The commit is the same but its attribution is gone, so the entry no longer applies
+    let head = tmp_repo.head_commit_sha().unwrap();
+    let ref_name = git_ai::git::refs::authorship_ref_name(tmp_repo.repo(), &head);
+    tmp_repo
+        .repo()
+        .find_reference(&format!("refs/{}", ref_name))
+        .unwrap()
+        .delete()
+        .unwrap();
+    let (_, options) = stats::parse_stats_args(&args(&["--cache", "--no-fetch"])).unwrap();
+    let report = stats::run(tmp_repo.repo(), Some("HEAD"), &options).unwrap();
+    assert_eq!(report.ai_additions, 0);
+}
+
+#[test]
+fn test_stats_normalize_by_diff_size() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    let mut file = tmp_repo
+        .write_file("rewrite.rs", "h1\nh2\nh3\nh4\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("test_user")
+        .unwrap();
+    tmp_repo.commit_with_message("human commit").unwrap();
+
+    // The agent rewrites two lines: two added, two deleted
+    file.update("h1\na2\na3\nh4\n").unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo.commit_with_message("agent rewrite").unwrap();
+
+    let (_, options) =
+        stats::parse_stats_args(&args(&["--normalize-by", "diff-size", "--no-fetch"])).unwrap();
+    assert_eq!(options.normalize_by, Some(stats::Normalization::DiffSize));
+    let report = stats::run(tmp_repo.repo(), Some("HEAD"), &options).unwrap();
+    assert_eq!(report.ai_additions, 2);
+    assert_eq!(report.total_deletions, 2);
+    assert_eq!(stats::diff_size_ai_percentage(&report), 50.0);
+
+    assert!(
+        stats::parse_stats_args(&args(&["--normalize-by", "diff-size", "--per-commit"])).is_err()
+    );
+}
+
+#[test]
+fn test_stats_author_domain() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    let base = tmp_repo.head_commit_sha().unwrap();
+    tmp_repo
+        .write_file("internal.rs", "h1\nh2\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("test_user")
+        .unwrap();
+    tmp_repo.commit_with_message("internal commit").unwrap();
+
+    // A contractor commits agent-written code
+    tmp_repo
+        .write_file("vendor.rs", "a1\na2\na3\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo
+        .commit_with_author("vendor commit", "Dev", "dev@Vendor.io")
+        .unwrap();
+
+    let (revspec, options) = stats::parse_stats_args(&args(&[
+        &format!("{}..HEAD", base),
+        "--author-domain",
+        "--no-fetch",
+    ]))
+    .unwrap();
+    assert!(options.author_domain);
+    let report = stats::run(tmp_repo.repo(), revspec.as_deref(), &options).unwrap();
+    let domains = stats::domain_breakdown(&report);
+    assert_eq!(
+        domains["example.com"],
+        stats::DomainStats {
+            human_additions: 2,
+            ai_additions: 0
+        }
+    );
+    assert_eq!(
+        domains["vendor.io"],
+        stats::DomainStats {
+            human_additions: 0,
+            ai_additions: 3
+        }
+    );
+
+    // Excluded files leave the domain totals too
+    let options = StatsOptions {
+        min_lines: Some(3),
+        exclude_small: true,
+        ..options
+    };
+    let report = stats::run(tmp_repo.repo(), revspec.as_deref(), &options).unwrap();
+    assert_eq!(
+        stats::domain_breakdown(&report).keys().collect::<Vec<_>>(),
+        vec!["vendor.io"]
+    );
+
+    assert!(stats::parse_stats_args(&args(&["--author-domain", "--per-commit"])).is_err());
+}
+
+#[test]
+fn test_stats_min_confidence_counts_guesses_as_human() {
+    use git_ai::commands::checkpoint::{self, AuthorType, CheckpointOptions, resolve_confidence};
+    use git_ai::log_fmt::working_log::Confidence;
+
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    let mut file = tmp_repo.write_file("mixed.rs", "reported\n", true).unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    file.append("guessed\nguessed\n").unwrap();
+    let confidence = resolve_confidence(None, Some(AuthorType::Ai), None);
+    assert_eq!(confidence, Confidence::Low);
+    checkpoint::run(
+        tmp_repo.repo(),
+        "Claude",
+        &CheckpointOptions {
+            quiet: true,
+            model: Some("unknown".to_string()),
+            human_author: Some("Test User".to_string()),
+            confidence,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    tmp_repo.commit_with_message("mixed commit").unwrap();
+
+    let options = StatsOptions {
+        no_fetch: true,
+        ..Default::default()
+    };
+    let report = stats::run(tmp_repo.repo(), None, &options).unwrap();
+    assert_eq!(report.ai_additions, 3);
+
+    let (_, options) =
+        stats::parse_stats_args(&args(&["--min-confidence", "high", "--no-fetch"])).unwrap();
+    let report = stats::run(tmp_repo.repo(), None, &options).unwrap();
+    assert_eq!(report.ai_additions, 1);
+    assert_eq!(report.human_additions, 2);
+    assert_eq!(report.total_additions_by_author.get("Test User"), Some(&2));
+
+    assert!(stats::parse_stats_args(&args(&["--min-confidence", "certain"])).is_err());
+}
+
+#[test]
+fn test_stats_baseline_branch_reports_branch_divergence() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    let default_branch = tmp_repo.get_default_branch().unwrap();
+
+    tmp_repo.create_branch("feature").unwrap();
+    tmp_repo
+        .write_file("feature.md", "AI LINE 1\nAI LINE 2\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo.commit_with_message("feature work").unwrap();
+
+    // The baseline moves on after the branch point; none of that is the branch's work
+    tmp_repo.switch_branch(&default_branch).unwrap();
+    tmp_repo
+        .write_file("main.md", "HUMAN LINE\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("test_user")
+        .unwrap();
+    tmp_repo.commit_with_message("main work").unwrap();
+    tmp_repo.switch_branch("feature").unwrap();
+
+    let (revspec, options) =
+        stats::parse_stats_args(&args(&["--baseline-branch", &default_branch, "--no-fetch"]))
+            .unwrap();
+    let report = stats::run(tmp_repo.repo(), revspec.as_deref(), &options).unwrap();
+    assert_eq!(report.commits, 1);
+    assert_eq!(report.ai_additions, 2);
+    assert_eq!(report.human_additions, 0);
+
+    // An explicit head revision replaces HEAD, but ranges are refused
+    let report = stats::run(tmp_repo.repo(), Some(&default_branch), &options).unwrap();
+    assert_eq!(report.commits, 0);
+    assert!(stats::run(tmp_repo.repo(), Some("HEAD~1..HEAD"), &options).is_err());
+}
+
+#[test]
+fn test_stats_top_files() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    tmp_repo
+        .write_file("human.rs", "h1\nh2\nh3\n", true)
+        .unwrap();
+    tmp_repo.write_file("tiny.rs", "h1\n", true).unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("test_user")
+        .unwrap();
+    tmp_repo
+        .write_file("agent.rs", "a1\na2\na3\na4\n", true)
+        .unwrap();
+    tmp_repo.write_file("helper.rs", "a1\na2\n", true).unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo.commit_with_message("mixed commit").unwrap();
+
+    let (_, options) =
+        stats::parse_stats_args(&args(&["--top", "1", "--min-lines", "2", "--no-fetch"])).unwrap();
+    assert_eq!(options.output, stats::StatsOutput::Top(1));
+    let report = stats::run(tmp_repo.repo(), None, &options).unwrap();
+    assert_eq!(
+        stats::top_files(&report, 1, 2, &PercentFormat::default()),
+        "Most AI-authored files:\n  agent.rs  +4 AI (100%)\n\
+         Most human-authored files:\n  human.rs  +3 human (100%)\n"
+    );
+    // tiny.rs is under --min-lines
+    assert!(!stats::top_files(&report, 5, 2, &PercentFormat::default()).contains("tiny.rs"));
+    assert!(stats::top_files(&report, 5, 0, &PercentFormat::default()).contains("tiny.rs"));
+
+    assert!(stats::parse_stats_args(&args(&["--top", "0"])).is_err());
+}
+
+#[test]
+fn test_stats_emit_prometheus() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    tmp_repo.write_file("human.rs", "h1\n", true).unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("test_user")
+        .unwrap();
+    tmp_repo
+        .write_file("agent.rs", "a1\na2\na3\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo.commit_with_message("mixed commit").unwrap();
+
+    let (_, options) =
+        stats::parse_stats_args(&args(&["--emit-prometheus", "--no-fetch"])).unwrap();
+    assert_eq!(options.output, stats::StatsOutput::Prometheus);
+    let report = stats::run(tmp_repo.repo(), None, &options).unwrap();
+
+    let metrics = stats::prometheus_metrics(&report, "web \"app\"", "main");
+    assert!(metrics.contains("# TYPE git_ai_lines gauge\n"));
+    assert!(
+        metrics.contains("git_ai_lines{repo=\"web \\\"app\\\"\",branch=\"main\",type=\"ai\"} 3\n")
+    );
+    assert!(
+        metrics
+            .contains("git_ai_lines{repo=\"web \\\"app\\\"\",branch=\"main\",type=\"human\"} 1\n")
+    );
+    assert!(metrics.contains("git_ai_ai_ratio{repo=\"web \\\"app\\\"\",branch=\"main\"} 0.75\n"));
+    assert!(metrics.contains("git_ai_commits{repo=\"web \\\"app\\\"\",branch=\"main\"} 1\n"));
+
+    let (_, branch) = stats::metric_labels(tmp_repo.repo());
+    assert_eq!(branch, tmp_repo.get_default_branch().unwrap());
+
+    assert!(stats::parse_stats_args(&args(&["--emit-prometheus", "--per-commit"])).is_err());
+}
+
+#[test]
+fn test_stats_resolve_only_reports_shas_and_merge_base() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    let default_branch = tmp_repo.get_default_branch().unwrap();
+    let branch_point = tmp_repo.head_commit_sha().unwrap();
+
+    tmp_repo.create_branch("feature").unwrap();
+    tmp_repo
+        .write_file("feature.md", "AI LINE\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo.commit_with_message("feature work").unwrap();
+    let feature_head = tmp_repo.head_commit_sha().unwrap();
+
+    tmp_repo.switch_branch(&default_branch).unwrap();
+    tmp_repo.write_file("main.md", "HUMAN\n", true).unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("test_user")
+        .unwrap();
+    tmp_repo.commit_with_message("main work").unwrap();
+    let main_head = tmp_repo.head_commit_sha().unwrap();
+
+    let resolve = |values: &[&str]| {
+        let (revspec, options) = stats::parse_stats_args(&args(values)).unwrap();
+        assert!(options.resolve_only);
+        stats::resolve_only(tmp_repo.repo(), revspec.as_deref(), &options).unwrap()
+    };
+
+    let resolution = resolve(&["--resolve-only"]);
+    assert_eq!(resolution.head.sha, main_head);
+    assert_eq!(
+        resolution.head.refname.as_deref(),
+        Some(format!("refs/heads/{}", default_branch).as_str())
+    );
+    assert!(resolution.base.is_none());
+
+    let range = format!("{}...feature", default_branch);
+    let resolution = resolve(&["--resolve-only", &range]);
+    assert_eq!(resolution.base.as_ref().unwrap().sha, main_head);
+    assert_eq!(resolution.head.sha, feature_head);
+    assert_eq!(
+        resolution.merge_base.as_deref(),
+        Some(branch_point.as_str())
+    );
+    assert!(resolution.symmetric);
+    let rendered = stats::render_resolution(&resolution);
+    assert!(rendered.contains(&format!(
+        "head:       feature -> refs/heads/feature -> {}",
+        feature_head
+    )));
+    assert!(rendered.contains(&format!(
+        "walks:      {}..{}",
+        &branch_point[..7],
+        &feature_head[..7]
+    )));
+
+    // --baseline-branch resolves to the same symmetric range
+    let resolution = resolve(&[
+        "--resolve-only",
+        "--baseline-branch",
+        &default_branch,
+        "feature",
+    ]);
+    assert_eq!(resolution.revspec, range);
+}
+
+#[test]
+fn test_stats_output_file_replaces_artifact() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    tmp_repo.write_file("agent.rs", "a1\na2\n", true).unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo.commit_with_message("agent commit").unwrap();
+
+    // Parent directories are created, and an existing artifact is replaced whole
+    let artifacts = tmp_dir.path().join("ci/artifacts");
+    let output_file = artifacts.join("stats.txt");
+    std::fs::create_dir_all(&artifacts).unwrap();
+    std::fs::write(
+        &output_file,
+        "stale contents that are longer than the summary\n",
+    )
+    .unwrap();
+    let (_, options) = stats::parse_stats_args(&args(&[
+        "--output",
+        "summary-line",
+        "--no-fetch",
+        "--output-file",
+        &output_file.to_string_lossy(),
+    ]))
+    .unwrap();
+    let report = stats::run(tmp_repo.repo(), None, &options).unwrap();
+    assert_eq!(
+        std::fs::read_to_string(&output_file).unwrap(),
+        format!(
+            "{}\n",
+            stats::summary_line(&report, &PercentFormat::default())
+        )
+    );
+
+    let nested = tmp_dir.path().join("fresh/dir/stats.prom");
+    let (_, options) = stats::parse_stats_args(&args(&[
+        "--emit-prometheus",
+        "--no-fetch",
+        "--output-file",
+        &nested.to_string_lossy(),
+    ]))
+    .unwrap();
+    stats::run(tmp_repo.repo(), None, &options).unwrap();
+    assert!(
+        std::fs::read_to_string(&nested)
+            .unwrap()
+            .contains("# TYPE git_ai_lines gauge\n")
+    );
+    // Nothing but the artifact is left behind
+    let leftovers: Vec<_> = std::fs::read_dir(nested.parent().unwrap())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .collect();
+    assert_eq!(leftovers, ["stats.prom"]);
+}
+
+#[test]
+fn test_stats_author_regex_filters_before_aggregation() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    tmp_repo.write_file("human.rs", "h1\n", true).unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("test_user")
+        .unwrap();
+    tmp_repo
+        .write_file("deps.lock", "d1\nd2\nd3\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("dependabot-bot", "dependabot")
+        .unwrap();
+    tmp_repo.write_file("ci.yml", "r1\n", true).unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("renovate-bot", "renovate")
+        .unwrap();
+    tmp_repo.write_file("agent.rs", "a1\na2\n", true).unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo.commit_with_message("mixed commit").unwrap();
+
+    let (_, options) = stats::parse_stats_args(&args(&[
+        "--author-regex",
+        "-bot$",
+        "--by-author",
+        "--no-fetch",
+    ]))
+    .unwrap();
+    assert_eq!(options.output, stats::StatsOutput::ByAuthor);
+    let report = stats::run(tmp_repo.repo(), None, &options).unwrap();
+    assert_eq!(report.ai_additions, 4);
+    assert_eq!(report.human_additions, 0);
+    assert_eq!(report.commits, 1);
+    let mut files: Vec<_> = report.file_stats.keys().cloned().collect();
+    files.sort();
+    assert_eq!(files, ["ci.yml", "deps.lock"]);
+    assert_eq!(
+        stats::by_author(&report, &PercentFormat::default()),
+        "Author             Lines  Share\ndependabot-bot        +3    75%\nrenovate-bot          +1    25%\n"
+    );
+
+    // The commit author doesn't match, so nothing of theirs is counted
+    let (_, options) =
+        stats::parse_stats_args(&args(&["--author-regex", "^nobody$", "--no-fetch"])).unwrap();
+    let report = stats::run(tmp_repo.repo(), None, &options).unwrap();
+    assert_eq!(report.total_additions(), 0);
+    assert_eq!(report.commits, 0);
+
+    assert!(stats::parse_stats_args(&args(&["--author-regex", "("])).is_err());
+}
+
+#[test]
+fn test_stats_percent_rounding() {
+    let (_, options) = stats::parse_stats_args(&args(&[
+        "--round",
+        "ceil",
+        "--decimals",
+        "1",
+        "--sum-to-100",
+    ]))
+    .unwrap();
+    assert_eq!(
+        options.percent,
+        PercentFormat {
+            round: Rounding::Ceil,
+            decimals: 1,
+            sum_to_100: true
+        }
+    );
+    assert!(stats::parse_stats_args(&args(&["--round", "up"])).is_err());
+    assert!(stats::parse_stats_args(&args(&["--decimals", "9"])).is_err());
+
+    // Ties go to the even neighbour by default, as before
+    let default = PercentFormat::default();
+    assert_eq!(default.format(12.5), "12%");
+    assert_eq!(default.format(13.5), "14%");
+    let with = |round, decimals| PercentFormat {
+        round,
+        decimals,
+        sum_to_100: false,
+    };
+    assert_eq!(with(Rounding::Nearest, 0).format(12.5), "13%");
+    assert_eq!(with(Rounding::Floor, 1).format(200.0 / 3.0), "66.6%");
+    assert_eq!(with(Rounding::Ceil, 1).format(200.0 / 3.0), "66.7%");
+
+    // Three equal shares round to 33% each; largest remainder makes them add up to 100
+    let thirds = [100.0 / 3.0; 3];
+    assert_eq!(default.format_split(&thirds), ["33%", "33%", "33%"]);
+    let exact = PercentFormat {
+        sum_to_100: true,
+        ..Default::default()
+    };
+    assert_eq!(exact.format_split(&thirds), ["34%", "33%", "33%"]);
+    assert_eq!(exact.format_split(&[68.4, 0.9, 30.7]), ["68%", "1%", "31%"]);
+    assert_eq!(
+        PercentFormat {
+            decimals: 1,
+            ..exact
+        }
+        .format_split(&[2.0 / 3.0 * 100.0, 100.0 / 3.0]),
+        ["66.7%", "33.3%"]
+    );
+    assert_eq!(exact.format_split(&[0.0, 0.0]), ["0%", "0%"]);
+}
+
+#[test]
+fn test_stats_human_effort_score() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    let base = tmp_repo.head_commit_sha().unwrap();
+    tmp_repo.write_file("human.rs", "h1\nh2\n", true).unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("test_user")
+        .unwrap();
+    let mut reworked = tmp_repo.write_file("agent.rs", "a1\na2\n", true).unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo.commit_with_message("first pass").unwrap();
+    reworked.append("a3\n").unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo.commit_with_message("rework").unwrap();
+
+    let (_, options) = stats::parse_stats_args(&args(&["--human-effort", "--no-fetch"])).unwrap();
+    assert!(options.human_effort);
+    let range = format!("{}..HEAD", base);
+    let report = stats::report(tmp_repo.repo(), Some(&range), &options).unwrap();
+
+    // 3 AI lines in 1 file, reworked in 1 extra commit: 3*1 + 1*10 + 1*5
+    let weights = stats::effort_weights(tmp_repo.repo()).unwrap();
+    assert_eq!(weights, stats::EffortWeights::default());
+    let effort = stats::human_effort(&report, &weights);
+    assert_eq!(
+        (effort.ai_lines, effort.ai_files, effort.rework_commits),
+        (3, 1, 1)
+    );
+    assert_eq!(
+        stats::render_human_effort(&effort, &weights),
+        "Human effort: 18 (3 AI line(s) x1 + 1 file(s) x10 + 1 rework commit(s) x5)"
+    );
+
+    let mut config = tmp_repo.repo().config().unwrap();
+    config.set_str("git-ai.effort.fileWeight", "2.5").unwrap();
+    let weights = stats::effort_weights(tmp_repo.repo()).unwrap();
+    assert_eq!(stats::human_effort(&report, &weights).score, 10.5);
+    config.set_str("git-ai.effort.churnWeight", "lots").unwrap();
+    assert!(stats::effort_weights(tmp_repo.repo()).is_err());
+
+    assert!(stats::parse_stats_args(&args(&["--human-effort", "--by-author"])).is_err());
+}
+
+#[test]
+fn test_stats_accept_rate_breakdown() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    tmp_repo
+        .write_file("agent.rs", "a\nb\nc\nd\n", true)
+        .unwrap();
+    tmp_repo
+        .write_file("scratch.rs", "scratch\n", false)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    // A person rewrites one suggested line, the agent another
+    tmp_repo
+        .write_file("agent.rs", "a\nB\nc\nd\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("test_user")
+        .unwrap();
+    tmp_repo
+        .write_file("agent.rs", "a\nB\nC\nd\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    std::fs::remove_file(repo_path.join("scratch.rs")).unwrap();
+    tmp_repo.commit_with_message("Reviewed").unwrap();
+
+    let (_, options) = stats::parse_stats_args(&args(&["--accept-rate", "--no-fetch"])).unwrap();
+    assert!(options.accept_rate);
+    let rate = stats::accept_rate(tmp_repo.repo(), None, &options).unwrap();
+    assert_eq!(
+        rate,
+        stats::AcceptRate {
+            suggested: 6,
+            retained: 3,
+            removed_by_humans: 1,
+            revised_by_ai: 1,
+            not_committed: 1,
+        }
+    );
+    assert_eq!(
+        stats::render_accept_rate(&rate, &stats::PercentFormat::default()),
+        "Accept rate: 50% of 6 AI-suggested line(s) retained (retained / suggested)\n    retained           3\n    removed by humans  1\n    revised by AI      1\n    not committed      1\n"
+    );
+
+    assert!(stats::parse_stats_args(&args(&["--accept-rate", "--per-commit"])).is_err());
+}
+
+#[test]
+fn test_stats_delta_from_parent() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    let main = tmp_repo.current_branch().unwrap();
+    let base = tmp_repo.head_commit_sha().unwrap();
+    tmp_repo.create_branch("feature").unwrap();
+    tmp_repo
+        .write_file("agent.rs", "fn a() {}\nfn b() {}\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo.write_file("notes.md", "by hand\n", true).unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("test_user")
+        .unwrap();
+    tmp_repo.commit_with_message("Feature").unwrap();
+    let feature = tmp_repo.head_commit_sha().unwrap();
+
+    let (revspec, options) =
+        stats::parse_stats_args(&args(&[&feature, "--delta-from-parent", "--no-fetch"])).unwrap();
+    assert!(options.delta_from_parent);
+    let delta = stats::delta_from_parent(tmp_repo.repo(), revspec.as_deref(), &options).unwrap();
+    assert_eq!(delta.parent.as_deref(), Some(base.as_str()));
+    assert_eq!(
+        stats::render_delta(&delta, &options.percent),
+        format!(
+            "Delta of {} from its parent {}:\n    agent.rs  AI +2  human +0  -0\n    notes.md  AI +0  human +1  -0\nAI +2 (67%)  Human +1 (33%)  -0 deleted\n",
+            &feature[..7],
+            &base[..7]
+        )
+    );
+
+    // A merge is skipped by the default view but measured against its first parent here
+    tmp_repo.switch_branch(&main).unwrap();
+    tmp_repo
+        .write_file("main.rs", "fn main() {}\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("test_user")
+        .unwrap();
+    tmp_repo.commit_with_message("Main").unwrap();
+    tmp_repo.merge_branch("feature", "Merge feature").unwrap();
+    let (_, default_options) = stats::parse_stats_args(&args(&["--no-fetch"])).unwrap();
+    let report = stats::report(tmp_repo.repo(), None, &default_options).unwrap();
+    assert_eq!((report.commits, report.merges_skipped), (0, 1));
+    let delta = stats::delta_from_parent(tmp_repo.repo(), None, &options).unwrap();
+    assert_eq!(delta.report.total_additions(), 3);
+    assert_eq!(
+        delta.report.file_stats.keys().collect::<Vec<_>>(),
+        ["agent.rs", "notes.md"]
+    );
+
+    let range = format!("{}..HEAD", base);
+    assert!(stats::delta_from_parent(tmp_repo.repo(), Some(&range), &options).is_err());
+    assert!(
+        stats::parse_stats_args(&args(&["--delta-from-parent", "--baseline-branch", &main]))
+            .is_err()
+    );
+}
+
+#[test]
+fn test_stats_format_table() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    let mut notes = tmp_repo
+        .write_file("notes.md", "HUMAN LINE\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("test_user")
+        .unwrap();
+    notes.append("AI LINE 1\nAI LINE 2\nAI LINE 3\n").unwrap();
+    tmp_repo
+        .write_file("src/generated/parser_tables.rs", "AI LINE\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo.commit_with_message("mixed commit").unwrap();
+
+    let (_, options) =
+        stats::parse_stats_args(&args(&["--format", "table", "--no-color", "--no-fetch"])).unwrap();
+    assert_eq!(options.format, stats::StatsFormat::Table);
+    let report = stats::run(tmp_repo.repo(), None, &options).unwrap();
+
+    let style = stats::TableStyle {
+        width: 80,
+        theme: None,
+    };
+    assert_eq!(
+        stats::stats_table(&report, 0, &PercentFormat::default(), &style),
+        "\
++--------------------------------+-------+----+------+---------+
+| File                           | Human | AI | AI % | Deleted |
++--------------------------------+-------+----+------+---------+
+| notes.md                       |     1 |  3 |  75% |       0 |
+| src/generated/parser_tables.rs |     0 |  1 | 100% |       0 |
++--------------------------------+-------+----+------+---------+
+| Total                          |     1 |  4 |  80% |       0 |
++--------------------------------+-------+----+------+---------+
+
++-----------+-------+-------+
+| Author    | Lines | Share |
++-----------+-------+-------+
+| Claude    |     4 |   80% |
+| test_user |     1 |   20% |
++-----------+-------+-------+
+| Total     |     5 |  100% |
++-----------+-------+-------+
+"
+    );
+
+    // Too wide for the terminal: the path column gives way
+    let narrow = stats::TableStyle {
+        width: 50,
+        theme: None,
+    };
+    let table = stats::stats_table(&report, 0, &PercentFormat::default(), &narrow);
+    assert!(
+        table.lines().all(|line| line.chars().count() <= 50),
+        "{}",
+        table
+    );
+    assert!(table.contains("| src/generated... |"), "{}", table);
+
+    assert!(stats::parse_stats_args(&args(&["--format", "table", "--markdown-table"])).is_err());
+    assert!(stats::parse_stats_args(&args(&["--no-color"])).is_err());
+}
+
+#[test]
+fn test_stats_annotate_commit_writes_reusable_note() {
+    use git_ai::commands::{checkpoint, reset_attribution};
+    use git_ai::log_fmt::working_log::Confidence;
+
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    tmp_repo
+        .write_file("noted.rs", "ai 1\nai 2\n", true)
+        .unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+    tmp_repo.commit_with_message("agent commit").unwrap();
+    let head = tmp_repo.repo().head().unwrap().target().unwrap();
+    let notes_tip = || tmp_repo.repo().refname_to_id("refs/notes/git-ai").unwrap();
+
+    let (_, options) =
+        stats::parse_stats_args(&args(&["--annotate-commit", "--no-fetch"])).unwrap();
+    stats::run(tmp_repo.repo(), Some("HEAD"), &options).unwrap();
+    let note = git_ai::git::notes::read_note(tmp_repo.repo(), head).unwrap();
+    assert!(note.starts_with("git-ai: 0% human / 100% AI across 1 file\n\ngit-ai-stats "));
+
+    let log = std::process::Command::new("git")
+        .current_dir(&repo_path)
+        .args(["log", "--notes=git-ai", "-1"])
+        .output()
+        .unwrap();
+    assert!(String::from_utf8_lossy(&log.stdout).contains("git-ai: 0% human / 100% AI"));
+
+    // Annotating again with the same result leaves the notes history alone
+    let tip = notes_tip();
+    stats::run(tmp_repo.repo(), Some("HEAD"), &options).unwrap();
+    assert_eq!(notes_tip(), tip);
+
+    // Doctor the note to prove an identical cached query is served from it, and only a
+    // cached one
+    let doctored = note.replace("\"ai_additions\":2", "\"ai_additions\":99");
+    assert_ne!(doctored, note);
+    git_ai::git::notes::write_note(tmp_repo.repo(), head, &doctored).unwrap();
+    let (_, plain) = stats::parse_stats_args(&args(&["--no-fetch"])).unwrap();
+    let report = stats::run(tmp_repo.repo(), Some("HEAD"), &plain).unwrap();
+    assert_eq!(report.ai_additions, 2);
+    let cached = args(&["--cache", "--no-fetch"]);
+    let (_, cached) = stats::parse_stats_args(&cached).unwrap();
+    let report = stats::run(tmp_repo.repo(), Some("HEAD"), &cached).unwrap();
+    assert_eq!(report.ai_additions, 99);
+
+    // --annotate-commit recomputes and updates it
+    let report = stats::run(tmp_repo.repo(), Some("HEAD"), &options).unwrap();
+    assert_eq!(report.ai_additions, 2);
+    assert_eq!(
+        git_ai::git::notes::read_note(tmp_repo.repo(), head).unwrap(),
+        note
+    );
+
+    let range = args(&["--annotate-commit", "HEAD~1..HEAD"]);
+    assert!(stats::parse_stats_args(&range).is_err());
+
+    // Rewriting the attribution drops the note and misses the cache
+    let report = stats::run(tmp_repo.repo(), Some("HEAD"), &cached).unwrap();
+    assert_eq!(report.ai_additions, 2);
+    checkpoint::link_commit(
+        tmp_repo.repo(),
+        "HEAD",
+        "test_user",
+        None,
+        None,
+        Confidence::High,
+        true,
+    )
+    .unwrap();
+    assert!(git_ai::git::notes::read_note(tmp_repo.repo(), head).is_none());
+    let report = stats::run(tmp_repo.repo(), Some("HEAD"), &cached).unwrap();
+    assert_eq!((report.human_additions, report.ai_additions), (2, 0));
+
+    let reset = reset_attribution::ResetScope::Commits("HEAD".to_string());
+    stats::run(tmp_repo.repo(), Some("HEAD"), &options).unwrap();
+    reset_attribution::run(tmp_repo.repo(), &reset).unwrap();
+    assert!(git_ai::git::notes::read_note(tmp_repo.repo(), head).is_none());
+}
+
+#[test]
+fn test_stats_zero_when_empty_reports_zeros_before_first_commit() {
+    let tmp_dir = tempdir().unwrap();
+    let tmp_repo = TmpRepo::new(tmp_dir.path().join("repo")).unwrap();
+    let output_file = tmp_dir.path().join("stats.txt");
+
+    let mut options = StatsOptions {
+        output: stats::StatsOutput::SummaryLine,
+        output_file: Some(output_file.to_string_lossy().to_string()),
+        no_fetch: true,
+        ..Default::default()
+    };
+    assert!(stats::run(tmp_repo.repo(), None, &options).is_err());
+
+    options.zero_when_empty = true;
+    let report = stats::run(tmp_repo.repo(), None, &options).unwrap();
+    assert_eq!(report.total_additions(), 0);
+    assert_eq!(
+        std::fs::read_to_string(&output_file).unwrap(),
+        "git-ai: 0% human / 0% AI across 0 files\n"
+    );
+
+    options.output = stats::StatsOutput::Full;
+    options.per_commit = true;
+    options.format = stats::StatsFormat::Json;
+    stats::run(tmp_repo.repo(), None, &options).unwrap();
+    assert_eq!(std::fs::read_to_string(&output_file).unwrap(), "[]\n");
+
+    // Only HEAD falls back; a named revision is still an error
+    assert!(stats::run(tmp_repo.repo(), Some("HEAD"), &options).is_ok());
+    assert!(stats::run(tmp_repo.repo(), Some("no-such-branch"), &options).is_err());
+
+    // There is no commit to annotate yet, so the note is skipped rather than failing
+    options.annotate_commit = true;
+    stats::run(tmp_repo.repo(), None, &options).unwrap();
+    options.annotate_commit = false;
+
+    // A revision that doesn't exist is still an error once there are commits
+    tmp_repo.write_file("a.txt", "one\n", true).unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("test_user")
+        .unwrap();
+    tmp_repo.commit_with_message("first").unwrap();
+    assert!(stats::run(tmp_repo.repo(), Some("no-such-branch"), &options).is_err());
+}
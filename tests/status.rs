@@ -0,0 +1,40 @@
+use assert_cmd::Command;
+use git_ai::commands::status;
+use git_ai::tmp_repo::{TmpRepo, args};
+use tempfile::tempdir;
+
+#[test]
+fn test_status_porcelain_lists_paths_with_line_counts() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, mut lines_file, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    lines_file.append("human edit\n").unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_author("test_user")
+        .unwrap();
+    std::fs::write(repo_path.join("new file.rs"), "fn a() {}\nfn b() {}\n").unwrap();
+    tmp_repo
+        .trigger_checkpoint_with_ai("Claude", "claude-4-sonnet")
+        .unwrap();
+
+    let paths = status::run(tmp_repo.repo()).unwrap();
+    assert_eq!(
+        status::porcelain(&paths),
+        "M  0 1 lines.md\n?? 2 0 new file.rs\n"
+    );
+
+    let output = Command::cargo_bin("git-ai")
+        .unwrap()
+        .current_dir(&repo_path)
+        .args(["status", "--ai", "--porcelain"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        status::porcelain(&paths)
+    );
+
+    assert!(status::parse_status_args(&args(&["--ai", "-s"])).is_err());
+}
@@ -0,0 +1,63 @@
+#![cfg(unix)]
+
+use git_ai::tmp_repo::TmpRepo;
+use tempfile::tempdir;
+
+/// git-ai run from `cwd`, which need not be the repository's own path
+fn git_ai(tmp_repo: &TmpRepo, cwd: &std::path::Path, args: &[&str]) -> String {
+    let output = tmp_repo
+        .git_ai_command()
+        .current_dir(cwd)
+        .args(args)
+        .env("GIT_PAGER", "cat")
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+#[test]
+fn test_attribution_through_symlinked_work_tree() {
+    let tmp_dir = tempdir().unwrap();
+    let real_path = tmp_dir.path().join("real");
+    std::fs::create_dir(&real_path).unwrap();
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(real_path.clone()).unwrap();
+    let link_path = tmp_dir.path().join("link");
+    std::os::unix::fs::symlink(&real_path, &link_path).unwrap();
+
+    // The agent works inside the link, in a subdirectory
+    let link_src = link_path.join("src");
+    std::fs::create_dir(&link_src).unwrap();
+    std::fs::write(link_src.join("agent.rs"), "fn a() {}\nfn b() {}\n").unwrap();
+    git_ai(
+        &tmp_repo,
+        &link_src,
+        &[
+            "checkpoint",
+            "--author",
+            "Claude",
+            "--model",
+            "claude-4-sonnet",
+        ],
+    );
+    tmp_repo.commit_with_message("Agent work").unwrap();
+
+    // The same file through the link, relative to the subdirectory, and from the root
+    let through_link = link_src.join("agent.rs");
+    for (cwd, path) in [
+        (&link_src, "agent.rs"),
+        (&link_src, through_link.to_str().unwrap()),
+        (&link_path, "src/agent.rs"),
+        (&real_path, "src/agent.rs"),
+    ] {
+        let output = git_ai(&tmp_repo, cwd, &["blame", path]);
+        assert_eq!(output.lines().count(), 2, "{}", output);
+        assert!(
+            output.lines().all(|line| line.contains("Claude")),
+            "blame {} from {}: {}",
+            path,
+            cwd.display(),
+            output
+        );
+    }
+}
@@ -0,0 +1,87 @@
+use git_ai::commands::checkpoint::resolve_base_commit;
+use git_ai::git::refs::get_reference_as_working_log;
+use git_ai::tmp_repo::TmpRepo;
+use tempfile::tempdir;
+
+#[test]
+fn test_touch_only_is_finalized_by_next_ai_checkpoint() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    let base_commit = resolve_base_commit(tmp_repo.repo()).unwrap();
+    let working_log = || {
+        get_reference_as_working_log(tmp_repo.repo(), &format!("ai-working-log/{}", base_commit))
+            .unwrap_or_default()
+    };
+    let recorded_before = working_log().len();
+
+    let output = tmp_repo.git_ai(&[
+        "checkpoint",
+        "--touch-only",
+        "--files",
+        "agent.rs,./notes.md",
+        "--author",
+        "Claude",
+        "--model",
+        "claude-4-sonnet",
+        "--json",
+    ]);
+    assert!(output.status.success(), "{:?}", output);
+    let summary: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(summary["status"], "touched");
+    assert_eq!(summary["files"], 2);
+    assert_eq!(summary["added_lines"], 0);
+
+    let touch = working_log().pop().unwrap();
+    assert!(touch.touch_only);
+    let files: Vec<_> = touch
+        .entries
+        .iter()
+        .map(|entry| entry.file.as_str())
+        .collect();
+    assert_eq!(files, ["agent.rs", "notes.md"]);
+
+    // The edit only touches one of the announced files
+    std::fs::write(repo_path.join("agent.rs"), "fn a() {}\n").unwrap();
+    let output = tmp_repo.git_ai(&[
+        "checkpoint",
+        "--author",
+        "Claude",
+        "--model",
+        "claude-4-sonnet",
+    ]);
+    assert!(output.status.success(), "{:?}", output);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Claude announced but did not change: notes.md"),
+        "{}",
+        stderr
+    );
+
+    let working_log = working_log();
+    assert_eq!(working_log.len(), recorded_before + 1);
+    assert!(working_log.iter().all(|checkpoint| !checkpoint.touch_only));
+    let checkpoint = working_log.last().unwrap();
+    assert_eq!(checkpoint.entries.len(), 1);
+    assert_eq!(checkpoint.entries[0].file, "agent.rs");
+    assert!(!checkpoint.entries[0].added_lines.is_empty());
+}
+
+#[test]
+fn test_touch_only_requires_ai_and_files() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path).unwrap();
+
+    let output = tmp_repo.git_ai(&["checkpoint", "--touch-only", "--files", "a.rs"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--author-type ai"));
+
+    let output = tmp_repo.git_ai(&["checkpoint", "--touch-only", "--author-type", "ai"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--files"));
+
+    let output = tmp_repo.git_ai(&["checkpoint", "--files", "a.rs"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("requires --touch-only"));
+}
@@ -0,0 +1,46 @@
+use assert_cmd::Command;
+use git_ai::commands::checkpoint::{ChangeScope, verify_clean};
+use git_ai::tmp_repo::TmpRepo;
+use tempfile::tempdir;
+
+#[test]
+fn test_verify_clean_lists_files_outside_allowed_globs() {
+    let tmp_dir = tempdir().unwrap();
+    let repo_path = tmp_dir.path().to_path_buf();
+
+    let (tmp_repo, _, _) = TmpRepo::new_with_base_commit(repo_path.clone()).unwrap();
+    std::fs::create_dir_all(repo_path.join("src/parser")).unwrap();
+    std::fs::write(repo_path.join("src/parser/lexer.rs"), "fn lex() {}\n").unwrap();
+    std::fs::write(repo_path.join("src/main.rs"), "fn main() {}\n").unwrap();
+    std::fs::write(repo_path.join("README.md"), "stray\n").unwrap();
+
+    let allow = vec!["src/**".to_string()];
+    let offenders = verify_clean(tmp_repo.repo(), &allow, ChangeScope::All).unwrap();
+    assert_eq!(offenders, ["README.md"]);
+
+    // `*` stays within one directory
+    let allow = vec!["src/*.rs".to_string(), "*.md".to_string()];
+    let offenders = verify_clean(tmp_repo.repo(), &allow, ChangeScope::All).unwrap();
+    assert_eq!(offenders, ["src/parser/lexer.rs"]);
+
+    let output = Command::cargo_bin("git-ai")
+        .unwrap()
+        .current_dir(&repo_path)
+        .args(["checkpoint", "--verify-clean", "--allow", "src/**"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("changes outside the allowed paths:\n  README.md"));
+
+    let output = Command::cargo_bin("git-ai")
+        .unwrap()
+        .current_dir(&repo_path)
+        .args(["checkpoint", "--verify-clean", "--allow", "src/**"])
+        .args(["--allow", "README.md", "--json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    let recorded: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(recorded["status"], "recorded");
+}